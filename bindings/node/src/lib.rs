@@ -0,0 +1,83 @@
+//! Node.js bindings of `yapg-core`, as native addon for backends generating
+//! passwords in bulk.
+//!
+//! Build with `npm run build` in `bindings/node`, then:
+//!
+//! ```js
+//! const yapg = require('./bindings/node');
+//!
+//! console.log(yapg.generate('LUN', 20, 3));
+//! console.log(yapg.analyze('LUN', 20).entropyBits);
+//! ```
+use napi::{Error, Result};
+use napi_derive::napi;
+
+/// Translates errors of `yapg-core`, which are all about invalid input.
+fn invalid(e: std::io::Error) -> Error { Error::from_reason(e.to_string()) }
+
+/// Parses a charset spec like `"LUN+-_"`, rejecting empty charsets.
+fn charset(spec: &str) -> Result<Vec<char>> {
+    let charset =
+        spec.parse::<yapg_core::CharsetSpec>().map_err(invalid)?.construct();
+    if charset.is_empty() {
+        return Err(Error::from_reason("Charset is empty".to_string()));
+    }
+    Ok(charset)
+}
+
+/// Generates `count` (default: 1) passwords of `length` chars from the
+/// charset given by `spec`.
+#[napi]
+pub fn generate(
+    spec: String,
+    length: u32,
+    count: Option<u32>,
+) -> Result<Vec<String>> {
+    let mut pwg =
+        yapg_core::PasswordGenerator::new(charset(&spec)?, length as usize);
+    Ok(pwg.generate_n(count.unwrap_or(1) as usize))
+}
+
+/// Generates `count` (default: 1) passphrases of `words` words from the
+/// named `wordlist` (default: `eff-long`).
+#[napi]
+pub fn generate_passphrases(
+    words: u32,
+    count: Option<u32>,
+    wordlist: Option<String>,
+    separator: Option<String>,
+) -> Result<Vec<String>> {
+    let wordlist =
+        yapg_core::Wordlist::named(wordlist.as_deref().unwrap_or("eff-long"))
+            .map_err(invalid)?;
+    let mut ppg = yapg_core::PassphraseGenerator::new(wordlist, words as usize)
+        .separator(separator.as_deref().unwrap_or("-"));
+    Ok(ppg.generate_n(count.unwrap_or(1) as usize))
+}
+
+/// Properties of a configuration, see `analyze`.
+#[napi(object)]
+pub struct Analysis {
+    /// Number of distinct chars.
+    pub charset_size: u32,
+    /// Entropy of each password in bits.
+    pub entropy_bits: f64,
+    /// The `charset_fingerprint` of charset and length.
+    pub fingerprint: String,
+    /// Whether right-to-left chars may reorder displayed passwords.
+    pub may_reorder: bool,
+}
+
+/// Analyzes passwords of `length` chars from the charset given by `spec`.
+#[napi]
+pub fn analyze(spec: String, length: u32) -> Result<Analysis> {
+    let charset = charset(&spec)?;
+    let pwg =
+        yapg_core::PasswordGenerator::new(charset.clone(), length as usize);
+    Ok(Analysis {
+        charset_size: charset.len() as u32,
+        entropy_bits: pwg.entropy_bits(),
+        fingerprint: yapg_core::charset_fingerprint(&charset, length as usize),
+        may_reorder: yapg_core::BidiReport::of(&charset).may_reorder(),
+    })
+}
@@ -0,0 +1,135 @@
+//! Python bindings of `yapg-core`, so Python scripts generate passwords with
+//! exactly the same logic and entropy math as the `yapg` binary.
+//!
+//! Build and install into the active virtualenv with
+//! `maturin develop -m bindings/python/Cargo.toml`, then:
+//!
+//! ```python
+//! import yapg
+//!
+//! pwg = yapg.Generator(yapg.CharsetSpec("LUN"), 20)
+//! print(pwg.generate(), pwg.entropy)
+//! print(yapg.PassphraseGenerator(6).generate())
+//! ```
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// Translates errors of `yapg-core`, which are all about invalid input.
+fn value_error(e: std::io::Error) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+/// A charset spec like `"LUN+-_"`, see `yapg_core::CharsetSpec`.
+#[pyclass(name = "CharsetSpec")]
+#[derive(Clone)]
+struct PyCharsetSpec(yapg_core::CharsetSpec);
+
+#[pymethods]
+impl PyCharsetSpec {
+    #[new]
+    fn new(spec: &str) -> PyResult<Self> {
+        spec.parse().map(Self).map_err(value_error)
+    }
+
+    #[staticmethod]
+    fn std64() -> Self { Self(yapg_core::CharsetSpec::std64()) }
+
+    #[staticmethod]
+    fn printable_ascii() -> Self {
+        Self(yapg_core::CharsetSpec::printable_ascii())
+    }
+
+    /// All characters of the charset, sorted.
+    fn chars(&self) -> String { self.0.build().into_iter().collect() }
+
+    fn __len__(&self) -> usize { self.0.len() }
+
+    fn __str__(&self) -> String { self.0.to_spec_string() }
+
+    fn __repr__(&self) -> String {
+        format!("CharsetSpec({:?})", self.0.to_spec_string())
+    }
+
+    fn __eq__(&self, other: &Self) -> bool { self.0 == other.0 }
+
+    fn __or__(&self, other: &Self) -> Self { Self(&self.0 | &other.0) }
+
+    fn __and__(&self, other: &Self) -> Self { Self(&self.0 & &other.0) }
+
+    fn __sub__(&self, other: &Self) -> Self { Self(&self.0 - &other.0) }
+}
+
+/// Charsets are given as `CharsetSpec` or spec string.
+#[derive(FromPyObject)]
+enum CharsetArg {
+    Spec(PyCharsetSpec),
+    Str(String),
+}
+
+impl CharsetArg {
+    fn chars(self) -> PyResult<Vec<char>> {
+        let spec = match self {
+            Self::Spec(spec) => spec.0,
+            Self::Str(spec) => PyCharsetSpec::new(&spec)?.0,
+        };
+        Ok(spec.construct())
+    }
+}
+
+/// Generates passwords from chars, see `yapg_core::PasswordGenerator`.
+#[pyclass(name = "Generator", unsendable)]
+struct PyGenerator(yapg_core::PasswordGenerator);
+
+#[pymethods]
+impl PyGenerator {
+    #[new]
+    fn new(charset: CharsetArg, length: usize) -> PyResult<Self> {
+        let charset = charset.chars()?;
+        if charset.is_empty() {
+            return Err(PyValueError::new_err("Charset is empty"));
+        }
+        Ok(Self(yapg_core::PasswordGenerator::new(charset, length)))
+    }
+
+    fn generate(&mut self) -> String { self.0.generate() }
+
+    fn generate_n(&mut self, n: usize) -> Vec<String> { self.0.generate_n(n) }
+
+    /// Entropy of each password in bits.
+    #[getter]
+    fn entropy(&self) -> f64 { self.0.entropy_bits() }
+}
+
+/// Generates passphrases from words, see `yapg_core::PassphraseGenerator`.
+#[pyclass(name = "PassphraseGenerator", unsendable)]
+struct PyPassphraseGenerator(yapg_core::PassphraseGenerator);
+
+#[pymethods]
+impl PyPassphraseGenerator {
+    #[new]
+    #[pyo3(signature = (words, wordlist = "eff-long", separator = "-"))]
+    fn new(words: usize, wordlist: &str, separator: &str) -> PyResult<Self> {
+        let wordlist =
+            yapg_core::Wordlist::named(wordlist).map_err(value_error)?;
+        Ok(Self(
+            yapg_core::PassphraseGenerator::new(wordlist, words)
+                .separator(separator),
+        ))
+    }
+
+    fn generate(&mut self) -> String { self.0.generate() }
+
+    fn generate_n(&mut self, n: usize) -> Vec<String> { self.0.generate_n(n) }
+
+    /// Entropy of each passphrase in bits.
+    #[getter]
+    fn entropy(&self) -> f64 { self.0.entropy() }
+}
+
+#[pymodule]
+fn yapg(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<PyCharsetSpec>()?;
+    m.add_class::<PyGenerator>()?;
+    m.add_class::<PyPassphraseGenerator>()?;
+    Ok(())
+}
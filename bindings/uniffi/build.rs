@@ -0,0 +1 @@
+fn main() { uniffi::generate_scaffolding("src/yapg.udl").unwrap(); }
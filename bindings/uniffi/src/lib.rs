@@ -0,0 +1,158 @@
+//! Kotlin and Swift bindings of `yapg-core` via UniFFI, so mobile apps embed
+//! the same generators and policy checks as the `yapg` binary. The interface
+//! is defined in `src/yapg.udl`.
+//!
+//! Build the library for the target platform, then generate the foreign
+//! code, e.g. for Kotlin:
+//!
+//! ```sh
+//! cargo build --release
+//! cargo run --bin uniffi-bindgen -- generate src/yapg.udl --language kotlin
+//! ```
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+use yapg_core::CharsetName;
+
+uniffi::include_scaffolding!("yapg");
+
+/// Errors of `yapg-core`, which are all about invalid input.
+#[derive(Debug, thiserror::Error)]
+pub enum YapgError {
+    #[error("{0}")]
+    InvalidInput(String),
+}
+
+impl From<std::io::Error> for YapgError {
+    fn from(e: std::io::Error) -> Self { Self::InvalidInput(e.to_string()) }
+}
+
+/// Password policy of a target system, see `yapg_core::Policy`.
+pub struct Policy {
+    pub min_length: u32,
+    pub max_length: Option<u32>,
+    pub required: String,
+}
+
+impl TryFrom<Policy> for yapg_core::Policy {
+    type Error = YapgError;
+
+    fn try_from(policy: Policy) -> Result<Self, YapgError> {
+        let required = policy
+            .required
+            .chars()
+            .map(CharsetName::try_from)
+            .collect::<std::io::Result<Vec<_>>>()?;
+        Ok(yapg_core::Policy::new(
+            policy.min_length as usize,
+            policy.max_length.map(|m| m as usize),
+            required,
+        ))
+    }
+}
+
+impl From<yapg_core::Policy> for Policy {
+    fn from(policy: yapg_core::Policy) -> Self {
+        Self {
+            min_length: policy.min_length as u32,
+            max_length: policy.max_length.map(|m| m as u32),
+            required: policy.required.into_iter().map(char::from).collect(),
+        }
+    }
+}
+
+/// A named configuration, see `yapg_core::presets::Preset`.
+pub struct Preset {
+    pub name: String,
+    pub description: String,
+    pub charset: String,
+    pub length: u32,
+    pub policy: Policy,
+}
+
+/// Parses a charset spec like `"LUN+-_"`, rejecting empty charsets.
+fn parse_charset(spec: &str) -> Result<Vec<char>, YapgError> {
+    let charset = spec.parse::<yapg_core::CharsetSpec>()?.construct();
+    if charset.is_empty() {
+        return Err(YapgError::InvalidInput("Charset is empty".to_string()));
+    }
+    Ok(charset)
+}
+
+pub fn charset_fingerprint(
+    charset: String,
+    length: u32,
+) -> Result<String, YapgError> {
+    Ok(yapg_core::charset_fingerprint(
+        &parse_charset(&charset)?,
+        length as usize,
+    ))
+}
+
+pub fn presets() -> Vec<Preset> {
+    yapg_core::presets::all()
+        .into_iter()
+        .map(|preset| Preset {
+            name: preset.name.to_string(),
+            description: preset.description.to_string(),
+            charset: preset.charset.to_spec_string(),
+            length: preset.length as u32,
+            policy: preset.policy.into(),
+        })
+        .collect()
+}
+
+pub fn policy_allows(
+    policy: Policy,
+    password: String,
+) -> Result<bool, YapgError> {
+    Ok(yapg_core::Policy::try_from(policy)?.allows(&password))
+}
+
+pub fn policy_length_for_entropy(
+    policy: Policy,
+    charset_size: u32,
+    target_bits: f64,
+) -> Result<u32, YapgError> {
+    yapg_core::Policy::try_from(policy)?
+        .length_for_entropy(charset_size as usize, target_bits)
+        .map(|length| length as u32)
+        .map_err(|e| YapgError::InvalidInput(e.to_string()))
+}
+
+/// Generates passwords from chars, see `yapg_core::PasswordGenerator`.
+///
+/// Foreign code may call from any thread, while the wrapped generator is
+/// bound to its thread's RNG, so each call creates a fresh one.
+pub struct Generator {
+    charset: Vec<char>,
+    length: usize,
+}
+
+impl Generator {
+    pub fn new(charset: String, length: u32) -> Result<Self, YapgError> {
+        Ok(Self { charset: parse_charset(&charset)?, length: length as usize })
+    }
+
+    pub fn from_preset(name: String) -> Result<Arc<Self>, YapgError> {
+        let preset = yapg_core::presets::by_name(&name).ok_or_else(|| {
+            YapgError::InvalidInput(format!("Unknown preset: {}", name))
+        })?;
+        Ok(Arc::new(Self {
+            charset: preset.charset.construct(),
+            length: preset.length,
+        }))
+    }
+
+    fn generator(&self) -> yapg_core::PasswordGenerator {
+        yapg_core::PasswordGenerator::new(self.charset.clone(), self.length)
+    }
+
+    pub fn generate(&self) -> String { self.generator().generate() }
+
+    pub fn generate_n(&self, n: u32) -> Vec<String> {
+        self.generator().generate_n(n as usize)
+    }
+
+    pub fn entropy(&self) -> f64 { self.generator().entropy_bits() }
+}
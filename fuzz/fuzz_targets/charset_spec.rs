@@ -0,0 +1,9 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|s: &str| {
+    if let Ok(spec) = s.parse::<yapg::CharsetSpec>() {
+        let charset = spec.construct();
+        assert_eq!(yapg::CharsetSpec::from_chars(&charset).construct(), charset);
+    }
+});
@@ -0,0 +1,17 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|s: &str| {
+    let mut parts = s.splitn(2, '|');
+    let charset: Vec<yapg::Grapheme> =
+        parts.next().unwrap().chars().map(Into::into).collect();
+    let indices: Vec<usize> =
+        parts.next().unwrap_or("").bytes().map(usize::from).collect();
+    match yapg::compose(&charset, &indices) {
+        Ok(password) => {
+            assert!(indices.iter().all(|i| *i < charset.len()));
+            assert_eq!(password.chars().count(), indices.len());
+        },
+        Err(_) => assert!(indices.iter().any(|i| *i >= charset.len())),
+    }
+});
@@ -0,0 +1,8 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|s: &str| {
+    if let Ok(dice) = s.parse::<yapg::Dice>() {
+        assert_eq!(dice.to_string().parse::<yapg::Dice>().unwrap(), dice);
+    }
+});
@@ -0,0 +1,9 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|s: &str| {
+    if let Ok(secret) = yapg::proquint_decode(s) {
+        let quints = yapg::proquint_encode(&secret).unwrap();
+        assert_eq!(yapg::proquint_decode(&quints).unwrap(), secret);
+    }
+});
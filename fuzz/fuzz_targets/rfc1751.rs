@@ -0,0 +1,9 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|s: &str| {
+    if let Ok(secret) = yapg::rfc1751_decode(s) {
+        let words = yapg::rfc1751_encode(&secret).unwrap();
+        assert_eq!(yapg::rfc1751_decode(&words).unwrap(), secret);
+    }
+});
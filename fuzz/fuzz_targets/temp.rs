@@ -0,0 +1,9 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|s: &str| {
+    if let Ok(expiry) = yapg::TempExpiry::of(s) {
+        expiry.to_string();
+    }
+    let _ = yapg::verify_temp(s, 0);
+});
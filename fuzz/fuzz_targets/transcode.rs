@@ -0,0 +1,12 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|s: &str| {
+    let list = yapg::Wordlist::embedded("eff-short").unwrap();
+    if let Ok(secret) = yapg::parse_secret(s) {
+        let words = yapg::encode_words(&secret, &list).unwrap();
+        assert_eq!(yapg::decode_words(&words, &list).unwrap(), secret);
+    }
+    let words: Vec<&str> = s.split_whitespace().collect();
+    let _ = yapg::decode_words(&words, &list);
+});
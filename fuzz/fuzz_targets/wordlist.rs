@@ -0,0 +1,16 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|s: &str| {
+    if let Ok(mut list) = s.parse::<yapg::Wordlist>() {
+        list.report();
+        list.abbreviated(3).entropy_per_word();
+        list.normalize();
+        if !list.is_empty() {
+            let mut ppg = yapg::PassphraseGenerator::new(list, 4);
+            ppg.entropy();
+            ppg.generate();
+        }
+    }
+    let _ = s.parse::<yapg::Blocklist>();
+});
@@ -1,7 +1,15 @@
 #[macro_use]
 extern crate clap;
 
+mod session;
+
+use std::convert::TryInto;
 use std::io;
+use std::io::{BufRead, IsTerminal, Read};
+#[cfg(feature = "alloc-stats")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use yapg::BreachCheck;
 
 // TODO:
 //  [x] print warnings in highlighted coloring (auto-detect terminal)
@@ -19,14 +27,121 @@ use std::io;
 //  [] merge the two `PasswordGenerator::from` `impl`s by using `AsRef<str>`
 //  [] refactor `CharsetSpec` into bitflag + additions
 
-const DEFAULT_LENGTH: usize = 24;
+/// System allocator counting allocations, for the stats of `yapg bench`.
+/// Only built with the `alloc-stats` feature, as it slows down every
+/// allocation of the binary.
+#[cfg(feature = "alloc-stats")]
+struct CountingAllocator;
+
+#[cfg(feature = "alloc-stats")]
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+#[cfg(feature = "alloc-stats")]
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg(feature = "alloc-stats")]
+unsafe impl std::alloc::GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        std::alloc::System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+        std::alloc::System.dealloc(ptr, layout)
+    }
+}
+
+#[cfg(feature = "alloc-stats")]
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Allocations and allocated bytes so far, if built with `alloc-stats`.
+#[cfg(feature = "alloc-stats")]
+fn allocation_stats() -> Option<(usize, usize)> {
+    Some((
+        ALLOCATIONS.load(Ordering::Relaxed),
+        ALLOCATED_BYTES.load(Ordering::Relaxed),
+    ))
+}
+
+#[cfg(not(feature = "alloc-stats"))]
+fn allocation_stats() -> Option<(usize, usize)> { None }
+
 const DEFAULT_NUMBER: usize = 20;
 const ENTROPY_THRESHOLD: usize = 100;
+/// Passwords `yapg attack-sim` attacks.
+const DEFAULT_ATTACK_SAMPLES: usize = 1000;
+/// Guesses per second `yapg attack-sim` assumes, about a GPU rig against a
+/// fast hash like NTLM or SHA-1.
+const DEFAULT_GUESS_RATE: f64 = 1e10;
+/// Random passwords `yapg simulate-policy` puts to a policy.
+const DEFAULT_POLICY_SAMPLES: f64 = 1e6;
+/// Rate of false positives of bloom filters built by `yapg pwned-bloom`.
+const DEFAULT_BLOOM_FP_RATE: f64 = 0.001;
+/// Shortest emoji passphrase reaching `ENTROPY_THRESHOLD` (6 bits per emoji).
+const EMOJI_LENGTH: usize = 17;
+/// Typical code length of color-button locks.
+const COLOR_LENGTH: usize = 8;
+
+/// Default collision probability `yapg plan` recommends a length for.
+const DEFAULT_MAX_COLLISION_PROBABILITY: f64 = 1e-6;
+
+/// Default order of Markov models trained with `--markov`.
+const DEFAULT_MARKOV_ORDER: usize = 3;
+
+/// Longest password `--markov` generates to reach the entropy threshold.
+const MAX_MARKOV_LENGTH: usize = 256;
+
+/// Flags whose values `--history` never records: seeds reproduce the
+/// passwords, entropy sources and share targets may carry PINs or tokens.
+const REDACTED_FLAGS: [&str; 3] = ["--seed", "--entropy-source", "--share"];
+
+/// Environment variable through which `yapg ssh-key` hands the passphrase
+/// to ssh-keygen, which asks yapg itself for it as askpass helper.
+const SSH_PASSPHRASE_ENV: &str = "YAPG_SSH_KEY_PASSPHRASE";
 
 struct Args {
+    passphrase: Option<yapg::PassphraseGenerator>,
+    pronounceable: Option<yapg::PronounceableGenerator>,
+    markov: Option<yapg::MarkovGenerator>,
+    composition: Option<yapg::Composition>,
+    pattern: Option<yapg::Pattern>,
+    #[cfg_attr(not(feature = "regex-gen"), allow(dead_code))]
+    regex: Option<String>,
+    viz: Option<yapg::Visualization>,
+    encoding_report: bool,
+    fit_to: Option<usize>,
+    policy: yapg::Policy,
+    limit: yapg::InputLimit,
     length: usize,
     number: usize,
     charset: Vec<char>,
+    items: Vec<yapg::Grapheme>,
+    each_class: bool,
+    constraints: Option<yapg::Constraints>,
+    keymap_warnings: bool,
+    t9: bool,
+    emoji: bool,
+    colors: bool,
+    fingerprint: bool,
+    format: yapg::OutputFormat,
+    provenance: bool,
+    expires_in: Option<u64>,
+    seed: Option<[u8; 32]>,
+    stream: Option<u32>,
+    min_distance: Option<usize>,
+    match_filter: Option<regex::Regex>,
+    reject_filter: Option<regex::Regex>,
+    dictionary: Option<yapg::Dictionary>,
+    hibp: bool,
+    pwned_db: Option<yapg::PwnedDb>,
+    codebook: Option<String>,
+    on_generated: Option<String>,
+    entropy_source: Option<String>,
+    rng: Option<yapg::RandomSource>,
+    history: Option<String>,
+    export: Option<String>,
+    share: Option<String>,
     quiet: bool,
 }
 
@@ -44,29 +159,510 @@ where
     }
 }
 
+/// Parses a 32-byte seed given as hex or base64.
+fn parse_seed(s: &str) -> io::Result<[u8; 32]> {
+    let bytes = yapg::parse_secret(s)?;
+    let len = bytes.len();
+    bytes.try_into().map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Seed must be 32 bytes, not {}", len),
+        )
+    })
+}
+
+/// Compiles the regex of `--match` or `--reject`, if given.
+fn parse_regex(regex: Option<&str>) -> io::Result<Option<regex::Regex>> {
+    regex
+        .map(|r| {
+            regex::Regex::new(r).map_err(|e| {
+                io::Error::new(io::ErrorKind::InvalidInput, e.to_string())
+            })
+        })
+        .transpose()
+}
+
 impl Args {
-    fn get_matches() -> clap::ArgMatches<'static> {
+    fn app() -> clap::App<'static, 'static> {
         clap_app!(yapg =>
-            (version: "0.1")
+            (version: env!("CARGO_PKG_VERSION"))
             (author: "tillyboy (https://github.com/tillyboy)")
             (about: "Generate random passphrases")
             (@arg number: -n --number +takes_value "Number (count) of passwords to print")
             (@arg length: -l --length +takes_value "Length of each password")
+            (@arg bits: -e --bits +takes_value conflicts_with[length words wordlist max_syllables syllables markov compose pattern regex t9] "Derive the length from this many bits of entropy, e.g. 128")
             (@arg added_chars: -a --add +takes_value "Additional characters to use")
+            (@arg items: --items +takes_value conflicts_with[words wordlist max_syllables emoji colors t9] "Additional multi-char items, comma-separated, e.g. flag emoji or ch,ll")
+            (@arg safe_for: --("safe-for") +takes_value "Only use chars needing no escaping in json|xml|sql-literal|url|basic-auth|pgpass")
+            (@arg digits: --digits +takes_value conflicts_with[words wordlist max_syllables emoji colors t9] "Use the digits of this system or locale, e.g. devanagari or ar-EG")
+            (@arg accepted_digits: --("accepted-digits") +takes_value conflicts_with[words wordlist max_syllables emoji colors] "Check the target accepts the digits, given its digit systems, e.g. latin,arabic-indic")
+            (@arg each_class: --("each-class") conflicts_with[words wordlist max_syllables syllables markov compose emoji colors] "Make each password contain every class of its charsets, e.g. a digit")
+            (@arg constraints: --constraints +takes_value conflicts_with[each_class words wordlist max_syllables syllables markov compose emoji colors] "Bound the chars per charset, e.g. N>=2,S<=1 for at least 2 digits and at most 1 special char")
+            (@arg single_direction: --("single-direction") conflicts_with[words wordlist max_syllables] "Refuse charsets mixing left-to-right and right-to-left letters")
+            (@arg preset: -p --preset +takes_value conflicts_with[charsets] "Preset to use (see `yapg presets list`)")
+            (@arg t9: --t9 "Also print the phone keypad digits entering each password")
+            (@arg emoji: --emoji conflicts_with[charsets preset added_chars safe_for t9 expires_in] "Generate emoji passphrases, printed along with their :shortcodes:")
+            (@arg colors: --colors conflicts_with[charsets preset added_chars safe_for t9 emoji expires_in] "Generate codes for color-button locks, printed with names and hex values")
+            (@arg words: -w --words +takes_value conflicts_with[charsets preset added_chars safe_for t9 emoji colors expires_in provenance length] "Generate passphrases of this many words (default: enough for 100 bits)")
+            (@arg wordlist: --wordlist +takes_value conflicts_with[charsets preset added_chars safe_for t9 emoji colors expires_in provenance length] "Wordlist (file or name, see `yapg wordlist list`) for passphrases (default: eff-long)")
+            (@arg max_syllables: --("max-syllables") +takes_value conflicts_with[charsets preset added_chars safe_for t9 emoji colors expires_in provenance length] "Cap the estimated syllables per passphrase, e.g. for voice entry")
+            (@arg syllables: --syllables +takes_value conflicts_with[charsets preset added_chars items safe_for digits t9 emoji colors words wordlist max_syllables expires_in provenance length seed min_distance match_filter reject_filter codebook entropy_source on_generated] "Generate pronounceable passwords of this many syllables, e.g. bamkotipuz")
+            (@arg markov: --markov +takes_value conflicts_with[charsets preset added_chars items safe_for digits t9 emoji colors words wordlist max_syllables syllables expires_in provenance seed min_distance match_filter reject_filter codebook entropy_source on_generated] "Generate pronounceable passwords from a Markov model trained on this text file")
+            (@arg order: --order +takes_value requires[markov] "Letters each letter depends on in the Markov model (default: 3)")
+            (@arg pattern: --pattern +takes_value conflicts_with[charsets preset added_chars items safe_for digits t9 emoji colors words wordlist max_syllables syllables markov compose length each_class constraints] "Generate structured passwords, e.g. Cvcvc-NNNN-XX: C/c consonant, V/v vowel, N digit, X special char, \\ escapes, anything else is literal")
+            (@arg regex: --regex +takes_value conflicts_with[charsets preset added_chars items safe_for digits t9 emoji colors words wordlist max_syllables syllables markov compose pattern length each_class constraints expires_in min_distance match_filter reject_filter codebook on_generated seed stream rng entropy_source] "Generate strings matching this regex, e.g. [A-Z]{2}\\d{4} (needs the regex-gen feature)")
+            (@arg compose: --compose +takes_value conflicts_with[charsets preset added_chars items safe_for digits t9 emoji colors words wordlist max_syllables syllables markov length expires_in min_distance match_filter reject_filter codebook on_generated] "Draw exactly this many chars per charset, in random order, e.g. L:6,U:2,N:2,S:2")
+            (@arg fit_to: --("fit-to") +takes_value conflicts_with[max_syllables] "Make passphrases fit this many chars, by dropping separators, abbreviating words or switching to chars")
+            (@arg separator: --separator +takes_value "Separator between passphrase words (default: -)")
+            (@arg fingerprint: --("charset-fingerprint") "Print a fingerprint of charset and length for comparison")
+            (@arg format: -f --format +takes_value "Output format: plain|json|yaml|backup-sheet")
+            (@arg encoding_report: --("encoding-report") conflicts_with[words wordlist max_syllables] "Report on stderr which chars of each password need escaping in URLs, basic-auth headers and .pgpass files")
+            (@arg viz: --viz +takes_value conflicts_with[words wordlist max_syllables syllables markov compose regex] "Visualize each password on stderr: keyboard (heatmap of the keys and shift used)")
+            (@arg provenance: --provenance requires[format] "Include generation metadata (json, yaml and backup-sheet only)")
+            (@arg expires_in: --("expires-in") +takes_value conflicts_with[t9] "Append an expiry date this many days ahead (see `yapg verify-temp`)")
+            (@arg seed: --seed +takes_value "Generate reproducibly from this 32-byte seed (hex or base64)")
+            (@arg stream: --stream +takes_value requires[seed] conflicts_with[words wordlist max_syllables] "Generate substream <i> of the seed, e.g. one per distributed worker")
+            (@arg min_distance: --("min-distance") +takes_value conflicts_with[words wordlist max_syllables t9] "Make all passwords differ in at least this many positions, e.g. for voucher codes")
+            (@arg match_filter: --match +takes_value conflicts_with[words wordlist max_syllables min_distance] "Only keep passwords matching this regex (lowers the entropy)")
+            (@arg no_dictionary_words: --("no-dictionary-words") conflicts_with[words wordlist max_syllables syllables markov compose regex min_distance] "Discard passwords containing words of 5 or more letters from the embedded wordlists (lowers the entropy)")
+            (@arg hibp: --hibp conflicts_with[words wordlist max_syllables min_distance match_filter reject_filter no_dictionary_words] "Redraw passwords found in known breaches, sending only SHA-1 prefixes to Have I Been Pwned (needs the hibp feature)")
+            (@arg pwned_db: --("pwned-db") +takes_value conflicts_with[hibp words wordlist max_syllables min_distance match_filter reject_filter no_dictionary_words] "Redraw passwords found in this offline breach database, a sorted SHA-1 hash file (e.g. the HIBP corpus) or a bloom filter (see `yapg pwned-bloom`)")
+            (@arg reject_filter: --reject +takes_value conflicts_with[words wordlist max_syllables min_distance] "Discard passwords matching this regex (lowers the entropy)")
+            (@arg codebook: --codebook +takes_value conflicts_with[words wordlist max_syllables] "Also write a code book for checking redemptions (see `yapg verify-code`) to this file")
+            (@arg entropy_source: --("entropy-source") +takes_value conflicts_with[words wordlist max_syllables seed] "Draw all randomness from os or pkcs11:<module> (PIN in $YAPG_PKCS11_PIN)")
+            (@arg rng: --rng +takes_value conflicts_with[seed entropy_source] "RNG to draw from: thread (default), os or chacha (reseeded from the OS)")
+            (@arg on_generated: --("on-generated") +takes_value conflicts_with[words wordlist max_syllables] "Run this shell command after each password, with its fingerprint (never the password) in $YAPG_FINGERPRINT")
+            (@arg export: --("emit-export") +takes_value conflicts_with[format t9 emoji colors] "Print shell-escaped `export VAR='...'` lines instead, numbering VAR_1, VAR_2, ... for several passwords")
+            (@arg share: --share +takes_value conflicts_with[format export t9 emoji colors viz encoding_report codebook] "Post each password to a one-time-secret service, onetimesecret or an https:// URL, and print only the self-destructing links (needs the share feature)")
+            (@arg history: --history +takes_value "Record the fingerprints (never the passwords) under this label in ~/.local/share/yapg/history.jsonl")
             (@arg quiet: -q --quiet "Don't print debug/safety information")
             (@arg charsets: "Selection of charsets to use")
+            (@subcommand presets =>
+                (about: "Inspect the available presets")
+                (@setting SubcommandRequiredElseHelp)
+                (@subcommand list => (about: "List all presets"))
+            )
+            (@subcommand shuffle =>
+                (about: "Print the lines of a file (or stdin) in random order")
+                (@arg file: "File to read lines from, defaults to stdin")
+            )
+            (@subcommand check =>
+                (about: "Analyze existing passwords, printing a verdict, the estimated entropy and weaknesses of each")
+                (@arg min_bits: --("min-bits") +takes_value "Exit with an error if any password has fewer estimated bits (default: 60)")
+                (@arg hibp: --hibp "Also look the passwords up in known breaches, sending only SHA-1 prefixes to Have I Been Pwned (needs the hibp feature)")
+                (@arg pwned_db: --("pwned-db") +takes_value conflicts_with[hibp] "Also look the passwords up in this offline breach database")
+                (@arg passwords: +multiple "Passwords to check, defaults to the lines of stdin")
+            )
+            (@subcommand truncate =>
+                (about: "Truncate secrets at grapheme boundaries to fit a byte limit, warning about the entropy lost")
+                (@arg bytes: --bytes +takes_value "Maximum bytes of each secret (default: 72, bcrypt's limit)")
+                (@arg file: "File of secrets, one per line, defaults to stdin")
+            )
+            (@subcommand pick =>
+                (about: "Pick lines of a file (or stdin) uniformly at random")
+                (@arg k: -k +takes_value "Number of lines to pick (default: 1)")
+                (@arg file: "File to read lines from, defaults to stdin")
+            )
+            (@subcommand wordlist =>
+                (about: "Inspect and preprocess wordlists")
+                (@setting SubcommandRequiredElseHelp)
+                (@subcommand filter =>
+                    (about: "Print a wordlist without blocked words")
+                    (@arg locale: -L --locale +takes_value +multiple number_of_values(1) "Locale whose blocklist to apply, e.g. de-AT (see README)")
+                    (@arg blocklist: -b --blocklist +takes_value +multiple number_of_values(1) "File of additional words to block")
+                    (@arg file: +required "Wordlist to filter (file or name)")
+                )
+                (@subcommand list =>
+                    (about: "List available wordlists and where each is read from")
+                )
+                (@subcommand check =>
+                    (about: "Report on the quality of a wordlist")
+                    (@arg file: +required "Wordlist to check (file or name)")
+                )
+                (@subcommand normalize =>
+                    (about: "Print a wordlist without near-duplicate word forms")
+                    (@arg file: +required "Wordlist to normalize (file or name)")
+                )
+            )
+            (@subcommand schema =>
+                (about: "Print the JSON Schema of policies, manifests (json output) or credentials")
+                (@arg name: +required "Schema to print: policy|manifest|credentials")
+            )
+            (@subcommand version =>
+                (about: "Print version, enabled features, RNG and embedded wordlists")
+                (@arg json: --json "Print as JSON, e.g. for bug reports")
+            )
+            (@subcommand wizard =>
+                (about: "Answer a few questions to derive a configuration")
+            )
+            (@subcommand extend =>
+                (about: "Append random chars to a memorable base of your choice")
+                (@arg base: -b --base +takes_value +required "Memorable base, e.g. MyCat")
+                (@arg target: -e --("target-entropy") +takes_value "Bits of entropy to append (default: 100)")
+                (@arg charsets: "Selection of charsets to append from (default: std64)")
+            )
+            (@subcommand rotate =>
+                (about: "Derive this period's secret from a master key, e.g. for rotating shared passwords")
+                (@arg label: --label +takes_value +required "Name of the secret, e.g. db-backup")
+                (@arg period: --period +takes_value "Rotate every day|week|month|year (default: month)")
+                (@arg overlap: --("overlap-days") +takes_value "Also print neighbouring periods' secrets this many days around their boundary")
+                (@arg master_file: --("master-file") +takes_value "File containing the 32-byte master key as hex or base64 (default: stdin)")
+                (@arg length: -l --length +takes_value "Length of the secret (default: 24)")
+                (@arg charsets: "Selection of charsets to use (default: std64)")
+            )
+            (@subcommand replay =>
+                (about: "Reproduce a seeded batch from its recorded config hash, e.g. for forensics")
+                (@arg config_hash: --("config-hash") +takes_value +required "Recorded config hash (provenance `config_hash`)")
+                (@arg seed: --seed +takes_value +required "Seed the batch was generated from (hex or base64)")
+                (@arg stream: --stream +takes_value "Substream the batch was generated from")
+                (@arg charset: --charset +takes_value "Recorded charset (provenance `charset`), unless named or a preset's")
+                (@arg constraints: --constraints +takes_value "Recorded constraints (provenance `constraints`), if any")
+                (@arg pattern: --pattern +takes_value conflicts_with[charset constraints] "Recorded pattern (provenance `pattern`), if any")
+                (@arg number: -n --number +takes_value "Number (count) of passwords to reproduce")
+            )
+            (@subcommand bench =>
+                (about: "Measure how many passwords per second this machine generates")
+                (@arg seconds: --seconds +takes_value "Duration of the measurement (default: 5)")
+                (@arg length: -l --length +takes_value "Length of the passwords (default: 24)")
+                (@arg charsets: "Selection of charsets to use (default: std64)")
+            )
+            (@subcommand plan =>
+                (about: "Estimate collisions within a planned batch of ids or codes")
+                (@arg count: --count +takes_value +required "Number of passwords in the batch, e.g. 1e9")
+                (@arg length: -l --length +takes_value "Length of each password (default: 24)")
+                (@arg charsets: -c --charsets +takes_value "Selection of charsets to use (default: std64)")
+                (@arg max_probability: --("max-probability") +takes_value "Acceptable probability of any collision, for the recommended length (default: 1e-6)")
+            )
+            (@subcommand credpair =>
+                (about: "Generate usernames with policy-checked passwords, e.g. for service accounts")
+                (@arg policy: --policy +takes_value "Preset whose charset, length and policy the passwords follow (default: std64)")
+                (@arg username_style: --("username-style") +takes_value "Style of the usernames: random|pronounceable (default: random)")
+                (@arg number: -n --number +takes_value "Number of credential pairs (default: 1)")
+                (@arg format: -f --format +takes_value "Output format: plain|json|yaml")
+            )
+            (@subcommand dburl =>
+                (about: "Generate a database password and print the connection string with it URL-encoded")
+                (@arg engine: --engine +takes_value +required "Database engine: postgres|mysql|mariadb|mssql|mongodb|redis")
+                (@arg host: --host +takes_value +required "Host name or IP address of the server")
+                (@arg user: --user +takes_value +required "Login name")
+                (@arg port: --port +takes_value "Port (default: the engine's default port)")
+                (@arg database: --database +takes_value "Name of the database")
+                (@arg policy: --policy +takes_value "Preset whose charset, length and policy the password follows (default: std64)")
+                (@arg format: -f --format +takes_value "Output format: plain (URL only)|json|yaml (URL and components)")
+            )
+            (@subcommand proquint =>
+                (about: "Generate proquints (e.g. lusab-babad) as readable IDs, or encode and decode secrets as proquints")
+                (@arg quints: --quints +takes_value conflicts_with[encode decode] "Proquints per ID, 16 bits each (default: 8)")
+                (@arg number: -n --number +takes_value conflicts_with[encode decode] "Number of IDs (default: 1)")
+                (@arg encode: --encode +takes_value conflicts_with[decode] "Encode this hex or base64 secret of an even number of bytes")
+                (@arg decode: --decode +takes_value "Decode these proquints, printing the secret as hex")
+                (@arg base64: --base64 requires[decode] "Print the decoded secret as base64 instead of hex")
+            )
+            (@subcommand pin =>
+                (about: "Generate numeric PINs, rejecting weak ones such as 1234, 0000 or years")
+                (@arg length: -l --length +takes_value "Digits per PIN (default: 6)")
+                (@arg number: -n --number +takes_value "Number of PINs (default: 1)")
+                (@arg quiet: -q --quiet "Don't report the entropy")
+                (@arg seed: --seed +takes_value "Generate reproducibly from this 32-byte seed (hex or base64)")
+                (@arg entropy_source: --("entropy-source") +takes_value conflicts_with[seed] "Draw all randomness from os or pkcs11:<module> (PIN in $YAPG_PKCS11_PIN)")
+                (@arg rng: --rng +takes_value conflicts_with[seed entropy_source] "RNG to draw from: thread (default), os or chacha (reseeded from the OS)")
+            )
+            (@subcommand rfc1751 =>
+                (about: "Generate 64-bit keys as RFC 1751 (S/Key) words, or encode and decode secrets as such words")
+                (@arg keys: --keys +takes_value conflicts_with[encode decode] "64-bit keys per output, six words each (default: 2)")
+                (@arg number: -n --number +takes_value conflicts_with[encode decode] "Number of outputs (default: 1)")
+                (@arg encode: --encode +takes_value conflicts_with[decode] "Encode this hex or base64 secret of a multiple of 8 bytes")
+                (@arg decode: --decode +takes_value "Decode these words, printing the secret as hex")
+                (@arg base64: --base64 requires[decode] "Print the decoded secret as base64 instead of hex")
+            )
+            (@subcommand diceware =>
+                (about: "Build a passphrase from physical dice rolls, e.g. 41526")
+                (@arg wordlist: --wordlist +takes_value "Wordlist (file or name) in diceware order (default: eff-long)")
+                (@arg words: -w --words +takes_value "Number of words to ask for (default: enough for 100 bits)")
+                (@arg separator: --separator +takes_value "Separator between words (default: -)")
+                (@arg rolls: ... "Rolls, one per word (default: ask on stdin)")
+            )
+            (@subcommand history =>
+                (about: "Look up recorded fingerprints (see `--history`)")
+                (@setting SubcommandRequiredElseHelp)
+                (@subcommand search =>
+                    (about: "Print entries whose label contains the given one")
+                    (@arg label: +required "Label to look for, ignoring case")
+                )
+            )
+            (@subcommand roll =>
+                (about: "Roll dice without bias, e.g. `yapg roll 3d6`")
+                (@arg dice: +required "Dice to roll, e.g. 1d20")
+            )
+            (@subcommand int =>
+                (about: "Print a random integer without bias")
+                (@arg min: --min +takes_value +allow_hyphen_values "Smallest possible value (default: 0)")
+                (@arg max: --max +takes_value +allow_hyphen_values +required "Largest possible value")
+            )
+            (@subcommand net =>
+                (about: "Generate random identifiers for lab and test networks")
+                (@setting SubcommandRequiredElseHelp)
+                (@subcommand color =>
+                    (about: "Print random hex colors, e.g. #3fa2c9")
+                    (@arg number: -n --number +takes_value "Number of colors (default: 1)")
+                )
+                (@subcommand mac =>
+                    (about: "Print random locally administered unicast MAC addresses")
+                    (@arg number: -n --number +takes_value "Number of addresses (default: 1)")
+                )
+                (@subcommand ipv6 =>
+                    (about: "Print random 64-bit IPv6 interface identifiers")
+                    (@arg number: -n --number +takes_value "Number of identifiers (default: 1)")
+                    (@arg prefix: --prefix +takes_value "Print full addresses in this /64 network, e.g. fd00:1::/64")
+                )
+            )
+        )
+        // hyphenated subcommand names are not supported by `clap_app!`
+        .subcommand(
+            clap::SubCommand::with_name("encode-words")
+                .about("Re-encode a hex or base64 secret as words")
+                .arg(
+                    clap::Arg::with_name("wordlist")
+                        .long("wordlist")
+                        .takes_value(true)
+                        .help("Wordlist (file or name) to encode with (default: eff-long)"),
+                )
+                .arg(
+                    clap::Arg::with_name("secret")
+                        .required(true)
+                        .help("Secret as hex or base64"),
+                ),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("decode-words")
+                .about("Decode words created by `yapg encode-words`")
+                .arg(
+                    clap::Arg::with_name("wordlist")
+                        .long("wordlist")
+                        .takes_value(true)
+                        .help("Wordlist (file or name) to decode with (default: eff-long)"),
+                )
+                .arg(
+                    clap::Arg::with_name("base64")
+                        .long("base64")
+                        .help("Print the secret as base64 instead of hex"),
+                )
+                .arg(
+                    clap::Arg::with_name("words")
+                        .required(true)
+                        .multiple(true)
+                        .help("Words to decode"),
+                ),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("verify-code")
+                .about("Check whether a code belongs to a batch")
+                .arg(
+                    clap::Arg::with_name("codebook")
+                        .long("codebook")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Code book written by `yapg --codebook`"),
+                )
+                .arg(clap::Arg::with_name("code").help(
+                    "Code to check, defaults to the first line of stdin",
+                )),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("verify-temp")
+                .about("Check whether a temporary password has expired")
+                .arg(clap::Arg::with_name("password").help(
+                    "Password to check, defaults to the first line of stdin",
+                )),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("encoding-report")
+                .about("Report which chars of a password need escaping in URLs, basic-auth headers and .pgpass files")
+                .arg(clap::Arg::with_name("password").help(
+                    "Password to check, defaults to the first line of stdin",
+                )),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("attack-sim")
+                .about("Estimate the guesses common attacks (brute force, masks, dictionary+rules) need to crack generated passwords")
+                .arg(
+                    clap::Arg::with_name("length")
+                        .short("l")
+                        .long("length")
+                        .takes_value(true)
+                        .help("Length of the passwords (default: the preset's)"),
+                )
+                .arg(
+                    clap::Arg::with_name("preset")
+                        .short("p")
+                        .long("preset")
+                        .takes_value(true)
+                        .conflicts_with("charsets")
+                        .help("Preset to use (default: std64)"),
+                )
+                .arg(
+                    clap::Arg::with_name("words")
+                        .short("w")
+                        .long("words")
+                        .takes_value(true)
+                        .conflicts_with_all(&["charsets", "preset", "length"])
+                        .help("Attack passphrases of this many words (eff-long) instead"),
+                )
+                .arg(
+                    clap::Arg::with_name("samples")
+                        .long("samples")
+                        .takes_value(true)
+                        .help("Number of passwords to attack (default: 1000)"),
+                )
+                .arg(
+                    clap::Arg::with_name("rate")
+                        .long("rate")
+                        .takes_value(true)
+                        .help("Guesses per second, for the time to crack (default: 1e10, a GPU rig against a fast hash)"),
+                )
+                .arg(
+                    clap::Arg::with_name("charsets")
+                        .help("Selection of charsets to use"),
+                ),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("simulate-policy")
+                .about("Measure how many random passwords a policy rejects and the entropy it destroys")
+                .arg(
+                    clap::Arg::with_name("policy_file")
+                        .long("policy-file")
+                        .takes_value(true)
+                        .required(true)
+                        .help("TOML file of the policy and the random passwords to put to it (see README)"),
+                )
+                .arg(
+                    clap::Arg::with_name("samples")
+                        .long("samples")
+                        .takes_value(true)
+                        .help("Number of random passwords to draw, e.g. 1e6 (default: 1e6)"),
+                ),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("pwned-bloom")
+                .about("Build a bloom filter for --pwned-db from a SHA-1 hash file, e.g. the HIBP corpus")
+                .arg(
+                    clap::Arg::with_name("fp_rate")
+                        .long("fp-rate")
+                        .takes_value(true)
+                        .help("Rate of false positives (default: 0.001)"),
+                )
+                .arg(
+                    clap::Arg::with_name("hashes")
+                        .required(true)
+                        .help("File of HASH:COUNT lines"),
+                )
+                .arg(
+                    clap::Arg::with_name("out")
+                        .required(true)
+                        .help("File to write the bloom filter to"),
+                ),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("ssh-key")
+                .about("Generate an SSH key protected by a new passphrase, handed to ssh-keygen without exposing it on the command line")
+                .arg(
+                    clap::Arg::with_name("file")
+                        .short("f")
+                        .long("file")
+                        .takes_value(true)
+                        .required(true)
+                        .help("New file to write the private key to (and the public key to <file>.pub)"),
+                )
+                .arg(
+                    clap::Arg::with_name("type")
+                        .short("t")
+                        .long("type")
+                        .takes_value(true)
+                        .help("Key type, as for ssh-keygen -t (default: ed25519)"),
+                )
+                .arg(
+                    clap::Arg::with_name("comment")
+                        .short("C")
+                        .long("comment")
+                        .takes_value(true)
+                        .help("Comment of the key (default: ssh-keygen's user@host)"),
+                )
+                .arg(
+                    clap::Arg::with_name("words")
+                        .short("w")
+                        .long("words")
+                        .takes_value(true)
+                        .help("Number of passphrase words (default: 8)"),
+                )
+                .arg(
+                    clap::Arg::with_name("wordlist")
+                        .long("wordlist")
+                        .takes_value(true)
+                        .help("Wordlist (file or name) of the passphrase (default: eff-long)"),
+                ),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("age-key")
+                .about("Generate an age keypair, printing the public key and the identity file encrypted with a new passphrase (needs the age feature)")
+                .arg(
+                    clap::Arg::with_name("output")
+                        .short("o")
+                        .long("output")
+                        .takes_value(true)
+                        .help("Write the encrypted identity file to this new file instead"),
+                )
+                .arg(
+                    clap::Arg::with_name("words")
+                        .short("w")
+                        .long("words")
+                        .takes_value(true)
+                        .help("Number of passphrase words (default: 8)"),
+                )
+                .arg(
+                    clap::Arg::with_name("wordlist")
+                        .long("wordlist")
+                        .takes_value(true)
+                        .help("Wordlist (file or name) of the passphrase (default: eff-long)"),
+                )
+                .arg(
+                    clap::Arg::with_name("work_factor")
+                        .long("work-factor")
+                        .takes_value(true)
+                        .help("Binary logarithm of the scrypt work factor (default: 18)"),
+                ),
         )
-        .get_matches()
     }
 
-    pub fn get() -> io::Result<Self> {
-        let matches = Self::get_matches();
+    pub fn from_matches(matches: &clap::ArgMatches) -> io::Result<Self> {
+        // preset, falling back to std64 (or console in keypad mode)
+        let t9 = matches.is_present("t9");
+        let preset = match matches.value_of("preset") {
+            Some(name) => yapg::presets::by_name(name).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Invalid preset: {}", name),
+                )
+            })?,
+            None if t9 => yapg::presets::console(),
+            None => yapg::presets::std64(),
+        };
+
+        // structured passwords
+        let pattern: Option<yapg::Pattern> = match matches.value_of("pattern") {
+            Some(pattern) => Some(pattern.parse()?),
+            None => None,
+        };
 
         // length and number of passwords
-        let length = matches
-            .value_of("length")
-            .map(parse_arg_or_exit(1))
-            .unwrap_or(DEFAULT_LENGTH);
+        let emoji = matches.is_present("emoji");
+        let colors = matches.is_present("colors");
+        let length = matches.value_of("length").map(parse_arg_or_exit(1));
+        let mut length = match length {
+            Some(length) => length,
+            None if emoji => EMOJI_LENGTH,
+            None if colors => COLOR_LENGTH,
+            None => pattern.as_ref().map_or(preset.length, yapg::Pattern::len),
+        };
         let number = matches
             .value_of("number")
             .map(parse_arg_or_exit(1))
@@ -74,22 +670,1958 @@ impl Args {
 
         // charset
         let mut charset = match matches.value_of("charsets") {
-            None => yapg::CharsetSpec::std64(),
             Some(inits) => inits.parse::<yapg::CharsetSpec>()?,
+            None => preset.charset.clone(),
+        };
+        if let Some(additions) = matches.value_of_os("added_chars") {
+            let additions = additions.to_str().ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Additional characters are not valid unicode (unpaired \
+                     surrogates?)",
+                )
+            })?;
+            charset.try_add(additions)?;
+        }
+
+        let mut charset: Vec<char> = if emoji {
+            yapg::emoji_charset()
+        } else if colors {
+            yapg::color_charset()
+        } else if let Some(pattern) = &pattern {
+            pattern.chars()
+        } else {
+            charset.into()
+        };
+        if let Some(ctx) = matches.value_of("safe_for") {
+            charset = parse_arg_or_exit::<yapg::SafetyContext>(1)(ctx)
+                .restrict(charset);
+        }
+        let digits = matches.value_of("digits").map(|digits| {
+            digits
+                .parse::<yapg::DigitSystem>()
+                .unwrap_or_else(|_| yapg::DigitSystem::for_locale(digits))
+        });
+        if let Some(digits) = digits {
+            charset = digits.localize(charset);
+        }
+        let accepted_digits = match matches.value_of("accepted_digits") {
+            Some(list) => Some(
+                list.split(',')
+                    .map(str::parse)
+                    .collect::<io::Result<Vec<yapg::DigitSystem>>>()?,
+            ),
+            None => digits.map(|digits| vec![digits]),
+        };
+        if let Some(accepted) = accepted_digits {
+            yapg::check_digits(&charset, &accepted)?;
+        }
+        if matches.is_present("single_direction") {
+            yapg::BidiReport::of(&charset).check_unmixed()?;
+        }
+
+        if t9 {
+            yapg::t9_key_count(&charset)?;
+        }
+
+        // multi-char items, each counting as one character
+        let items = match matches.value_of("items") {
+            Some(list) => list
+                .split(',')
+                .map(str::parse)
+                .collect::<io::Result<Vec<yapg::Grapheme>>>()?,
+            None => vec![],
+        };
+        let mut all_items: Vec<yapg::Grapheme> =
+            charset.iter().map(|c| (*c).into()).collect();
+        all_items.extend(items.iter().cloned());
+        yapg::check_unambiguous(&all_items)?;
+
+        // passphrases from words
+        let wordlist = match matches.value_of("wordlist") {
+            Some(name) => Some(load_wordlist(name)?),
+            None if matches.is_present("words")
+                || matches.is_present("max_syllables") =>
+            {
+                Some(yapg::Wordlist::named("eff-long")?)
+            },
+            None => None,
+        };
+        let passphrase = match wordlist {
+            Some(wordlist) => {
+                let bits = wordlist.entropy_per_word();
+                let words = matches
+                    .value_of("words")
+                    .map(parse_arg_or_exit(1))
+                    .unwrap_or_else(|| {
+                        (ENTROPY_THRESHOLD as f64 / bits).ceil().max(1.0)
+                            as usize
+                    });
+                let separator = matches.value_of("separator").unwrap_or("-");
+                let mut ppg = yapg::PassphraseGenerator::new(wordlist, words)
+                    .separator(separator);
+                if let Some(max) = matches.value_of("max_syllables") {
+                    ppg = ppg.max_syllables(parse_arg_or_exit(1)(max));
+                    if !ppg.is_satisfiable() {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!(
+                                "No passphrase of {} words has at most {} \
+                                 syllables",
+                                words, max
+                            ),
+                        ));
+                    }
+                }
+                Some(ppg)
+            },
+            None => None,
         };
-        if let Some(additions) = matches.value_of("added_chars") {
-            charset += additions;
+
+        // pronounceable passwords from syllables
+        let pronounceable = matches.value_of("syllables").map(|n| {
+            yapg::PronounceableGenerator::new(
+                yapg::SyllableModel::default(),
+                parse_arg_or_exit(1)(n),
+            )
+        });
+
+        // pronounceable passwords from a Markov model of a corpus
+        let markov = match matches.value_of("markov") {
+            Some(path) => {
+                let corpus = std::fs::read_to_string(path)?;
+                let order = matches
+                    .value_of("order")
+                    .map(parse_arg_or_exit(1))
+                    .unwrap_or(DEFAULT_MARKOV_ORDER);
+                let model = yapg::MarkovModel::train(&corpus, order)?;
+                let mg = yapg::MarkovGenerator::new(model, 0);
+                let length = match matches.value_of("length") {
+                    Some(length) => parse_arg_or_exit(1)(length),
+                    None => mg
+                        .length_for_entropy(
+                            ENTROPY_THRESHOLD as f64,
+                            MAX_MARKOV_LENGTH,
+                        )
+                        .ok_or_else(|| {
+                            io::Error::new(
+                                io::ErrorKind::InvalidInput,
+                                format!(
+                                    "Corpus is too repetitive to reach {} \
+                                     bits within {} letters, set a length",
+                                    ENTROPY_THRESHOLD, MAX_MARKOV_LENGTH
+                                ),
+                            )
+                        })?,
+                };
+                Some(mg.length(length))
+            },
+            None => None,
+        };
+
+        // exact number of chars per charset
+        let composition = match matches.value_of("compose") {
+            Some(composition) => Some(composition.parse()?),
+            None => None,
+        };
+
+        // strings matching a regex
+        let regex = matches.value_of("regex").map(String::from);
+        if let Some(regex) = &regex {
+            regex_generator(regex)?;
         }
 
         // misc
+        let each_class = matches.is_present("each_class");
+        let constraints: Option<yapg::Constraints> =
+            match matches.value_of("constraints") {
+                Some(constraints) => Some(constraints.parse()?),
+                None => None,
+            };
+
+        // length from the entropy wanted with the effective charset
+        if let Some(bits) = matches.value_of("bits") {
+            let bits: f64 = parse_arg_or_exit(1)(bits);
+            let mut pwg = yapg::PasswordGenerator::from_items(all_items, 0)?
+                .require_each_class(each_class);
+            if let Some(constraints) = &constraints {
+                pwg = pwg.constraints(constraints.clone())?;
+            }
+            length = pwg.length_for_entropy(bits).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "No length reaches {} bits with this charset",
+                        bits
+                    ),
+                )
+            })?;
+        }
+        // legacy systems ignore what is beyond their limits
         let quiet = matches.is_present("quiet");
+        let limit = preset.limit;
+        let capped = limit.effective_length(&charset, length);
+        if capped < length {
+            if !quiet {
+                eprintln!(
+                    "Warning: only {} chars count with preset {}, capping the \
+                     length",
+                    capped, preset.name
+                );
+            }
+            length = capped;
+        }
+        let expires_in: Option<u64> =
+            matches.value_of("expires_in").map(parse_arg_or_exit(1));
+        if expires_in.is_some() {
+            preset.policy.check_length(length + yapg::TEMP_SUFFIX_LEN)?;
+        } else {
+            preset.policy.check_length(length)?;
+        }
+        let keymap_warnings = matches.value_of("preset") == Some("console");
+        let fingerprint = matches.is_present("fingerprint");
+        let format = matches
+            .value_of("format")
+            .map(parse_arg_or_exit(1))
+            .unwrap_or(yapg::OutputFormat::Plain);
+        let viz = matches.value_of("viz").map(parse_arg_or_exit(1));
+        let provenance = matches.is_present("provenance");
+        let encoding_report = matches.is_present("encoding_report");
+
+        let fit_to = matches.value_of("fit_to").map(parse_arg_or_exit(1));
+
+        // reproducible generation
+        let seed = match matches.value_of("seed") {
+            Some(seed) => Some(parse_seed(seed)?),
+            None => None,
+        };
+        let stream = matches.value_of("stream").map(parse_arg_or_exit(1));
+        let min_distance =
+            matches.value_of("min_distance").map(parse_arg_or_exit(1));
+        let match_filter = parse_regex(matches.value_of("match_filter"))?;
+        let reject_filter = parse_regex(matches.value_of("reject_filter"))?;
+        let dictionary = if matches.is_present("no_dictionary_words") {
+            Some(yapg::Dictionary::embedded(yapg::MIN_DICTIONARY_WORD_LEN))
+        } else {
+            None
+        };
+        let hibp = matches.is_present("hibp");
+        let pwned_db = match matches.value_of("pwned_db") {
+            Some(path) => Some(yapg::PwnedDb::open(path)?),
+            None => None,
+        };
+        let codebook = matches.value_of("codebook").map(String::from);
+        let on_generated = matches.value_of("on_generated").map(String::from);
+        let entropy_source =
+            matches.value_of("entropy_source").map(String::from);
+        let rng = match matches.value_of("rng") {
+            Some(rng) => Some(rng.parse()?),
+            None => None,
+        };
+        let history = matches.value_of("history").map(String::from);
+        let export = matches.value_of("export").map(String::from);
+        if let Some(var) = &export {
+            yapg::shell_export(var, "")?;
+        }
+        let share = matches.value_of("share").map(String::from);
+        #[cfg(feature = "share")]
+        if let Some(target) = &share {
+            target.parse::<yapg::ShareTarget>()?;
+        }
+
+        Ok(Args {
+            passphrase,
+            pronounceable,
+            markov,
+            composition,
+            pattern,
+            regex,
+            viz,
+            encoding_report,
+            fit_to,
+            policy: preset.policy,
+            limit,
+            number,
+            length,
+            charset,
+            items,
+            each_class,
+            constraints,
+            keymap_warnings,
+            t9,
+            emoji,
+            colors,
+            fingerprint,
+            format,
+            provenance,
+            expires_in,
+            seed,
+            stream,
+            min_distance,
+            match_filter,
+            reject_filter,
+            dictionary,
+            hibp,
+            pwned_db,
+            codebook,
+            on_generated,
+            entropy_source,
+            rng,
+            history,
+            export,
+            share,
+            quiet,
+        })
+    }
+}
+
+/// Makes `pwg` draw from the RNG selected by `--seed`, `--stream`, `--rng`
+/// or `--entropy-source`, exiting if it cannot be opened.
+fn with_rng(
+    mut pwg: yapg::PasswordGenerator,
+    args: &Args,
+) -> yapg::PasswordGenerator {
+    if let Some(seed) = args.seed {
+        pwg = pwg.seeded(seed);
+    }
+    if let Some(stream) = args.stream {
+        // `--stream` requires `--seed`, so the generator is seeded
+        pwg = pwg.stream(stream).unwrap();
+    }
+    let opened = match (args.rng, &args.entropy_source) {
+        (Some(source), _) => pwg.random_source(source),
+        (None, Some(spec)) => {
+            open_entropy_source(spec).map(|source| pwg.entropy_source(source))
+        },
+        (None, None) => Ok(pwg),
+    };
+    match opened {
+        Ok(pwg) => pwg,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        },
+    }
+}
+
+/// The command line, with the values of `REDACTED_FLAGS` redacted, as
+/// recorded in the history.
+fn redacted_command_line() -> String {
+    let mut redact = false;
+    let args: Vec<String> = std::env::args_os()
+        .skip(1)
+        .map(|arg| {
+            // arguments need not be unicode, the record is for humans
+            let arg = arg.to_string_lossy().into_owned();
+            let flag = arg.split('=').next().unwrap_or_default();
+            let arg = if redact {
+                "<redacted>".to_string()
+            } else if arg.contains('=') && REDACTED_FLAGS.contains(&flag) {
+                format!("{}=<redacted>", flag)
+            } else {
+                arg
+            };
+            redact = REDACTED_FLAGS.contains(&arg.as_str());
+            if arg.contains(char::is_whitespace) {
+                format!("{:?}", arg)
+            } else {
+                arg
+            }
+        })
+        .collect();
+    args.join(" ")
+}
+
+/// Records the fingerprints of `passwords` in the history under `label`,
+/// if `--history` was given. Failing to do so only warns, as the passwords
+/// are fine.
+fn record_history(label: &Option<String>, passwords: &[String], entropy: f64) {
+    if let Some(label) = label {
+        let config = redacted_command_line();
+        let recorded = yapg::FingerprintKey::open_default().and_then(|key| {
+            let entry = yapg::HistoryEntry::new(
+                &key, label, &config, entropy, passwords,
+            );
+            yapg::History::open_default()?.append(&entry)
+        });
+        if let Err(e) = recorded {
+            eprintln!("Failed to record history: {}", e);
+        }
+    }
+}
+
+fn search_history(matches: &clap::ArgMatches) -> io::Result<()> {
+    let history = yapg::History::open_default()?;
+    for entry in history.search(matches.value_of("label").unwrap())? {
+        println!(
+            "{}  {}  {:.0} bits  {}",
+            entry.timestamp, entry.label, entry.entropy, entry.config
+        );
+        for fingerprint in entry.fingerprints {
+            println!("    {}", fingerprint);
+        }
+    }
+    Ok(())
+}
+
+/// Generates `n` passwords not found in breaches, if built with `hibp`.
+#[cfg(feature = "hibp")]
+fn generate_unbreached(
+    pwg: &mut yapg::PasswordGenerator,
+    n: usize,
+) -> io::Result<Vec<String>> {
+    pwg.generate_unbreached(n, &yapg::HibpClient::new())
+}
+
+#[cfg(not(feature = "hibp"))]
+fn generate_unbreached(
+    _pwg: &mut yapg::PasswordGenerator,
+    _n: usize,
+) -> io::Result<Vec<String>> {
+    Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "Checking for breached passwords requires the `hibp` feature",
+    ))
+}
+
+/// Generates `n` passwords with `generate`, redrawing those found in
+/// breaches, if built with `hibp`.
+#[cfg(feature = "hibp")]
+fn hibp_checked<F>(n: usize, generate: F) -> io::Result<Vec<String>>
+where
+    F: FnMut() -> io::Result<String>,
+{
+    yapg::generate_unbreached_with(n, &yapg::HibpClient::new(), generate)
+}
+
+#[cfg(not(feature = "hibp"))]
+fn hibp_checked<F>(_n: usize, _generate: F) -> io::Result<Vec<String>>
+where
+    F: FnMut() -> io::Result<String>,
+{
+    Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "Checking for breached passwords requires the `hibp` feature",
+    ))
+}
+
+/// Generates `args.number` passwords with `generate`, for generators other
+/// than `PasswordGenerator`, redrawing those found in breaches if asked to.
+/// Exits on failure.
+fn generate_checked<F>(args: &Args, mut generate: F) -> Vec<String>
+where
+    F: FnMut() -> String,
+{
+    let generated = match &args.pwned_db {
+        _ if args.hibp => hibp_checked(args.number, || Ok(generate())),
+        Some(db) => {
+            yapg::generate_unbreached_with(args.number, db, || Ok(generate()))
+        },
+        None => Ok((0..args.number).map(|_| generate()).collect()),
+    };
+    generated.unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1)
+    })
+}
+
+/// How often each of `passwords` appears in breaches, if built with `hibp`.
+#[cfg(feature = "hibp")]
+fn breach_counts(passwords: &[String]) -> io::Result<Vec<u64>> {
+    let hibp = yapg::HibpClient::new();
+    passwords.iter().map(|pw| hibp.breach_count(pw)).collect()
+}
+
+#[cfg(not(feature = "hibp"))]
+fn breach_counts(_passwords: &[String]) -> io::Result<Vec<u64>> {
+    Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "Checking for breached passwords requires the `hibp` feature",
+    ))
+}
+
+/// Posts each of `passwords` to the one-time-secret service `target` and
+/// returns the links retrieving them, if built with `share`.
+#[cfg(feature = "share")]
+fn share_links(target: &str, passwords: &[String]) -> io::Result<Vec<String>> {
+    let client = yapg::ShareClient::new(target.parse()?);
+    passwords.iter().map(|pw| client.share(pw)).collect()
+}
+
+#[cfg(not(feature = "share"))]
+fn share_links(
+    _target: &str,
+    _passwords: &[String],
+) -> io::Result<Vec<String>> {
+    Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "Sharing passwords requires the `share` feature",
+    ))
+}
+
+/// Prints the links retrieving `passwords` from `target`, exiting if any
+/// cannot be shared.
+fn print_share_links(target: &str, passwords: &[String]) {
+    match share_links(target, passwords) {
+        Ok(links) => {
+            for link in links {
+                println!("{}", link);
+            }
+        },
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        },
+    }
+}
+
+/// Generator of strings matching `regex`, if built with `regex-gen`.
+#[cfg(feature = "regex-gen")]
+fn regex_generator(regex: &str) -> io::Result<yapg::RegexGenerator> {
+    yapg::RegexGenerator::new(regex)
+}
+
+#[cfg(not(feature = "regex-gen"))]
+fn regex_generator(_regex: &str) -> io::Result<std::convert::Infallible> {
+    Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "Generating from regexes requires the `regex-gen` feature",
+    ))
+}
+
+/// Opens the `--entropy-source` given by `spec`.
+fn open_entropy_source(spec: &str) -> io::Result<Box<dyn yapg::EntropySource>> {
+    if spec == "os" {
+        return Ok(Box::new(yapg::OsEntropy::new()?));
+    }
+    match spec.strip_prefix("pkcs11:") {
+        #[cfg(all(feature = "pkcs11", unix))]
+        Some(module) => {
+            let pin = std::env::var("YAPG_PKCS11_PIN").ok();
+            let source = yapg::Pkcs11Entropy::open(module, 0, pin.as_deref())?;
+            Ok(Box::new(source))
+        },
+        #[cfg(not(all(feature = "pkcs11", unix)))]
+        Some(_) => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "PKCS#11 support requires the `pkcs11` feature (unix only)",
+        )),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid entropy source: {}", spec),
+        )),
+    }
+}
+
+/// Runs the `--on-generated` shell `command`, passing `event` in `YAPG_*`
+/// environment variables. Failures are reported, but don't stop generation.
+fn run_hook(command: &str, event: &yapg::GenerationEvent) {
+    let mut shell = if cfg!(windows) {
+        let mut shell = std::process::Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = std::process::Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    let status = shell
+        .arg(command)
+        .env("YAPG_FINGERPRINT", &event.fingerprint)
+        .env("YAPG_CONFIG_HASH", &event.config_hash)
+        .env("YAPG_LENGTH", event.length.to_string())
+        .env("YAPG_ENTROPY", event.entropy.to_string())
+        .stdout(std::process::Stdio::null())
+        .status();
+    match status {
+        Ok(status) if status.success() => {},
+        Ok(status) => eprintln!("Hook `{}` failed with {}", command, status),
+        Err(e) => eprintln!("Could not run hook `{}`: {}", command, e),
+    }
+}
+
+/// Reads all lines of the file at `path`, or of stdin if `path` is `None`.
+fn read_lines(path: Option<&str>) -> io::Result<Vec<String>> {
+    let content = match path {
+        Some(path) => std::fs::read_to_string(path)?,
+        None => {
+            let mut content = String::new();
+            io::stdin().read_to_string(&mut content)?;
+            content
+        },
+    };
+    Ok(content.lines().map(str::to_string).collect())
+}
+
+fn shuffle_lines(matches: &clap::ArgMatches) -> io::Result<()> {
+    let mut lines = read_lines(matches.value_of("file"))?;
+    yapg::PasswordGenerator::new(vec![], 0).shuffle_with(&mut lines);
+    for line in lines {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+fn check_passwords(matches: &clap::ArgMatches) -> io::Result<()> {
+    let min_bits = matches
+        .value_of("min_bits")
+        .map(parse_arg_or_exit(1))
+        .unwrap_or_else(|| yapg::Strength::Strong.min_bits());
+    let passwords = match matches.values_of("passwords") {
+        Some(passwords) => passwords.map(String::from).collect(),
+        None => read_lines(None)?,
+    };
+    let breaches = if matches.is_present("hibp") {
+        breach_counts(&passwords)?
+    } else if let Some(path) = matches.value_of("pwned_db") {
+        let db = yapg::PwnedDb::open(path)?;
+        passwords
+            .iter()
+            .map(|pw| db.breach_count(pw))
+            .collect::<io::Result<_>>()?
+    } else {
+        vec![0; passwords.len()]
+    };
+    let dictionary = yapg::Dictionary::embedded(yapg::MIN_DICTIONARY_WORD_LEN);
+    let mut failed = 0;
+    for (i, password) in passwords.iter().enumerate() {
+        let report = yapg::analyze_with(password, &dictionary);
+        println!(
+            "Password {}: {}, about {:.0} bits ({:.0} if random)",
+            i + 1,
+            report.strength(),
+            report.entropy,
+            report.naive_entropy
+        );
+        for weakness in report.weaknesses.iter() {
+            println!("    {}", weakness);
+        }
+        match breaches[i] {
+            0 => {},
+            // bloom filters and hash files without counts report 1
+            1 => println!("    found in known breaches"),
+            n => println!("    found in {} breaches", n),
+        }
+        if report.entropy < min_bits || breaches[i] > 0 {
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} of {} passwords have fewer than {} bits or are breached",
+                failed,
+                passwords.len(),
+                min_bits
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn simulate_attacks(matches: &clap::ArgMatches) -> io::Result<()> {
+    let samples = matches
+        .value_of("samples")
+        .map(parse_arg_or_exit(1))
+        .unwrap_or(DEFAULT_ATTACK_SAMPLES);
+    let rate: f64 = matches
+        .value_of("rate")
+        .map(parse_arg_or_exit(1))
+        .unwrap_or(DEFAULT_GUESS_RATE);
+    let (passwords, entropy) = match matches.value_of("words") {
+        Some(words) => {
+            let words = parse_arg_or_exit(1)(words);
+            let mut ppg = yapg::PassphraseGenerator::eff_long().words(words);
+            (ppg.generate_n(samples), ppg.entropy())
+        },
+        None => {
+            let name = matches.value_of("preset").unwrap_or("std64");
+            let preset = yapg::presets::by_name(name).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Unknown preset {} (see `yapg presets list`)",
+                        name
+                    ),
+                )
+            })?;
+            let charset: Vec<char> = match matches.value_of("charsets") {
+                Some(inits) => inits.parse::<yapg::CharsetSpec>()?.into(),
+                None => preset.charset.into(),
+            };
+            if charset.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Cannot simulate attacks on an empty charset",
+                ));
+            }
+            let length = matches
+                .value_of("length")
+                .map(parse_arg_or_exit(1))
+                .unwrap_or(preset.length);
+            let mut pwg = yapg::PasswordGenerator::new(charset, length);
+            (pwg.generate_n(samples), pwg.entropy_bits())
+        },
+    };
+
+    let attacks = yapg::AttackSimulator::new(yapg::Dictionary::embedded(1));
+    let report = attacks.simulate(&passwords);
+    println!("Entropy of the generator: {:.1} bits", entropy);
+    for result in report.results.iter() {
+        print!(
+            "{}: finds {} of {}",
+            result.attack, result.found, result.samples
+        );
+        match result.median_bits {
+            Some(bits) => println!(", median {}", crack_effort(bits, rate)),
+            None => println!(),
+        }
+    }
+    match report.best_median_bits {
+        Some(bits) => println!(
+            "Best attack per password: median {}",
+            crack_effort(bits, rate)
+        ),
+        None => println!("No attack finds most of the passwords"),
+    }
+    Ok(())
+}
+
+/// `2^bits` guesses and the time they take at `rate` guesses per second.
+fn crack_effort(bits: f64, rate: f64) -> String {
+    let seconds = bits.exp2() / rate;
+    let units = [
+        ("years", 365.25 * 86400.0),
+        ("days", 86400.0),
+        ("hours", 3600.0),
+        ("minutes", 60.0),
+    ];
+    let time = units.iter().find(|(_, secs)| seconds >= *secs).map_or(
+        format!("{:.1} seconds", seconds),
+        |(unit, secs)| {
+            let n = seconds / secs;
+            if n < 1e6 {
+                format!("{:.1} {}", n, unit)
+            } else {
+                format!("{:.1e} {}", n, unit)
+            }
+        },
+    );
+    format!("2^{:.1} guesses ({} at {:e} guesses/s)", bits, time, rate)
+}
+
+fn simulate_policy(matches: &clap::ArgMatches) -> io::Result<()> {
+    let path = matches.value_of("policy_file").unwrap();
+    let spec: yapg::PolicySpec = std::fs::read_to_string(path)?.parse()?;
+    let samples: f64 = matches
+        .value_of("samples")
+        .map(parse_arg_or_exit(1))
+        .unwrap_or(DEFAULT_POLICY_SAMPLES);
+    if !(samples >= 1.0 && samples <= usize::MAX as f64) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid number of samples: {}", samples),
+        ));
+    }
+    let sim = spec.simulate(samples as usize);
+    println!(
+        "Passed: {} of {} random passwords ({:.2}%)",
+        sim.passed,
+        sim.samples,
+        sim.pass_rate() * 100.0
+    );
+    println!("Entropy without the policy: {:.1} bits", sim.entropy);
+    println!(
+        "Effective entropy: {:.1} bits ({:.2} bits lost)",
+        sim.effective_entropy(),
+        sim.entropy_lost()
+    );
+    if sim.passed == 0 {
+        eprintln!(
+            "No sample passed, so the policy leaves less than {:.1} bits!",
+            (sim.entropy - (sim.samples as f64).log2()).max(0.0)
+        );
+    }
+    Ok(())
+}
+
+fn build_pwned_bloom(matches: &clap::ArgMatches) -> io::Result<()> {
+    let fp_rate: f64 = matches
+        .value_of("fp_rate")
+        .map(parse_arg_or_exit(1))
+        .unwrap_or(DEFAULT_BLOOM_FP_RATE);
+    if !(fp_rate > 0.0 && fp_rate < 1.0) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "The rate of false positives must be between 0 and 1",
+        ));
+    }
+    // the corpus is too large to hold, so it is read twice
+    let path = matches.value_of("hashes").unwrap();
+    let open = || std::fs::File::open(path).map(io::BufReader::new);
+    let mut count = 0;
+    for line in open()?.lines() {
+        if !line?.trim().is_empty() {
+            count += 1;
+        }
+    }
+    let mut bloom = yapg::PwnedBloom::new(count, fp_rate);
+    for line in open()?.lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            bloom.insert_line(&line)?;
+        }
+    }
+    let out = std::fs::File::create(matches.value_of("out").unwrap())?;
+    let mut out = io::BufWriter::new(out);
+    bloom.write_to(&mut out)?;
+    io::Write::flush(&mut out)?;
+    eprintln!("Added {} hashes", count);
+    Ok(())
+}
+
+fn truncate_secrets(matches: &clap::ArgMatches) -> io::Result<()> {
+    let max_bytes = matches
+        .value_of("bytes")
+        .map(parse_arg_or_exit(1))
+        .unwrap_or(yapg::BCRYPT_MAX_BYTES);
+    let dictionary = yapg::Dictionary::embedded(yapg::MIN_DICTIONARY_WORD_LEN);
+    for (i, secret) in read_lines(matches.value_of("file"))?.iter().enumerate()
+    {
+        let truncated = yapg::truncate_to_bytes(secret, max_bytes);
+        if truncated.len() < secret.len() {
+            // the secrets are not ours, so only estimates are available
+            let before = yapg::analyze_with(secret, &dictionary).entropy;
+            let after = yapg::analyze_with(truncated, &dictionary).entropy;
+            eprintln!(
+                "Secret {}: dropped {} of {} chars, losing about {:.0} of \
+                 {:.0} bits (estimated)",
+                i + 1,
+                secret.chars().count() - truncated.chars().count(),
+                secret.chars().count(),
+                before - after,
+                before
+            );
+        }
+        println!("{}", truncated);
+    }
+    Ok(())
+}
+
+fn pick_lines(matches: &clap::ArgMatches) -> io::Result<()> {
+    let k = matches.value_of("k").map(parse_arg_or_exit(1)).unwrap_or(1);
+    let reader: Box<dyn BufRead> = match matches.value_of("file") {
+        Some(path) => Box::new(io::BufReader::new(std::fs::File::open(path)?)),
+        None => Box::new(io::BufReader::new(io::stdin())),
+    };
+
+    // stream the lines, stopping at (and remembering) the first read error
+    let mut error = None;
+    let lines = reader.lines().scan(&mut error, |error, line| match line {
+        Ok(line) => Some(line),
+        Err(e) => {
+            **error = Some(e);
+            None
+        },
+    });
+    let picked = yapg::PasswordGenerator::new(vec![], 0).pick_with(lines, k);
+    if let Some(e) = error {
+        return Err(e);
+    }
+
+    for line in picked {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+fn extend_base(matches: &clap::ArgMatches) -> io::Result<()> {
+    let charset: Vec<char> = match matches.value_of("charsets") {
+        Some(inits) => inits.parse::<yapg::CharsetSpec>()?.into(),
+        None => yapg::CharsetSpec::std64().construct(),
+    };
+    let target = matches
+        .value_of("target")
+        .map(parse_arg_or_exit(1))
+        .unwrap_or(ENTROPY_THRESHOLD as f64);
+    if target > 0.0 && charset.len() < 2 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Charset needs at least two characters",
+        ));
+    }
+    let length = yapg::Policy::default()
+        .length_for_entropy(charset.len(), target)
+        .map_err(io::Error::from)?;
+    let extended = yapg::PasswordGenerator::new(charset, length)
+        .extend(matches.value_of("base").unwrap());
+    eprintln!(
+        "Only the {} appended chars are random: {:.1} bits (the base adds \
+         nothing that can be relied upon)",
+        length, extended.random_bits
+    );
+    println!("{}", extended.password);
+    Ok(())
+}
+
+fn replay_batch(matches: &clap::ArgMatches) -> io::Result<()> {
+    let config_hash = matches.value_of("config_hash").unwrap();
+    let unknown = || {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "No known configuration has config hash {}, pass the recorded \
+                 charset, constraints and pattern with --charset, \
+                 --constraints and --pattern",
+                config_hash
+            ),
+        )
+    };
+    let pwg = match matches.value_of("pattern") {
+        Some(pattern) => {
+            let pwg = yapg::PasswordGenerator::from_pattern(pattern)?;
+            if pwg.config_hash() != config_hash {
+                return Err(unknown());
+            }
+            eprintln!("Reconstructed pattern {}", pattern);
+            pwg
+        },
+        None => {
+            let recorded: Vec<Vec<char>> = matches
+                .value_of("charset")
+                .map(|c| c.chars().collect())
+                .into_iter()
+                .collect();
+            let constraints: Option<yapg::Constraints> =
+                matches.value_of("constraints").map(str::parse).transpose()?;
+            let (charset, length) = yapg::find_configuration(
+                config_hash,
+                &recorded,
+                constraints.as_ref(),
+            )
+            .ok_or_else(unknown)?;
+            eprintln!(
+                "Reconstructed charset {} with length {}",
+                yapg::CharsetSpec::from_chars(&charset).to_spec_string(),
+                length
+            );
+            let pwg = yapg::PasswordGenerator::new(charset, length);
+            match constraints {
+                Some(constraints) => pwg.constraints(constraints)?,
+                None => pwg,
+            }
+        },
+    };
+
+    let seed = parse_seed(matches.value_of("seed").unwrap())?;
+    let mut pwg = pwg.seeded(seed);
+    if let Some(stream) = matches.value_of("stream") {
+        pwg = pwg.stream(parse_arg_or_exit(1)(stream))?;
+    }
+    let number = matches
+        .value_of("number")
+        .map(parse_arg_or_exit(1))
+        .unwrap_or(DEFAULT_NUMBER);
+    for password in pwg.generate_n(number) {
+        println!("{}", password);
+    }
+    Ok(())
+}
+
+fn run_bench(matches: &clap::ArgMatches) -> io::Result<()> {
+    let charset: Vec<char> = match matches.value_of("charsets") {
+        Some(inits) => inits.parse::<yapg::CharsetSpec>()?.into(),
+        None => yapg::CharsetSpec::std64().construct(),
+    };
+    if charset.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Cannot benchmark an empty charset",
+        ));
+    }
+    let length = matches
+        .value_of("length")
+        .map(parse_arg_or_exit(1))
+        .unwrap_or_else(|| yapg::presets::std64().length);
+    let seconds: f64 =
+        matches.value_of("seconds").map(parse_arg_or_exit(1)).unwrap_or(5.0);
+    if !(0.0..=86400.0).contains(&seconds) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Benchmark duration must be between 0 and 86400 seconds",
+        ));
+    }
+    let duration = std::time::Duration::from_secs_f64(seconds);
+    let mut pwg = yapg::PasswordGenerator::new(charset, length);
+
+    eprintln!("Generating passwords for {:.1} seconds...", seconds);
+    let allocated = allocation_stats();
+    let start = std::time::Instant::now();
+    let mut passwords: usize = 0;
+    loop {
+        // checking the clock for every password would dominate short ones
+        for _ in 0..1024 {
+            std::hint::black_box(pwg.generate());
+        }
+        passwords += 1024;
+        if start.elapsed() >= duration {
+            break;
+        }
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+    let allocated = allocated
+        .zip(allocation_stats())
+        .map(|(before, after)| (after.0 - before.0, after.1 - before.1));
+
+    println!("passwords:    {} in {:.2} s", passwords, elapsed);
+    println!("throughput:   {:.0} passwords/s", passwords as f64 / elapsed);
+    println!(
+        "              {:.0} chars/s",
+        (passwords * length) as f64 / elapsed
+    );
+    match allocated {
+        Some((allocations, bytes)) => println!(
+            "allocations:  {:.2} per password ({:.1} bytes)",
+            allocations as f64 / passwords as f64,
+            bytes as f64 / passwords as f64
+        ),
+        None => println!("allocations:  not counted without alloc-stats"),
+    }
+    Ok(())
+}
+
+fn rotate_secret(matches: &clap::ArgMatches) -> io::Result<()> {
+    let master = match matches.value_of("master_file") {
+        Some(path) => std::fs::read_to_string(path)?,
+        None => {
+            let mut line = String::new();
+            io::stdin().read_line(&mut line)?;
+            line
+        },
+    };
+    let period = matches
+        .value_of("period")
+        .map(parse_arg_or_exit(1))
+        .unwrap_or(yapg::RotationPeriod::Month);
+    let rotation = yapg::Rotation::new(
+        yapg::parse_secret(&master)?,
+        matches.value_of("label").unwrap(),
+        period,
+    )?;
+
+    let charset: Vec<char> = match matches.value_of("charsets") {
+        Some(inits) => inits.parse::<yapg::CharsetSpec>()?.into(),
+        None => yapg::CharsetSpec::std64().construct(),
+    };
+    let length = matches
+        .value_of("length")
+        .map(parse_arg_or_exit(1))
+        .unwrap_or_else(|| yapg::presets::std64().length);
+    let pwg = yapg::PasswordGenerator::new(charset, length);
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    match matches.value_of("overlap") {
+        Some(days) => {
+            let overlap = parse_arg_or_exit::<u64>(1)(days) * 86400;
+            for index in rotation.periods_valid_at(now, overlap) {
+                let secret = rotation.secret(pwg.clone(), index);
+                println!("{}\t{}", period.label(index), secret);
+            }
+        },
+        None => println!("{}", rotation.secret_at(pwg, now)),
+    }
+    Ok(())
+}
+
+fn plan_batch(matches: &clap::ArgMatches) -> io::Result<()> {
+    let charset: Vec<char> = match matches.value_of("charsets") {
+        Some(inits) => inits.parse::<yapg::CharsetSpec>()?.into(),
+        None => yapg::CharsetSpec::std64().construct(),
+    };
+    let length = matches
+        .value_of("length")
+        .map(parse_arg_or_exit(1))
+        .unwrap_or_else(|| yapg::presets::std64().length);
+    let count = parse_arg_or_exit(1)(matches.value_of("count").unwrap());
+    let max_probability: f64 = matches
+        .value_of("max_probability")
+        .map(parse_arg_or_exit(1))
+        .unwrap_or(DEFAULT_MAX_COLLISION_PROBABILITY);
+    let plan = yapg::UniquenessPlan::new(count, charset.len(), length)?;
+
+    println!(
+        "Possible passwords: {:.3e} ({:.1} bits)",
+        plan.bits().exp2(),
+        plan.bits()
+    );
+    println!("Expected collisions: {:.3e}", plan.expected_collisions());
+    println!(
+        "Probability of any collision: {:.3e}",
+        plan.collision_probability()
+    );
+    println!(
+        "Expected passwords until the first collision: {:.3e}",
+        plan.expected_draws_until_collision()
+    );
+    match plan.recommended_length(max_probability) {
+        Some(length) => println!(
+            "Length for a collision probability of at most {:e}: {}",
+            max_probability, length
+        ),
+        None => println!(
+            "No length keeps the collision probability at most {:e}",
+            max_probability
+        ),
+    }
+    Ok(())
+}
+
+fn credential_pairs(matches: &clap::ArgMatches) -> io::Result<()> {
+    let name = matches.value_of("policy").unwrap_or("std64");
+    let preset = yapg::presets::by_name(name).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Unknown preset {} (see `yapg presets list`)", name),
+        )
+    })?;
+    let style = matches
+        .value_of("username_style")
+        .map(parse_arg_or_exit(1))
+        .unwrap_or(yapg::UsernameStyle::Random);
+    let number = matches.value_of("number").map(parse_arg_or_exit(1));
+    let format = matches
+        .value_of("format")
+        .map(parse_arg_or_exit(1))
+        .unwrap_or(yapg::OutputFormat::Plain);
+    let mut cpg = yapg::CredentialPairGenerator::new(&preset, style)?;
+    if cpg.password_entropy() < ENTROPY_THRESHOLD {
+        eprintln!("Low password entropy of {} bits!", cpg.password_entropy());
+    }
+    let pairs = cpg.generate_n(number.unwrap_or(1));
+    print!("{}", yapg::CredentialPair::render(&pairs, format)?);
+    Ok(())
+}
+
+fn database_url(matches: &clap::ArgMatches) -> io::Result<()> {
+    let engine: yapg::DatabaseEngine =
+        matches.value_of("engine").map(parse_arg_or_exit(1)).unwrap();
+    let name = matches.value_of("policy").unwrap_or("std64");
+    let preset = yapg::presets::by_name(name).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Unknown preset {} (see `yapg presets list`)", name),
+        )
+    })?;
+    let format = matches
+        .value_of("format")
+        .map(parse_arg_or_exit(1))
+        .unwrap_or(yapg::OutputFormat::Plain);
+    preset.policy.check_length(preset.length)?;
+    let required = preset
+        .policy
+        .required
+        .iter()
+        .fold(yapg::Constraints::new(), |constraints, name| {
+            constraints.at_least(*name, 1)
+        });
+    let mut pwg = preset.generator().constraints(required)?;
+    if pwg.entropy_bits() < ENTROPY_THRESHOLD as f64 {
+        eprintln!("Low password entropy of {:.1} bits!", pwg.entropy_bits());
+    }
+    let mut url = yapg::DatabaseUrl::new(
+        engine,
+        matches.value_of("host").unwrap(),
+        matches.value_of("user").unwrap(),
+    )
+    .password(&pwg.generate());
+    if let Some(port) = matches.value_of("port") {
+        url = url.port(parse_arg_or_exit(1)(port));
+    }
+    if let Some(database) = matches.value_of("database") {
+        url = url.database(database);
+    }
+    print!("{}", url.render(format)?);
+    Ok(())
+}
+
+fn proquints(matches: &clap::ArgMatches) -> io::Result<()> {
+    if let Some(secret) = matches.value_of("encode") {
+        println!("{}", yapg::proquint_encode(&yapg::parse_secret(secret)?)?);
+        return Ok(());
+    }
+    if let Some(quints) = matches.value_of("decode") {
+        let secret = yapg::proquint_decode(quints)?;
+        if matches.is_present("base64") {
+            println!("{}", yapg::base64_encode(&secret));
+        } else {
+            println!("{}", yapg::hex_encode(&secret));
+        }
+        return Ok(());
+    }
+    let quints = matches.value_of("quints").map(parse_arg_or_exit(1));
+    let number = matches.value_of("number").map(parse_arg_or_exit(1));
+    let mut pqg = yapg::ProquintGenerator::new(quints.unwrap_or(8));
+    if pqg.entropy() < ENTROPY_THRESHOLD as f64 {
+        eprintln!("Low entropy of {} bits!", pqg.entropy());
+    }
+    for id in pqg.generate_n(number.unwrap_or(1)) {
+        println!("{}", id);
+    }
+    Ok(())
+}
+
+fn pins(matches: &clap::ArgMatches) -> io::Result<()> {
+    let length = matches.value_of("length").map(parse_arg_or_exit(1));
+    let number = matches.value_of("number").map(parse_arg_or_exit(1));
+    let mut pg = yapg::PinGenerator::new(length.unwrap_or(6));
+    if let Some(seed) = matches.value_of("seed") {
+        pg = pg.seeded(parse_seed(seed)?);
+    }
+    if let Some(rng) = matches.value_of("rng") {
+        pg = pg.random_source(rng.parse()?)?;
+    }
+    if let Some(spec) = matches.value_of("entropy_source") {
+        pg = pg.entropy_source(open_entropy_source(spec)?);
+    }
+    if !matches.is_present("quiet") {
+        eprintln!(
+            "Rejecting {} weak PINs leaves {:.1} bits of entropy ({:.3} bits \
+             less)",
+            pg.rejected(),
+            pg.entropy(),
+            pg.entropy_cost()
+        );
+    }
+    for pin in pg.generate_n(number.unwrap_or(1)) {
+        println!("{}", pin);
+    }
+    Ok(())
+}
+
+fn rfc1751_keys(matches: &clap::ArgMatches) -> io::Result<()> {
+    if let Some(secret) = matches.value_of("encode") {
+        println!("{}", yapg::rfc1751_encode(&yapg::parse_secret(secret)?)?);
+        return Ok(());
+    }
+    if let Some(words) = matches.value_of("decode") {
+        let secret = yapg::rfc1751_decode(words)?;
+        if matches.is_present("base64") {
+            println!("{}", yapg::base64_encode(&secret));
+        } else {
+            println!("{}", yapg::hex_encode(&secret));
+        }
+        return Ok(());
+    }
+    let keys = matches.value_of("keys").map(parse_arg_or_exit(1));
+    let number = matches.value_of("number").map(parse_arg_or_exit(1));
+    let mut rg = yapg::Rfc1751Generator::new(keys.unwrap_or(2));
+    if rg.entropy() < ENTROPY_THRESHOLD as f64 {
+        eprintln!("Low entropy of {} bits!", rg.entropy());
+    }
+    for key in rg.generate_n(number.unwrap_or(1)) {
+        println!("{}", key);
+    }
+    Ok(())
+}
+
+fn diceware_passphrase(matches: &clap::ArgMatches) -> io::Result<()> {
+    let wordlist =
+        load_wordlist(matches.value_of("wordlist").unwrap_or("eff-long"))?;
+    let diceware = yapg::Diceware::new(&wordlist)?;
+    let mut words = vec![];
+    match matches.values_of("rolls") {
+        Some(rolls) => {
+            for roll in rolls {
+                words.push(diceware.word(roll)?.to_string());
+            }
+        },
+        None => {
+            let bits = diceware.entropy_per_word();
+            let count = matches
+                .value_of("words")
+                .map(parse_arg_or_exit(1))
+                .unwrap_or_else(|| {
+                    (ENTROPY_THRESHOLD as f64 / bits).ceil() as usize
+                });
+            let stdin = io::stdin();
+            let mut lines = stdin.lock().lines();
+            let mut roll = String::new();
+            while words.len() < count {
+                eprint!(
+                    "Roll {} dice for word {}/{}: ",
+                    diceware.dice(),
+                    words.len() + 1,
+                    count
+                );
+                let line = match lines.next() {
+                    Some(line) => line?,
+                    None => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::UnexpectedEof,
+                            "Input ended before all words were rolled",
+                        ))
+                    },
+                };
+                // partial rolls are continued on the next line
+                roll.push_str(&line);
+                match diceware.parse_roll(&roll) {
+                    Ok(yapg::Roll::Complete(_)) => {
+                        words.push(diceware.word(&roll)?.to_string());
+                        roll.clear();
+                    },
+                    Ok(yapg::Roll::Partial { missing }) => {
+                        eprintln!("{} more to go", missing);
+                        roll.push(' ');
+                    },
+                    Err(e) => {
+                        eprintln!("{}, please enter the whole roll again", e);
+                        roll.clear();
+                    },
+                }
+            }
+        },
+    }
+    let bits = diceware.entropy_per_word() * words.len() as f64;
+    eprintln!("Entropy: {:.1} bits, provided your dice are fair", bits);
+    println!("{}", words.join(matches.value_of("separator").unwrap_or("-")));
+    Ok(())
+}
+
+fn roll_dice(matches: &clap::ArgMatches) -> io::Result<()> {
+    let dice: yapg::Dice = matches.value_of("dice").unwrap().parse()?;
+    let rolls = yapg::PasswordGenerator::new(vec![], 0).roll_with(&dice);
+    let total: i64 = rolls.iter().sum();
+    let rolls: Vec<String> = rolls.iter().map(i64::to_string).collect();
+    println!("{} ({})", rolls.join(" "), total);
+    Ok(())
+}
+
+fn net_ids(kind: &str, matches: &clap::ArgMatches) -> io::Result<()> {
+    let number = matches.value_of("number").map(parse_arg_or_exit(1));
+    let mut nig = yapg::NetIdGenerator::new(kind.parse()?);
+    if let Some(prefix) = matches.value_of("prefix") {
+        let (addr, len) = match prefix.split_once('/') {
+            Some((addr, len)) => (addr, Some(len)),
+            None => (prefix, None),
+        };
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid /64 prefix: {}", prefix),
+            )
+        };
+        if len.is_some_and(|len| len != "64") {
+            return Err(invalid());
+        }
+        nig = nig.prefix(addr.parse().map_err(|_| invalid())?);
+    }
+    for id in nig.generate_n(number.unwrap_or(1)) {
+        println!("{}", id);
+    }
+    Ok(())
+}
+
+fn random_int(matches: &clap::ArgMatches) -> io::Result<()> {
+    let min = matches.value_of("min").map(parse_arg_or_exit(1)).unwrap_or(0);
+    let max = parse_arg_or_exit(1)(matches.value_of("max").unwrap());
+    if min > max {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Empty range: {}..={}", min, max),
+        ));
+    }
+    println!("{}", yapg::PasswordGenerator::new(vec![], 0).gen_int(min, max));
+    Ok(())
+}
+
+fn verify_code(matches: &clap::ArgMatches) -> io::Result<()> {
+    let book = yapg::CodeBook::load(matches.value_of("codebook").unwrap())?;
+    let code = match matches.value_of("code") {
+        Some(code) => code.to_string(),
+        None => {
+            let mut line = String::new();
+            io::stdin().read_line(&mut line)?;
+            line.trim_end_matches(&['\r', '\n'][..]).to_string()
+        },
+    };
+    if !book.verify(&code) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Code does not belong to the batch",
+        ));
+    }
+    println!("Valid code");
+    Ok(())
+}
+
+fn verify_temp(matches: &clap::ArgMatches) -> io::Result<()> {
+    let password = match matches.value_of("password") {
+        Some(password) => password.to_string(),
+        None => {
+            let mut line = String::new();
+            io::stdin().read_line(&mut line)?;
+            line.trim_end_matches(&['\r', '\n'][..]).to_string()
+        },
+    };
+    println!("Valid until {}", yapg::verify_temp_now(&password)?);
+    Ok(())
+}
+
+/// Contexts covered by `--encoding-report`, where credentials commonly end
+/// up unescaped.
+const ENCODING_CONTEXTS: [yapg::SafetyContext; 3] = [
+    yapg::SafetyContext::Url,
+    yapg::SafetyContext::BasicAuth,
+    yapg::SafetyContext::Pgpass,
+];
+
+/// Lists per context of `ENCODING_CONTEXTS` the chars of `password` needing
+/// escaping, one line each.
+fn encoding_report(password: &str) -> String {
+    let mut report = String::new();
+    for ctx in ENCODING_CONTEXTS.iter() {
+        let chars = ctx.unsafe_chars(password);
+        if chars.is_empty() {
+            report.push_str(&format!("  {}: safe\n", ctx));
+        } else {
+            let chars: Vec<String> =
+                chars.iter().map(|c| format!("{:?}", c)).collect();
+            report.push_str(&format!(
+                "  {}: needs escaping of {}\n",
+                ctx,
+                chars.join(" ")
+            ));
+        }
+    }
+    report
+}
+
+fn report_encoding(matches: &clap::ArgMatches) -> io::Result<()> {
+    let password = match matches.value_of("password") {
+        Some(password) => password.to_string(),
+        None => {
+            let mut line = String::new();
+            io::stdin().read_line(&mut line)?;
+            line.trim_end_matches(&['\r', '\n'][..]).to_string()
+        },
+    };
+    print!("{}", encoding_report(&password));
+    Ok(())
+}
+
+/// Prints color codes with the names and hex values of their colors, and a
+/// row of swatches if stdout is a terminal supporting truecolor.
+fn print_color_codes(codes: &[String]) {
+    let truecolor = io::stdout().is_terminal()
+        && std::env::var("COLORTERM")
+            .is_ok_and(|v| v == "truecolor" || v == "24bit");
+    for code in codes {
+        let colors = yapg::colors_of(code).unwrap();
+        let names: Vec<_> = colors.iter().map(|c| c.name).collect();
+        let hexes: Vec<_> = colors.iter().map(|c| c.hex()).collect();
+        println!("{}\t{}\t{}", code, names.join("-"), hexes.join(" "));
+        if truecolor {
+            let swatches: Vec<_> = colors.iter().map(|c| swatch(c)).collect();
+            println!("\t{}", swatches.join(" "));
+        }
+    }
+}
+
+/// Two blanks on a background of `color`, using 24-bit ANSI escape codes.
+fn swatch(color: &yapg::Color) -> String {
+    let [r, g, b] = color.rgb;
+    format!("\x1b[48;2;{};{};{}m  \x1b[0m", r, g, b)
+}
+
+/// Generates an SSH key with ssh-keygen, protected by a new passphrase
+/// printed to stderr, and prints its fingerprint.
+fn ssh_key(matches: &clap::ArgMatches) -> io::Result<()> {
+    use std::process::{Command, Stdio};
+
+    // required by clap
+    let file = matches.value_of("file").unwrap();
+    if std::path::Path::new(file).exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", file),
+        ));
+    }
+    let wordlist =
+        load_wordlist(matches.value_of("wordlist").unwrap_or("eff-long"))?;
+    let words = matches.value_of("words").map(parse_arg_or_exit(1));
+    let mut ppg = yapg::PassphraseGenerator::new(wordlist, words.unwrap_or(8));
+    if ppg.entropy() < ENTROPY_THRESHOLD as f64 {
+        eprintln!("Low passphrase entropy of {:.1} bits!", ppg.entropy());
+    }
+    let passphrase = ppg.generate();
+
+    // older versions ignore `SSH_ASKPASS_REQUIRE` and prompt on the terminal
+    // instead, taking whatever is typed as the passphrase
+    let version = openssh_version()?;
+    if version < (8, 4) {
+        return Err(io::Error::other(format!(
+            "OpenSSH {}.{} cannot take the passphrase from yapg, 8.4 or newer \
+             is needed",
+            version.0, version.1
+        )));
+    }
+    // instead of taking the passphrase as argument, visible to all users,
+    // ssh-keygen asks its askpass helper, i.e. yapg (see `main`), which
+    // reads it from the environment only ssh-keygen inherits
+    let askpass = |command: &mut Command| -> io::Result<()> {
+        command
+            .env("SSH_ASKPASS", std::env::current_exe()?)
+            .env("SSH_ASKPASS_REQUIRE", "force")
+            .env(SSH_PASSPHRASE_ENV, &passphrase)
+            .stdin(Stdio::null());
+        Ok(())
+    };
+    let mut keygen = Command::new("ssh-keygen");
+    keygen.args(["-q", "-f", file]);
+    keygen.args(["-t", matches.value_of("type").unwrap_or("ed25519")]);
+    if let Some(comment) = matches.value_of("comment") {
+        keygen.args(["-C", comment]);
+    }
+    askpass(&mut keygen)?;
+    let status = keygen.status()?;
+    if !status.success() {
+        return Err(io::Error::other(format!(
+            "ssh-keygen failed ({})",
+            status
+        )));
+    }
+
+    // make sure the passphrase printed is the one protecting the key
+    let mut check = Command::new("ssh-keygen");
+    check.args(["-y", "-f", file]).stdout(Stdio::null());
+    askpass(&mut check)?;
+    if !check.status()?.success() {
+        std::fs::remove_file(file)?;
+        std::fs::remove_file(format!("{}.pub", file))?;
+        return Err(io::Error::other(
+            "The key does not open with the generated passphrase, removed it",
+        ));
+    }
+
+    let fingerprint = Command::new("ssh-keygen")
+        .args(["-l", "-f", &format!("{}.pub", file)])
+        .stderr(Stdio::inherit())
+        .output()?;
+    if !fingerprint.status.success() {
+        return Err(io::Error::other(format!(
+            "ssh-keygen failed to fingerprint {}.pub ({})",
+            file, fingerprint.status
+        )));
+    }
+    io::Write::write_all(&mut io::stdout(), &fingerprint.stdout)?;
+    eprintln!("Passphrase: {}", passphrase);
+    Ok(())
+}
+
+/// Major and minor version of the installed OpenSSH, from `ssh -V`.
+fn openssh_version() -> io::Result<(u32, u32)> {
+    let output = std::process::Command::new("ssh").arg("-V").output()?;
+    // printed to stderr, e.g. "OpenSSH_9.2p1 Debian-2, OpenSSL 3.0.11"
+    let text = String::from_utf8_lossy(&output.stderr);
+    text.split("OpenSSH_")
+        .nth(1)
+        .and_then(|rest| {
+            let mut parts = rest.split(|c: char| !c.is_ascii_digit());
+            Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
+        })
+        .ok_or_else(|| {
+            io::Error::other(format!("Unknown SSH version: {}", text.trim()))
+        })
+}
+
+/// Generates an age keypair whose identity file is encrypted with a new
+/// passphrase, printed to stderr.
+#[cfg(feature = "age")]
+fn age_key(matches: &clap::ArgMatches) -> io::Result<()> {
+    let wordlist =
+        load_wordlist(matches.value_of("wordlist").unwrap_or("eff-long"))?;
+    let words = matches.value_of("words").map(parse_arg_or_exit(1));
+    let work_factor = matches
+        .value_of("work_factor")
+        .map(parse_arg_or_exit(1))
+        .unwrap_or(yapg::AGE_SCRYPT_WORK_FACTOR);
+    let mut ppg = yapg::PassphraseGenerator::new(wordlist, words.unwrap_or(8));
+    if ppg.entropy() < ENTROPY_THRESHOLD as f64 {
+        eprintln!("Low passphrase entropy of {:.1} bits!", ppg.entropy());
+    }
+    let passphrase = ppg.generate();
+    let keypair = yapg::AgeKeypair::generate()?;
+    let identity = keypair.encrypted_identity_file(&passphrase, work_factor)?;
+    match matches.value_of("output") {
+        Some(path) => {
+            use std::io::Write;
+            std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(path)?
+                .write_all(identity.as_bytes())?;
+            println!("{}", keypair.public_key());
+        },
+        None => print!("{}\n{}", keypair.public_key(), identity),
+    }
+    eprintln!("Passphrase: {}", passphrase);
+    Ok(())
+}
+
+#[cfg(not(feature = "age"))]
+fn age_key(_matches: &clap::ArgMatches) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "Generating age keys requires the `age` feature",
+    ))
+}
+
+/// Loads the wordlist at `path`, or the named one if there is no such file.
+fn load_wordlist(path: &str) -> io::Result<yapg::Wordlist> {
+    if std::path::Path::new(path).exists() {
+        yapg::Wordlist::load(path)
+    } else {
+        yapg::Wordlist::named(path)
+    }
+}
+
+fn list_wordlists() -> io::Result<()> {
+    for (name, source) in yapg::wordlist_sources()? {
+        let size = yapg::Wordlist::named(&name)?.len();
+        println!("{:<12} {:>6} words  {}", name, size, source);
+    }
+    Ok(())
+}
+
+fn filter_wordlist(matches: &clap::ArgMatches) -> io::Result<()> {
+    let mut list = load_wordlist(matches.value_of("file").unwrap())?;
+    let mut blocklist = yapg::Blocklist::default();
+    for locale in matches.values_of("locale").into_iter().flatten() {
+        blocklist.extend(yapg::Blocklist::for_locale(locale)?);
+    }
+    for path in matches.values_of("blocklist").into_iter().flatten() {
+        blocklist.extend(yapg::Blocklist::load(path)?);
+    }
+    let total = list.len();
+    let removed = list.remove_blocked(&blocklist);
+    eprintln!("Removed {} of {} words", removed, total);
+    for word in list.words() {
+        println!("{}", word);
+    }
+    Ok(())
+}
+
+fn check_wordlist(matches: &clap::ArgMatches) -> io::Result<()> {
+    let report = load_wordlist(matches.value_of("file").unwrap())?.report();
+    println!("Words:              {}", report.size);
+    println!("Entropy per word:   {:.2} bits", report.entropy_per_word);
+    println!("Average length:     {:.1} chars", report.average_length);
+    println!("Duplicates:         {}", report.duplicates.len());
+    for word in report.duplicates.iter().take(10) {
+        println!("  {}", word);
+    }
+    println!("Near-duplicates:    {}", report.near_duplicates);
+    println!("Prefix collisions:  {}", report.prefix_collisions.len());
+    for (prefix, word) in report.prefix_collisions.iter().take(10) {
+        println!("  {} / {}", prefix, word);
+    }
+    println!("Passphrase estimates (separated words):");
+    for bits in [64.0, 80.0, 100.0, 128.0].iter() {
+        match report.passphrase_estimate(*bits) {
+            Some((words, chars)) => println!(
+                "  {:>3} bits: {:>2} words, ~{} chars",
+                bits, words, chars
+            ),
+            None => println!("  {:>3} bits: unreachable", bits),
+        }
+    }
+    Ok(())
+}
+
+fn normalize_wordlist(matches: &clap::ArgMatches) -> io::Result<()> {
+    let mut list = load_wordlist(matches.value_of("file").unwrap())?;
+    let removed = list.normalize();
+    eprintln!(
+        "Kept {} words ({} near-duplicates removed), {:.2} bits per word",
+        list.len(),
+        removed,
+        list.entropy_per_word()
+    );
+    for word in list.words() {
+        println!("{}", word);
+    }
+    Ok(())
+}
+
+fn print_passphrases(
+    passphrases: &[String],
+    entropy: f64,
+    format: yapg::OutputFormat,
+    export: &Option<String>,
+    share: &Option<String>,
+    quiet: bool,
+) {
+    if !quiet && passphrases.len() < 10 {
+        eprintln!(
+            "Any eavesdropper will have an easy time trying one of your {} \
+             passphrases!",
+            passphrases.len()
+        );
+    }
+    if !quiet && entropy < ENTROPY_THRESHOLD as f64 {
+        eprintln!("Low passphrase entropy of {:.1} bits!", entropy);
+    }
+    match (export, share) {
+        (Some(var), _) => print_exports(var, passphrases),
+        (None, Some(target)) => print_share_links(target, passphrases),
+        (None, None) => print!("{}", format.render(passphrases, None)),
+    }
+}
+
+/// Prints an `export` line per password, numbering the variables if there
+/// are several.
+fn print_exports(var: &str, passwords: &[String]) {
+    for (i, pw) in passwords.iter().enumerate() {
+        let var = match passwords.len() {
+            1 => var.to_string(),
+            _ => format!("{}_{}", var, i + 1),
+        };
+        match yapg::shell_export(&var, pw) {
+            Ok(line) => println!("{}", line),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            },
+        }
+    }
+}
+
+fn encode_words(matches: &clap::ArgMatches) -> io::Result<()> {
+    let wordlist =
+        load_wordlist(matches.value_of("wordlist").unwrap_or("eff-long"))?;
+    let secret = yapg::parse_secret(matches.value_of("secret").unwrap())?;
+    println!("{}", yapg::encode_words(&secret, &wordlist)?.join(" "));
+    Ok(())
+}
+
+fn decode_words(matches: &clap::ArgMatches) -> io::Result<()> {
+    let wordlist =
+        load_wordlist(matches.value_of("wordlist").unwrap_or("eff-long"))?;
+    let words: Vec<&str> = matches.values_of("words").unwrap().collect();
+    let secret = yapg::decode_words(&words, &wordlist)?;
+    if matches.is_present("base64") {
+        println!("{}", yapg::base64_encode(&secret));
+    } else {
+        println!("{}", yapg::hex_encode(&secret));
+    }
+    Ok(())
+}
+
+fn print_schema(matches: &clap::ArgMatches) -> io::Result<()> {
+    let name = matches.value_of("name").unwrap();
+    let schema = yapg::json_schema(name).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid schema: {}", name),
+        )
+    })?;
+    print!("{}", schema);
+    Ok(())
+}
+
+fn print_version(matches: &clap::ArgMatches) -> io::Result<()> {
+    let info = yapg::BuildInfo::current();
+    if matches.is_present("json") {
+        println!("{}", info.to_json());
+    } else {
+        println!("{}", info);
+    }
+    Ok(())
+}
+
+fn list_presets() -> io::Result<()> {
+    for preset in yapg::presets::all() {
+        println!(
+            "{:<12} {:>3} chars from {:<22} {}",
+            preset.name,
+            preset.length,
+            preset.charset.to_spec_string(),
+            preset.description
+        );
+    }
+    Ok(())
+}
+
+/// Runs the subcommand given on the command line, if any.
+fn run_subcommand(matches: &clap::ArgMatches) -> Option<io::Result<()>> {
+    match matches.subcommand() {
+        ("presets", Some(_)) => Some(list_presets()),
+        ("shuffle", Some(sub)) => Some(shuffle_lines(sub)),
+        ("pick", Some(sub)) => Some(pick_lines(sub)),
+        ("truncate", Some(sub)) => Some(truncate_secrets(sub)),
+        ("check", Some(sub)) => Some(check_passwords(sub)),
+        ("verify-code", Some(sub)) => Some(verify_code(sub)),
+        ("verify-temp", Some(sub)) => Some(verify_temp(sub)),
+        ("encoding-report", Some(sub)) => Some(report_encoding(sub)),
+        ("pwned-bloom", Some(sub)) => Some(build_pwned_bloom(sub)),
+        ("simulate-policy", Some(sub)) => Some(simulate_policy(sub)),
+        ("attack-sim", Some(sub)) => Some(simulate_attacks(sub)),
+        ("ssh-key", Some(sub)) => Some(ssh_key(sub)),
+        ("age-key", Some(sub)) => Some(age_key(sub)),
+        ("encode-words", Some(sub)) => Some(encode_words(sub)),
+        ("decode-words", Some(sub)) => Some(decode_words(sub)),
+        ("wordlist", Some(sub)) => match sub.subcommand() {
+            ("filter", Some(sub)) => Some(filter_wordlist(sub)),
+            ("list", Some(_)) => Some(list_wordlists()),
+            ("check", Some(sub)) => Some(check_wordlist(sub)),
+            ("normalize", Some(sub)) => Some(normalize_wordlist(sub)),
+            _ => None,
+        },
+        ("extend", Some(sub)) => Some(extend_base(sub)),
+        ("schema", Some(sub)) => Some(print_schema(sub)),
+        ("version", Some(sub)) => Some(print_version(sub)),
+        ("rotate", Some(sub)) => Some(rotate_secret(sub)),
+        ("replay", Some(sub)) => Some(replay_batch(sub)),
+        ("bench", Some(sub)) => Some(run_bench(sub)),
+        ("plan", Some(sub)) => Some(plan_batch(sub)),
+        ("credpair", Some(sub)) => Some(credential_pairs(sub)),
+        ("dburl", Some(sub)) => Some(database_url(sub)),
+        ("proquint", Some(sub)) => Some(proquints(sub)),
+        ("pin", Some(sub)) => Some(pins(sub)),
+        ("rfc1751", Some(sub)) => Some(rfc1751_keys(sub)),
+        ("diceware", Some(sub)) => Some(diceware_passphrase(sub)),
+        ("history", Some(sub)) => match sub.subcommand() {
+            ("search", Some(sub)) => Some(search_history(sub)),
+            _ => None,
+        },
+        ("roll", Some(sub)) => Some(roll_dice(sub)),
+        ("int", Some(sub)) => Some(random_int(sub)),
+        ("net", Some(sub)) => match sub.subcommand() {
+            (kind, Some(sub)) => Some(net_ids(kind, sub)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Asks `question` on stderr and reads the answer from stdin.
+fn ask(question: &str) -> io::Result<String> {
+    eprint!("{} ", question);
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer)? == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "No answer given",
+        ));
+    }
+    Ok(answer.trim().to_string())
+}
+
+/// Asks `question` until the answer parses, using `default` for empty ones.
+fn ask_until<T: std::str::FromStr>(
+    question: &str,
+    default: T,
+) -> io::Result<T> {
+    loop {
+        let answer = ask(question)?;
+        if answer.is_empty() {
+            return Ok(default);
+        }
+        match answer.parse() {
+            Ok(value) => return Ok(value),
+            Err(_) => eprintln!("Please answer again."),
+        }
+    }
+}
 
-        Ok(Args { number, length, charset: charset.into(), quiet })
+/// Asks a few questions and derives the equivalent command line flags.
+fn run_wizard() -> io::Result<Vec<String>> {
+    eprintln!(
+        "Where will this password be used?\n  1) website or app\n  2) \
+         pre-boot prompt (BIOS, disk encryption)\n  3) wifi\n  4) PIN pad\n  \
+         5) other"
+    );
+    let usage = loop {
+        match ask_until("Choice [1]:", 1)? {
+            n @ 1..=5 => break n,
+            _ => eprintln!("Please answer again."),
+        }
+    };
+    let typed = usage == 2
+        || ask("Can you copy-paste it? [Y/n]")?.to_lowercase().starts_with('n');
+    let cap: Option<usize> = loop {
+        let answer = ask("Maximum length, if any (empty for none):")?;
+        if answer.is_empty() {
+            break None;
+        }
+        match answer.parse() {
+            Ok(cap) => break Some(cap),
+            Err(_) => eprintln!("Please answer again."),
+        }
+    };
+
+    let mut flags: Vec<String> = vec![];
+    match (usage, typed) {
+        (2, _) => flags.extend(vec!["--preset".into(), "console".into()]),
+        (4, _) => flags.extend(vec!["--preset".into(), "pin6".into()]),
+        (3, false) => flags.extend(vec!["--preset".into(), "wpa2".into()]),
+        (_, true) => flags.extend(vec!["--words".into(), "8".into()]),
+        (_, false) => flags.extend(vec!["--preset".into(), "std64".into()]),
+    }
+    let words = flags[0] == "--words";
+    let cap = match (usage, cap) {
+        (3, None) if words => Some(63),
+        (_, cap) => cap,
+    };
+    if let Some(cap) = cap {
+        let preset_length = flags
+            .get(1)
+            .and_then(|name| yapg::presets::by_name(name))
+            .map(|preset| preset.length);
+        if words {
+            flags.extend(vec!["--fit-to".into(), cap.to_string()]);
+        } else if preset_length.is_some_and(|length| length > cap) {
+            flags.extend(vec!["--length".into(), cap.to_string()]);
+        }
     }
+    flags.extend(vec!["--number".into(), "1".into()]);
+    Ok(flags)
 }
 
 fn main() {
-    let args = match Args::get() {
+    // `yapg ssh-key` has ssh-keygen run yapg as askpass helper, with the
+    // prompt as only argument
+    if let Some(passphrase) = std::env::var_os(SSH_PASSPHRASE_ENV) {
+        if std::env::args_os().len() == 2 {
+            println!("{}", passphrase.to_string_lossy());
+            return;
+        }
+    }
+    let mut matches = Args::app().get_matches();
+    if let ("wizard", Some(_)) = matches.subcommand() {
+        let flags = match run_wizard() {
+            Ok(flags) => flags,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            },
+        };
+        eprintln!("Equivalent invocation: yapg {}", flags.join(" "));
+        let argv = std::iter::once("yapg".to_string()).chain(flags);
+        matches = Args::app().get_matches_from(argv);
+    }
+    if let Some(result) = run_subcommand(&matches) {
+        if let Err(e) = result {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let mut args = match Args::from_matches(&matches) {
         Ok(args) => args,
         Err(e) => {
             eprintln!("Encountered error while parsing arguments: {}", e);
@@ -97,7 +2629,244 @@ fn main() {
         },
     };
 
-    let mut pwg = yapg::PasswordGenerator::new(args.charset, args.length);
+    // print warning if the terminal session keeps what is printed
+    if !args.quiet && io::stdout().is_terminal() {
+        for recorder in session::SessionRecorder::current() {
+            eprintln!(
+                "Running inside {}, passwords may persist in {}!",
+                recorder,
+                recorder.persists_in()
+            );
+        }
+    }
+
+    if let Some(mut ppg) = args.passphrase {
+        if let Some(seed) = args.seed {
+            ppg = ppg.seeded(seed);
+        }
+        if let Some(source) = args.rng {
+            ppg = match ppg.random_source(source) {
+                Ok(ppg) => ppg,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                },
+            };
+        }
+        let fitted = match args.fit_to {
+            Some(max_len) => ppg.fit_to(max_len),
+            None => yapg::Fitted::Words(ppg, yapg::FitStrategy::Unchanged),
+        };
+        let (passphrases, entropy) = match fitted {
+            yapg::Fitted::Words(mut ppg, strategy) => {
+                if !args.quiet {
+                    match strategy {
+                        yapg::FitStrategy::Unchanged => {},
+                        yapg::FitStrategy::DropSeparator => {
+                            eprintln!(
+                                "Fitted passphrases by dropping separators"
+                            )
+                        },
+                        yapg::FitStrategy::Abbreviate(n) => eprintln!(
+                            "Fitted passphrases by abbreviating words to {} \
+                             chars",
+                            n
+                        ),
+                    }
+                }
+                (ppg.generate_n(args.number), ppg.entropy())
+            },
+            yapg::Fitted::Chars(mut pwg) => {
+                if !args.quiet {
+                    eprintln!("No passphrase fits, switched to characters");
+                }
+                (pwg.generate_n(args.number), pwg.entropy_bits())
+            },
+        };
+        record_history(&args.history, &passphrases, entropy);
+        print_passphrases(
+            &passphrases,
+            entropy,
+            args.format,
+            &args.export,
+            &args.share,
+            args.quiet,
+        );
+        return;
+    }
+
+    if let Some(mut pg) = args.pronounceable.take() {
+        if let Some(source) = args.rng {
+            pg = pg.random_source(source).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1)
+            });
+        }
+        let passwords = generate_checked(&args, || pg.generate());
+        record_history(&args.history, &passwords, pg.entropy());
+        print_passphrases(
+            &passwords,
+            pg.entropy(),
+            args.format,
+            &args.export,
+            &args.share,
+            args.quiet,
+        );
+        return;
+    }
+
+    if let Some(mut mg) = args.markov.take() {
+        if let Some(source) = args.rng {
+            mg = mg.random_source(source).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                std::process::exit(1)
+            });
+        }
+        let passwords = generate_checked(&args, || mg.generate());
+        if !args.quiet {
+            // the entropy is an average, some passwords are more likely
+            let weakest = passwords
+                .iter()
+                .filter_map(|pw| mg.information(pw))
+                .fold(f64::INFINITY, f64::min);
+            if weakest < ENTROPY_THRESHOLD as f64 {
+                eprintln!(
+                    "The weakest password has only {:.1} bits of information!",
+                    weakest
+                );
+            }
+        }
+        record_history(&args.history, &passwords, mg.entropy());
+        print_passphrases(
+            &passwords,
+            mg.entropy(),
+            args.format,
+            &args.export,
+            &args.share,
+            args.quiet,
+        );
+        return;
+    }
+
+    if let Some(composition) = &args.composition {
+        let mut pwg = with_rng(yapg::PasswordGenerator::new(vec![], 0), &args);
+        let passwords =
+            generate_checked(&args, || pwg.generate_composed(composition));
+        let entropy = composition.entropy();
+        record_history(&args.history, &passwords, entropy);
+        print_passphrases(
+            &passwords,
+            entropy,
+            args.format,
+            &args.export,
+            &args.share,
+            args.quiet,
+        );
+        return;
+    }
+
+    #[cfg(feature = "regex-gen")]
+    if let Some(regex) = &args.regex {
+        // the regex was checked when parsing the arguments
+        let mut rg = regex_generator(regex).unwrap();
+        let passwords = generate_checked(&args, || rg.generate());
+        let entropy = rg.entropy();
+        record_history(&args.history, &passwords, entropy);
+        print_passphrases(
+            &passwords,
+            entropy,
+            args.format,
+            &args.export,
+            &args.share,
+            args.quiet,
+        );
+        return;
+    }
+
+    // print keymap warnings for passwords typed at pre-boot prompts
+    if !args.quiet && args.keymap_warnings {
+        for (keymap, chars) in yapg::keymap_hazards(&args.charset) {
+            eprintln!(
+                "Prompt assumes a US keymap, on {} keyboards these keys \
+                 differ: {}",
+                keymap,
+                chars.iter().collect::<String>()
+            );
+        }
+    }
+
+    // print warning if right-to-left chars may reorder the displayed password
+    if !args.quiet && yapg::BidiReport::of(&args.charset).may_reorder() {
+        eprintln!(
+            "Charset contains right-to-left chars, passwords may display in a \
+             different order than they are typed!"
+        );
+    }
+
+    let items: Vec<yapg::Grapheme> = args
+        .charset
+        .iter()
+        .map(|c| (*c).into())
+        .chain(args.items.clone())
+        .collect();
+    if args.fingerprint {
+        eprintln!(
+            "Charset fingerprint: {}",
+            yapg::items_fingerprint(&items, args.length)
+        );
+    }
+
+    // print warning if the target system's policy caps the entropy
+    if !args.quiet && args.pattern.is_none() {
+        let target = ENTROPY_THRESHOLD as f64;
+        if let Err(e) = args.policy.length_for_entropy(items.len(), target) {
+            eprintln!("{}!", e);
+        }
+    }
+    // and the true entropy if it ignores part of the passwords
+    if !args.quiet && !args.limit.is_unlimited() {
+        eprintln!(
+            "Effective entropy under the target system's limits: {:.1} bits",
+            args.limit.effective_entropy(&args.charset, args.length)
+        );
+    }
+
+    // the pattern and the items were checked when parsing the arguments
+    let pwg = match &args.pattern {
+        Some(pattern) => {
+            yapg::PasswordGenerator::from_pattern(&pattern.to_string())
+        },
+        None => yapg::PasswordGenerator::from_items(items, args.length),
+    };
+    let mut pwg = pwg.unwrap().require_each_class(args.each_class);
+    if let Some(constraints) = args.constraints.clone() {
+        // the constraints were checked when parsing the arguments
+        pwg = pwg.constraints(constraints).unwrap();
+    }
+    if let Err(e) = pwg.check() {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+    let mut pwg = with_rng(pwg, &args);
+    if let Some(command) = args.on_generated {
+        // the history's key, so that hooks and history agree
+        let key = yapg::FingerprintKey::open_default().unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1)
+        });
+        pwg = pwg.on_generated(key, move |event| run_hook(&command, event));
+    }
+
+    // only the key presses reach the receiving system in keypad mode, and
+    // constraints may leave even fewer
+    let entropy = if args.t9 {
+        // the charset was checked when parsing the arguments
+        yapg::t9_entropy(&args.charset, args.length)
+            .unwrap()
+            .min(pwg.entropy_bits())
+    } else {
+        pwg.entropy_bits()
+    };
 
     // print eavesdropper warning
     if !args.quiet && args.number < 10 {
@@ -109,13 +2878,105 @@ fn main() {
     }
 
     // print low entropy warning
-    if !args.quiet && pwg.entropy() < ENTROPY_THRESHOLD {
-        eprintln!("Low password entropy of {} bits!", pwg.entropy() as i32);
+    if !args.quiet && entropy < ENTROPY_THRESHOLD as f64 {
+        eprintln!("Low password entropy of {:.1} bits!", entropy);
     }
 
     // generate and print the passwords
-    for pw in pwg.generate_n(args.number).iter() {
-        println!("{}", pw);
+    let filtered = args.match_filter.is_some()
+        || args.reject_filter.is_some()
+        || args.dictionary.is_some();
+    let quiet = args.quiet;
+    let generated = match args.min_distance {
+        Some(d) => pwg.generate_codes(args.number, d),
+        None if filtered => {
+            let (keep, discard) = (&args.match_filter, &args.reject_filter);
+            let dictionary = &args.dictionary;
+            pwg.generate_filtered(args.number, |pw| {
+                keep.as_ref().is_none_or(|r| r.is_match(pw))
+                    && !discard.as_ref().is_some_and(|r| r.is_match(pw))
+                    && dictionary.as_ref().is_none_or(|d| d.accepts(pw))
+            })
+            .map(|batch| {
+                // filters shrink the set of possible passwords
+                let bits = batch.entropy(entropy);
+                if !quiet {
+                    eprintln!(
+                        "Filters kept {:.1}% of {} candidates, so passwords \
+                         only have about {:.0} instead of {:.0} bits!",
+                        batch.acceptance() * 100.0,
+                        batch.candidates,
+                        bits,
+                        entropy
+                    );
+                }
+                batch.passwords
+            })
+        },
+        None if args.hibp => generate_unbreached(&mut pwg, args.number),
+        None => match &args.pwned_db {
+            Some(db) => pwg.generate_unbreached(args.number, db),
+            None => Ok(pwg.generate_n(args.number)),
+        },
+    };
+    let mut passwords = match generated {
+        Ok(passwords) => passwords,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        },
+    };
+    if let Some(days) = args.expires_in {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let expires_at = now + days * 86400;
+        for pw in passwords.iter_mut() {
+            *pw = yapg::with_expiry(pw, expires_at);
+        }
+    }
+    record_history(&args.history, &passwords, entropy);
+    if let Some(target) = &args.share {
+        print_share_links(target, &passwords);
+    } else if args.t9 && args.format == yapg::OutputFormat::Plain {
+        for pw in passwords.iter() {
+            println!("{}\t{}", pw, yapg::t9_encode(pw).unwrap());
+        }
+    } else if args.emoji && args.format == yapg::OutputFormat::Plain {
+        for pw in passwords.iter() {
+            println!("{}\t{}", pw, yapg::emoji_shortcodes(pw).unwrap());
+        }
+    } else if args.colors && args.format == yapg::OutputFormat::Plain {
+        print_color_codes(&passwords);
+    } else if let Some(var) = &args.export {
+        print_exports(var, &passwords);
+    } else {
+        let provenance = if args.provenance
+            || args.format == yapg::OutputFormat::BackupSheet
+        {
+            Some(yapg::Provenance::of(&pwg))
+        } else {
+            None
+        };
+        print!("{}", args.format.render(&passwords, provenance.as_ref()));
+    }
+    if let Some(viz) = args.viz {
+        for pw in passwords.iter() {
+            eprintln!("\n{}\n{}", pw, viz.render(pw));
+        }
+    }
+    if args.encoding_report {
+        for pw in passwords.iter() {
+            eprint!("{}\n{}", pw, encoding_report(pw));
+        }
+    }
+
+    if let Some(path) = args.codebook {
+        let book = yapg::CodeBook::new(&passwords);
+        if let Err(e) = std::fs::write(&path, format!("{}\n", book)) {
+            eprintln!("Error writing code book {}: {}", path, e);
+            std::process::exit(1);
+        }
     }
 
     // println!("Entropy: {} bits", pwg.entropy() as i32);
@@ -0,0 +1,116 @@
+//! Detecting terminal sessions that keep what is printed, e.g. multiplexer
+//! scrollback and session recordings.
+
+use std::fmt;
+
+/// Programs that may persist the output of a terminal session beyond the
+/// screen, detected from the environment by `SessionRecorder::detect`.
+///
+/// | SessionRecorder | detected by                  | keeps output in     |
+/// | --------------- | ---------------------------- | ------------------- |
+/// | `Tmux`          | `$TMUX`                      | scrollback, buffers |
+/// | `Screen`        | `$STY`, `$TERM` of `screen*` | scrollback, logs    |
+/// | `Script`        | `$SCRIPT`                    | typescript file     |
+/// | `Asciinema`     | `$ASCIINEMA_REC`             | recording           |
+/// | `Emacs`         | `$INSIDE_EMACS`, `dumb` term | shell buffer        |
+///
+/// These are heuristics: script(1) itself sets no variable, `$SCRIPT` is the
+/// common convention of wrappers and shell prompts around it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SessionRecorder {
+    /// The tmux terminal multiplexer.
+    Tmux,
+    /// The GNU screen terminal multiplexer.
+    Screen,
+    /// A session recorded by script(1).
+    Script,
+    /// A session recorded by asciinema.
+    Asciinema,
+    /// A shell inside Emacs, e.g. `M-x shell`.
+    Emacs,
+}
+
+impl SessionRecorder {
+    /// Recorders the session with the environment variables `env` runs in.
+    pub fn detect<F>(env: F) -> Vec<Self>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let set = |var: &str| env(var).is_some_and(|v| !v.is_empty());
+        let term = env("TERM").unwrap_or_default();
+        let mut recorders = vec![];
+        if set("SCRIPT") {
+            recorders.push(Self::Script);
+        }
+        if set("ASCIINEMA_REC") {
+            recorders.push(Self::Asciinema);
+        }
+        if set("INSIDE_EMACS") || term == "dumb" {
+            recorders.push(Self::Emacs);
+        }
+        if set("STY") || (term.starts_with("screen") && !set("TMUX")) {
+            recorders.push(Self::Screen);
+        }
+        if set("TMUX") {
+            recorders.push(Self::Tmux);
+        }
+        recorders
+    }
+
+    /// Recorders the current process runs in, see `SessionRecorder::detect`.
+    pub fn current() -> Vec<Self> {
+        Self::detect(|var| std::env::var(var).ok())
+    }
+
+    /// Where the recorder keeps output.
+    pub fn persists_in(&self) -> &'static str {
+        match self {
+            Self::Tmux => "its scrollback and paste buffers",
+            Self::Screen => "its scrollback and log files",
+            Self::Script => "its typescript file",
+            Self::Asciinema => "its recording",
+            Self::Emacs => "its shell buffer",
+        }
+    }
+}
+
+impl fmt::Display for SessionRecorder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Tmux => "tmux",
+            Self::Screen => "screen",
+            Self::Script => "script",
+            Self::Asciinema => "asciinema",
+            Self::Emacs => "emacs",
+        })
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::SessionRecorder::{self, *};
+
+    fn detect(vars: &[(&str, &str)]) -> Vec<SessionRecorder> {
+        SessionRecorder::detect(|var| {
+            vars.iter().find(|(k, _)| *k == var).map(|(_, v)| v.to_string())
+        })
+    }
+
+    #[test]
+    fn detecting_recorders() {
+        assert_eq!(detect(&[("TERM", "xterm-256color")]), vec![]);
+        assert_eq!(detect(&[("STY", "1234.pts-0.host")]), vec![Screen]);
+        assert_eq!(detect(&[("TERM", "screen-256color")]), vec![Screen]);
+        // tmux uses screen's TERM by default
+        assert_eq!(
+            detect(&[("TERM", "screen"), ("TMUX", "/tmp/tmux-0/default,1,0")]),
+            vec![Tmux]
+        );
+        assert_eq!(detect(&[("TMUX", "")]), vec![]);
+        assert_eq!(detect(&[("SCRIPT", "/tmp/log"), ("TERM", "dumb")]), vec![
+            Script, Emacs
+        ]);
+        assert_eq!(detect(&[("ASCIINEMA_REC", "1")]), vec![Asciinema]);
+    }
+}
@@ -0,0 +1,319 @@
+//! [age](https://age-encryption.org/v1) keypairs, and encryption of their
+//! identity files with a passphrase, so that a new identity never touches
+//! the disk unencrypted.
+
+use std::{fmt, io};
+
+use openssl::hash::MessageDigest;
+use openssl::md::Md;
+use openssl::pkey::{Id, PKey};
+use openssl::pkey_ctx::PkeyCtx;
+use openssl::sign::Signer;
+use openssl::symm::{encrypt_aead, Cipher};
+use rand::RngCore;
+
+use crate::date::{format_timestamp, unix_now};
+use crate::{base64_encode, LockedBuffer};
+
+/// Default binary logarithm of the scrypt work factor, as used by `age`
+/// itself (about a second on current hardware).
+pub const AGE_SCRYPT_WORK_FACTOR: u8 = 18;
+
+const VERSION_LINE: &str = "age-encryption.org/v1";
+const SCRYPT_LABEL: &[u8] = b"age-encryption.org/v1/scrypt";
+const SCRYPT_R: u64 = 8;
+/// Plaintext bytes per chunk of the payload.
+const CHUNK_SIZE: usize = 64 * 1024;
+const BECH32_ALPHABET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// An X25519 keypair in age's format.
+///
+/// # Example
+/// ```
+/// let keypair = yapg_core::AgeKeypair::generate().unwrap();
+/// assert!(keypair.public_key().starts_with("age1"));
+/// assert!(keypair.secret_key().starts_with("AGE-SECRET-KEY-1"));
+/// let identity = keypair.identity_file();
+/// assert!(identity.contains(&keypair.public_key()));
+/// ```
+pub struct AgeKeypair {
+    secret: LockedBuffer,
+    public: Vec<u8>,
+}
+
+impl AgeKeypair {
+    /// Generates a new keypair.
+    pub fn generate() -> io::Result<Self> {
+        let mut secret = vec![0; 32];
+        rand::thread_rng().fill_bytes(&mut secret);
+        Self::from_secret(secret)
+    }
+
+    /// The keypair of the 32-byte X25519 `secret`.
+    pub(crate) fn from_secret(secret: Vec<u8>) -> io::Result<Self> {
+        let secret = LockedBuffer::new(secret);
+        let public = PKey::private_key_from_raw_bytes(&secret, Id::X25519)
+            .and_then(|key| key.raw_public_key())
+            .map_err(io::Error::other)?;
+        Ok(Self { secret, public })
+    }
+
+    /// The public key (recipient) as `age1...`.
+    pub fn public_key(&self) -> String { bech32_encode("age", &self.public) }
+
+    /// The secret key (identity) as `AGE-SECRET-KEY-1...`.
+    pub fn secret_key(&self) -> String {
+        bech32_encode("age-secret-key-", &self.secret).to_uppercase()
+    }
+
+    /// The identity file as written by `age-keygen`.
+    pub fn identity_file(&self) -> String {
+        format!(
+            "# created: {}\n# public key: {}\n{}\n",
+            format_timestamp(unix_now()),
+            self.public_key(),
+            self.secret_key()
+        )
+    }
+
+    /// The identity file encrypted with `passphrase` and armored, see
+    /// `age_encrypt_with_passphrase`.
+    pub fn encrypted_identity_file(
+        &self,
+        passphrase: &str,
+        work_factor: u8,
+    ) -> io::Result<String> {
+        let identity = LockedBuffer::new(self.identity_file().into_bytes());
+        let encrypted =
+            age_encrypt_with_passphrase(&identity, passphrase, work_factor)?;
+        Ok(age_armor(&encrypted))
+    }
+}
+
+impl fmt::Debug for AgeKeypair {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "AgeKeypair({})", self.public_key())
+    }
+}
+
+/// Encrypts `plaintext` with `passphrase` to the binary age format, as
+/// `age --passphrase` does, so that `age --decrypt` asks for the passphrase.
+/// The passphrase is stretched with scrypt of cost `2^work_factor`, see
+/// `AGE_SCRYPT_WORK_FACTOR`.
+pub fn age_encrypt_with_passphrase(
+    plaintext: &[u8],
+    passphrase: &str,
+    work_factor: u8,
+) -> io::Result<Vec<u8>> {
+    if work_factor == 0 || work_factor > 30 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid scrypt work factor: {}", work_factor),
+        ));
+    }
+    let mut rng = rand::thread_rng();
+    let mut file_key = vec![0; 16];
+    rng.fill_bytes(&mut file_key);
+    let file_key = LockedBuffer::new(file_key);
+    let mut salt = [0; 16];
+    rng.fill_bytes(&mut salt);
+
+    let n = 1 << work_factor;
+    let mut wrap_key = vec![0; 32];
+    openssl::pkcs5::scrypt(
+        passphrase.as_bytes(),
+        &[SCRYPT_LABEL, &salt].concat(),
+        n,
+        SCRYPT_R,
+        1,
+        128 * SCRYPT_R * (n + 3),
+        &mut wrap_key,
+    )
+    .map_err(io::Error::other)?;
+    let wrap_key = LockedBuffer::new(wrap_key);
+    let wrapped = seal(&wrap_key, &[0; 12], &file_key)?;
+
+    let mut header = format!(
+        "{}\n-> scrypt {} {}\n{}\n---",
+        VERSION_LINE,
+        base64_raw(&salt),
+        work_factor,
+        base64_raw(&wrapped)
+    );
+    let mac_key = hkdf_sha256(&file_key, &[], b"header")?;
+    let mac = PKey::hmac(&mac_key)
+        .and_then(|key| {
+            let mut signer = Signer::new(MessageDigest::sha256(), &key)?;
+            signer.update(header.as_bytes())?;
+            signer.sign_to_vec()
+        })
+        .map_err(io::Error::other)?;
+    header.push_str(&format!(" {}\n", base64_raw(&mac)));
+
+    let mut nonce = [0; 16];
+    rng.fill_bytes(&mut nonce);
+    let payload_key = hkdf_sha256(&file_key, &nonce, b"payload")?;
+    let mut out = header.into_bytes();
+    out.extend_from_slice(&nonce);
+    let chunks: Vec<&[u8]> = match plaintext.len() {
+        0 => vec![&[]],
+        _ => plaintext.chunks(CHUNK_SIZE).collect(),
+    };
+    for (i, chunk) in chunks.iter().enumerate() {
+        let mut chunk_nonce = [0; 12];
+        chunk_nonce[3..11].copy_from_slice(&(i as u64).to_be_bytes());
+        chunk_nonce[11] = (i + 1 == chunks.len()) as u8;
+        out.extend(seal(&payload_key, &chunk_nonce, chunk)?);
+    }
+    Ok(out)
+}
+
+/// Armors the binary age format as `age --armor` does.
+pub fn age_armor(encrypted: &[u8]) -> String {
+    let base64 = base64_encode(encrypted);
+    let lines: Vec<&str> = base64
+        .as_bytes()
+        .chunks(64)
+        // base64 is ASCII
+        .map(|line| std::str::from_utf8(line).unwrap())
+        .collect();
+    format!(
+        "-----BEGIN AGE ENCRYPTED FILE-----\n{}\n-----END AGE ENCRYPTED \
+         FILE-----\n",
+        lines.join("\n")
+    )
+}
+
+/// Unpadded standard base64, as used in age headers.
+fn base64_raw(bytes: &[u8]) -> String {
+    base64_encode(bytes).trim_end_matches('=').to_string()
+}
+
+/// ChaCha20-Poly1305 ciphertext of `plaintext` with the tag appended.
+fn seal(key: &[u8], nonce: &[u8], plaintext: &[u8]) -> io::Result<Vec<u8>> {
+    let mut tag = [0; 16];
+    let mut sealed = encrypt_aead(
+        Cipher::chacha20_poly1305(),
+        key,
+        Some(nonce),
+        &[],
+        plaintext,
+        &mut tag,
+    )
+    .map_err(io::Error::other)?;
+    sealed.extend_from_slice(&tag);
+    Ok(sealed)
+}
+
+fn hkdf_sha256(
+    ikm: &[u8],
+    salt: &[u8],
+    info: &[u8],
+) -> io::Result<LockedBuffer> {
+    let mut key = vec![0; 32];
+    let mut ctx = PkeyCtx::new_id(Id::HKDF).map_err(io::Error::other)?;
+    ctx.derive_init()
+        .and_then(|_| ctx.set_hkdf_md(Md::sha256()))
+        .and_then(|_| ctx.set_hkdf_key(ikm))
+        .and_then(|_| match salt {
+            [] => Ok(()),
+            _ => ctx.set_hkdf_salt(salt),
+        })
+        .and_then(|_| ctx.add_hkdf_info(info))
+        .and_then(|_| ctx.derive(Some(&mut key)))
+        .map_err(io::Error::other)?;
+    Ok(LockedBuffer::new(key))
+}
+
+/// Bech32 (BIP 173) encoding of `data` with the human-readable part `hrp`,
+/// in lower case.
+fn bech32_encode(hrp: &str, data: &[u8]) -> String {
+    let mut values = vec![];
+    let (mut buffer, mut bits) = (0u32, 0);
+    for b in data {
+        buffer = (buffer << 8) | *b as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            values.push(((buffer >> bits) & 0x1f) as u8);
+        }
+    }
+    if bits > 0 {
+        values.push(((buffer << (5 - bits)) & 0x1f) as u8);
+    }
+    let mut checked: Vec<u8> = hrp.bytes().map(|b| b >> 5).collect();
+    checked.push(0);
+    checked.extend(hrp.bytes().map(|b| b & 0x1f));
+    checked.extend(&values);
+    checked.extend(&[0; 6]);
+    let polymod = bech32_polymod(&checked) ^ 1;
+    values.extend((0..6).map(|i| ((polymod >> (5 * (5 - i))) & 0x1f) as u8));
+    let encoded: String =
+        values.iter().map(|v| BECH32_ALPHABET[*v as usize] as char).collect();
+    format!("{}1{}", hrp, encoded)
+}
+
+fn bech32_polymod(values: &[u8]) -> u32 {
+    const GENERATOR: [u32; 5] =
+        [0x3b6a_57b2, 0x2650_8e6d, 0x1ea1_19fa, 0x3d42_33dd, 0x2a14_62b3];
+    values.iter().fold(1, |checksum, v| {
+        let top = checksum >> 25;
+        let checksum = ((checksum & 0x1ff_ffff) << 5) ^ *v as u32;
+        (0..5)
+            .filter(|i| (top >> i) & 1 == 1)
+            .fold(checksum, |checksum, i| checksum ^ GENERATOR[i])
+    })
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::{
+        age_armor, age_encrypt_with_passphrase, bech32_encode, AgeKeypair,
+    };
+
+    #[test]
+    fn encoding_keys() {
+        // test vectors from BIP 173
+        assert_eq!(bech32_encode("a", &[]), "a12uel5l");
+        let data = "00443214c74254b635cf84653a56d7c675be77df";
+        assert_eq!(
+            bech32_encode("abcdef", &crate::parse_secret(data).unwrap()),
+            "abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw"
+        );
+
+        // key pair from RFC 7748
+        let secret =
+            "77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a";
+        let secret = crate::parse_secret(secret).unwrap();
+        let keypair = AgeKeypair::from_secret(secret).unwrap();
+        let public =
+            "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a";
+        let public = crate::parse_secret(public).unwrap();
+        assert_eq!(keypair.public_key(), bech32_encode("age", &public));
+        assert!(!format!("{:?}", keypair).contains(&keypair.secret_key()));
+    }
+
+    #[test]
+    fn encrypting_with_passphrase() {
+        let encrypted =
+            age_encrypt_with_passphrase(b"secret", "passphrase", 10).unwrap();
+        let text = String::from_utf8_lossy(&encrypted);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "age-encryption.org/v1");
+        assert!(
+            lines[1].starts_with("-> scrypt ") && lines[1].ends_with(" 10")
+        );
+        assert_eq!(lines[2].len(), 43);
+        assert!(lines[3].starts_with("--- ") && lines[3].len() == 47);
+        // header, nonce and the sealed chunk
+        let header_len = lines[..4].iter().map(|l| l.len() + 1).sum::<usize>();
+        assert_eq!(encrypted.len(), header_len + 16 + 6 + 16);
+        assert!(age_encrypt_with_passphrase(b"", "passphrase", 0).is_err());
+
+        let armored = age_armor(&encrypted);
+        assert!(armored.starts_with("-----BEGIN AGE ENCRYPTED FILE-----\n"));
+        assert!(armored.ends_with("\n-----END AGE ENCRYPTED FILE-----\n"));
+        assert!(armored.lines().all(|line| line.len() <= 64));
+    }
+}
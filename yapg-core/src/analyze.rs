@@ -0,0 +1,262 @@
+use std::fmt;
+
+use crate::{CharsetName, Dictionary, MIN_DICTIONARY_WORD_LEN};
+
+/// Length of the shortest repeated or sequential runs reported as weakness.
+pub const MIN_RUN_LEN: usize = 3;
+
+/// Characters an attacker is assumed to try for chars outside the named
+/// charsets, e.g. accented letters.
+const UNLISTED_POOL: usize = 100;
+
+/// A pattern in a password that makes it easier to guess than its length
+/// and charset suggest.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Weakness {
+    /// The same char repeated, e.g. `aaaa`.
+    Repeat(String),
+    /// Consecutive chars ascending or descending, e.g. `abcd` or `4321`.
+    Sequence(String),
+    /// A dictionary word, found ignoring case.
+    Word(String),
+}
+
+impl fmt::Display for Weakness {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Repeat(run) => write!(f, "repeated char {:?}", run),
+            Self::Sequence(run) => write!(f, "sequence {:?}", run),
+            Self::Word(word) => write!(f, "dictionary word {:?}", word),
+        }
+    }
+}
+
+/// Verdicts on the estimated entropy of a password, on the scale used by
+/// KeePass.
+///
+/// | Strength     | bits       |
+/// | ------------ | ---------- |
+/// | `VeryWeak`   | below 28   |
+/// | `Weak`       | 28 to 35   |
+/// | `Fair`       | 36 to 59   |
+/// | `Strong`     | 60 to 127  |
+/// | `VeryStrong` | 128 and up |
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Strength {
+    /// Guessed within seconds.
+    VeryWeak,
+    /// Guessed within hours, if at all protected by slow hashing.
+    Weak,
+    /// Fine against online attacks, not against offline ones.
+    Fair,
+    /// Fine against offline attacks on hashes.
+    Strong,
+    /// Fine as a key.
+    VeryStrong,
+}
+
+impl Strength {
+    /// The verdict on `bits` of entropy.
+    ///
+    /// # Example
+    /// ```
+    /// use yapg_core::Strength;
+    ///
+    /// assert_eq!(Strength::of(27.9), Strength::VeryWeak);
+    /// assert_eq!(Strength::of(60.0), Strength::Strong);
+    /// assert_eq!(Strength::of(Strength::Fair.min_bits()), Strength::Fair);
+    /// ```
+    pub fn of(bits: f64) -> Self {
+        [Self::VeryStrong, Self::Strong, Self::Fair, Self::Weak]
+            .iter()
+            .copied()
+            .find(|strength| bits >= strength.min_bits())
+            .unwrap_or(Self::VeryWeak)
+    }
+
+    /// The fewest bits of entropy getting this verdict.
+    pub fn min_bits(&self) -> f64 {
+        match self {
+            Self::VeryWeak => 0.0,
+            Self::Weak => 28.0,
+            Self::Fair => 36.0,
+            Self::Strong => 60.0,
+            Self::VeryStrong => 128.0,
+        }
+    }
+}
+
+impl fmt::Display for Strength {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::VeryWeak => write!(f, "very weak"),
+            Self::Weak => write!(f, "weak"),
+            Self::Fair => write!(f, "fair"),
+            Self::Strong => write!(f, "strong"),
+            Self::VeryStrong => write!(f, "very strong"),
+        }
+    }
+}
+
+/// Analysis of an existing password, see `analyze`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrengthReport {
+    /// Length in chars.
+    pub length: usize,
+    /// The atomic charsets the password draws from.
+    pub classes: Vec<CharsetName>,
+    /// Whether the password contains chars outside the named charsets.
+    pub unlisted_chars: bool,
+    /// Size of the pool an attacker brute-forces: all chars of the covered
+    /// charsets, plus 100 for unlisted chars.
+    pub pool_size: usize,
+    /// Repeated runs, sequences and dictionary words, in order.
+    pub weaknesses: Vec<Weakness>,
+    /// Entropy in bits if the password were drawn uniformly from the pool.
+    pub naive_entropy: f64,
+    /// Estimated entropy in bits, charging weaknesses only for the choices
+    /// they leave, e.g. which word of the dictionary.
+    pub entropy: f64,
+}
+
+impl StrengthReport {
+    /// The verdict on the estimated entropy.
+    #[inline]
+    pub fn strength(&self) -> Strength { Strength::of(self.entropy) }
+}
+
+/// Analyzes `password`, which need not be generated by yapg, looking for
+/// words of the embedded wordlists, see `analyze_with`.
+///
+/// # Example
+/// ```
+/// use yapg_core::{analyze, CharsetName, Weakness};
+///
+/// let report = analyze("Tiger1234!!!");
+/// assert_eq!(report.classes, vec![
+///     CharsetName::AlphaLower,
+///     CharsetName::AlphaUpper,
+///     CharsetName::Numeric,
+///     CharsetName::Prose,
+/// ]);
+/// assert_eq!(report.weaknesses, vec![
+///     Weakness::Word("tiger".to_string()),
+///     Weakness::Sequence("1234".to_string()),
+///     Weakness::Repeat("!!!".to_string()),
+/// ]);
+/// assert!(report.entropy < report.naive_entropy / 2.0);
+/// assert_eq!(report.strength(), yapg_core::Strength::Weak);
+/// ```
+pub fn analyze(password: &str) -> StrengthReport {
+    analyze_with(password, &Dictionary::embedded(MIN_DICTIONARY_WORD_LEN))
+}
+
+/// Analyzes `password`, looking for words of `dictionary`.
+///
+/// The estimated entropy splits the password greedily from the left into
+/// dictionary words (the longest first), runs of at least `MIN_RUN_LEN`
+/// repeated or sequential chars, and single chars. A word costs the bits of
+/// picking it from the dictionary (plus one if it has upper-case letters),
+/// a run those of its first char, its length and (for sequences) its
+/// direction, and a single char those of picking it from the pool.
+pub fn analyze_with(password: &str, dictionary: &Dictionary) -> StrengthReport {
+    let chars: Vec<char> = password.chars().collect();
+    let classes: Vec<CharsetName> = CharsetName::ATOMIC
+        .iter()
+        .copied()
+        .filter(|name| name.chars().iter().any(|c| chars.contains(c)))
+        .collect();
+    let unlisted_chars = chars.iter().any(|c| {
+        !CharsetName::ATOMIC.iter().any(|name| name.chars().contains(c))
+    });
+    let pool_size =
+        classes.iter().map(|name| name.chars().len()).sum::<usize>()
+            + if unlisted_chars { UNLISTED_POOL } else { 0 };
+    let char_bits = (pool_size.max(1) as f64).log2();
+    let naive_entropy = chars.len() as f64 * char_bits;
+
+    let lower: Vec<char> =
+        chars.iter().map(|c| c.to_lowercase().next().unwrap_or(*c)).collect();
+    let word_bits = (dictionary.len().max(1) as f64).log2();
+    let (mut weaknesses, mut entropy, mut i) = (vec![], 0.0, 0);
+    while i < chars.len() {
+        let run = |step: i64| {
+            1 + chars[i..]
+                .windows(2)
+                .take_while(|w| w[1] as i64 - w[0] as i64 == step)
+                .count()
+        };
+        let text = |len: usize| chars[i..i + len].iter().collect::<String>();
+        if let Some(len) = dictionary.longest_prefix(&lower[i..]) {
+            let cased = chars[i..i + len].iter().any(|c| c.is_uppercase());
+            entropy += word_bits + if cased { 1.0 } else { 0.0 };
+            weaknesses.push(Weakness::Word(lower[i..i + len].iter().collect()));
+            i += len;
+        } else if run(0) >= MIN_RUN_LEN {
+            let len = run(0);
+            entropy += char_bits + (len as f64).log2();
+            weaknesses.push(Weakness::Repeat(text(len)));
+            i += len;
+        } else if run(1).max(run(-1)) >= MIN_RUN_LEN {
+            let len = run(1).max(run(-1));
+            entropy += char_bits + 1.0 + (len as f64).log2();
+            weaknesses.push(Weakness::Sequence(text(len)));
+            i += len;
+        } else {
+            entropy += char_bits;
+            i += 1;
+        }
+    }
+
+    StrengthReport {
+        length: chars.len(),
+        classes,
+        unlisted_chars,
+        pool_size,
+        weaknesses,
+        naive_entropy,
+        entropy: entropy.min(naive_entropy),
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::{analyze_with, Weakness};
+    use crate::{Dictionary, Wordlist};
+
+    #[test]
+    fn estimating_entropy() {
+        let list: Wordlist = "staple\nbattery\n".parse().unwrap();
+        let dictionary = Dictionary::from_wordlist(&list, 5);
+
+        let report = analyze_with("x7Kq", &dictionary);
+        assert!(report.weaknesses.is_empty());
+        assert_eq!(report.pool_size, 62);
+        assert_eq!(report.entropy, report.naive_entropy);
+
+        let report = analyze_with("BatteryStaple", &dictionary);
+        assert_eq!(report.weaknesses, vec![
+            Weakness::Word("battery".to_string()),
+            Weakness::Word("staple".to_string()),
+        ]);
+        assert_eq!(report.entropy, 4.0);
+
+        let report = analyze_with("zyxwaaaaé", &dictionary);
+        assert_eq!(report.weaknesses, vec![
+            Weakness::Sequence("zyxw".to_string()),
+            Weakness::Repeat("aaaa".to_string()),
+        ]);
+        assert!(report.unlisted_chars);
+        assert_eq!(report.pool_size, 126);
+        let char_bits = 126f64.log2();
+        assert!((report.entropy - (3.0 * char_bits + 5.0)).abs() < 1e-9);
+
+        let report = analyze_with("", &dictionary);
+        assert_eq!((report.length, report.entropy), (0, 0.0));
+        assert_eq!(
+            Weakness::Repeat("aaa".to_string()).to_string(),
+            "repeated char \"aaa\""
+        );
+    }
+}
@@ -0,0 +1,429 @@
+//! Estimating how many guesses common attacks need to crack passwords, a
+//! more realistic picture than the entropy of their generator.
+
+use std::fmt;
+
+use crate::Dictionary;
+
+/// Printable ASCII chars, which brute force and mask attacks try.
+const PRINTABLE_POOL: f64 = 95.0;
+
+/// Printable ASCII specials, including space.
+const SPECIAL_POOL: f64 = 33.0;
+
+/// Separators between words dictionary attacks try.
+const WORD_SEPARATORS: [&str; 5] = ["", " ", "-", "_", "."];
+
+/// Most words dictionary attacks combine.
+pub const MAX_ATTACK_WORDS: usize = 12;
+
+/// Suffixes dictionary attacks append: a number of digits, optionally
+/// followed by a special char.
+const SUFFIXES: [(usize, bool); 8] = [
+    (0, false),
+    (1, false),
+    (2, false),
+    (3, false),
+    (4, false),
+    (0, true),
+    (1, true),
+    (2, true),
+];
+
+/// Common strategies of password crackers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Attack {
+    /// All printable ASCII strings, shortest first.
+    BruteForce,
+    /// Masks of char classes per position (as `?l?u?d?s` in hashcat), of
+    /// the shortest length first and the smallest keyspace first within a
+    /// length.
+    Mask,
+    /// Combinations of dictionary words with a separator and a case rule
+    /// (lower, capitalized, title or upper case), followed by up to four
+    /// digits and a special char, the smallest keyspace first.
+    DictionaryRules,
+}
+
+impl Attack {
+    /// All attacks, in the order they are reported.
+    pub const ALL: [Attack; 3] =
+        [Attack::BruteForce, Attack::Mask, Attack::DictionaryRules];
+}
+
+impl fmt::Display for Attack {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::BruteForce => write!(f, "brute force"),
+            Self::Mask => write!(f, "mask"),
+            Self::DictionaryRules => write!(f, "dictionary+rules"),
+        }
+    }
+}
+
+/// How one attack fared against a sample of passwords, see
+/// `AttackSimulator::simulate`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AttackResult {
+    /// The attack.
+    pub attack: Attack,
+    /// Number of passwords the attack finds at all.
+    pub found: usize,
+    /// Number of passwords attacked.
+    pub samples: usize,
+    /// Median of the bits of expected guesses to crack a password, `None`
+    /// if the attack misses half of them or more.
+    pub median_bits: Option<f64>,
+}
+
+/// How all attacks fared against a sample of passwords.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttackReport {
+    /// Results of each attack, in the order of `Attack::ALL`.
+    pub results: Vec<AttackResult>,
+    /// Median of the bits of expected guesses to crack a password with the
+    /// attack best against it, `None` if all attacks miss half of them or
+    /// more.
+    pub best_median_bits: Option<f64>,
+}
+
+/// Simulates common attacks on passwords, estimating the guesses each
+/// needs to crack them. The estimates are expected values given the
+/// structure the attack exploits, e.g. the mask of a password: all
+/// structures cheaper to exhaust are tried first, then half of the
+/// password's own.
+///
+/// # Example
+/// ```
+/// use yapg_core::{Attack, AttackSimulator, Dictionary};
+///
+/// let attacks = AttackSimulator::new(Dictionary::embedded(3));
+/// let brute = attacks.guess_bits(Attack::BruteForce, "Tiger1!").unwrap();
+/// let mask = attacks.guess_bits(Attack::Mask, "Tiger1!").unwrap();
+/// let rules = attacks.guess_bits(Attack::DictionaryRules, "Tiger1!");
+/// assert!(rules.unwrap() < mask && mask < brute);
+/// assert_eq!(attacks.guess_bits(Attack::DictionaryRules, "x7Kq"), None);
+///
+/// let report = attacks.simulate(&["Tiger1!".to_string()]);
+/// assert_eq!(report.best_median_bits, rules);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttackSimulator {
+    dictionary: Dictionary,
+}
+
+impl AttackSimulator {
+    /// Creates a simulator whose dictionary attacks use `dictionary`.
+    pub fn new(dictionary: Dictionary) -> Self { Self { dictionary } }
+
+    /// Bits of the expected guesses `attack` needs to crack `password`, or
+    /// `None` if it never finds it.
+    pub fn guess_bits(&self, attack: Attack, password: &str) -> Option<f64> {
+        match attack {
+            Attack::BruteForce => brute_force_bits(password),
+            Attack::Mask => mask_bits(password),
+            Attack::DictionaryRules => self.dictionary_bits(password),
+        }
+    }
+
+    /// The attack needing the fewest guesses to crack `password`, along
+    /// with the bits of their expected number.
+    pub fn best_attack(&self, password: &str) -> Option<(Attack, f64)> {
+        Attack::ALL
+            .iter()
+            .filter_map(|a| Some((*a, self.guess_bits(*a, password)?)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// Runs all attacks against `passwords`, e.g. a sample of a generator's
+    /// output.
+    pub fn simulate(&self, passwords: &[String]) -> AttackReport {
+        let results = Attack::ALL
+            .iter()
+            .map(|attack| {
+                let bits: Vec<Option<f64>> = passwords
+                    .iter()
+                    .map(|pw| self.guess_bits(*attack, pw))
+                    .collect();
+                AttackResult {
+                    attack: *attack,
+                    found: bits.iter().flatten().count(),
+                    samples: passwords.len(),
+                    median_bits: median(bits),
+                }
+            })
+            .collect();
+        let best = passwords
+            .iter()
+            .map(|pw| self.best_attack(pw).map(|(_, bits)| bits))
+            .collect();
+        AttackReport { results, best_median_bits: median(best) }
+    }
+
+    /// See `Attack::DictionaryRules`.
+    fn dictionary_bits(&self, password: &str) -> Option<f64> {
+        let chars: Vec<char> = password.chars().collect();
+        let word_bits = (self.dictionary.len().max(1) as f64).log2();
+        let suffix_bits = |(digits, special): (usize, bool)| {
+            digits as f64 * 10f64.log2()
+                + if special { SPECIAL_POOL.log2() } else { 0.0 }
+        };
+        // the cheapest reading of the password as words and a suffix
+        let mut cheapest: Option<f64> = None;
+        for suffix in SUFFIXES.iter().copied() {
+            let body = match strip_suffix(&chars, suffix) {
+                Some(body) if !body.is_empty() => body,
+                _ => continue,
+            };
+            for separator in WORD_SEPARATORS.iter() {
+                let sep: Vec<char> = separator.chars().collect();
+                let starts = match self.segment(body, &sep) {
+                    Some(starts) => starts,
+                    None => continue,
+                };
+                if starts.len() > MAX_ATTACK_WORDS
+                    || !matches_case_rule(body, &starts)
+                {
+                    continue;
+                }
+                let bits =
+                    starts.len() as f64 * word_bits + suffix_bits(suffix);
+                cheapest = Some(cheapest.map_or(bits, |c| c.min(bits)));
+            }
+        }
+        let own = cheapest?;
+
+        // all combinations of words, separator, case rule and suffix
+        let mut cheaper = vec![];
+        let mut ties = 0.0;
+        for words in 1..=MAX_ATTACK_WORDS {
+            let (separators, cases) = match words {
+                1 => (1.0, 3.0),
+                _ => (WORD_SEPARATORS.len() as f64, 4.0),
+            };
+            for suffix in SUFFIXES.iter().copied() {
+                let bits = words as f64 * word_bits + suffix_bits(suffix);
+                if (bits - own).abs() < 1e-9 {
+                    ties += separators * cases;
+                } else if bits < own {
+                    cheaper.push(bits + (separators * cases).log2());
+                }
+            }
+        }
+        cheaper.push(own + ties.log2() - 1.0);
+        Some(log2_sum(&cheaper).max(0.0))
+    }
+
+    /// Starts of the fewest dictionary words `body` consists of, separated
+    /// by `sep`.
+    fn segment(&self, body: &[char], sep: &[char]) -> Option<Vec<usize>> {
+        let lower: Vec<char> = body
+            .iter()
+            .map(|c| c.to_lowercase().next().unwrap_or(*c))
+            .collect();
+        // fewest words to reach a word start, and the previous start
+        let mut best: Vec<Option<(usize, usize)>> = vec![None; body.len() + 1];
+        best[0] = Some((0, 0));
+        let mut end = None;
+        for start in 0..body.len() {
+            let words = match best[start] {
+                Some((words, _)) => words,
+                None => continue,
+            };
+            for len in self.dictionary.prefix_lens(&lower[start..]) {
+                let next = start + len;
+                let improve = |entry: &Option<(usize, usize)>| {
+                    entry.is_none_or(|(w, _)| words + 1 < w)
+                };
+                if next == body.len() {
+                    if improve(&end) {
+                        end = Some((words + 1, start));
+                    }
+                } else if lower[next..].starts_with(sep) && !sep.is_empty() {
+                    let next = next + sep.len();
+                    if next < body.len() && improve(&best[next]) {
+                        best[next] = Some((words + 1, start));
+                    }
+                } else if sep.is_empty() && improve(&best[next]) {
+                    best[next] = Some((words + 1, start));
+                }
+            }
+        }
+        let (_, mut start) = end?;
+        let mut starts = vec![start];
+        while start > 0 {
+            start = best[start].unwrap().1;
+            starts.push(start);
+        }
+        starts.reverse();
+        Some(starts)
+    }
+}
+
+/// `chars` without a suffix of `digits` digits and (if `special`) a special
+/// char, if it has one.
+fn strip_suffix(
+    chars: &[char],
+    (digits, special): (usize, bool),
+) -> Option<&[char]> {
+    let mut body = chars;
+    if special {
+        let (last, rest) = body.split_last()?;
+        if !(last.is_ascii_punctuation() || *last == ' ') {
+            return None;
+        }
+        body = rest;
+    }
+    let split = body.len().checked_sub(digits)?;
+    if !body[split..].iter().all(char::is_ascii_digit) {
+        return None;
+    }
+    Some(&body[..split])
+}
+
+/// Whether the case of `body`, made of words starting at `starts`, follows
+/// a rule: all lower or upper case, only the first or each word
+/// capitalized.
+fn matches_case_rule(body: &[char], starts: &[usize]) -> bool {
+    let lower = |c: &char| !c.is_uppercase();
+    let capitalized_at =
+        |i: usize| body[i..].iter().take(1).all(|c| !c.is_lowercase());
+    body.iter().all(lower)
+        || body.iter().all(|c| !c.is_lowercase())
+        || (capitalized_at(0) && body[1..].iter().all(lower))
+        || body.iter().enumerate().all(|(i, c)| {
+            if starts.contains(&i) {
+                capitalized_at(i)
+            } else {
+                lower(c)
+            }
+        })
+}
+
+/// See `Attack::BruteForce`.
+fn brute_force_bits(password: &str) -> Option<f64> {
+    if !password.chars().all(|c| c == ' ' || c.is_ascii_graphic()) {
+        return None;
+    }
+    let n = password.chars().count() as f64;
+    // all shorter strings, then half of those of the same length
+    let shorter = (1.0 - PRINTABLE_POOL.powf(-n)) / (PRINTABLE_POOL - 1.0);
+    Some((n * PRINTABLE_POOL.log2() + (shorter + 0.5).log2()).max(0.0))
+}
+
+/// See `Attack::Mask`.
+fn mask_bits(password: &str) -> Option<f64> {
+    let (mut letters, mut digits, mut specials) = (0, 0, 0);
+    for c in password.chars() {
+        match c {
+            _ if c.is_ascii_alphabetic() => letters += 1,
+            _ if c.is_ascii_digit() => digits += 1,
+            _ if c == ' ' || c.is_ascii_punctuation() => specials += 1,
+            _ => return None,
+        }
+    }
+    let n = letters + digits + specials;
+    if n == 0 {
+        return Some(0.0);
+    }
+    let log_fact: Vec<f64> = (0..=n)
+        .scan(0.0, |acc, i| {
+            *acc += if i > 1 { (i as f64).log2() } else { 0.0 };
+            Some(*acc)
+        })
+        .collect();
+    let keyspace = |l: usize, d: usize, s: usize| {
+        l as f64 * 26f64.log2()
+            + d as f64 * 10f64.log2()
+            + s as f64 * SPECIAL_POOL.log2()
+    };
+    // masks of a letter count come in both cases at each letter
+    let masks = |l: usize, d: usize, s: usize| {
+        log_fact[n] - log_fact[l] - log_fact[d] - log_fact[s] + l as f64
+    };
+    let own = keyspace(letters, digits, specials);
+    // all shorter strings, then all cheaper masks of this length
+    let shorter = n as f64 * PRINTABLE_POOL.log2()
+        + ((1.0 - PRINTABLE_POOL.powf(-(n as f64))) / (PRINTABLE_POOL - 1.0))
+            .log2();
+    let mut terms = vec![shorter];
+    for l in 0..=n {
+        for d in 0..=n - l {
+            let s = n - l - d;
+            if keyspace(l, d, s) < own - 1e-9 {
+                terms.push(masks(l, d, s) + keyspace(l, d, s));
+            }
+        }
+    }
+    // then half of the masks sharing the keyspace, which are the same
+    // classes in a different order
+    terms.push(masks(letters, digits, specials) + own - 1.0);
+    Some(log2_sum(&terms).max(0.0))
+}
+
+/// `log2` of the sum of the powers of two of `terms`.
+fn log2_sum(terms: &[f64]) -> f64 {
+    let max = terms.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if !max.is_finite() {
+        return max;
+    }
+    max + terms.iter().map(|t| (t - max).exp2()).sum::<f64>().log2()
+}
+
+/// Upper median of `bits`, where `None` counts as infinite.
+fn median(bits: Vec<Option<f64>>) -> Option<f64> {
+    let mut bits: Vec<f64> =
+        bits.into_iter().map(|b| b.unwrap_or(f64::INFINITY)).collect();
+    bits.sort_by(f64::total_cmp);
+    bits.get(bits.len() / 2).copied().filter(|b| b.is_finite())
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::{Attack, AttackSimulator};
+    use crate::{Dictionary, Wordlist};
+
+    #[test]
+    fn estimating_guesses() {
+        let list: Wordlist =
+            "staple\nbattery\nhorse\ncorrect\nt-shirt\n".parse().unwrap();
+        let attacks = AttackSimulator::new(Dictionary::from_wordlist(&list, 1));
+        let bits = |attack, pw| attacks.guess_bits(attack, pw);
+
+        // one printable char: half of 95 (plus the empty string)
+        let brute = bits(Attack::BruteForce, "a").unwrap();
+        assert!((brute - 48.5f64.log2()).abs() < 1e-9);
+        assert_eq!(bits(Attack::BruteForce, "é"), None);
+        // masks of one letter come after those of one digit
+        let mask = bits(Attack::Mask, "a").unwrap();
+        assert!((mask - 37f64.log2()).abs() < 1e-9);
+        assert!(bits(Attack::Mask, "0").unwrap() < mask);
+
+        // five words: after all readings of up to four words, each of the
+        // 20 separator and case rules with any suffix
+        let passphrase = "correct-horse-battery-staple-t-shirt";
+        let rules = bits(Attack::DictionaryRules, passphrase).unwrap();
+        assert!(rules > 5.0 * 5f64.log2() && rules < 5.0 * 5f64.log2() + 23.0);
+        assert!(rules < bits(Attack::Mask, passphrase).unwrap() - 100.0);
+        for pw in ["Correct Horse1!", "CORRECT_HORSE", "Correct.horse77"].iter()
+        {
+            assert!(bits(Attack::DictionaryRules, pw).is_some(), "{}", pw);
+        }
+        for pw in
+            ["cOrrect", "correct-horse_staple", "correct!1", "-horse"].iter()
+        {
+            assert_eq!(bits(Attack::DictionaryRules, pw), None, "{}", pw);
+        }
+
+        let samples = vec!["horse1".to_string(), "x7Kq".to_string()];
+        let report = attacks.simulate(&samples);
+        assert_eq!(report.results[2].found, 1);
+        assert_eq!(report.results[2].median_bits, None);
+        assert_eq!(
+            report.results[0].median_bits,
+            bits(Attack::BruteForce, "horse1")
+        );
+        assert!(report.best_median_bits.is_some());
+    }
+}
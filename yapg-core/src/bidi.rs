@@ -0,0 +1,164 @@
+use std::io;
+
+/// How a character affects the display order of the text around it, as a
+/// simplification of the Unicode bidirectional algorithm.
+///
+/// # Example
+///
+/// ```
+/// use yapg_core::Direction;
+///
+/// assert_eq!(Direction::of('a'), Direction::LeftToRight);
+/// assert_eq!(Direction::of('א'), Direction::RightToLeft);
+/// assert_eq!(Direction::of('ب'), Direction::RightToLeft);
+/// assert_eq!(Direction::of('7'), Direction::Neutral);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Strong left-to-right characters, e.g. latin letters.
+    LeftToRight,
+    /// Strong right-to-left characters, e.g. Hebrew and Arabic letters.
+    RightToLeft,
+    /// Digits, punctuation and symbols, which take the direction of their
+    /// surroundings and may thus move when displayed next to right-to-left
+    /// characters.
+    Neutral,
+}
+
+impl Direction {
+    /// The direction of `c`.
+    pub fn of(c: char) -> Self {
+        if is_rtl(c) {
+            Self::RightToLeft
+        } else if c.is_alphabetic() {
+            Self::LeftToRight
+        } else {
+            Self::Neutral
+        }
+    }
+}
+
+/// Whether `c` belongs to a right-to-left script block, excluding the
+/// Arabic-Indic digits, which are weak like all digits.
+fn is_rtl(c: char) -> bool {
+    match c {
+        '\u{660}'..='\u{669}' | '\u{6f0}'..='\u{6f9}' => false,
+        '\u{590}'..='\u{8ff}'
+        | '\u{fb1d}'..='\u{fdff}'
+        | '\u{fe70}'..='\u{feff}'
+        | '\u{10800}'..='\u{10fff}'
+        | '\u{1e800}'..='\u{1efff}' => c.is_alphabetic(),
+        _ => false,
+    }
+}
+
+/// Directionality of a charset, for judging whether passwords drawn from it
+/// display unambiguously.
+///
+/// Once a password contains right-to-left characters, displays reorder its
+/// runs, so the shown order no longer matches the order to type it in,
+/// unless all its characters are right-to-left. Explicit direction controls
+/// (e.g. U+202E) never occur, as they are rejected by `validate_addition`.
+///
+/// # Example
+///
+/// ```
+/// let report = yapg_core::BidiReport::of(&['a', 'b', '1', 'א']);
+/// assert!(report.may_reorder());
+/// assert!(report.is_mixed());
+/// assert_eq!(report.rtl, vec!['א']);
+/// assert!(report.check_unmixed().is_err());
+///
+/// let report = yapg_core::BidiReport::of(&['a', 'b', '1']);
+/// assert!(!report.may_reorder());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BidiReport {
+    /// Strong left-to-right characters.
+    pub ltr: Vec<char>,
+    /// Strong right-to-left characters.
+    pub rtl: Vec<char>,
+    /// Characters taking the direction of their surroundings.
+    pub neutral: Vec<char>,
+}
+
+impl BidiReport {
+    /// Sorts the characters of `charset` by direction.
+    pub fn of(charset: &[char]) -> Self {
+        let mut report = Self { ltr: vec![], rtl: vec![], neutral: vec![] };
+        for &c in charset {
+            match Direction::of(c) {
+                Direction::LeftToRight => report.ltr.push(c),
+                Direction::RightToLeft => report.rtl.push(c),
+                Direction::Neutral => report.neutral.push(c),
+            }
+        }
+        report
+    }
+
+    /// Whether the charset contains strong characters of both directions.
+    pub fn is_mixed(&self) -> bool {
+        !self.ltr.is_empty() && !self.rtl.is_empty()
+    }
+
+    /// Whether passwords may display in a different order than typed.
+    pub fn may_reorder(&self) -> bool {
+        let others = self.ltr.len() + self.neutral.len();
+        !self.rtl.is_empty() && others > 0
+    }
+
+    /// Fails with `io::ErrorKind::InvalidInput` if the charset mixes
+    /// directions.
+    pub fn check_unmixed(&self) -> io::Result<()> {
+        if self.is_mixed() {
+            let rtl: String = self.rtl.iter().take(5).collect();
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Charset mixes left-to-right and right-to-left characters \
+                     (e.g. {})",
+                    rtl
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::{BidiReport, Direction};
+    use crate::DigitSystem;
+
+    #[test]
+    fn digits_are_neutral() {
+        for system in DigitSystem::ALL.iter() {
+            for c in system.chars() {
+                assert_eq!(Direction::of(c), Direction::Neutral, "{:?}", c);
+            }
+        }
+    }
+
+    #[test]
+    fn classifying_scripts() {
+        assert_eq!(Direction::of('ß'), Direction::LeftToRight);
+        assert_eq!(Direction::of('ж'), Direction::LeftToRight);
+        assert_eq!(Direction::of('क'), Direction::LeftToRight);
+        assert_eq!(Direction::of('ש'), Direction::RightToLeft);
+        assert_eq!(Direction::of('ﻻ'), Direction::RightToLeft);
+        assert_eq!(Direction::of('ހ'), Direction::RightToLeft); // Thaana
+        assert_eq!(Direction::of('،'), Direction::Neutral); // Arabic comma
+        assert_eq!(Direction::of('-'), Direction::Neutral);
+    }
+
+    #[test]
+    fn reordering() {
+        // purely right-to-left passwords display reversed as a whole
+        assert!(!BidiReport::of(&['א', 'ב']).may_reorder());
+        assert!(BidiReport::of(&['א', 'ב', '-']).may_reorder());
+        assert!(!BidiReport::of(&['א', 'ב', '-']).is_mixed());
+        assert!(BidiReport::of(&['א', 'ב', '-']).check_unmixed().is_ok());
+        assert!(!BidiReport::of(&[]).may_reorder());
+    }
+}
@@ -0,0 +1,121 @@
+use std::io;
+
+/// Lengths of several fields (e.g. username suffix, password and recovery
+/// PIN) sharing an overall entropy budget, see `EntropyBudget::split`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntropyBudget {
+    /// Length of each field.
+    pub lengths: Vec<usize>,
+    /// Entropy of each field in bits.
+    pub bits: Vec<f64>,
+}
+
+impl EntropyBudget {
+    /// Distributes `total_bits` over fields given as `(charset_len, weight)`,
+    /// in proportion to their weights, and computes the lengths needed.
+    ///
+    /// Fields are first given as many characters as fit into their share,
+    /// then the remaining bits go character by character to the field
+    /// furthest below its share. The fields thus reach the budget together,
+    /// with as few characters as the weights allow. Fields of weight zero
+    /// are left empty.
+    ///
+    /// # Example
+    /// ```
+    /// // 128 bits over a base32 suffix, an std64 password and a PIN, with
+    /// // the password carrying most of the weight
+    /// let budget = yapg_core::EntropyBudget::split(128.0, &[
+    ///     (32, 1.0),
+    ///     (64, 6.0),
+    ///     (10, 1.0),
+    /// ])
+    /// .unwrap();
+    /// assert_eq!(budget.lengths, [4, 16, 5]);
+    /// assert!(budget.total_bits() >= 128.0);
+    /// ```
+    pub fn split(total_bits: f64, fields: &[(usize, f64)]) -> io::Result<Self> {
+        let invalid = |msg: &str| {
+            io::Error::new(io::ErrorKind::InvalidInput, msg.to_string())
+        };
+        if !(total_bits >= 0.0 && total_bits.is_finite()) {
+            return Err(invalid("Entropy budget must be non-negative"));
+        }
+        if fields.iter().any(|(_, w)| !(*w >= 0.0 && w.is_finite())) {
+            return Err(invalid("Field weights must be non-negative"));
+        }
+        let weights: f64 = fields.iter().map(|(_, w)| w).sum();
+        if total_bits > 0.0 && weights <= 0.0 {
+            return Err(invalid("At least one field weight must be positive"));
+        }
+        if fields.iter().any(|(len, w)| *w > 0.0 && *len < 2) {
+            return Err(invalid(
+                "Weighted fields need charsets of at least two characters",
+            ));
+        }
+
+        let per_char: Vec<f64> = fields
+            .iter()
+            .map(|(len, _)| if *len < 2 { 0.0 } else { (*len as f64).log2() })
+            .collect();
+        let shares: Vec<f64> = fields
+            .iter()
+            .map(|(_, w)| if *w > 0.0 { total_bits * w / weights } else { 0.0 })
+            .collect();
+        let mut lengths: Vec<usize> =
+            shares
+                .iter()
+                .zip(per_char.iter())
+                .map(|(share, bits)| {
+                    if *bits > 0.0 {
+                        (share / bits).floor() as usize
+                    } else {
+                        0
+                    }
+                })
+                .collect();
+        let bits_of =
+            |lengths: &[usize], i: usize| lengths[i] as f64 * per_char[i];
+        // tolerate rounding errors of the shares
+        while (0..fields.len()).map(|i| bits_of(&lengths, i)).sum::<f64>()
+            < total_bits - 1e-9
+        {
+            let neediest = (0..fields.len())
+                .filter(|i| shares[*i] > 0.0)
+                .max_by(|a, b| {
+                    let deficit = |i: usize| shares[i] - bits_of(&lengths, i);
+                    deficit(*a).partial_cmp(&deficit(*b)).unwrap()
+                })
+                .unwrap();
+            lengths[neediest] += 1;
+        }
+        let bits = (0..fields.len()).map(|i| bits_of(&lengths, i)).collect();
+        Ok(Self { lengths, bits })
+    }
+
+    /// Combined entropy of all fields in bits.
+    pub fn total_bits(&self) -> f64 { self.bits.iter().sum() }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::EntropyBudget;
+
+    #[test]
+    fn splitting_budgets() {
+        let budget =
+            EntropyBudget::split(64.0, &[(16, 1.0), (16, 1.0)]).unwrap();
+        assert_eq!(budget.lengths, [8, 8]);
+        assert_eq!(budget.bits, [32.0, 32.0]);
+
+        let budget =
+            EntropyBudget::split(10.0, &[(64, 0.0), (2, 1.0)]).unwrap();
+        assert_eq!(budget.lengths, [0, 10]);
+        assert!(EntropyBudget::split(0.0, &[]).unwrap().lengths.is_empty());
+
+        assert!(EntropyBudget::split(10.0, &[(64, 0.0)]).is_err());
+        assert!(EntropyBudget::split(10.0, &[(1, 1.0)]).is_err());
+        assert!(EntropyBudget::split(10.0, &[(64, -1.0)]).is_err());
+        assert!(EntropyBudget::split(f64::NAN, &[(64, 1.0)]).is_err());
+    }
+}
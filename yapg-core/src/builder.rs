@@ -0,0 +1,244 @@
+use std::{error, fmt, io};
+
+use crate::{
+    Constraints, EntropyTooLow, Grapheme, PasswordGenerator, RandomSource,
+};
+
+/// Assembles a `PasswordGenerator` step by step and validates the whole
+/// configuration at once, see `PasswordGenerator::builder`.
+///
+/// # Example
+/// ```
+/// use yapg_core::{BuildError, CharsetName, Constraints, PasswordGenerator};
+///
+/// let mut pwg = PasswordGenerator::builder()
+///     .charset("abcdef0123".chars())
+///     .length(12)
+///     .constraints(Constraints::new().at_least(CharsetName::Numeric, 2))
+///     .require_entropy(30.0)
+///     .build()
+///     .unwrap();
+/// assert_eq!(pwg.generate().len(), 12);
+///
+/// let built = PasswordGenerator::builder().length(12).build();
+/// assert!(matches!(built, Err(BuildError::EmptyCharset)));
+/// let built = PasswordGenerator::builder()
+///     .charset("ab".chars())
+///     .length(12)
+///     .require_entropy(30.0)
+///     .build();
+/// assert!(matches!(built, Err(BuildError::EntropyTooLow(_))));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PasswordGeneratorBuilder {
+    items: Vec<Grapheme>,
+    length: usize,
+    constraints: Option<Constraints>,
+    each_class: bool,
+    rng: Option<RandomSource>,
+    seed: Option<[u8; 32]>,
+    min_entropy: Option<f64>,
+}
+
+impl PasswordGeneratorBuilder {
+    /// Draws from `chars`, replacing any charset or items set earlier.
+    /// Consumes and returns itself.
+    pub fn charset<I: IntoIterator<Item = char>>(mut self, chars: I) -> Self {
+        self.items = chars.into_iter().map(Grapheme::from).collect();
+        self
+    }
+
+    /// Draws from `items`, which may contain multi-char graphemes (see
+    /// `PasswordGenerator::from_items`), replacing any charset or items set
+    /// earlier. Consumes and returns itself.
+    pub fn items(mut self, items: Vec<Grapheme>) -> Self {
+        self.items = items;
+        self
+    }
+
+    /// Sets the number of items per password, consumes and returns itself.
+    #[inline]
+    pub fn length(mut self, length: usize) -> Self {
+        self.length = length;
+        self
+    }
+
+    /// Sets the constraints, see `PasswordGenerator::constraints`. Consumes
+    /// and returns itself.
+    pub fn constraints(mut self, constraints: Constraints) -> Self {
+        self.constraints = Some(constraints);
+        self
+    }
+
+    /// Demands one item of each class, see
+    /// `PasswordGenerator::require_each_class`. Overrides the constraints.
+    /// Consumes and returns itself.
+    #[inline]
+    pub fn require_each_class(mut self, required: bool) -> Self {
+        self.each_class = required;
+        self
+    }
+
+    /// Sets the RNG, see `PasswordGenerator::random_source`. Consumes and
+    /// returns itself.
+    #[inline]
+    pub fn rng(mut self, source: RandomSource) -> Self {
+        self.rng = Some(source);
+        self
+    }
+
+    /// Makes the generator deterministic, see `PasswordGenerator::seeded`.
+    /// Overrides the RNG. Consumes and returns itself.
+    #[inline]
+    pub fn seed(mut self, seed: [u8; 32]) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Sets the entropy floor, see `PasswordGenerator::require_entropy`.
+    /// Unlike there, falling below it already fails the build. Consumes and
+    /// returns itself.
+    #[inline]
+    pub fn require_entropy(mut self, bits: f64) -> Self {
+        self.min_entropy = Some(bits);
+        self
+    }
+
+    /// Creates the `PasswordGenerator`, failing if the configuration cannot
+    /// yield the passwords it describes.
+    pub fn build(self) -> Result<PasswordGenerator, BuildError> {
+        if self.items.is_empty() {
+            return Err(BuildError::EmptyCharset);
+        }
+        if self.length == 0 {
+            return Err(BuildError::ZeroLength);
+        }
+        let mut pwg = PasswordGenerator::from_items(self.items, self.length)
+            .map_err(BuildError::AmbiguousItems)?;
+        if let Some(constraints) = self.constraints {
+            pwg = pwg
+                .constraints(constraints)
+                .map_err(BuildError::InvalidConstraints)?;
+        }
+        if self.each_class {
+            pwg = pwg.require_each_class(true);
+        }
+        if pwg.check().is_err() {
+            return Err(BuildError::Unsatisfiable);
+        }
+        if let Some(required) = self.min_entropy {
+            let actual = pwg.entropy_bits();
+            if actual < required {
+                return Err(BuildError::EntropyTooLow(EntropyTooLow {
+                    required,
+                    actual,
+                }));
+            }
+            pwg = pwg.require_entropy(required);
+        }
+        if let Some(source) = self.rng {
+            pwg = pwg.random_source(source).map_err(BuildError::Rng)?;
+        }
+        if let Some(seed) = self.seed {
+            pwg = pwg.seeded(seed);
+        }
+        Ok(pwg)
+    }
+}
+
+/// Error of building a `PasswordGenerator`, see
+/// `PasswordGeneratorBuilder::build`.
+#[derive(Debug)]
+pub enum BuildError {
+    /// No items to draw from were given.
+    EmptyCharset,
+    /// The passwords would be empty.
+    ZeroLength,
+    /// Passwords could be split into the items in more than one way.
+    AmbiguousItems(io::Error),
+    /// The constraints are invalid.
+    InvalidConstraints(io::Error),
+    /// No password of the length satisfies the constraints.
+    Unsatisfiable,
+    /// The passwords would fall below the entropy floor.
+    EntropyTooLow(EntropyTooLow),
+    /// The RNG is unavailable.
+    Rng(io::Error),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::EmptyCharset => write!(f, "The charset is empty"),
+            Self::ZeroLength => write!(f, "The length is zero"),
+            Self::AmbiguousItems(e)
+            | Self::InvalidConstraints(e)
+            | Self::Rng(e) => write!(f, "{}", e),
+            Self::Unsatisfiable => {
+                write!(f, "No password of the length satisfies the constraints")
+            },
+            Self::EntropyTooLow(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl error::Error for BuildError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::AmbiguousItems(e)
+            | Self::InvalidConstraints(e)
+            | Self::Rng(e) => Some(e),
+            Self::EntropyTooLow(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<BuildError> for io::Error {
+    fn from(e: BuildError) -> io::Error {
+        match e {
+            BuildError::AmbiguousItems(e)
+            | BuildError::InvalidConstraints(e)
+            | BuildError::Rng(e) => e,
+            e => io::Error::new(io::ErrorKind::InvalidInput, e),
+        }
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::BuildError;
+    use crate::{CharsetName, Constraints, Grapheme, PasswordGenerator};
+
+    #[test]
+    fn validating_configurations() {
+        let builder = || PasswordGenerator::builder().charset("ab01".chars());
+        let built = builder().build();
+        assert!(matches!(built, Err(BuildError::ZeroLength)));
+        let items =
+            vec!['c'.into(), 'h'.into(), "ch".parse::<Grapheme>().unwrap()];
+        let built = builder().items(items).length(4).build();
+        assert!(matches!(built, Err(BuildError::AmbiguousItems(_))));
+        let overlapping = Constraints::new()
+            .at_least(CharsetName::Alpha, 1)
+            .at_least(CharsetName::AlphaLower, 1);
+        let built = builder().length(4).constraints(overlapping).build();
+        assert!(matches!(built, Err(BuildError::InvalidConstraints(_))));
+        let built = builder()
+            .length(1)
+            .constraints(Constraints::new().at_least(CharsetName::Numeric, 2))
+            .build();
+        assert!(matches!(built, Err(BuildError::Unsatisfiable)));
+        let built = builder().length(4).require_each_class(true).build();
+        assert!(built.is_ok());
+
+        assert_eq!(builder().length(4), builder().length(4));
+        assert_ne!(builder().length(4), builder().length(5));
+
+        let seeded = || builder().length(8).seed([3; 32]).build().unwrap();
+        assert_eq!(seeded().generate_n(3), seeded().generate_n(3));
+        let e: std::io::Error = BuildError::EmptyCharset.into();
+        assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
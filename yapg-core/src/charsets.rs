@@ -0,0 +1,826 @@
+use std::convert::TryFrom;
+use std::io;
+
+use unicode_width::UnicodeWidthChar;
+
+/// Contains all lower-case latin letters
+pub static CHARSET_ALPHA_LOWER: [char; 26] = [
+    'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o',
+    'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z',
+];
+
+/// Contains all upper-case latin letters.
+pub static CHARSET_ALPHA_UPPER: [char; 26] = [
+    'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O',
+    'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z',
+];
+
+/// Contains all digits.
+pub static CHARSET_NUMERIC: [char; 10] =
+    ['0', '1', '2', '3', '4', '5', '6', '7', '8', '9'];
+
+/// Contains '.', ':', ',', ';', '!', '?', ' ', '\'', and '"'.
+pub static CHARSET_PROSE: [char; 9] =
+    ['.', ':', ',', ';', '!', '?', ' ', '\'', '"'];
+
+/// Contains '+', '-', '*', '/', '=', '<', and '>'.
+pub static CHARSET_MATHOPS: [char; 7] = ['+', '-', '*', '/', '=', '<', '>'];
+
+/// Contains '(', ')', '[', ']', '{', and '}'.
+pub static CHARSET_DELIM: [char; 6] = ['(', ')', '[', ']', '{', '}'];
+
+/// Contains '#', '@', '$', '%', '&', '|', '\\', '~',
+/// '^', '_', and '`'.
+pub static CHARSET_MISC_SPECIAL: [char; 11] =
+    ['#', '@', '$', '%', '&', '|', '\\', '~', '^', '_', '`'];
+
+// total specials: 9 + 7 + 6 + 11 = 33
+
+/// Checks that `c` is visible and occupies exactly one column, so it can be
+/// safely used as a custom addition to a charset.
+///
+/// Control characters, zero-width characters (including combining marks and
+/// joiners) and double-width characters are rejected with an
+/// `io::ErrorKind::InvalidInput` error. Unpaired surrogates cannot occur,
+/// since they are already rejected when decoding input into a `str`.
+///
+/// # Example
+/// ```
+/// assert!(yapg_core::validate_addition('x').is_ok());
+/// assert!(yapg_core::validate_addition('\u{200b}').is_err()); // zero-width space
+/// assert!(yapg_core::validate_addition('\t').is_err());
+/// ```
+pub fn validate_addition(c: char) -> io::Result<char> {
+    let problem = match c.width() {
+        None => "control character",
+        Some(0) => "zero-width character",
+        Some(1) => return Ok(c),
+        Some(_) => "double-width character",
+    };
+    Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("Refusing to add {} {:?} (U+{:04X})", problem, c, c as u32),
+    ))
+}
+
+// ----------------------- intermediaries for user IO ----------------------- //
+/// Translation layer between chars (e.g. for cli flags) and the actual
+/// character sets.
+///
+/// Especially, you can do `CharsetName::from::<char>(c)`. Translations are:
+///
+/// | CharsetName   | associated char | contained chars                                       |
+/// | ------------- | --------------- | ----------------------------------------------------- |
+/// | `AlphaLower`  | `'L'`           | matching regex `[a-z]`                                |
+/// | `AlphaUpper`  | `'U'`           | matching regex `[A-Z]`                                |
+/// | `Numeric`     | `'N'`           | matching regex `[0-9]`                                |
+/// | `Mathops`     | `'M'`           | `+`, `-`, `*`, `/`, `=`, `<`, `>`                     |
+/// | `Prose`       | `'P'`           | `.`, `,`, `:`, `;`, `!`, `?`, `'`, `"`, ` `           |
+/// | `Delim`       | `'D'`           | `(`, `)`, `{`, `}`, `[`, `]`                          |
+/// | `MiscSpecial` | `'X'`           | `#`, `@`, `$`, `%`, `&`, `|`, `\`, `~`, `^`, `_`, ``` |
+///
+/// For convenience, there are also some charsets built from the "atomic"
+/// charsets shown above:
+///
+/// | CharsetName | associated char | Contained Charsets                                           |
+/// | ----------- | --------------- | ------------------------------------------------------------ |
+/// | `Alpha`     | `'A'`           | `AlphaLower`, `AlphaUpper`                                   |
+/// | `Special`   | `'S'`           | `Mathops`, `Punct`, `Delim`, `Quote`, `Blank`, `MiscSpecial` |
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CharsetName {
+    // atomic
+    AlphaLower,
+    AlphaUpper,
+    Numeric,
+    Mathops,
+    Prose,
+    Delim,
+    MiscSpecial,
+    // compound
+    Alpha,
+    Special,
+}
+
+impl CharsetName {
+    /// All atomic charset names, i.e. those not composed of others.
+    pub const ATOMIC: [CharsetName; 7] = [
+        Self::AlphaLower,
+        Self::AlphaUpper,
+        Self::Numeric,
+        Self::Mathops,
+        Self::Prose,
+        Self::Delim,
+        Self::MiscSpecial,
+    ];
+
+    /// The (sorted) characters of the named charset.
+    ///
+    /// # Example
+    /// ```
+    /// assert_eq!(yapg_core::CharsetName::Delim.chars(), vec![
+    ///     '(', ')', '[', ']', '{', '}'
+    /// ]);
+    /// ```
+    pub fn chars(self) -> Vec<char> {
+        let mut spec = CharsetSpec::empty();
+        spec += self;
+        spec.construct()
+    }
+}
+
+impl TryFrom<char> for CharsetName {
+    type Error = io::Error;
+
+    fn try_from(c: char) -> io::Result<Self> {
+        match c {
+            // atomic
+            'U' => Ok(Self::AlphaUpper),
+            'L' => Ok(Self::AlphaLower),
+            'N' => Ok(Self::Numeric),
+            'M' => Ok(Self::Mathops),
+            'P' => Ok(Self::Prose),
+            'D' => Ok(Self::Delim),
+            'X' => Ok(Self::MiscSpecial),
+            // compound
+            'A' => Ok(Self::Alpha),
+            'S' => Ok(Self::Special),
+            // invalid input
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid character set abbreviation: {}", c),
+            )),
+        }
+    }
+}
+
+impl From<CharsetName> for char {
+    /// The abbreviation of `name`, inverse to `CharsetName::try_from`.
+    fn from(name: CharsetName) -> char {
+        match name {
+            CharsetName::AlphaUpper => 'U',
+            CharsetName::AlphaLower => 'L',
+            CharsetName::Numeric => 'N',
+            CharsetName::Mathops => 'M',
+            CharsetName::Prose => 'P',
+            CharsetName::Delim => 'D',
+            CharsetName::MiscSpecial => 'X',
+            CharsetName::Alpha => 'A',
+            CharsetName::Special => 'S',
+        }
+    }
+}
+
+// TODO: impl as bitflags
+/// Represents a specification for a charset
+///
+/// Any of the predefined `CharsetName`s can be toggled and additional
+/// characters may be included.
+/// For this purpose, `CharsetSpec` implements `AddAssign<CharsetName>` and
+/// `SubAssign<CharsetName>`.
+/// Alternatively, you can parse a string containing the corresponding chars,
+/// optionally followed by `+` and literal additions (e.g. `"LUN+-_"`), which
+/// is also the canonical form produced by `CharsetSpec::to_spec_string`.
+/// Specs can be combined with `|` (union), `&` (intersection) and `-`
+/// (difference).
+///
+/// Two specs compare (and hash) equal if they construct the same charset, and
+/// the default spec is the empty one. With the `serde` feature, specs are
+/// (de)serialized as spec strings.
+///
+/// # Example
+///
+/// ```
+/// let mut spec = yapg_core::CharsetSpec::empty();
+/// spec += yapg_core::CharsetName::Numeric; // Adding a named charset
+/// spec += "+-*"; // Adding a string
+/// spec += '/'; // Adding a single char
+///
+/// assert_eq!(spec.construct().as_slice(), [
+///     '*', '+', '-', '/', '0', '1', '2', '3', '4', '5', '6', '7', '8', '9',
+/// ]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CharsetSpec {
+    alpha_lower: bool,
+    alpha_upper: bool,
+    numeric: bool,
+    mathops: bool,
+    prose: bool,
+    delim: bool,
+    misc_special: bool,
+    additions: Vec<char>,
+}
+
+impl CharsetSpec {
+    /// Builds the actual character set in form of a `Vec<char>`, which is
+    /// sorted and deduplicated. Consumes the spec, which saves copying the
+    /// additions; see `CharsetSpec::build` for a non-consuming variant.
+    pub fn construct(mut self) -> Vec<char> {
+        let additions = std::mem::take(&mut self.additions);
+        self.assemble(additions)
+    }
+
+    /// Builds the actual character set like `CharsetSpec::construct`, but
+    /// leaves the spec intact for further tweaking.
+    ///
+    /// # Example
+    /// ```
+    /// let mut spec = yapg_core::CharsetSpec::empty();
+    /// spec += "ba";
+    /// assert_eq!(spec.build(), vec!['a', 'b']);
+    /// spec += 'c';
+    /// assert_eq!(spec.build(), vec!['a', 'b', 'c']);
+    /// ```
+    pub fn build(&self) -> Vec<char> { self.assemble(self.additions.clone()) }
+
+    /// Number of distinct characters in the charset.
+    ///
+    /// # Example
+    /// ```
+    /// assert_eq!(yapg_core::CharsetSpec::std64().len(), 64);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize { self.build().len() }
+
+    /// Whether the charset contains no characters at all.
+    ///
+    /// # Example
+    /// ```
+    /// assert!(yapg_core::CharsetSpec::empty().is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        let named = self.alpha_lower
+            || self.alpha_upper
+            || self.numeric
+            || self.mathops
+            || self.prose
+            || self.delim
+            || self.misc_special;
+        !named && self.additions.is_empty()
+    }
+
+    /// Canonical, compact string form of the spec, e.g. `"LUN+-_"` for
+    /// `CharsetSpec::std64()`: the associated chars of all fully contained
+    /// atomic charsets (in the order `LUNMPDX`), followed by `+` and all
+    /// remaining chars, sorted. Equal specs yield equal strings, and the
+    /// string parses back into an equal spec (provided all additions pass
+    /// `validate_addition`).
+    ///
+    /// # Example
+    /// ```
+    /// let spec = yapg_core::CharsetSpec::std64();
+    /// assert_eq!(spec.to_spec_string(), "LUN+-_");
+    /// assert_eq!(
+    ///     spec.to_spec_string().parse::<yapg_core::CharsetSpec>().unwrap(),
+    ///     spec
+    /// );
+    /// ```
+    pub fn to_spec_string(&self) -> String {
+        let canonical = Self::from_chars(&self.build());
+        let named = [
+            (canonical.alpha_lower, 'L'),
+            (canonical.alpha_upper, 'U'),
+            (canonical.numeric, 'N'),
+            (canonical.mathops, 'M'),
+            (canonical.prose, 'P'),
+            (canonical.delim, 'D'),
+            (canonical.misc_special, 'X'),
+        ];
+        let mut s: String =
+            named.iter().filter(|(on, _)| *on).map(|(_, c)| *c).collect();
+        if !canonical.additions.is_empty() {
+            s.push('+');
+            s.extend(canonical.additions.iter());
+        }
+        s
+    }
+
+    /// Appends the chars of all toggled named charsets to `set`, then sorts
+    /// and deduplicates it.
+    fn assemble(&self, mut set: Vec<char>) -> Vec<char> {
+        if self.alpha_lower {
+            set.extend_from_slice(&CHARSET_ALPHA_LOWER);
+        }
+        if self.alpha_upper {
+            set.extend_from_slice(&CHARSET_ALPHA_UPPER);
+        }
+        if self.numeric {
+            set.extend_from_slice(&CHARSET_NUMERIC);
+        }
+        if self.mathops {
+            set.extend_from_slice(&CHARSET_MATHOPS);
+        }
+        if self.prose {
+            set.extend_from_slice(&CHARSET_PROSE);
+        }
+        if self.delim {
+            set.extend_from_slice(&CHARSET_DELIM);
+        }
+        if self.misc_special {
+            set.extend_from_slice(&CHARSET_MISC_SPECIAL);
+        }
+        set.sort();
+        set.dedup();
+        set
+    }
+
+    /// Adds all characters of `more`, after checking each of them with
+    /// `validate_addition`. Nothing is added if any character is rejected.
+    ///
+    /// # Example
+    /// ```
+    /// let mut spec = yapg_core::CharsetSpec::empty();
+    /// assert!(spec.try_add("a\u{200d}b").is_err());
+    /// spec.try_add("ab").unwrap();
+    /// assert_eq!(spec.construct(), vec!['a', 'b']);
+    /// ```
+    pub fn try_add(&mut self, more: &str) -> io::Result<()> {
+        let chars = more
+            .chars()
+            .map(validate_addition)
+            .collect::<io::Result<Vec<_>>>()?;
+        self.additions.extend(chars);
+        Ok(())
+    }
+
+    /// Creates the specification for an empty charset.
+    ///
+    /// # Example
+    /// ```
+    /// let charset = yapg_core::CharsetSpec::empty().construct();
+    /// assert_eq!(charset.len(), 0);
+    /// ```
+    pub fn empty() -> Self {
+        Self {
+            alpha_lower: false,
+            alpha_upper: false,
+            numeric: false,
+            mathops: false,
+            prose: false,
+            delim: false,
+            misc_special: false,
+            additions: vec![],
+        }
+    }
+
+    /// Creates the specification for a standard charset, including all
+    /// alphanumerics, `-` and `_`.
+    /// Should be safe to use in most places, except for strict
+    /// "no-special-characters"-policies or where neither `-` nor `_` are
+    /// considered to be special, yet special chars are required.
+    ///
+    /// # Example
+    /// ```
+    /// let charset = yapg_core::CharsetSpec::std64().construct();
+    /// assert_eq!(charset.len(), 64);
+    /// ```
+    pub fn std64() -> Self {
+        Self {
+            alpha_lower: true,
+            alpha_upper: true,
+            numeric: true,
+            mathops: false,
+            prose: false,
+            delim: false,
+            misc_special: false,
+            additions: vec!['-', '_'],
+        }
+    }
+
+    /// Creates the specification for charset that contains all printable ASCII
+    /// characters.
+    ///
+    /// # Example
+    /// ```
+    /// let charset = yapg_core::CharsetSpec::printable_ascii().construct();
+    /// assert_eq!(charset.len(), 95);
+    /// ```
+    pub fn printable_ascii() -> Self {
+        Self {
+            alpha_lower: true,
+            alpha_upper: true,
+            numeric: true,
+            mathops: true,
+            prose: true,
+            delim: true,
+            misc_special: true,
+            additions: vec![],
+        }
+    }
+
+    /// Creates the specification for a charset that is reliably enterable at
+    /// pre-boot prompts (BIOS, LUKS unlock, bare consoles), which commonly
+    /// assume a US keyboard layout and may mishandle shift states or dead
+    /// keys. Contains lower-case latin letters and digits only.
+    ///
+    /// Some of these still sit on other keys of non-US keyboards, see
+    /// `keymap_hazards`.
+    ///
+    /// # Example
+    /// ```
+    /// let charset = yapg_core::CharsetSpec::console().construct();
+    /// assert_eq!(charset.len(), 36);
+    /// ```
+    pub fn console() -> Self {
+        Self {
+            alpha_lower: true,
+            alpha_upper: false,
+            numeric: true,
+            mathops: false,
+            prose: false,
+            delim: false,
+            misc_special: false,
+            additions: vec![],
+        }
+    }
+
+    /// Creates the specification for exactly the given characters. Named
+    /// charsets that are fully contained in `chars` are toggled, all other
+    /// characters become additions.
+    ///
+    /// # Example
+    /// ```
+    /// let chars = yapg_core::CharsetSpec::std64().construct();
+    /// assert_eq!(
+    ///     yapg_core::CharsetSpec::from_chars(&chars),
+    ///     yapg_core::CharsetSpec::std64()
+    /// );
+    /// ```
+    pub fn from_chars(chars: &[char]) -> Self {
+        let has_all = |set: &[char]| set.iter().all(|c| chars.contains(c));
+        let mut spec = Self {
+            alpha_lower: has_all(&CHARSET_ALPHA_LOWER),
+            alpha_upper: has_all(&CHARSET_ALPHA_UPPER),
+            numeric: has_all(&CHARSET_NUMERIC),
+            mathops: has_all(&CHARSET_MATHOPS),
+            prose: has_all(&CHARSET_PROSE),
+            delim: has_all(&CHARSET_DELIM),
+            misc_special: has_all(&CHARSET_MISC_SPECIAL),
+            additions: vec![],
+        };
+        let named = spec.build();
+        spec.additions = charset_difference(chars, &named);
+        spec
+    }
+}
+
+/// Sorted and deduplicated copy of `chars`.
+fn sorted(chars: &[char]) -> Vec<char> {
+    let mut set = chars.to_vec();
+    set.sort();
+    set.dedup();
+    set
+}
+
+/// All characters contained in `a` or `b`, sorted and deduplicated.
+///
+/// # Example
+/// ```
+/// assert_eq!(yapg_core::charset_union(&['b', 'a'], &['c', 'a']), vec![
+///     'a', 'b', 'c'
+/// ]);
+/// ```
+pub fn charset_union(a: &[char], b: &[char]) -> Vec<char> {
+    sorted(&[a, b].concat())
+}
+
+/// All characters contained in both `a` and `b`, sorted and deduplicated.
+///
+/// # Example
+/// ```
+/// assert_eq!(
+///     yapg_core::charset_intersection(&['b', 'a'], &['c', 'a']),
+///     vec!['a']
+/// );
+/// ```
+pub fn charset_intersection(a: &[char], b: &[char]) -> Vec<char> {
+    let b = sorted(b);
+    sorted(a).into_iter().filter(|c| b.binary_search(c).is_ok()).collect()
+}
+
+/// All characters contained in `a` but not in `b`, sorted and deduplicated.
+///
+/// # Example
+/// ```
+/// assert_eq!(yapg_core::charset_difference(&['b', 'a'], &['c', 'a']), vec![
+///     'b'
+/// ]);
+/// ```
+pub fn charset_difference(a: &[char], b: &[char]) -> Vec<char> {
+    let b = sorted(b);
+    sorted(a).into_iter().filter(|c| b.binary_search(c).is_err()).collect()
+}
+
+impl PartialEq for CharsetSpec {
+    fn eq(&self, other: &Self) -> bool { self.build() == other.build() }
+}
+
+impl Eq for CharsetSpec {}
+
+impl std::hash::Hash for CharsetSpec {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.build().hash(state);
+    }
+}
+
+impl std::str::FromStr for CharsetSpec {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        let mut spec = Self::empty();
+        let mut parts = s.splitn(2, '+');
+        for c in parts.next().unwrap_or("").chars() {
+            let name = CharsetName::try_from(c)?;
+            spec += name;
+        }
+        if let Some(additions) = parts.next() {
+            spec.try_add(additions)?;
+        }
+        Ok(spec)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for CharsetSpec {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&self.to_spec_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for CharsetSpec {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        d: D,
+    ) -> Result<Self, D::Error> {
+        let spec = String::deserialize(d)?;
+        spec.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl From<CharsetSpec> for Vec<char> {
+    #[inline]
+    fn from(spec: CharsetSpec) -> Vec<char> { spec.construct() }
+}
+
+impl std::ops::AddAssign<&str> for CharsetSpec {
+    #[inline]
+    fn add_assign(&mut self, more: &str) {
+        for c in more.chars() {
+            (*self) += c;
+        }
+    }
+}
+
+impl std::ops::AddAssign<char> for CharsetSpec {
+    #[inline]
+    fn add_assign(&mut self, c: char) { self.additions.push(c); }
+}
+
+impl std::ops::AddAssign<CharsetName> for CharsetSpec {
+    fn add_assign(&mut self, name: CharsetName) {
+        match name {
+            // atomic
+            CharsetName::AlphaLower => self.alpha_lower = true,
+            CharsetName::AlphaUpper => self.alpha_upper = true,
+            CharsetName::Numeric => self.numeric = true,
+            CharsetName::Mathops => self.mathops = true,
+            CharsetName::Prose => self.prose = true,
+            CharsetName::Delim => self.delim = true,
+            CharsetName::MiscSpecial => self.misc_special = true,
+            // compound
+            CharsetName::Alpha => {
+                self.alpha_lower = true;
+                self.alpha_upper = true;
+            },
+            CharsetName::Special => {
+                self.mathops = true;
+                self.prose = true;
+                self.delim = true;
+                self.misc_special = true;
+            },
+        }
+    }
+}
+
+impl std::ops::SubAssign<CharsetName> for CharsetSpec {
+    fn sub_assign(&mut self, name: CharsetName) {
+        match name {
+            // atomic
+            CharsetName::AlphaLower => self.alpha_lower = false,
+            CharsetName::AlphaUpper => self.alpha_upper = false,
+            CharsetName::Numeric => self.numeric = false,
+            CharsetName::Mathops => self.mathops = false,
+            CharsetName::Prose => self.prose = false,
+            CharsetName::Delim => self.delim = false,
+            CharsetName::MiscSpecial => self.misc_special = false,
+            // compound
+            CharsetName::Alpha => {
+                self.alpha_lower = false;
+                self.alpha_upper = false;
+            },
+            CharsetName::Special => {
+                self.mathops = false;
+                self.prose = false;
+                self.delim = false;
+                self.misc_special = false;
+            },
+        }
+    }
+}
+
+impl std::ops::BitOr for &CharsetSpec {
+    type Output = CharsetSpec;
+
+    /// Union of both charsets.
+    fn bitor(self, other: &CharsetSpec) -> CharsetSpec {
+        CharsetSpec::from_chars(&charset_union(&self.build(), &other.build()))
+    }
+}
+
+impl std::ops::BitAnd for &CharsetSpec {
+    type Output = CharsetSpec;
+
+    /// Intersection of both charsets.
+    fn bitand(self, other: &CharsetSpec) -> CharsetSpec {
+        CharsetSpec::from_chars(&charset_intersection(
+            &self.build(),
+            &other.build(),
+        ))
+    }
+}
+
+impl std::ops::Sub for &CharsetSpec {
+    type Output = CharsetSpec;
+
+    /// All chars of the left charset that are not in the right one.
+    fn sub(self, other: &CharsetSpec) -> CharsetSpec {
+        CharsetSpec::from_chars(&charset_difference(
+            &self.build(),
+            &other.build(),
+        ))
+    }
+}
+
+impl std::ops::BitOr for CharsetSpec {
+    type Output = CharsetSpec;
+
+    #[inline]
+    fn bitor(self, other: CharsetSpec) -> CharsetSpec { &self | &other }
+}
+
+impl std::ops::BitAnd for CharsetSpec {
+    type Output = CharsetSpec;
+
+    #[inline]
+    fn bitand(self, other: CharsetSpec) -> CharsetSpec { &self & &other }
+}
+
+impl std::ops::Sub for CharsetSpec {
+    type Output = CharsetSpec;
+
+    #[inline]
+    fn sub(self, other: CharsetSpec) -> CharsetSpec { &self - &other }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::CharsetName::*;
+    use super::{CharsetName, CharsetSpec};
+
+    #[test]
+    fn parsing_charset_names() {
+        // atomic
+        assert_eq!(CharsetName::try_from('U').unwrap(), AlphaUpper);
+        assert_eq!(CharsetName::try_from('L').unwrap(), AlphaLower);
+        assert_eq!(CharsetName::try_from('N').unwrap(), Numeric);
+        assert_eq!(CharsetName::try_from('M').unwrap(), Mathops);
+        assert_eq!(CharsetName::try_from('P').unwrap(), Prose);
+        assert_eq!(CharsetName::try_from('D').unwrap(), Delim);
+        assert_eq!(CharsetName::try_from('X').unwrap(), MiscSpecial);
+        // compound
+        assert_eq!(CharsetName::try_from('A').unwrap(), Alpha);
+        assert_eq!(CharsetName::try_from('S').unwrap(), Special);
+        // invalid input
+        assert!(CharsetName::try_from('Z').is_err());
+    }
+
+    #[test]
+    fn abbreviating_charset_names() {
+        for c in "LUNMPDXAS".chars() {
+            assert_eq!(char::from(CharsetName::try_from(c).unwrap()), c);
+        }
+    }
+
+    #[test]
+    fn parsing_charset_specs() {
+        let (alpha, alnum) = {
+            let mut alpha = [
+                super::CHARSET_ALPHA_UPPER.to_vec(),
+                super::CHARSET_ALPHA_LOWER.to_vec(),
+            ]
+            .concat();
+            let mut alnum = [
+                super::CHARSET_ALPHA_UPPER.to_vec(),
+                super::CHARSET_ALPHA_LOWER.to_vec(),
+                super::CHARSET_NUMERIC.to_vec(),
+            ]
+            .concat();
+            alpha.sort();
+            alnum.sort();
+            (alpha, alnum)
+        };
+
+        assert_eq!("LU".parse::<CharsetSpec>().unwrap().construct(), alpha);
+        assert_eq!("LUN".parse::<CharsetSpec>().unwrap().construct(), alnum);
+    }
+
+    #[test]
+    fn round_tripping_spec_strings() {
+        for s in ["", "LUN+-_", "MPDX", "++ab", "N+ "].iter() {
+            let spec = s.parse::<CharsetSpec>().unwrap();
+            assert_eq!(&spec.to_spec_string(), s);
+        }
+        let spec = "SA+0".parse::<CharsetSpec>().unwrap();
+        assert_eq!(spec.to_spec_string(), "LUMPDX+0");
+        assert!("L+\u{200b}".parse::<CharsetSpec>().is_err());
+    }
+
+    #[test]
+    fn adding_charset_to_spec() {
+        let mut spec = CharsetSpec::empty();
+        spec += Mathops;
+        assert_eq!(spec.construct(), vec!['*', '+', '-', '/', '<', '=', '>'])
+    }
+
+    #[test]
+    fn subtracting_charset_from_spec() {
+        let mut spec = CharsetSpec::std64();
+        spec -= Alpha;
+        spec -= Numeric;
+        assert_eq!(spec.construct(), vec!['-', '_'])
+    }
+
+    #[test]
+    fn adding_chars_to_spec() {
+        let mut spec = CharsetSpec::empty();
+        spec += 'a';
+        spec += 'b';
+        spec += 'c';
+        spec += 'd';
+        assert_eq!(spec.construct(), vec!['a', 'b', 'c', 'd']);
+    }
+
+    #[test]
+    fn comparing_specs() {
+        let mut spec = CharsetSpec::default();
+        assert_eq!(spec, CharsetSpec::empty());
+        spec += "_-";
+        spec += Alpha;
+        spec += Numeric;
+        assert_eq!(spec, CharsetSpec::std64());
+        assert_ne!(spec, CharsetSpec::console());
+
+        let mut cache = std::collections::HashSet::new();
+        cache.insert(spec.clone());
+        assert!(cache.contains(&CharsetSpec::std64()));
+    }
+
+    #[test]
+    fn combining_specs() {
+        let policy: CharsetSpec = "LUNM".parse().unwrap();
+        let layout_safe = CharsetSpec::console();
+        let both = &policy & &layout_safe;
+        assert_eq!(both, CharsetSpec::console());
+        assert_eq!(both.construct().len(), 36);
+
+        let mut dashes = CharsetSpec::empty();
+        dashes += '-';
+        let no_dashes = &policy - &dashes;
+        assert_eq!(no_dashes.len(), 26 + 26 + 10 + 6);
+        assert!(!no_dashes.construct().contains(&'-'));
+
+        assert_eq!((layout_safe | dashes).len(), 37);
+    }
+
+    #[test]
+    fn validating_additions() {
+        assert!(super::validate_addition('~').is_ok());
+        assert!(super::validate_addition('ä').is_ok());
+        assert!(super::validate_addition('\u{7f}').is_err()); // DEL
+        assert!(super::validate_addition('\u{feff}').is_err()); // BOM
+        assert!(super::validate_addition('\u{301}').is_err()); // combining
+        assert!(super::validate_addition('漢').is_err());
+    }
+
+    #[test]
+    fn adding_strings_to_spec() {
+        let mut spec = CharsetSpec::empty();
+        spec += "abcd";
+        assert_eq!(spec.construct(), vec!['a', 'b', 'c', 'd']);
+    }
+}
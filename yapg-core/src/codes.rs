@@ -0,0 +1,173 @@
+use std::convert::TryInto;
+use std::path::Path;
+use std::{fmt, fs, io};
+
+use rand::RngCore;
+
+use crate::{ct_eq, hex_decode, hex_encode};
+
+/// Number of positions at which `a` and `b` differ, counted in characters,
+/// or `None` if they differ in length.
+///
+/// # Example
+/// ```
+/// assert_eq!(yapg_core::hamming_distance("K7Q2", "K7Q2"), Some(0));
+/// assert_eq!(yapg_core::hamming_distance("K7Q2", "K1Q3"), Some(2));
+/// assert_eq!(yapg_core::hamming_distance("K7Q2", "K7Q"), None);
+/// ```
+pub fn hamming_distance(a: &str, b: &str) -> Option<usize> {
+    if a.chars().count() != b.chars().count() {
+        return None;
+    }
+    Some(a.chars().zip(b.chars()).filter(|(x, y)| x != y).count())
+}
+
+/// First line of an exported `CodeBook`, followed by the hex key.
+const CODEBOOK_HEADER: &str = "yapg-codebook v1";
+
+/// A compact record of a batch of codes for checking redemptions, storing
+/// keyed 64-bit hashes (truncated HMAC-SHA256) instead of the codes
+/// themselves.
+///
+/// Export it alongside the batch via `Display` and read it back via
+/// `FromStr` or `CodeBook::load`. Lookups run in logarithmic time, and their
+/// timing depends only on the hashes, which reveal nothing about the codes
+/// to anyone without the key. Note that the key is part of the export, so
+/// the exported file must be protected like the codes: with it, codes from
+/// a small code space can be found by trying them all.
+///
+/// A random code passes `CodeBook::verify` with probability `n / 2^64`.
+///
+/// # Example
+/// ```
+/// let codes = vec!["K7Q2-9XPA".to_string(), "M3RT-V8WZ".to_string()];
+/// let book = yapg_core::CodeBook::new(&codes);
+/// assert!(book.verify("M3RT-V8WZ"));
+/// assert!(!book.verify("M3RT-V8WX"));
+///
+/// let imported: yapg_core::CodeBook = book.to_string().parse().unwrap();
+/// assert!(imported.verify("K7Q2-9XPA"));
+/// assert_eq!(imported.len(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CodeBook {
+    key: [u8; 32],
+    hashes: Vec<u64>,
+}
+
+impl CodeBook {
+    /// Records `codes` under a new random key.
+    pub fn new<S: AsRef<str>>(codes: &[S]) -> Self {
+        let mut key = [0; 32];
+        rand::thread_rng().fill_bytes(&mut key);
+        let mut hashes: Vec<u64> =
+            codes.iter().map(|code| keyed_hash(key, code.as_ref())).collect();
+        hashes.sort_unstable();
+        hashes.dedup();
+        Self { key, hashes }
+    }
+
+    /// Reads a code book exported with `Display` from the file at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    /// Whether `code` belongs to the batch.
+    pub fn verify(&self, code: &str) -> bool {
+        let hash = keyed_hash(self.key, code);
+        let i = self.hashes.partition_point(|h| *h < hash);
+        match self.hashes.get(i) {
+            Some(h) => ct_eq(h.to_be_bytes(), hash.to_be_bytes()),
+            None => false,
+        }
+    }
+
+    /// Number of distinct codes recorded.
+    #[inline]
+    pub fn len(&self) -> usize { self.hashes.len() }
+
+    /// Whether no codes are recorded.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.hashes.is_empty() }
+}
+
+/// First 64 bits (big-endian) of the HMAC-SHA256 of `code` under `key`.
+fn keyed_hash(key: [u8; 32], code: &str) -> u64 {
+    let mac = hmac_sha256::HMAC::mac(code.as_bytes(), key);
+    u64::from_be_bytes(mac[..8].try_into().unwrap())
+}
+
+impl fmt::Display for CodeBook {
+    /// One line with header and key, then one hash per line, all as hex.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", CODEBOOK_HEADER, hex_encode(&self.key))?;
+        for hash in &self.hashes {
+            write!(f, "\n{:016x}", hash)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for CodeBook {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        let invalid = |msg: &str| {
+            io::Error::new(io::ErrorKind::InvalidInput, msg.to_string())
+        };
+        let parse_hex = |hex: &str| {
+            if hex.len() != 16 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(invalid("Invalid code book hash"));
+            }
+            u64::from_str_radix(hex, 16)
+                .map_err(|_| invalid("Invalid code book hash"))
+        };
+        let mut lines = s.lines().map(str::trim).filter(|l| !l.is_empty());
+        let key = lines
+            .next()
+            .unwrap_or_default()
+            .strip_prefix(CODEBOOK_HEADER)
+            .map(str::trim)
+            .filter(|key| key.bytes().all(|b| b.is_ascii_hexdigit()))
+            .and_then(hex_decode)
+            .and_then(|key| key.try_into().ok())
+            .ok_or_else(|| invalid("Not a yapg code book"))?;
+        let mut hashes =
+            lines.map(parse_hex).collect::<io::Result<Vec<_>>>()?;
+        hashes.sort_unstable();
+        hashes.dedup();
+        Ok(Self { key, hashes })
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::{keyed_hash, CodeBook};
+
+    #[test]
+    fn importing_code_books() {
+        let book = CodeBook::new(&["a", "b", "a"]);
+        assert_eq!(book.len(), 2);
+        let exported = book.to_string();
+        assert_eq!(exported.lines().count(), 3);
+        assert_eq!(exported.parse::<CodeBook>().unwrap(), book);
+
+        assert!("".parse::<CodeBook>().is_err());
+        assert!("yapg-codebook v1 00".parse::<CodeBook>().is_err());
+        let key = format!("yapg-codebook v1 {:0>64}", "ff");
+        assert!(key.parse::<CodeBook>().unwrap().is_empty());
+        assert!(format!("{}\nxyz", key).parse::<CodeBook>().is_err());
+        assert!(format!("{}\n+00000000000000f", key)
+            .parse::<CodeBook>()
+            .is_err());
+    }
+
+    #[test]
+    fn hashing_codes() {
+        // as computed by Python's hmac module
+        let mut key = [0; 32];
+        key.iter_mut().enumerate().for_each(|(i, b)| *b = i as u8);
+        assert_eq!(keyed_hash(key, "K7Q2-9XPA"), 0x1660021e485aae24);
+    }
+}
@@ -0,0 +1,75 @@
+use std::io;
+
+/// A button color of color-code locks and similar hardware.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Color {
+    /// Human-readable name, e.g. `"red"`.
+    pub name: &'static str,
+    /// Upper-case initial standing for the color in generated codes.
+    pub initial: char,
+    /// Red, green and blue components.
+    pub rgb: [u8; 3],
+}
+
+/// Palette of colors that are told apart easily, also under poor lighting.
+/// Black is abbreviated `K`, as `B` is taken by blue.
+pub static COLORS: [Color; 8] = [
+    Color { name: "red", initial: 'R', rgb: [0xff, 0x00, 0x00] },
+    Color { name: "orange", initial: 'O', rgb: [0xff, 0x80, 0x00] },
+    Color { name: "yellow", initial: 'Y', rgb: [0xff, 0xff, 0x00] },
+    Color { name: "green", initial: 'G', rgb: [0x00, 0xc0, 0x00] },
+    Color { name: "blue", initial: 'B', rgb: [0x00, 0x00, 0xff] },
+    Color { name: "purple", initial: 'P', rgb: [0x80, 0x00, 0xff] },
+    Color { name: "white", initial: 'W', rgb: [0xff, 0xff, 0xff] },
+    Color { name: "black", initial: 'K', rgb: [0x00, 0x00, 0x00] },
+];
+
+impl Color {
+    /// Looks up the color of `COLORS` abbreviated by `initial`.
+    pub fn by_initial(initial: char) -> Option<&'static Color> {
+        COLORS.iter().find(|color| color.initial == initial)
+    }
+
+    /// Hex triplet as used in CSS, e.g. `"#ff8000"`.
+    pub fn hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.rgb[0], self.rgb[1], self.rgb[2])
+    }
+}
+
+/// The initials of `COLORS`, for use as charset of a `PasswordGenerator`.
+///
+/// # Example
+/// ```
+/// let mut pwg =
+///     yapg_core::PasswordGenerator::new(yapg_core::color_charset(), 6);
+/// assert_eq!(pwg.entropy(), 18);
+/// let colors = yapg_core::colors_of(&pwg.generate()).unwrap();
+/// assert_eq!(colors.len(), 6);
+/// ```
+pub fn color_charset() -> Vec<char> {
+    COLORS.iter().map(|color| color.initial).collect()
+}
+
+/// Decodes a code of color initials, failing with an
+/// `io::ErrorKind::InvalidInput` error on unknown initials.
+///
+/// # Example
+/// ```
+/// let colors = yapg_core::colors_of("RKW").unwrap();
+/// let names: Vec<_> = colors.iter().map(|c| c.name).collect();
+/// assert_eq!(names, ["red", "black", "white"]);
+/// assert_eq!(colors[0].hex(), "#ff0000");
+/// assert!(yapg_core::colors_of("RX").is_err());
+/// ```
+pub fn colors_of(code: &str) -> io::Result<Vec<&'static Color>> {
+    code.chars()
+        .map(|c| {
+            Color::by_initial(c).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Not a color initial: {}", c),
+                )
+            })
+        })
+        .collect()
+}
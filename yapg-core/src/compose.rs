@@ -0,0 +1,142 @@
+use std::convert::TryFrom;
+use std::{fmt, io};
+
+use crate::CharsetName;
+
+/// Number of characters to draw from each of several charsets, e.g. 6
+/// lowercase letters, 2 uppercase letters, 2 digits and 2 special
+/// characters, as in the composition rules of some admins. See
+/// `PasswordGenerator::generate_composed`.
+///
+/// Parsed from comma-separated `<charset>:<count>` pairs, using the
+/// abbreviations of `CharsetName`. The charsets must not overlap.
+///
+/// # Example
+/// ```
+/// let composition: yapg_core::Composition =
+///     "L:6,U:2,N:2,S:2".parse().unwrap();
+/// assert_eq!(composition.length(), 12);
+/// assert_eq!(composition.to_string(), "L:6,U:2,N:2,S:2");
+/// assert!("A:4,L:2".parse::<yapg_core::Composition>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Composition {
+    parts: Vec<(CharsetName, usize)>,
+}
+
+impl Composition {
+    /// Creates a composition from charsets and the number of characters
+    /// drawn from each. Fails if charsets overlap, as each character must
+    /// belong to a single charset for the entropy to be correct.
+    pub fn new(parts: Vec<(CharsetName, usize)>) -> io::Result<Self> {
+        for (i, (a, _)) in parts.iter().enumerate() {
+            for (b, _) in parts[i + 1..].iter() {
+                let b = b.chars();
+                if a.chars().iter().any(|c| b.contains(c)) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("Charsets {:?} and {:?} overlap", a, b),
+                    ));
+                }
+            }
+        }
+        Ok(Self { parts })
+    }
+
+    /// The charsets with the number of characters drawn from each.
+    #[inline]
+    pub fn parts(&self) -> &[(CharsetName, usize)] { &self.parts }
+
+    /// Total number of characters.
+    pub fn length(&self) -> usize { self.parts.iter().map(|(_, n)| n).sum() }
+
+    /// Number of possible passwords: the ways of placing each charset's
+    /// characters among the positions, times the choices of characters.
+    ///
+    /// # Example
+    /// ```
+    /// let composition: yapg_core::Composition = "L:1,N:1".parse().unwrap();
+    /// // 2 orders of a letter and a digit
+    /// assert_eq!(composition.combinations(), 2.0 * 26.0 * 10.0);
+    /// ```
+    pub fn combinations(&self) -> f64 { self.entropy().exp2() }
+
+    /// Entropy of the composed passwords in bits. Every possible password is
+    /// equally likely, as the characters are shuffled uniformly.
+    pub fn entropy(&self) -> f64 {
+        let log2_factorial =
+            |n: usize| (2..=n).map(|k| (k as f64).log2()).sum::<f64>();
+        let mut bits = log2_factorial(self.length());
+        for (name, n) in self.parts.iter() {
+            bits -= log2_factorial(*n);
+            bits += *n as f64 * (name.chars().len() as f64).log2();
+        }
+        bits
+    }
+}
+
+impl std::str::FromStr for Composition {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        let invalid = |part: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid composition {:?} (expected e.g. L:6)", part),
+            )
+        };
+        let mut parts = vec![];
+        for part in s.split(',') {
+            let mut fields = part.trim().splitn(2, ':');
+            let mut name = fields.next().unwrap().trim().chars();
+            let name = match (name.next(), name.next()) {
+                (Some(c), None) => CharsetName::try_from(c)?,
+                _ => return Err(invalid(part)),
+            };
+            let count = fields
+                .next()
+                .and_then(|n| n.trim().parse().ok())
+                .ok_or_else(|| invalid(part))?;
+            parts.push((name, count));
+        }
+        Self::new(parts)
+    }
+}
+
+impl fmt::Display for Composition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let parts: Vec<String> = self
+            .parts
+            .iter()
+            .map(|(name, n)| format!("{}:{}", char::from(*name), n))
+            .collect();
+        write!(f, "{}", parts.join(","))
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::Composition;
+    use crate::CharsetName;
+
+    #[test]
+    fn counting_arrangements() {
+        let composition: Composition = "L:6,U:2,N:2,S:2".parse().unwrap();
+        // 12! / (6! 2! 2! 2!) arrangements
+        let special = CharsetName::Special.chars().len() as f64;
+        let expected =
+            (83_160f64 * 26f64.powi(8) * 100.0 * special.powi(2)).log2();
+        assert!((composition.entropy() - expected).abs() < 1e-9);
+        let single: Composition = "N:4".parse().unwrap();
+        assert!((single.entropy() - 4.0 * 10f64.log2()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn parsing_compositions() {
+        assert!(" L:2 , N : 3".parse::<Composition>().is_ok());
+        for invalid in ["", "L", "L:", "L:x", "LU:2", "Q:2", "L:2,L:1"].iter() {
+            assert!(invalid.parse::<Composition>().is_err(), "{}", invalid);
+        }
+    }
+}
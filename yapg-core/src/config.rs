@@ -0,0 +1,129 @@
+use crate::{
+    BuildError, CharsetSpec, PasswordGenerator, PasswordGeneratorBuilder,
+    RandomSource,
+};
+
+/// Settings of a `PasswordGenerator` that applications can persist and
+/// restore, e.g. as JSON, TOML or YAML with the `serde` feature. Missing
+/// settings take their default: std64 chars, 24 of them, no further demands
+/// and the thread-local RNG.
+///
+/// The charset is (de)serialized in the canonical form of
+/// `CharsetSpec::to_spec_string`, e.g. `"LUN+-_"`.
+///
+/// # Example
+/// ```
+/// use yapg_core::{CharsetSpec, GeneratorConfig};
+///
+/// let config = GeneratorConfig {
+///     charset: "N".parse().unwrap(),
+///     length: 6,
+///     ..GeneratorConfig::default()
+/// };
+/// let pin = config.build().unwrap().generate();
+/// assert!(pin.len() == 6 && pin.chars().all(|c| c.is_ascii_digit()));
+/// assert_eq!(GeneratorConfig::default().charset, CharsetSpec::std64());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct GeneratorConfig {
+    /// Charset the passwords are drawn from.
+    pub charset: CharsetSpec,
+    /// Number of chars per password.
+    pub length: usize,
+    /// Whether each password holds a char of each class, see
+    /// `PasswordGenerator::require_each_class`.
+    pub require_each_class: bool,
+    /// Entropy floor in bits, if any, see
+    /// `PasswordGenerator::require_entropy`.
+    pub min_entropy: Option<f64>,
+    /// RNG the passwords are drawn with.
+    pub rng: RandomSource,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        Self {
+            charset: CharsetSpec::std64(),
+            length: 24,
+            require_each_class: false,
+            min_entropy: None,
+            rng: RandomSource::Thread,
+        }
+    }
+}
+
+impl GeneratorConfig {
+    /// A builder with these settings, to be tweaked further.
+    pub fn builder(&self) -> PasswordGeneratorBuilder {
+        let mut builder = PasswordGenerator::builder()
+            .charset(self.charset.build())
+            .length(self.length)
+            .require_each_class(self.require_each_class)
+            .rng(self.rng);
+        if let Some(bits) = self.min_entropy {
+            builder = builder.require_entropy(bits);
+        }
+        builder
+    }
+
+    /// Creates the `PasswordGenerator`, failing if the settings cannot yield
+    /// the passwords they describe.
+    #[inline]
+    pub fn build(&self) -> Result<PasswordGenerator, BuildError> {
+        self.builder().build()
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::GeneratorConfig;
+    use crate::BuildError;
+
+    #[test]
+    fn building_from_config() {
+        let config =
+            GeneratorConfig { length: 0, ..GeneratorConfig::default() };
+        assert!(matches!(config.build(), Err(BuildError::ZeroLength)));
+        let config = GeneratorConfig {
+            charset: "N".parse().unwrap(),
+            length: 4,
+            min_entropy: Some(20.0),
+            ..GeneratorConfig::default()
+        };
+        assert!(matches!(config.build(), Err(BuildError::EntropyTooLow(_))));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn persisting_config() {
+        let config = GeneratorConfig {
+            charset: "LN+-".parse().unwrap(),
+            length: 16,
+            require_each_class: true,
+            min_entropy: Some(80.0),
+            rng: crate::RandomSource::ChaCha,
+        };
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(
+            json,
+            concat!(
+                r#"{"charset":"LN+-","length":16,"require_each_class":true,"#,
+                r#""min_entropy":80.0,"rng":"chacha"}"#
+            )
+        );
+        assert_eq!(
+            serde_json::from_str::<GeneratorConfig>(&json).unwrap(),
+            config
+        );
+
+        let partial = r#"{"charset": "N", "length": 6}"#;
+        let config: GeneratorConfig = serde_json::from_str(partial).unwrap();
+        assert_eq!(config.rng, crate::RandomSource::Thread);
+        assert_eq!(config.build().unwrap().generate().len(), 6);
+        assert!(serde_json::from_str::<GeneratorConfig>(r#"{"charset": "Q"}"#)
+            .is_err());
+    }
+}
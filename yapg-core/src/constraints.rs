@@ -0,0 +1,553 @@
+use std::convert::TryFrom;
+use std::{fmt, io};
+
+#[cfg(feature = "num-bigint")]
+use num_bigint::BigUint;
+use rand::Rng;
+
+use crate::{CharsetName, GenerateError, Grapheme};
+
+/// Minimum and maximum number of characters from some charsets, e.g. at
+/// least 2 digits and at most 1 special character, as some sites demand.
+/// See `PasswordGenerator::constraints`.
+///
+/// Parsed from comma-separated `<charset><op><count>` bounds, with `>=`,
+/// `<=` or `=` as operator and the abbreviations of `CharsetName`. The
+/// charsets must not overlap.
+///
+/// # Example
+/// ```
+/// use yapg_core::{CharsetName, Constraints};
+///
+/// let constraints = Constraints::new()
+///     .at_least(CharsetName::Numeric, 2)
+///     .at_most(CharsetName::Special, 1);
+/// assert_eq!(constraints.to_string(), "N>=2,S<=1");
+/// assert_eq!("N>=2,S<=1".parse::<Constraints>().unwrap(), constraints);
+/// assert!("A>=2,U<=1".parse::<Constraints>().is_err());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Constraints {
+    /// Charsets with the minimum and (if any) maximum count of each.
+    bounds: Vec<(CharsetName, usize, Option<usize>)>,
+}
+
+impl Constraints {
+    /// No constraints at all.
+    #[inline]
+    pub fn new() -> Self { Self::default() }
+
+    /// Demands at least `n` characters from `charset`, consumes and returns
+    /// itself.
+    pub fn at_least(mut self, charset: CharsetName, n: usize) -> Self {
+        self.bound(charset).1 = n;
+        self
+    }
+
+    /// Allows at most `n` characters from `charset`, consumes and returns
+    /// itself.
+    pub fn at_most(mut self, charset: CharsetName, n: usize) -> Self {
+        self.bound(charset).2 = Some(n);
+        self
+    }
+
+    /// Demands exactly `n` characters from `charset`, consumes and returns
+    /// itself.
+    #[inline]
+    pub fn exactly(self, charset: CharsetName, n: usize) -> Self {
+        self.at_least(charset, n).at_most(charset, n)
+    }
+
+    /// The bounds of `charset`, added without any if missing.
+    fn bound(
+        &mut self,
+        charset: CharsetName,
+    ) -> &mut (CharsetName, usize, Option<usize>) {
+        let i = match self.bounds.iter().position(|(c, _, _)| *c == charset) {
+            Some(i) => i,
+            None => {
+                self.bounds.push((charset, 0, None));
+                self.bounds.len() - 1
+            },
+        };
+        &mut self.bounds[i]
+    }
+
+    /// The charsets with their minimum and (if any) maximum count.
+    #[inline]
+    pub fn bounds(&self) -> &[(CharsetName, usize, Option<usize>)] {
+        &self.bounds
+    }
+
+    /// Fails if charsets overlap, as each character must count towards a
+    /// single bound for the entropy to be correct, or if a minimum exceeds
+    /// its maximum.
+    pub fn check(&self) -> io::Result<()> {
+        let invalid = |msg| io::Error::new(io::ErrorKind::InvalidInput, msg);
+        for (i, (a, min, max)) in self.bounds.iter().enumerate() {
+            if max.is_some_and(|max| max < *min) {
+                return Err(invalid(format!(
+                    "At least {} but at most {:?} chars of {:?}",
+                    min, max, a
+                )));
+            }
+            for (b, _, _) in self.bounds[i + 1..].iter() {
+                let b_chars = b.chars();
+                if a.chars().iter().any(|c| b_chars.contains(c)) {
+                    return Err(invalid(format!(
+                        "Charsets {:?} and {:?} overlap",
+                        a, b
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Constraints {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        let invalid = |part: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid constraint {:?} (expected e.g. N>=2)", part),
+            )
+        };
+        let mut constraints = Self::new();
+        for part in s.split(',') {
+            let part = part.trim();
+            let mut chars = part.chars();
+            let name = chars.next().ok_or_else(|| invalid(part))?;
+            let name = CharsetName::try_from(name)?;
+            let rest = chars.as_str().trim_start();
+            let (op, count) = [">=", "<=", "="]
+                .iter()
+                .find_map(|op| rest.strip_prefix(op).map(|n| (*op, n)))
+                .ok_or_else(|| invalid(part))?;
+            let n = count.trim().parse().map_err(|_| invalid(part))?;
+            constraints = match op {
+                ">=" => constraints.at_least(name, n),
+                "<=" => constraints.at_most(name, n),
+                _ => constraints.exactly(name, n),
+            };
+        }
+        constraints.check()?;
+        Ok(constraints)
+    }
+}
+
+impl fmt::Display for Constraints {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut parts = vec![];
+        for (name, min, max) in self.bounds.iter() {
+            let name = char::from(*name);
+            if *max == Some(*min) {
+                parts.push(format!("{}={}", name, min));
+                continue;
+            }
+            if *min > 0 {
+                parts.push(format!("{}>={}", name, min));
+            }
+            if let Some(max) = max {
+                parts.push(format!("{}<={}", name, max));
+            }
+        }
+        write!(f, "{}", parts.join(","))
+    }
+}
+
+/// `Constraints` applied to the items of a charset: which items each bound
+/// counts, to count and sample the passwords satisfying all bounds.
+///
+/// Counting works on the logarithms of the fractions of passwords rather
+/// than their numbers, which would overflow, or the fractions themselves,
+/// which would underflow. Passwords are sampled uniformly by first drawing
+/// how many items each bound gets, weighted by the number of passwords with
+/// those counts, then their positions and finally the items themselves.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ConstrainedCharset {
+    constraints: Constraints,
+    /// Indices of the items in each bound's charset.
+    classes: Vec<Vec<usize>>,
+    /// Indices of the items in no bound's charset.
+    rest: Vec<usize>,
+    /// Index of the bound counting each item, if any.
+    class_of: Vec<Option<usize>>,
+}
+
+impl ConstrainedCharset {
+    /// Applies `constraints` to `charset`. Fails if the constraints are
+    /// invalid, see `Constraints::check`.
+    pub(crate) fn new(
+        constraints: Constraints,
+        charset: &[Grapheme],
+    ) -> io::Result<Self> {
+        constraints.check()?;
+        let names: Vec<Vec<char>> = constraints
+            .bounds
+            .iter()
+            .map(|(name, _, _)| name.chars())
+            .collect();
+        let class_of: Vec<Option<usize>> = charset
+            .iter()
+            .map(|item| {
+                let c = item.as_char()?;
+                names.iter().position(|chars| chars.contains(&c))
+            })
+            .collect();
+        let mut classes = vec![vec![]; names.len()];
+        let mut rest = vec![];
+        for (i, class) in class_of.iter().enumerate() {
+            match class {
+                Some(class) => classes[*class].push(i),
+                None => rest.push(i),
+            }
+        }
+        Ok(Self { constraints, classes, rest, class_of })
+    }
+
+    /// The constraints applied.
+    #[inline]
+    pub(crate) fn constraints(&self) -> &Constraints { &self.constraints }
+
+    /// Total number of items.
+    fn len(&self) -> usize { self.class_of.len() }
+
+    /// Minimum and maximum count of bound `i`, the latter capped at
+    /// `length` and at zero for charsets without items.
+    fn range(&self, i: usize, length: usize) -> (usize, usize) {
+        let (_, min, max) = self.constraints.bounds[i];
+        let max =
+            if self.classes[i].is_empty() { 0 } else { max.unwrap_or(length) };
+        (min, max.min(length))
+    }
+
+    /// Whether any password of `length` items satisfies all bounds.
+    pub(crate) fn feasible(&self, length: usize) -> bool {
+        let ranges: Vec<_> =
+            (0..self.classes.len()).map(|i| self.range(i, length)).collect();
+        ranges.iter().all(|(min, max)| min <= max)
+            && ranges.iter().map(|(min, _)| min).sum::<usize>() <= length
+            && (!self.rest.is_empty()
+                || ranges.iter().map(|(_, max)| max).sum::<usize>() >= length)
+    }
+
+    /// Whether the items at `indices` satisfy all bounds.
+    pub(crate) fn accepts(&self, indices: &[usize]) -> bool {
+        let mut counts = vec![0; self.classes.len()];
+        for i in indices {
+            if let Some(class) = self.class_of[*i] {
+                counts[class] += 1;
+            }
+        }
+        self.constraints.bounds.iter().zip(counts).all(|((_, min, max), n)| {
+            *min <= n && max.is_none_or(|max| n <= max)
+        })
+    }
+
+    /// Natural logarithms of the fractions of passwords filling `j` given
+    /// positions with items of the first `i` bounds' charsets only, all
+    /// bounds satisfied, by `i` and `j`. Fractions are of all passwords of
+    /// `j` items, and kept as logarithms since they underflow for long
+    /// passwords; impossible fillings are negative infinity.
+    fn ln_table(&self, length: usize) -> Vec<Vec<f64>> {
+        let ln_binomial = ln_binomials(length);
+        let n = self.len() as f64;
+        let mut table = vec![vec![f64::NEG_INFINITY; length + 1]];
+        table[0][0] = 0.0;
+        for (i, class) in self.classes.iter().enumerate() {
+            let p = class.len() as f64 / n;
+            let (min, max) = self.range(i, length);
+            let prev = &table[i];
+            let row = (0..=length)
+                .map(|j| {
+                    ln_sum_exp((min..=max.min(j)).map(|k| {
+                        ln_binomial(j, k) + ln_pow(p, k) + prev[j - k]
+                    }))
+                })
+                .collect();
+            table.push(row);
+        }
+        table
+    }
+
+    /// Logarithmic weights of filling `j` of `length` positions with items
+    /// of the bounds' charsets, the rest with the remaining items, by `j`.
+    fn ln_rest_weights(&self, length: usize, table: &[Vec<f64>]) -> Vec<f64> {
+        let ln_binomial = ln_binomials(length);
+        let p = self.rest.len() as f64 / self.len() as f64;
+        let last = &table[self.classes.len()];
+        (0..=length)
+            .map(|j| ln_binomial(length, j) + ln_pow(p, length - j) + last[j])
+            .collect()
+    }
+
+    /// Natural logarithm of the fraction of all passwords of `length` items
+    /// satisfying all bounds, negative infinity if none does.
+    pub(crate) fn ln_fraction(&self, length: usize) -> f64 {
+        if !self.feasible(length) {
+            return f64::NEG_INFINITY;
+        }
+        let table = self.ln_table(length);
+        ln_sum_exp(self.ln_rest_weights(length, &table).into_iter())
+    }
+
+    /// Number of passwords of `length` items satisfying all bounds, counted
+    /// exactly like `ln_table` and `ln_rest_weights` do approximately.
+    #[cfg(feature = "num-bigint")]
+    pub(crate) fn count_exact(&self, length: usize) -> BigUint {
+        if !self.feasible(length) {
+            return BigUint::default();
+        }
+        let mut ways = vec![BigUint::default(); length + 1];
+        ways[0] = BigUint::from(1u8);
+        for (i, class) in self.classes.iter().enumerate() {
+            let (min, max) = self.range(i, length);
+            let size = BigUint::from(class.len());
+            let powers: Vec<BigUint> =
+                (0..=max).map(|k| size.pow(k as u32)).collect();
+            ways = binomial_rows(length)
+                .enumerate()
+                .map(|(j, row)| {
+                    (min..=max.min(j))
+                        .map(|k| &row[k] * &powers[k] * &ways[j - k])
+                        .sum()
+                })
+                .collect();
+        }
+        let rest = BigUint::from(self.rest.len());
+        // the last row holds the binomials `(length choose j)`
+        let row = binomial_rows(length).last().unwrap();
+        (0..=length)
+            .map(|j| &row[j] * rest.pow((length - j) as u32) * &ways[j])
+            .sum()
+    }
+
+    /// Draws the indices of `length` items satisfying all bounds, uniformly
+    /// among all such passwords. Fails if there are none, see `feasible`.
+    pub(crate) fn sample<R: Rng>(
+        &self,
+        rng: &mut R,
+        length: usize,
+    ) -> Result<Vec<usize>, GenerateError> {
+        let unsatisfiable = || GenerateError::Unsatisfiable {
+            length,
+            constraints: self.constraints.to_string(),
+        };
+        if !self.feasible(length) {
+            return Err(unsatisfiable());
+        }
+        let ln_binomial = ln_binomials(length);
+        let table = self.ln_table(length);
+        let weights = self.ln_rest_weights(length, &table);
+        let mut j = pick(rng, &weights).ok_or_else(unsatisfiable)?;
+        // positions for the bounds' charsets come first, then the rest
+        let mut positions: Vec<usize> = (0..length).collect();
+        rng.shuffle(&mut positions);
+        let mut indices = vec![0; length];
+        for &pos in positions[j..].iter() {
+            indices[pos] = self.rest[rng.gen_range(0, self.rest.len())];
+        }
+        let n = self.len() as f64;
+        for (i, class) in self.classes.iter().enumerate().rev() {
+            let p = class.len() as f64 / n;
+            let (min, max) = self.range(i, length);
+            let weights: Vec<f64> = (0..=max.min(j))
+                .map(|k| match k < min {
+                    true => f64::NEG_INFINITY,
+                    false => ln_binomial(j, k) + ln_pow(p, k) + table[i][j - k],
+                })
+                .collect();
+            let k = pick(rng, &weights).ok_or_else(unsatisfiable)?;
+            for &pos in positions[j - k..j].iter() {
+                indices[pos] = class[rng.gen_range(0, class.len())];
+            }
+            j -= k;
+        }
+        Ok(indices)
+    }
+}
+
+/// `ln(sum(exp(x)))` of `terms`, computed against their maximum so that
+/// neither over- nor underflows. Negative infinity if there are no terms.
+pub(crate) fn ln_sum_exp<I: Iterator<Item = f64>>(terms: I) -> f64 {
+    let terms: Vec<f64> = terms.collect();
+    let max = terms.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    if max == f64::NEG_INFINITY {
+        return max;
+    }
+    max + terms.iter().map(|x| (x - max).exp()).sum::<f64>().ln()
+}
+
+/// `ln(p^k)`, which is zero for `k = 0` even if `p` is.
+fn ln_pow(p: f64, k: usize) -> f64 {
+    if k == 0 {
+        0.0
+    } else {
+        k as f64 * p.ln()
+    }
+}
+
+/// The natural logarithm of binomial coefficients `(n choose k)` for `n` up
+/// to `max_n`.
+fn ln_binomials(max_n: usize) -> impl Fn(usize, usize) -> f64 {
+    let mut ln_factorials = vec![0.0; max_n + 1];
+    for n in 1..=max_n {
+        ln_factorials[n] = ln_factorials[n - 1] + (n as f64).ln();
+    }
+    move |n, k| ln_factorials[n] - ln_factorials[k] - ln_factorials[n - k]
+}
+
+/// The rows of Pascal's triangle up to row `n`, i.e. the binomial
+/// coefficients `(j choose k)` for `j` up to `n`, by `j`.
+#[cfg(feature = "num-bigint")]
+fn binomial_rows(n: usize) -> impl Iterator<Item = Vec<BigUint>> {
+    let one = BigUint::from(1u8);
+    std::iter::successors(Some(vec![one.clone()]), move |row| {
+        let mut next = vec![one.clone(); row.len() + 1];
+        for k in 1..row.len() {
+            next[k] = &row[k - 1] + &row[k];
+        }
+        Some(next)
+    })
+    .take(n + 1)
+}
+
+/// Picks an index with probability proportional to the exponential of its
+/// logarithmic weight, or none if all weights are zero.
+pub(crate) fn pick<R: Rng>(rng: &mut R, ln_weights: &[f64]) -> Option<usize> {
+    let max = ln_weights.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    if !max.is_finite() {
+        return None;
+    }
+    // relative to the largest, so that at least one weight is 1
+    let weights: Vec<f64> =
+        ln_weights.iter().map(|w| (w - max).exp()).collect();
+    let mut x = rng.gen::<f64>() * weights.iter().sum::<f64>();
+    for (i, w) in weights.iter().enumerate() {
+        x -= w;
+        if x < 0.0 {
+            return Some(i);
+        }
+    }
+    // rounding may leave a tiny remainder
+    weights.iter().rposition(|w| *w > 0.0)
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::{ConstrainedCharset, Constraints};
+    use crate::{CharsetName, Grapheme};
+
+    fn items(s: &str) -> Vec<Grapheme> {
+        s.chars().map(Grapheme::from).collect()
+    }
+
+    /// Counts the passwords of `length` items satisfying the constraints by
+    /// enumerating all of them.
+    fn brute_force(cc: &ConstrainedCharset, length: usize) -> usize {
+        let n = cc.len();
+        (0..n.pow(length as u32))
+            .filter(|x| {
+                let indices: Vec<usize> =
+                    (0..length).map(|k| x / n.pow(k as u32) % n).collect();
+                cc.accepts(&indices)
+            })
+            .count()
+    }
+
+    #[test]
+    fn counting_constrained_passwords() {
+        let charset = items("ab01+-X");
+        let constraints = [
+            "N>=2,X<=1",
+            "N=1,L>=1",
+            "L>=1,N>=1,X>=1,U>=1",
+            "L<=0",
+            "U>=2,N=1",
+        ];
+        for constraints in constraints.iter() {
+            let cc =
+                ConstrainedCharset::new(constraints.parse().unwrap(), &charset)
+                    .unwrap();
+            for length in 0..=4 {
+                let expected = brute_force(&cc, length) as f64;
+                let counted =
+                    cc.ln_fraction(length).exp() * 7f64.powi(length as i32);
+                assert!(
+                    (counted - expected).abs() < 1e-6,
+                    "{} at {}: {} instead of {}",
+                    constraints,
+                    length,
+                    counted,
+                    expected
+                );
+                assert_eq!(cc.feasible(length), expected > 0.0);
+                #[cfg(feature = "num-bigint")]
+                assert_eq!(
+                    cc.count_exact(length),
+                    num_bigint::BigUint::from(expected as usize)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn sampling_uniformly() {
+        let charset = items("ab01");
+        let constraints = Constraints::new().at_least(CharsetName::Numeric, 2);
+        let cc = ConstrainedCharset::new(constraints, &charset).unwrap();
+        // 3 * 4 * 2 passwords of 3 items have 2 digits, 8 have 3
+        let mut counts = std::collections::BTreeMap::new();
+        let mut rng = rand::thread_rng();
+        for _ in 0..32_000 {
+            let indices = cc.sample(&mut rng, 3).unwrap();
+            assert!(cc.accepts(&indices));
+            *counts.entry(indices).or_insert(0) += 1;
+        }
+        assert_eq!(counts.len(), 32);
+        assert!(counts.values().all(|n| *n > 800 && *n < 1200), "{:?}", counts);
+    }
+
+    #[test]
+    fn sampling_long_passwords() {
+        // the counts of these overflow f64 long before the lengths
+        let charset = items("abAB01+-");
+        let cases = [
+            ("N>=900", 1000),
+            ("N>=3000", 4000),
+            ("L>=1,U>=1,N>=1,S>=1", 4000),
+        ];
+        let mut rng = rand::thread_rng();
+        for (constraints, length) in cases.iter() {
+            let cc =
+                ConstrainedCharset::new(constraints.parse().unwrap(), &charset)
+                    .unwrap();
+            assert!(cc.ln_fraction(*length).is_finite(), "{}", constraints);
+            let indices = cc.sample(&mut rng, *length).unwrap();
+            assert_eq!(indices.len(), *length);
+            assert!(cc.accepts(&indices), "{}", constraints);
+        }
+        let cc =
+            ConstrainedCharset::new("N>=5".parse().unwrap(), &charset).unwrap();
+        assert_eq!(
+            cc.sample(&mut rng, 4).unwrap_err().to_string(),
+            "No password of length 4 satisfies the constraints N>=5"
+        );
+    }
+
+    #[test]
+    fn parsing_constraints() {
+        let constraints: Constraints =
+            " N >= 2, N<=4 ,S=1,L>=0".parse().unwrap();
+        assert_eq!(constraints.to_string(), "N>=2,N<=4,S=1");
+        for invalid in
+            ["", "N", "N>2", "N>=x", "Q>=1", "N>=3,N<=2", "A>=1,L<=2"].iter()
+        {
+            assert!(invalid.parse::<Constraints>().is_err(), "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,389 @@
+use std::collections::HashSet;
+use std::fmt::{self, Write};
+use std::io;
+
+use rand::RngCore;
+
+use crate::output::json_string;
+use crate::presets::Preset;
+use crate::{
+    random_string, CharsetSpec, Constraints, OutputFormat, PasswordGenerator,
+    Policy, PronounceableGenerator, SyllableModel, CHARSET_ALPHA_LOWER,
+    CHARSET_NUMERIC,
+};
+
+/// Number of syllables of pronounceable usernames, e.g. `bamkotipuz`.
+const USERNAME_SYLLABLES: usize = 4;
+
+/// Alphabet of RFC 4648 base32, as expected by TOTP authenticator apps.
+static BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Encodes `bytes` as unpadded RFC 4648 base32.
+pub(crate) fn base32(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity((bytes.len() * 8).div_ceil(5));
+    let (mut buffer, mut bits) = (0u32, 0);
+    for b in bytes {
+        buffer = (buffer << 8) | *b as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            s.push(BASE32_ALPHABET[(buffer >> bits) as usize & 0x1f] as char);
+        }
+    }
+    if bits > 0 {
+        s.push(BASE32_ALPHABET[(buffer << (5 - bits)) as usize & 0x1f] as char);
+    }
+    s
+}
+
+/// A bundle of credentials for provisioning a single account.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CredentialSet {
+    /// Login name, e.g. `user-4k2j9x1m`.
+    pub username: String,
+    /// The account password.
+    pub password: String,
+    /// Single-use codes for account recovery, e.g. `x7k2m-9pq4r`.
+    pub recovery_codes: Vec<String>,
+    /// Base32-encoded shared secret for TOTP (RFC 6238) authenticator apps.
+    pub totp_secret: String,
+}
+
+/// Generates complete `CredentialSet`s from a single RNG.
+///
+/// # Example
+/// ```
+/// let mut csg = yapg_core::CredentialSetGenerator::new()
+///     .username_prefix("svc-")
+///     .recovery_codes(4);
+/// let creds = csg.generate();
+/// assert!(creds.username.starts_with("svc-"));
+/// assert_eq!(creds.password.chars().count(), 24);
+/// assert_eq!(creds.recovery_codes.len(), 4);
+/// assert_eq!(creds.recovery_codes[0].len(), 11);
+/// assert_eq!(creds.totp_secret.len(), 32);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CredentialSetGenerator {
+    username_prefix: String,
+    username_length: usize,
+    password_charset: Vec<char>,
+    password_length: usize,
+    recovery_codes: usize,
+    totp_bytes: usize,
+    rng: rand::ThreadRng,
+}
+
+impl CredentialSetGenerator {
+    /// Creates a generator with defaults: usernames `user-` followed by eight
+    /// lower-case alphanumerics, 24 character passwords from
+    /// `CharsetSpec::std64`, ten recovery codes and a 160 bit TOTP secret
+    /// (as recommended by RFC 4226).
+    pub fn new() -> Self {
+        Self {
+            username_prefix: "user-".to_string(),
+            username_length: 8,
+            password_charset: CharsetSpec::std64().construct(),
+            password_length: 24,
+            recovery_codes: 10,
+            totp_bytes: 20,
+            rng: rand::thread_rng(),
+        }
+    }
+
+    /// Changes the fixed prefix of usernames, consumes and returns itself.
+    #[inline]
+    pub fn username_prefix(mut self, prefix: &str) -> Self {
+        self.username_prefix = prefix.to_string();
+        self
+    }
+
+    /// Changes charset and length of passwords, consumes and returns itself.
+    #[inline]
+    pub fn password(mut self, charset: Vec<char>, length: usize) -> Self {
+        self.password_charset = charset;
+        self.password_length = length;
+        self
+    }
+
+    /// Changes the number of recovery codes, consumes and returns itself.
+    #[inline]
+    pub fn recovery_codes(mut self, n: usize) -> Self {
+        self.recovery_codes = n;
+        self
+    }
+
+    /// Generates one set of credentials.
+    pub fn generate(&mut self) -> CredentialSet {
+        let alnum = [&CHARSET_ALPHA_LOWER[..], &CHARSET_NUMERIC[..]].concat();
+        let username = format!(
+            "{}{}",
+            self.username_prefix,
+            random_string(&mut self.rng, &alnum, self.username_length)
+        );
+        let password = random_string(
+            &mut self.rng,
+            &self.password_charset,
+            self.password_length,
+        );
+        let recovery_codes = (0..self.recovery_codes)
+            .map(|_| {
+                format!(
+                    "{}-{}",
+                    random_string(&mut self.rng, &alnum, 5),
+                    random_string(&mut self.rng, &alnum, 5)
+                )
+            })
+            .collect();
+        let mut secret = vec![0; self.totp_bytes];
+        self.rng.fill_bytes(&mut secret);
+        let totp_secret = base32(&secret);
+        CredentialSet { username, password, recovery_codes, totp_secret }
+    }
+}
+
+impl Default for CredentialSetGenerator {
+    fn default() -> Self { Self::new() }
+}
+
+/// Styles of the usernames generated by `CredentialPairGenerator`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UsernameStyle {
+    /// `user-` followed by eight lower-case alphanumerics, e.g.
+    /// `user-4k2j9x1m`.
+    Random,
+    /// Four syllables of the default `SyllableModel`, e.g. `bamkotipuz`.
+    Pronounceable,
+}
+
+impl std::str::FromStr for UsernameStyle {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        match s {
+            "random" => Ok(Self::Random),
+            "pronounceable" => Ok(Self::Pronounceable),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Invalid username style {:?} (expected random or \
+                     pronounceable)",
+                    s
+                ),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for UsernameStyle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Random => write!(f, "random"),
+            Self::Pronounceable => write!(f, "pronounceable"),
+        }
+    }
+}
+
+/// A username with its password, e.g. for a service account.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CredentialPair {
+    /// Login name, e.g. `bamkotipuz`.
+    pub username: String,
+    /// The account password.
+    pub password: String,
+}
+
+impl CredentialPair {
+    /// Renders `pairs` in `format`, including a trailing newline: plain as
+    /// tab-separated lines, JSON and YAML as a `credentials` list. Fails for
+    /// backup sheets, which hold a single secret each.
+    ///
+    /// # Example
+    /// ```
+    /// use yapg_core::{CredentialPair, OutputFormat};
+    ///
+    /// let pairs = vec![CredentialPair {
+    ///     username: "bamko".to_string(),
+    ///     password: "a\"b".to_string(),
+    /// }];
+    /// assert_eq!(
+    ///     CredentialPair::render(&pairs, OutputFormat::Plain).unwrap(),
+    ///     "bamko\ta\"b\n"
+    /// );
+    /// let json = CredentialPair::render(&pairs, OutputFormat::Json).unwrap();
+    /// assert!(json.starts_with("{\"credentials\":[{\"username\":\"bamko\","));
+    /// let sheet = CredentialPair::render(&pairs, OutputFormat::BackupSheet);
+    /// assert!(sheet.is_err());
+    /// ```
+    pub fn render(pairs: &[Self], format: OutputFormat) -> io::Result<String> {
+        let mut s = String::new();
+        match format {
+            OutputFormat::Plain => {
+                for pair in pairs {
+                    writeln!(s, "{}\t{}", pair.username, pair.password)
+                        .unwrap();
+                }
+            },
+            OutputFormat::Json => {
+                let pairs: Vec<String> = pairs
+                    .iter()
+                    .map(|pair| {
+                        format!(
+                            "{{\"username\":{},\"password\":{}}}",
+                            json_string(&pair.username),
+                            json_string(&pair.password)
+                        )
+                    })
+                    .collect();
+                writeln!(s, "{{\"credentials\":[{}]}}", pairs.join(","))
+                    .unwrap();
+            },
+            OutputFormat::Yaml => {
+                s.push_str("credentials:\n");
+                for pair in pairs {
+                    writeln!(
+                        s,
+                        "  - username: {}",
+                        json_string(&pair.username)
+                    )
+                    .unwrap();
+                    writeln!(
+                        s,
+                        "    password: {}",
+                        json_string(&pair.password)
+                    )
+                    .unwrap();
+                }
+            },
+            OutputFormat::BackupSheet => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Credential pairs cannot be rendered as backup sheets",
+                ))
+            },
+        }
+        Ok(s)
+    }
+}
+
+/// Generates usernames along with passwords following the charset, length
+/// and policy of a `Preset`, e.g. when bulk-creating service accounts.
+///
+/// Passwords are drawn uniformly among those containing every charset the
+/// policy requires, and redrawn if they contain the username (which Active
+/// Directory rejects).
+///
+/// # Example
+/// ```
+/// use yapg_core::{presets, CredentialPairGenerator, UsernameStyle};
+///
+/// let preset = presets::by_name("ad").unwrap();
+/// let mut cpg =
+///     CredentialPairGenerator::new(&preset, UsernameStyle::Pronounceable)
+///         .unwrap();
+/// let pairs = cpg.generate_n(10);
+/// for pair in pairs.iter() {
+///     assert!(preset.policy.allows(&pair.password));
+///     assert!(pair.username.chars().all(|c| c.is_ascii_lowercase()));
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CredentialPairGenerator {
+    style: UsernameStyle,
+    usernames: PronounceableGenerator,
+    passwords: PasswordGenerator,
+    policy: Policy,
+    rng: rand::ThreadRng,
+}
+
+impl CredentialPairGenerator {
+    /// Creates a generator of usernames of `style` and passwords following
+    /// `preset`. Fails if the preset's charset and length cannot satisfy
+    /// its policy.
+    pub fn new(preset: &Preset, style: UsernameStyle) -> io::Result<Self> {
+        preset.policy.check_length(preset.length)?;
+        let required = preset
+            .policy
+            .required
+            .iter()
+            .fold(Constraints::new(), |constraints, name| {
+                constraints.at_least(*name, 1)
+            });
+        let passwords = preset.generator().constraints(required)?;
+        if passwords.combinations() == 0.0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Passwords of preset {} cannot satisfy its policy",
+                    preset.name
+                ),
+            ));
+        }
+        Ok(Self {
+            style,
+            usernames: PronounceableGenerator::new(
+                SyllableModel::default(),
+                USERNAME_SYLLABLES,
+            ),
+            passwords,
+            policy: preset.policy.clone(),
+            rng: rand::thread_rng(),
+        })
+    }
+
+    /// Generates one username with its password.
+    pub fn generate(&mut self) -> CredentialPair {
+        let username = match self.style {
+            UsernameStyle::Random => {
+                let alnum =
+                    [&CHARSET_ALPHA_LOWER[..], &CHARSET_NUMERIC[..]].concat();
+                format!("user-{}", random_string(&mut self.rng, &alnum, 8))
+            },
+            UsernameStyle::Pronounceable => self.usernames.generate(),
+        };
+        let lowercase = username.to_lowercase();
+        loop {
+            let password = self.passwords.generate();
+            if self.policy.allows(&password)
+                && !password.to_lowercase().contains(&lowercase)
+            {
+                return CredentialPair { username, password };
+            }
+        }
+    }
+
+    /// Generates `n` pairs with distinct usernames.
+    pub fn generate_n(&mut self, n: usize) -> Vec<CredentialPair> {
+        let mut seen = HashSet::new();
+        let mut pairs = Vec::with_capacity(n);
+        while pairs.len() < n {
+            let pair = self.generate();
+            if seen.insert(pair.username.clone()) {
+                pairs.push(pair);
+            }
+        }
+        pairs
+    }
+
+    /// Entropy of the passwords in bits.
+    #[inline]
+    pub fn password_entropy(&self) -> usize { self.passwords.entropy() }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn encoding_base32() {
+        // test vectors from RFC 4648, without padding
+        assert_eq!(super::base32(b""), "");
+        assert_eq!(super::base32(b"f"), "MY");
+        assert_eq!(super::base32(b"fo"), "MZXQ");
+        assert_eq!(super::base32(b"foo"), "MZXW6");
+        assert_eq!(super::base32(b"foob"), "MZXW6YQ");
+        assert_eq!(super::base32(b"fooba"), "MZXW6YTB");
+        assert_eq!(super::base32(b"foobar"), "MZXW6YTBOI");
+    }
+}
@@ -0,0 +1,22 @@
+/// Compares two secrets in constant time, i.e. without returning early at
+/// the first differing byte, so that the time taken does not tell an
+/// attacker how much of a guess was right. Only the lengths may leak, which
+/// for generated secrets are part of the (public) configuration anyway.
+///
+/// Use this instead of `==` whenever one side of the comparison is secret.
+///
+/// # Example
+/// ```
+/// assert!(yapg_core::ct_eq("hunter2", "hunter2"));
+/// assert!(!yapg_core::ct_eq("hunter2", "hunter3"));
+/// assert!(!yapg_core::ct_eq(b"hunter2", b"hunter22"));
+/// ```
+pub fn ct_eq<A: AsRef<[u8]>, B: AsRef<[u8]>>(a: A, b: B) -> bool {
+    let (a, b) = (a.as_ref(), b.as_ref());
+    if a.len() != b.len() {
+        return false;
+    }
+    let diff = a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    // keep the compiler from reintroducing an early exit
+    std::hint::black_box(diff) == 0
+}
@@ -0,0 +1,80 @@
+//! Minimal UTC calendar arithmetic, so that timestamps can be rendered without
+//! pulling in a date/time crate.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Seconds since the unix epoch. Clocks before 1970 are clamped to 0.
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Converts days since 1970-01-01 into a (year, month, day) triple of the
+/// proleptic gregorian calendar (Howard Hinnant's `civil_from_days`).
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Converts a (year, month, day) triple of the proleptic gregorian calendar
+/// into days since 1970-01-01, the inverse of `civil_from_days`.
+pub(crate) fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Formats days since the unix epoch as `YYYY-MM-DD`.
+pub(crate) fn format_date(days: i64) -> String {
+    let (y, m, d) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+/// Formats seconds since the unix epoch as RFC 3339 UTC timestamp.
+pub(crate) fn format_timestamp(secs: u64) -> String {
+    let time = secs % 86400;
+    format!(
+        "{}T{:02}:{:02}:{:02}Z",
+        format_date((secs / 86400) as i64),
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn formatting_dates() {
+        assert_eq!(super::format_date(0), "1970-01-01");
+        assert_eq!(super::format_date(-1), "1969-12-31");
+        assert_eq!(super::format_date(11016), "2000-02-29");
+        assert_eq!(
+            super::format_timestamp(1_700_000_000),
+            "2023-11-14T22:13:20Z"
+        );
+    }
+
+    #[test]
+    fn round_tripping_days() {
+        for days in [-719_468, -1, 0, 11016, 20_000, 1_000_000].iter() {
+            let (y, m, d) = super::civil_from_days(*days);
+            assert_eq!(super::days_from_civil(y, m, d), *days);
+        }
+    }
+}
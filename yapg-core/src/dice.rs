@@ -0,0 +1,50 @@
+use std::io;
+
+/// A dice specification in common notation, e.g. `3d6` for three six-sided
+/// dice. The count may be omitted and defaults to one (`d20`).
+///
+/// # Example
+/// ```
+/// let dice = "3d6".parse::<yapg_core::Dice>().unwrap();
+/// assert_eq!(dice, yapg_core::Dice { count: 3, sides: 6 });
+/// assert_eq!("d20".parse::<yapg_core::Dice>().unwrap().count, 1);
+/// assert!("3d0".parse::<yapg_core::Dice>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Dice {
+    /// Number of dice to roll.
+    pub count: u32,
+    /// Number of sides of each die.
+    pub sides: u32,
+}
+
+impl std::str::FromStr for Dice {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid dice (expected e.g. 3d6): {}", s),
+            )
+        };
+        let mut parts = s.splitn(2, ['d', 'D']);
+        let count = match parts.next() {
+            Some("") => 1,
+            Some(count) => count.parse().map_err(|_| invalid())?,
+            None => return Err(invalid()),
+        };
+        let sides =
+            parts.next().ok_or_else(invalid)?.parse().map_err(|_| invalid())?;
+        if count == 0 || sides == 0 {
+            return Err(invalid());
+        }
+        Ok(Self { count, sides })
+    }
+}
+
+impl std::fmt::Display for Dice {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}d{}", self.count, self.sides)
+    }
+}
@@ -0,0 +1,177 @@
+use std::io;
+
+use crate::Wordlist;
+
+/// A wordlist keyed by dice rolls, for building passphrases from physical
+/// dice instead of trusting the machine's RNG: each word is looked up by
+/// the faces of `dice()` six-sided dice, as in the classic diceware lists.
+///
+/// # Example
+/// ```
+/// let list = yapg_core::Wordlist::embedded("eff-long").unwrap();
+/// let diceware = yapg_core::Diceware::new(&list).unwrap();
+/// assert_eq!(diceware.dice(), 5);
+/// assert_eq!(diceware.word("11111").unwrap(), "abacus");
+/// assert_eq!(diceware.word("6 6 6 6 6").unwrap(), "zoom");
+/// assert_eq!(diceware.parse_roll("415").unwrap(), yapg_core::Roll::Partial {
+///     missing: 2
+/// });
+/// assert!(diceware.word("41527").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Diceware {
+    dice: u32,
+    words: Vec<String>,
+}
+
+/// A parsed dice roll, see `Diceware::parse_roll`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Roll {
+    /// All dice are given, selecting the word at this index.
+    Complete(usize),
+    /// Some dice are still missing.
+    Partial { missing: u32 },
+}
+
+impl Diceware {
+    /// Keys the words of `wordlist` by roll, in list order: the first word
+    /// is `11111`, the second `11112` and so on, as in diceware lists. The
+    /// list must contain a power of 6 of words and must not be weighted.
+    pub fn new(wordlist: &Wordlist) -> io::Result<Self> {
+        if wordlist.is_weighted() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Dice cannot draw from weighted wordlists",
+            ));
+        }
+        let mut words = 1;
+        for dice in 1..=8 {
+            words *= 6;
+            if words == wordlist.len() {
+                return Ok(Self { dice, words: wordlist.words().to_vec() });
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Diceware needs a power of 6 of words (e.g. 7776 for 5 dice), \
+                 not {}",
+                wordlist.len()
+            ),
+        ))
+    }
+
+    /// Number of dice selecting a word.
+    #[inline]
+    pub fn dice(&self) -> u32 { self.dice }
+
+    /// Entropy of each word in bits, provided the dice are fair.
+    pub fn entropy_per_word(&self) -> f64 { self.dice as f64 * 6f64.log2() }
+
+    /// Parses the faces of a roll, e.g. `41526`. Faces may be separated by
+    /// whitespace, `,` or `-`. Fails on faces other than 1 to 6 and on too
+    /// many faces, but accepts incomplete rolls.
+    pub fn parse_roll(&self, roll: &str) -> io::Result<Roll> {
+        let mut index = 0;
+        let mut faces = 0;
+        for c in roll.chars() {
+            if c.is_whitespace() || c == ',' || c == '-' {
+                continue;
+            }
+            let face = match c.to_digit(10) {
+                Some(face @ 1..=6) => face as usize,
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("Invalid die face {:?} (expected 1 to 6)", c),
+                    ))
+                },
+            };
+            faces += 1;
+            if faces > self.dice {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Roll {} has more than {} dice",
+                        roll.trim(),
+                        self.dice
+                    ),
+                ));
+            }
+            index = index * 6 + face - 1;
+        }
+        if faces < self.dice {
+            return Ok(Roll::Partial { missing: self.dice - faces });
+        }
+        Ok(Roll::Complete(index))
+    }
+
+    /// The word selected by `roll`, see `Diceware::parse_roll`. Fails on
+    /// incomplete rolls.
+    pub fn word(&self, roll: &str) -> io::Result<&str> {
+        match self.parse_roll(roll)? {
+            Roll::Complete(index) => Ok(&self.words[index]),
+            Roll::Partial { missing } => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Roll {} is incomplete, {} more {} needed",
+                    roll.trim(),
+                    missing,
+                    if missing == 1 { "die is" } else { "dice are" }
+                ),
+            )),
+        }
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::{Diceware, Roll};
+    use crate::Wordlist;
+
+    #[test]
+    fn keying_embedded_lists_like_eff() {
+        for (name, list) in [
+            ("eff-long", eff_wordlist::large::LIST),
+            ("eff-short", eff_wordlist::short::LIST),
+        ]
+        .iter()
+        {
+            let diceware =
+                Diceware::new(&Wordlist::embedded(name).unwrap()).unwrap();
+            for (roll, word) in list.iter() {
+                assert_eq!(diceware.word(&roll.to_string()).unwrap(), *word);
+            }
+        }
+    }
+
+    #[test]
+    fn parsing_rolls() {
+        let words = (1..=36).map(|i| format!("w{}", i)).collect();
+        let list = Wordlist::new(words);
+        let diceware = Diceware::new(&list).unwrap();
+        assert_eq!(diceware.dice(), 2);
+        assert_eq!(diceware.parse_roll("").unwrap(), Roll::Partial {
+            missing: 2
+        });
+        assert_eq!(diceware.parse_roll("6").unwrap(), Roll::Partial {
+            missing: 1
+        });
+        assert_eq!(diceware.parse_roll("6-1").unwrap(), Roll::Complete(30));
+        assert_eq!(diceware.parse_roll(" 2, 3 ").unwrap(), Roll::Complete(8));
+        assert!(diceware.parse_roll("0").is_err());
+        assert!(diceware.parse_roll("123").is_err());
+        assert!(diceware.parse_roll("1x").is_err());
+        assert!(diceware.word("1").is_err());
+    }
+
+    #[test]
+    fn rejecting_unfit_lists() {
+        let list: Wordlist = "a\nb\nc".parse().unwrap();
+        assert!(Diceware::new(&list).is_err());
+        let list: Wordlist =
+            "a\t2\nb\t1\nc\t1\nd\t1\ne\t1\nf\t1".parse().unwrap();
+        assert!(Diceware::new(&list).is_err());
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::HashSet;
+
+use crate::{Wordlist, EMBEDDED_WORDLISTS};
+
+/// Length of the shortest words a `Dictionary` looks for by default.
+pub const MIN_DICTIONARY_WORD_LEN: usize = 5;
+
+/// Words that must not appear inside passwords, as some policies forbid
+/// recognizable words. Matching ignores case.
+///
+/// # Example
+/// ```
+/// use yapg_core::{Dictionary, PasswordGenerator};
+///
+/// let dictionary = Dictionary::embedded(5);
+/// assert_eq!(dictionary.find_in("x7ABACUSq").as_deref(), Some("abacus"));
+/// assert!(dictionary.accepts("x7abacq"));
+///
+/// let mut pwg = PasswordGenerator::from("abcdefghij").length(16);
+/// let batch = pwg.generate_filtered(5, |pw| dictionary.accepts(pw)).unwrap();
+/// assert!(batch.passwords.iter().all(|pw| dictionary.find_in(pw).is_none()));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Dictionary {
+    words: HashSet<String>,
+    /// Length of the shortest and longest word in chars.
+    lengths: (usize, usize),
+}
+
+impl Dictionary {
+    /// The words of at least `min_len` chars of all embedded wordlists, see
+    /// `EMBEDDED_WORDLISTS`.
+    pub fn embedded(min_len: usize) -> Self {
+        let lists = EMBEDDED_WORDLISTS.iter().map(|name| {
+            // all names in `EMBEDDED_WORDLISTS` are embedded
+            Wordlist::embedded(name).unwrap()
+        });
+        let mut dictionary = Self::default();
+        for list in lists {
+            dictionary.extend(&list, min_len);
+        }
+        dictionary
+    }
+
+    /// The words of at least `min_len` chars of `list`.
+    pub fn from_wordlist(list: &Wordlist, min_len: usize) -> Self {
+        let mut dictionary = Self::default();
+        dictionary.extend(list, min_len);
+        dictionary
+    }
+
+    /// Adds the words of at least `min_len` chars (and at least one) of
+    /// `list`.
+    pub fn extend(&mut self, list: &Wordlist, min_len: usize) {
+        for word in list.words() {
+            let len = word.chars().count();
+            if len >= min_len.max(1) {
+                self.words.insert(word.to_lowercase());
+                self.lengths = match self.lengths {
+                    (0, 0) => (len, len),
+                    (min, max) => (min.min(len), max.max(len)),
+                };
+            }
+        }
+    }
+
+    /// Number of words.
+    #[inline]
+    pub fn len(&self) -> usize { self.words.len() }
+
+    /// Whether there are no words.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.words.is_empty() }
+
+    /// The first word found in `password`, ignoring case.
+    pub fn find_in(&self, password: &str) -> Option<String> {
+        let chars: Vec<char> = password.to_lowercase().chars().collect();
+        let (min, max) = self.lengths;
+        for start in 0..chars.len() {
+            for len in min..=max.min(chars.len() - start) {
+                let candidate: String =
+                    chars[start..start + len].iter().collect();
+                if self.words.contains(&candidate) {
+                    return Some(candidate);
+                }
+            }
+        }
+        None
+    }
+
+    /// Length of the longest word `chars` (in lower case) starts with.
+    pub(crate) fn longest_prefix(&self, chars: &[char]) -> Option<usize> {
+        self.prefix_lens(chars).next()
+    }
+
+    /// Lengths of all words `chars` (in lower case) starts with, longest
+    /// first.
+    pub(crate) fn prefix_lens<'a>(
+        &'a self,
+        chars: &'a [char],
+    ) -> impl Iterator<Item = usize> + 'a {
+        let (min, max) = self.lengths;
+        (min.max(1)..=max.min(chars.len())).rev().filter(move |len| {
+            self.words.contains(&chars[..*len].iter().collect::<String>())
+        })
+    }
+
+    /// Whether `password` contains none of the words, ignoring case.
+    #[inline]
+    pub fn accepts(&self, password: &str) -> bool {
+        self.find_in(password).is_none()
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::Dictionary;
+    use crate::Wordlist;
+
+    #[test]
+    fn finding_words() {
+        let list: Wordlist = "cat\nhorse\nstaple\nbattery\n".parse().unwrap();
+        let dictionary = Dictionary::from_wordlist(&list, 5);
+        assert_eq!(dictionary.len(), 3);
+        assert_eq!(dictionary.find_in("xxBaTTeRy").as_deref(), Some("battery"));
+        assert_eq!(dictionary.find_in("horsestaple").as_deref(), Some("horse"));
+        assert_eq!(dictionary.find_in("cathorse").as_deref(), Some("horse"));
+        assert!(dictionary.accepts("cat"));
+        assert!(dictionary.accepts("hors"));
+        assert!(dictionary.accepts(""));
+        assert!(Dictionary::default().accepts("horse"));
+
+        let dictionary = Dictionary::from_wordlist(&list, 0);
+        assert_eq!(dictionary.find_in("concatenate").as_deref(), Some("cat"));
+    }
+}
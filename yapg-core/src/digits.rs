@@ -0,0 +1,273 @@
+use std::io;
+
+/// Digit systems besides the western (latin) digits `0`-`9`, for users whose
+/// keyboards default to native numerals.
+///
+/// | DigitSystem           | name                      | digits      |
+/// | --------------------- | ------------------------- | ----------- |
+/// | `Latin`               | `"latin"`                 | `0`-`9`     |
+/// | `ArabicIndic`         | `"arabic-indic"`          | `٠`-`٩`     |
+/// | `ExtendedArabicIndic` | `"extended-arabic-indic"` | `۰`-`۹`     |
+/// | `Devanagari`          | `"devanagari"`            | `०`-`९`     |
+/// | `Bengali`             | `"bengali"`               | `০`-`৯`     |
+///
+/// Many systems fold all digits to latin ones before comparing, or reject
+/// non-latin digits outright, so check a charset with `check_digits` before
+/// generating passwords for them.
+///
+/// # Example
+///
+/// ```
+/// use yapg_core::DigitSystem;
+///
+/// let charset = DigitSystem::Devanagari.localize(vec!['0', '1', 'a']);
+/// assert_eq!(charset, vec!['a', '०', '१']);
+/// assert_eq!(
+///     DigitSystem::for_locale("fa-IR"),
+///     DigitSystem::ExtendedArabicIndic
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DigitSystem {
+    /// The western digits `0`-`9`.
+    Latin,
+    /// Arabic-Indic digits, U+0660 to U+0669, used in Arabic in the Middle
+    /// East.
+    ArabicIndic,
+    /// Extended (eastern) Arabic-Indic digits, U+06F0 to U+06F9, used in
+    /// Persian and Urdu.
+    ExtendedArabicIndic,
+    /// Devanagari digits, U+0966 to U+096F, used in Hindi, Marathi and
+    /// Nepali.
+    Devanagari,
+    /// Bengali digits, U+09E6 to U+09EF, used in Bengali and Assamese.
+    Bengali,
+}
+
+impl DigitSystem {
+    /// All digit systems.
+    pub const ALL: [DigitSystem; 5] = [
+        Self::Latin,
+        Self::ArabicIndic,
+        Self::ExtendedArabicIndic,
+        Self::Devanagari,
+        Self::Bengali,
+    ];
+
+    /// The digit zero, which the other digits follow consecutively.
+    fn zero(self) -> char {
+        match self {
+            Self::Latin => '0',
+            Self::ArabicIndic => '\u{660}',
+            Self::ExtendedArabicIndic => '\u{6f0}',
+            Self::Devanagari => '\u{966}',
+            Self::Bengali => '\u{9e6}',
+        }
+    }
+
+    /// The digits from zero to nine.
+    ///
+    /// # Example
+    /// ```
+    /// let digits = yapg_core::DigitSystem::ArabicIndic.chars();
+    /// assert_eq!(digits.len(), 10);
+    /// assert_eq!(digits[3], '٣');
+    /// ```
+    pub fn chars(self) -> Vec<char> {
+        let zero = self.zero() as u32;
+        (zero..zero + 10).filter_map(std::char::from_u32).collect()
+    }
+
+    /// The digit system and value of `c`, if it is a digit.
+    fn of(c: char) -> Option<(Self, u32)> {
+        Self::ALL.iter().find_map(|system| {
+            let value = (c as u32).checked_sub(system.zero() as u32)?;
+            if value < 10 {
+                Some((*system, value))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// The digit system native keyboards default to in `locale`, given as
+    /// BCP 47 tag or POSIX name (e.g. `hi-IN`, `fa_IR.UTF-8`). Falls back to
+    /// `Latin`, also for Arabic in the Maghreb, which uses latin digits.
+    pub fn for_locale(locale: &str) -> Self {
+        let mut parts =
+            locale.split(['.', '@']).next().unwrap_or("").split(['-', '_']);
+        let language = parts.next().unwrap_or("").to_ascii_lowercase();
+        let region = parts.next_back().unwrap_or("").to_ascii_uppercase();
+        match language.as_str() {
+            "ar" => match region.as_str() {
+                "MA" | "DZ" | "TN" | "LY" | "EH" => Self::Latin,
+                _ => Self::ArabicIndic,
+            },
+            "fa" | "ur" | "ps" => Self::ExtendedArabicIndic,
+            "hi" | "mr" | "ne" | "sa" => Self::Devanagari,
+            "bn" | "as" => Self::Bengali,
+            _ => Self::Latin,
+        }
+    }
+
+    /// Replaces all digits in `charset` with those of this system. The
+    /// result is sorted and deduplicated.
+    pub fn localize(self, charset: Vec<char>) -> Vec<char> {
+        let zero = self.zero() as u32;
+        let mut localized: Vec<char> = charset
+            .into_iter()
+            .map(|c| match Self::of(c) {
+                Some((_, value)) => std::char::from_u32(zero + value).unwrap(),
+                None => c,
+            })
+            .collect();
+        localized.sort();
+        localized.dedup();
+        localized
+    }
+}
+
+/// Checks that a target system accepting only the digits of the `accepted`
+/// systems accepts every password drawn from `charset`.
+///
+/// Fails with `io::ErrorKind::InvalidInput` if `charset` contains digits of
+/// other systems, or if it mixes digits of equal value from several systems,
+/// which targets folding digits to latin ones would treat as the same
+/// character.
+///
+/// # Example
+/// ```
+/// use yapg_core::DigitSystem;
+///
+/// let charset = DigitSystem::Devanagari.chars();
+/// assert!(
+///     yapg_core::check_digits(&charset, &[DigitSystem::Devanagari]).is_ok()
+/// );
+/// assert!(yapg_core::check_digits(&charset, &[DigitSystem::Latin]).is_err());
+///
+/// let mixed = ['1', '१'];
+/// let both = [DigitSystem::Latin, DigitSystem::Devanagari];
+/// assert!(yapg_core::check_digits(&mixed, &both).is_err());
+/// ```
+pub fn check_digits(
+    charset: &[char],
+    accepted: &[DigitSystem],
+) -> io::Result<()> {
+    let mut systems = [None; 10];
+    for &c in charset {
+        let (system, value) = match DigitSystem::of(c) {
+            Some(digit) => digit,
+            None => continue,
+        };
+        if !accepted.contains(&system) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Target does not accept {} digit {:?}", system, c),
+            ));
+        }
+        match systems[value as usize] {
+            Some(other) if other != system => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Charset mixes {} and {} digits, which targets \
+                         folding digits cannot tell apart",
+                        other, system
+                    ),
+                ))
+            },
+            _ => systems[value as usize] = Some(system),
+        }
+    }
+    Ok(())
+}
+
+impl std::fmt::Display for DigitSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Latin => "latin",
+            Self::ArabicIndic => "arabic-indic",
+            Self::ExtendedArabicIndic => "extended-arabic-indic",
+            Self::Devanagari => "devanagari",
+            Self::Bengali => "bengali",
+        })
+    }
+}
+
+impl std::str::FromStr for DigitSystem {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        Self::ALL
+            .iter()
+            .find(|system| system.to_string() == s)
+            .copied()
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Invalid digit system: {}", s),
+                )
+            })
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::{check_digits, DigitSystem};
+    use crate::validate_addition;
+
+    #[test]
+    fn parsing_digit_systems() {
+        for system in DigitSystem::ALL.iter() {
+            assert_eq!(
+                system.to_string().parse::<DigitSystem>().unwrap(),
+                *system
+            );
+        }
+        assert!("roman".parse::<DigitSystem>().is_err());
+    }
+
+    #[test]
+    fn digits_are_single_width() {
+        for system in DigitSystem::ALL.iter() {
+            for c in system.chars() {
+                assert!(validate_addition(c).is_ok(), "{:?}", c);
+                assert_eq!(DigitSystem::of(c).unwrap().0, *system);
+            }
+        }
+    }
+
+    #[test]
+    fn locales() {
+        assert_eq!(DigitSystem::for_locale("ar-EG"), DigitSystem::ArabicIndic);
+        assert_eq!(DigitSystem::for_locale("ar_MA.UTF-8"), DigitSystem::Latin);
+        assert_eq!(
+            DigitSystem::for_locale("ur"),
+            DigitSystem::ExtendedArabicIndic
+        );
+        assert_eq!(DigitSystem::for_locale("hi_IN"), DigitSystem::Devanagari);
+        assert_eq!(DigitSystem::for_locale("bn-BD"), DigitSystem::Bengali);
+        assert_eq!(DigitSystem::for_locale("de-DE"), DigitSystem::Latin);
+        assert_eq!(DigitSystem::for_locale(""), DigitSystem::Latin);
+    }
+
+    #[test]
+    fn localizing_round_trips() {
+        let charset = vec!['0', '5', '9', 'x'];
+        let bengali = DigitSystem::Bengali.localize(charset.clone());
+        assert_eq!(bengali, vec!['x', '০', '৫', '৯']);
+        assert_eq!(DigitSystem::Latin.localize(bengali), charset);
+    }
+
+    #[test]
+    fn checking_digits() {
+        let latin = [DigitSystem::Latin];
+        assert!(check_digits(&['a', '1', '2'], &latin).is_ok());
+        assert!(check_digits(&['٢'], &latin).is_err());
+        // distinct values of different systems don't collide
+        let both = [DigitSystem::Latin, DigitSystem::ArabicIndic];
+        assert!(check_digits(&['1', '٢'], &both).is_ok());
+        assert!(check_digits(&['2', '٢'], &both).is_err());
+    }
+}
@@ -0,0 +1,135 @@
+use std::io;
+
+/// Curated emoji for passphrases, with their shortcodes as used by GitHub and
+/// Slack. All are single codepoints rendered as emoji by default (no
+/// variation selectors, skin tones or joiners) and depict clearly distinct
+/// things, so that they can be told apart on any platform.
+pub static EMOJI: [(char, &str); 64] = [
+    ('🐶', "dog"),
+    ('🐱', "cat"),
+    ('🐭', "mouse"),
+    ('🐰', "rabbit"),
+    ('🦊', "fox_face"),
+    ('🐻', "bear"),
+    ('🐼', "panda_face"),
+    ('🐨', "koala"),
+    ('🐯', "tiger"),
+    ('🦁', "lion"),
+    ('🐮', "cow"),
+    ('🐷', "pig"),
+    ('🐸', "frog"),
+    ('🐵', "monkey_face"),
+    ('🐔', "chicken"),
+    ('🐧', "penguin"),
+    ('🦉', "owl"),
+    ('🦄', "unicorn"),
+    ('🐝', "bee"),
+    ('🐢', "turtle"),
+    ('🐍', "snake"),
+    ('🐙', "octopus"),
+    ('🦀', "crab"),
+    ('🐳', "whale"),
+    ('🐘', "elephant"),
+    ('🦒', "giraffe"),
+    ('🦋', "butterfly"),
+    ('🍎', "apple"),
+    ('🍌', "banana"),
+    ('🍇', "grapes"),
+    ('🍉', "watermelon"),
+    ('🍋', "lemon"),
+    ('🍒', "cherries"),
+    ('🍓', "strawberry"),
+    ('🍍', "pineapple"),
+    ('🥕', "carrot"),
+    ('🌽', "corn"),
+    ('🍄', "mushroom"),
+    ('🍕', "pizza"),
+    ('🍔', "hamburger"),
+    ('🍩', "doughnut"),
+    ('🚀', "rocket"),
+    ('🚗', "car"),
+    ('🚲', "bike"),
+    ('⛵', "sailboat"),
+    ('⚓', "anchor"),
+    ('🌵', "cactus"),
+    ('🌻', "sunflower"),
+    ('🌲', "evergreen_tree"),
+    ('🌙', "crescent_moon"),
+    ('⭐', "star"),
+    ('🔥', "fire"),
+    ('🌈', "rainbow"),
+    ('⛄', "snowman"),
+    ('🎸', "guitar"),
+    ('🎈', "balloon"),
+    ('🔑', "key"),
+    ('🔔', "bell"),
+    ('💡', "bulb"),
+    ('📚', "books"),
+    ('⚽', "soccer"),
+    ('🎲', "game_die"),
+    ('👑', "crown"),
+    ('💎', "gem"),
+];
+
+/// The emoji of `EMOJI`, for use as charset of a `PasswordGenerator`.
+///
+/// # Example
+/// ```
+/// let mut pwg =
+///     yapg_core::PasswordGenerator::new(yapg_core::emoji_charset(), 4);
+/// assert_eq!(pwg.entropy(), 24);
+/// let pass = pwg.generate();
+/// assert_eq!(pass.chars().count(), 4);
+/// ```
+pub fn emoji_charset() -> Vec<char> { EMOJI.iter().map(|(c, _)| *c).collect() }
+
+/// Transliterates an emoji passphrase into ASCII shortcodes, for entering it
+/// where emoji are not accepted or cannot be typed. Fails with an
+/// `io::ErrorKind::InvalidInput` error on characters not in `EMOJI`.
+///
+/// # Example
+/// ```
+/// assert_eq!(yapg_core::emoji_shortcodes("🐶🚀").unwrap(), ":dog::rocket:");
+/// assert!(yapg_core::emoji_shortcodes("🐶x").is_err());
+/// ```
+pub fn emoji_shortcodes(passphrase: &str) -> io::Result<String> {
+    passphrase
+        .chars()
+        .map(|c| {
+            EMOJI
+                .iter()
+                .find(|(e, _)| *e == c)
+                .map(|(_, code)| format!(":{}:", code))
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("Not a passphrase emoji: {}", c),
+                    )
+                })
+        })
+        .collect()
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use unicode_width::UnicodeWidthChar;
+
+    #[test]
+    fn emoji_are_unambiguous() {
+        let mut chars = super::emoji_charset();
+        chars.sort();
+        chars.dedup();
+        assert_eq!(chars.len(), super::EMOJI.len());
+
+        let mut codes: Vec<_> = super::EMOJI.iter().map(|(_, s)| *s).collect();
+        codes.sort();
+        codes.dedup();
+        assert_eq!(codes.len(), super::EMOJI.len());
+
+        for (c, code) in super::EMOJI.iter() {
+            assert_eq!(c.width(), Some(2), "{}", code);
+            assert!(code.bytes().all(|b| b.is_ascii_lowercase() || b == b'_'));
+        }
+    }
+}
@@ -0,0 +1,340 @@
+use std::io;
+
+/// A provider of random bytes that generators can be forced through, e.g. a
+/// hardware security module in regulated environments.
+///
+/// See `PasswordGenerator::entropy_source`. Implementations should fail
+/// rather than return weak bytes.
+///
+/// # Example
+/// ```
+/// use yapg_core::EntropySource;
+///
+/// let mut source = yapg_core::OsEntropy::new().unwrap();
+/// let mut bytes = [0; 32];
+/// source.fill(&mut bytes).unwrap();
+/// assert_eq!(source.name(), "OS RNG");
+/// ```
+pub trait EntropySource {
+    /// Fills `dest` entirely with random bytes.
+    fn fill(&mut self, dest: &mut [u8]) -> io::Result<()>;
+
+    /// Name of the source, as recorded in `Provenance`.
+    fn name(&self) -> String;
+}
+
+/// Boxed sources, e.g. chosen at runtime.
+impl<S: EntropySource + ?Sized> EntropySource for Box<S> {
+    fn fill(&mut self, dest: &mut [u8]) -> io::Result<()> {
+        (**self).fill(dest)
+    }
+
+    fn name(&self) -> String { (**self).name() }
+}
+
+/// The operating system's RNG, e.g. `getrandom(2)` on Linux.
+#[derive(Debug)]
+pub struct OsEntropy(rand::rngs::OsRng);
+
+impl OsEntropy {
+    /// Opens the OS RNG.
+    pub fn new() -> io::Result<Self> {
+        rand::rngs::OsRng::new().map(Self).map_err(io::Error::other)
+    }
+}
+
+impl EntropySource for OsEntropy {
+    fn fill(&mut self, dest: &mut [u8]) -> io::Result<()> {
+        rand::RngCore::try_fill_bytes(&mut self.0, dest)
+            .map_err(io::Error::other)
+    }
+
+    fn name(&self) -> String { "OS RNG".to_string() }
+}
+
+#[cfg(all(feature = "pkcs11", unix))]
+pub use self::pkcs11::Pkcs11Entropy;
+
+/// Random bytes from a PKCS#11 token, loading the vendor's module at runtime
+/// so that no vendor SDK is needed at build time.
+#[cfg(all(feature = "pkcs11", unix))]
+mod pkcs11 {
+    use std::ffi::CString;
+    use std::io;
+    use std::os::raw::{c_ulong, c_void};
+    use std::ptr;
+
+    use super::EntropySource;
+
+    type CkRv = c_ulong;
+    type InitializeFn = unsafe extern "C" fn(*mut c_void) -> CkRv;
+    type GetSlotListFn =
+        unsafe extern "C" fn(u8, *mut c_ulong, *mut c_ulong) -> CkRv;
+    type OpenSessionFn = unsafe extern "C" fn(
+        c_ulong,
+        c_ulong,
+        *mut c_void,
+        *mut c_void,
+        *mut c_ulong,
+    ) -> CkRv;
+    type LoginFn =
+        unsafe extern "C" fn(c_ulong, c_ulong, *const u8, c_ulong) -> CkRv;
+    type GenerateRandomFn =
+        unsafe extern "C" fn(c_ulong, *mut u8, c_ulong) -> CkRv;
+    type CloseSessionFn = unsafe extern "C" fn(c_ulong) -> CkRv;
+
+    const CKR_OK: CkRv = 0;
+    const CKR_CRYPTOKI_ALREADY_INITIALIZED: CkRv = 0x191;
+    const CKF_SERIAL_SESSION: c_ulong = 0x4;
+    const CKU_USER: c_ulong = 1;
+    const CK_TRUE: u8 = 1;
+
+    /// Random bytes from `C_GenerateRandom` of a PKCS#11 token.
+    ///
+    /// # Example
+    /// ```no_run
+    /// let source = yapg_core::Pkcs11Entropy::open(
+    ///     "/usr/lib/softhsm/libsofthsm2.so",
+    ///     0,
+    ///     Some("1234"),
+    /// )
+    /// .unwrap();
+    /// let mut pwg =
+    ///     yapg_core::PasswordGenerator::from("abc").entropy_source(source);
+    /// pwg.generate();
+    /// ```
+    #[derive(Debug)]
+    pub struct Pkcs11Entropy {
+        module: String,
+        generate_random: GenerateRandomFn,
+        // fields drop in order: the session, the module, the library
+        session: Session,
+        _initialized: Initialized,
+        _library: Library,
+    }
+
+    /// A loaded PKCS#11 module, unloaded on drop.
+    #[derive(Debug)]
+    struct Library(*mut c_void);
+
+    impl Drop for Library {
+        fn drop(&mut self) { unsafe { libc::dlclose(self.0) }; }
+    }
+
+    /// `C_Finalize` of a module, called on drop if this instance initialized
+    /// it. Modules others in the process initialized are left to them.
+    #[derive(Debug)]
+    struct Initialized(Option<InitializeFn>);
+
+    impl Drop for Initialized {
+        fn drop(&mut self) {
+            if let Some(finalize) = self.0 {
+                unsafe { finalize(ptr::null_mut()) };
+            }
+        }
+    }
+
+    /// An open session, closed on drop.
+    #[derive(Debug)]
+    struct Session {
+        handle: c_ulong,
+        close: CloseSessionFn,
+    }
+
+    impl Drop for Session {
+        fn drop(&mut self) { unsafe { (self.close)(self.handle) }; }
+    }
+
+    /// Fails with an error naming the PKCS#11 `function` unless `rv` is
+    /// `CKR_OK`.
+    fn check(function: &str, rv: CkRv) -> io::Result<()> {
+        if rv == CKR_OK {
+            return Ok(());
+        }
+        Err(io::Error::other(format!(
+            "PKCS#11 {} failed with CKR 0x{:x}",
+            function, rv
+        )))
+    }
+
+    /// Looks up the function `name` in `library`.
+    ///
+    /// # Safety
+    /// `T` must be the function pointer type of `name`.
+    unsafe fn symbol<T>(library: &Library, name: &str) -> io::Result<T> {
+        let c_name = CString::new(name).unwrap();
+        let address = libc::dlsym(library.0, c_name.as_ptr());
+        if address.is_null() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("PKCS#11 module lacks {}", name),
+            ));
+        }
+        Ok(std::mem::transmute_copy(&address))
+    }
+
+    impl Pkcs11Entropy {
+        /// Loads the PKCS#11 `module` and opens a session on the `slot`-th
+        /// slot holding a token, logging in with `pin` if given. Whatever
+        /// was acquired is released again if a later step fails.
+        pub fn open(
+            module: &str,
+            slot: usize,
+            pin: Option<&str>,
+        ) -> io::Result<Self> {
+            let path = CString::new(module)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            let library =
+                unsafe { libc::dlopen(path.as_ptr(), libc::RTLD_NOW) };
+            if library.is_null() {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("Cannot load PKCS#11 module {}", module),
+                ));
+            }
+            let library = Library(library);
+            unsafe {
+                let generate_random = symbol(&library, "C_GenerateRandom")?;
+                let (initialized, session) =
+                    Self::open_session(&library, slot, pin)?;
+                Ok(Self {
+                    module: module.to_string(),
+                    generate_random,
+                    session,
+                    _initialized: initialized,
+                    _library: library,
+                })
+            }
+        }
+
+        /// Initializes the module and opens a (logged in) session.
+        unsafe fn open_session(
+            library: &Library,
+            slot: usize,
+            pin: Option<&str>,
+        ) -> io::Result<(Initialized, Session)> {
+            let initialize: InitializeFn = symbol(library, "C_Initialize")?;
+            let finalize: InitializeFn = symbol(library, "C_Finalize")?;
+            let get_slot_list: GetSlotListFn =
+                symbol(library, "C_GetSlotList")?;
+            let open_session: OpenSessionFn = symbol(library, "C_OpenSession")?;
+            let login: LoginFn = symbol(library, "C_Login")?;
+            let close: CloseSessionFn = symbol(library, "C_CloseSession")?;
+
+            let rv = initialize(ptr::null_mut());
+            let initialized =
+                Initialized(Some(finalize).filter(|_| rv == CKR_OK));
+            if rv != CKR_CRYPTOKI_ALREADY_INITIALIZED {
+                check("C_Initialize", rv)?;
+            }
+            let mut count: c_ulong = 0;
+            check(
+                "C_GetSlotList",
+                get_slot_list(CK_TRUE, ptr::null_mut(), &mut count),
+            )?;
+            let mut slots = vec![0; count as usize];
+            check(
+                "C_GetSlotList",
+                get_slot_list(CK_TRUE, slots.as_mut_ptr(), &mut count),
+            )?;
+            slots.truncate(count as usize);
+            let slot_id = *slots.get(slot).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "No PKCS#11 token in slot {} ({} slots with tokens)",
+                        slot,
+                        slots.len()
+                    ),
+                )
+            })?;
+            let mut handle: c_ulong = 0;
+            check(
+                "C_OpenSession",
+                open_session(
+                    slot_id,
+                    CKF_SERIAL_SESSION,
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                    &mut handle,
+                ),
+            )?;
+            let session = Session { handle, close };
+            if let Some(pin) = pin {
+                check(
+                    "C_Login",
+                    login(
+                        session.handle,
+                        CKU_USER,
+                        pin.as_ptr(),
+                        pin.len() as c_ulong,
+                    ),
+                )?;
+            }
+            Ok((initialized, session))
+        }
+    }
+
+    impl EntropySource for Pkcs11Entropy {
+        fn fill(&mut self, dest: &mut [u8]) -> io::Result<()> {
+            let rv = unsafe {
+                (self.generate_random)(
+                    self.session.handle,
+                    dest.as_mut_ptr(),
+                    dest.len() as c_ulong,
+                )
+            };
+            check("C_GenerateRandom", rv)
+        }
+
+        fn name(&self) -> String { format!("PKCS#11 ({})", self.module) }
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use rand::RngCore;
+
+    use super::{EntropySource, OsEntropy};
+    use crate::rng::SourceRng;
+
+    /// Yields 0, 1, 2, ... to make buffering visible.
+    struct Counter(u8);
+
+    impl EntropySource for Counter {
+        fn fill(&mut self, dest: &mut [u8]) -> io::Result<()> {
+            for byte in dest.iter_mut() {
+                *byte = self.0;
+                self.0 = self.0.wrapping_add(1);
+            }
+            Ok(())
+        }
+
+        fn name(&self) -> String { "counter".to_string() }
+    }
+
+    #[test]
+    fn buffering_hands_out_bytes_once() {
+        let mut rng = SourceRng::new(Counter(0));
+        let mut clone = rng.clone();
+        let mut a = [0; 50];
+        let mut b = [0; 100];
+        rng.fill_bytes(&mut a);
+        clone.fill_bytes(&mut b);
+        let all: Vec<u8> = a.iter().chain(b.iter()).cloned().collect();
+        assert_eq!(all, (0..150).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn filling_from_the_os() {
+        let mut source = OsEntropy::new().unwrap();
+        let mut a = [0; 32];
+        let mut b = [0; 32];
+        source.fill(&mut a).unwrap();
+        source.fill(&mut b).unwrap();
+        assert_ne!(a, b);
+    }
+}
@@ -0,0 +1,255 @@
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+use rand::RngCore;
+
+use crate::history::data_dir;
+use crate::{Constraints, Grapheme, Pattern};
+
+/// Alphabet for fingerprints: Crockford's base32, which avoids the easily
+/// confused `i`, `l`, `o` and `u`.
+pub(crate) static FINGERPRINT_ALPHABET: [char; 32] = [
+    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', 'a', 'b', 'c', 'd', 'e',
+    'f', 'g', 'h', 'j', 'k', 'm', 'n', 'p', 'q', 'r', 's', 't', 'v', 'w', 'x',
+    'y', 'z',
+];
+
+/// 64-bit FNV-1a hash. Used instead of `std`'s hashers, whose output is not
+/// guaranteed to be stable across rust releases.
+pub(crate) fn fnv1a64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Renders the upper 60 bits of `hash` as three dash-separated groups of four
+/// characters, which are easy to read out and compare.
+pub(crate) fn render_fingerprint(hash: u64) -> String {
+    let mut s = String::with_capacity(14);
+    for i in 0..12 {
+        if i > 0 && i % 4 == 0 {
+            s.push('-');
+        }
+        let index = (hash >> (59 - 5 * i)) & 0x1f;
+        s.push(FINGERPRINT_ALPHABET[index as usize]);
+    }
+    s
+}
+
+/// Short, human-comparable fingerprint of a charset and password length.
+///
+/// Two parties generating with the same (resolved) charset and length get
+/// identical fingerprints, regardless of the order of `charset`. Duplicate
+/// chars change the fingerprint, as they change the password distribution.
+/// The fingerprint is not secret and reveals nothing about generated
+/// passwords.
+///
+/// # Example
+/// ```
+/// let a = yapg_core::charset_fingerprint(&['a', 'b', 'c'], 20);
+/// let b = yapg_core::charset_fingerprint(&['c', 'b', 'a'], 20);
+/// assert_eq!(a, b);
+/// assert_eq!(a.len(), 14);
+/// assert_ne!(a, yapg_core::charset_fingerprint(&['a', 'b', 'c'], 21));
+/// ```
+pub fn charset_fingerprint(charset: &[char], length: usize) -> String {
+    let items: Vec<Grapheme> = charset.iter().map(|c| (*c).into()).collect();
+    items_fingerprint(&items, length)
+}
+
+/// `charset_fingerprint` of a charset with multi-char items (see
+/// `Grapheme`). Charsets of single chars get the same fingerprint either
+/// way.
+///
+/// # Example
+/// ```
+/// use yapg_core::{charset_fingerprint, items_fingerprint, Grapheme};
+///
+/// let chars: Vec<Grapheme> = vec!['a'.into(), 'b'.into()];
+/// assert_eq!(
+///     items_fingerprint(&chars, 8),
+///     charset_fingerprint(&['a', 'b'], 8)
+/// );
+/// let digraph = vec!["ab".parse().unwrap()];
+/// assert_ne!(items_fingerprint(&digraph, 8), items_fingerprint(&chars, 8));
+/// ```
+pub fn items_fingerprint(items: &[Grapheme], length: usize) -> String {
+    render_fingerprint(fnv1a64(&items_bytes(items, length)))
+}
+
+/// `items_fingerprint` that also covers the constraints or the pattern
+/// passwords are drawn with, which change the passwords a seed yields. Equal
+/// to `items_fingerprint` without either.
+///
+/// # Example
+/// ```
+/// use yapg_core::{config_fingerprint, items_fingerprint, Grapheme};
+///
+/// let chars: Vec<Grapheme> = "ab01".chars().map(Grapheme::from).collect();
+/// let plain = items_fingerprint(&chars, 8);
+/// assert_eq!(config_fingerprint(&chars, 8, None, None), plain);
+/// let constraints = "N>=2".parse().unwrap();
+/// let constrained = config_fingerprint(&chars, 8, Some(&constraints), None);
+/// assert_ne!(constrained, plain);
+/// ```
+pub fn config_fingerprint(
+    items: &[Grapheme],
+    length: usize,
+    constraints: Option<&Constraints>,
+    pattern: Option<&Pattern>,
+) -> String {
+    let mut bytes = items_bytes(items, length);
+    // no char is u32::MAX - 1, so this cannot be mistaken for items
+    for (tag, spec) in [
+        (b'c', constraints.map(|c| c.to_string())),
+        (b'p', pattern.map(|p| p.to_string())),
+    ]
+    .iter()
+    {
+        if let Some(spec) = spec {
+            bytes.extend_from_slice(&(u32::MAX - 1).to_be_bytes());
+            bytes.push(*tag);
+            bytes.extend_from_slice(&(spec.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(spec.as_bytes());
+        }
+    }
+    render_fingerprint(fnv1a64(&bytes))
+}
+
+/// The bytes `items_fingerprint` hashes: the length, then the sorted items.
+fn items_bytes(items: &[Grapheme], length: usize) -> Vec<u8> {
+    let mut items = items.to_vec();
+    items.sort();
+    let mut bytes = (length as u64).to_be_bytes().to_vec();
+    for item in items {
+        match item.as_char() {
+            Some(c) => bytes.extend_from_slice(&(c as u32).to_be_bytes()),
+            None => {
+                // no char is u32::MAX, so this cannot be mistaken for chars
+                let chars: Vec<char> = item.as_str().chars().collect();
+                bytes.extend_from_slice(&u32::MAX.to_be_bytes());
+                bytes.extend_from_slice(&(chars.len() as u32).to_be_bytes());
+                for c in chars {
+                    bytes.extend_from_slice(&(c as u32).to_be_bytes());
+                }
+            },
+        }
+    }
+    bytes
+}
+
+/// Length in bytes of a `FingerprintKey`.
+const FINGERPRINT_KEY_LEN: usize = 32;
+
+/// Secret key of `secret_fingerprint`, without which fingerprints cannot be
+/// checked against guessed secrets. Keep it private, like the secrets.
+///
+/// # Example
+/// ```
+/// let path = std::env::temp_dir().join("yapg-doctest-fingerprint.key");
+/// # let _ = std::fs::remove_file(&path);
+/// let key = yapg_core::FingerprintKey::open(&path).unwrap();
+/// assert_eq!(yapg_core::FingerprintKey::open(&path).unwrap(), key);
+/// assert_ne!(yapg_core::FingerprintKey::generate(), key);
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct FingerprintKey([u8; FINGERPRINT_KEY_LEN]);
+
+impl FingerprintKey {
+    /// The key of the given bytes.
+    #[inline]
+    pub fn new(bytes: [u8; FINGERPRINT_KEY_LEN]) -> Self { Self(bytes) }
+
+    /// A fresh random key.
+    pub fn generate() -> Self {
+        let mut bytes = [0; FINGERPRINT_KEY_LEN];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        Self(bytes)
+    }
+
+    /// The key of this installation in `~/.local/share/yapg/fingerprint.key`
+    /// (or below `$XDG_DATA_HOME`), next to the `History`, created on first
+    /// use. Fails if the home directory is unknown.
+    pub fn open_default() -> io::Result<Self> {
+        let dir = data_dir().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "Cannot locate the fingerprint key without $HOME or \
+                 $XDG_DATA_HOME",
+            )
+        })?;
+        Self::open(dir.join("fingerprint.key"))
+    }
+
+    /// The key in the file at `path`, creating the file (readable by the
+    /// owner only) and its directory with a random key if it is missing.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        match options.open(path) {
+            Ok(mut file) => {
+                let key = Self::generate();
+                file.write_all(&key.0)?;
+                Ok(key)
+            },
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                let bytes = fs::read(path)?;
+                let mut key = [0; FINGERPRINT_KEY_LEN];
+                if bytes.len() != key.len() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "Fingerprint key {} is not {} bytes long",
+                            path.display(),
+                            FINGERPRINT_KEY_LEN
+                        ),
+                    ));
+                }
+                key.copy_from_slice(&bytes);
+                Ok(Self(key))
+            },
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl fmt::Debug for FingerprintKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "FingerprintKey(<redacted>)")
+    }
+}
+
+/// Short fingerprint of a secret, to refer to it (e.g. in logs, the
+/// `History` or `on_generated` hooks) without revealing it. It is an
+/// identifier only, not a password hash to verify secrets with: it is the
+/// keyed HMAC-SHA256 of the secret, truncated to 60 bits, so that without
+/// `key` guesses cannot be checked against it, and with `key` weak secrets
+/// can still be guessed.
+///
+/// # Example
+/// ```
+/// use yapg_core::{secret_fingerprint, FingerprintKey};
+///
+/// let key = FingerprintKey::new([7; 32]);
+/// let fp = secret_fingerprint(&key, "Kx9_pQ2-zzLmN0aB7yT4");
+/// // the upper 60 bits of HMAC-SHA256, in Crockford's base32
+/// assert_eq!(fp, "x0eh-j52e-qwbz");
+/// assert_ne!(fp, secret_fingerprint(&key, "Kx9_pQ2-zzLmN0aB7yT5"));
+/// let other = FingerprintKey::new([8; 32]);
+/// assert_ne!(fp, secret_fingerprint(&other, "Kx9_pQ2-zzLmN0aB7yT4"));
+/// ```
+pub fn secret_fingerprint(key: &FingerprintKey, secret: &str) -> String {
+    let mac = hmac_sha256::HMAC::mac(secret.as_bytes(), key.0);
+    let mut head = [0; 8];
+    head.copy_from_slice(&mac[..8]);
+    render_fingerprint(u64::from_be_bytes(head))
+}
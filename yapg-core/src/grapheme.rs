@@ -0,0 +1,186 @@
+use std::{fmt, io};
+
+/// Bytes a `Grapheme` can hold without allocating, enough for flag emoji
+/// and digraphs.
+const INLINE_CAPACITY: usize = 22;
+
+/// One item of a charset: usually a single char, but possibly several
+/// codepoints perceived as one "character", such as flag emoji (two
+/// regional indicators) or digraphs like `ch` and `ll` in alphabets that
+/// treat them as letters.
+///
+/// Passwords drawn from graphemes have their length counted in graphemes,
+/// and each grapheme counts as one draw towards the entropy.
+///
+/// # Example
+/// ```
+/// use yapg_core::Grapheme;
+///
+/// let flag = Grapheme::new("🇩🇪").unwrap();
+/// assert_eq!(flag.as_str(), "🇩🇪");
+/// assert_eq!(flag.as_char(), None);
+/// assert_eq!(Grapheme::from('a').as_char(), Some('a'));
+/// assert!(Grapheme::new("").is_err());
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Grapheme(Repr);
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum Repr {
+    Inline { len: u8, bytes: [u8; INLINE_CAPACITY] },
+    Heap(Box<str>),
+}
+
+impl Grapheme {
+    /// Creates a grapheme from its codepoints. Fails if `s` is empty.
+    pub fn new(s: &str) -> io::Result<Self> {
+        if s.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Charset items must not be empty",
+            ));
+        }
+        if s.len() > INLINE_CAPACITY {
+            return Ok(Self(Repr::Heap(s.into())));
+        }
+        let mut bytes = [0; INLINE_CAPACITY];
+        bytes[..s.len()].copy_from_slice(s.as_bytes());
+        Ok(Self(Repr::Inline { len: s.len() as u8, bytes }))
+    }
+
+    /// The codepoints of the grapheme.
+    pub fn as_str(&self) -> &str {
+        match &self.0 {
+            // only ever filled from a `&str`
+            Repr::Inline { len, bytes } => {
+                std::str::from_utf8(&bytes[..*len as usize]).unwrap()
+            },
+            Repr::Heap(s) => s,
+        }
+    }
+
+    /// The grapheme as char, if it is a single codepoint.
+    pub fn as_char(&self) -> Option<char> {
+        let mut chars = self.as_str().chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c),
+            _ => None,
+        }
+    }
+}
+
+impl From<char> for Grapheme {
+    fn from(c: char) -> Self {
+        let mut bytes = [0; INLINE_CAPACITY];
+        let len = c.encode_utf8(&mut bytes).len();
+        Self(Repr::Inline { len: len as u8, bytes })
+    }
+}
+
+impl std::str::FromStr for Grapheme {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> { Self::new(s) }
+}
+
+impl PartialOrd for Grapheme {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Orders by codepoints, so single-char graphemes order like their chars.
+impl Ord for Grapheme {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl fmt::Display for Grapheme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for Grapheme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Grapheme({:?})", self.as_str())
+    }
+}
+
+/// Checks that passwords drawn from `items` can be split back into their
+/// items in only one way, as otherwise different draws yield the same
+/// password and the entropy is lower than computed. This holds if no item
+/// is a prefix of another one, e.g. not for `c`, `h` and `ch`, where `ch`
+/// might be either one or two draws.
+///
+/// # Example
+/// ```
+/// use yapg_core::{check_unambiguous, Grapheme};
+///
+/// let items = |s: &[&str]| -> Vec<Grapheme> {
+///     s.iter().map(|s| s.parse().unwrap()).collect()
+/// };
+/// assert!(check_unambiguous(&items(&["a", "ch", "ll", "🇩🇪", "🇩🇰"])).is_ok());
+/// assert!(check_unambiguous(&items(&["c", "h", "ch"])).is_err());
+/// ```
+pub fn check_unambiguous(items: &[Grapheme]) -> io::Result<()> {
+    let mut sorted: Vec<&str> = items.iter().map(Grapheme::as_str).collect();
+    sorted.sort();
+    sorted.dedup();
+    // a prefix sorts right before the items it is a prefix of
+    for pair in sorted.windows(2) {
+        if pair[1].starts_with(pair[0]) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Charset item {:?} is a prefix of {:?}, so passwords \
+                     could not be told apart",
+                    pair[0], pair[1]
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::{check_unambiguous, Grapheme};
+
+    #[test]
+    fn storing_short_and_long_graphemes() {
+        let family = "👨\u{200d}👩\u{200d}👧\u{200d}👦";
+        assert!(family.len() > super::INLINE_CAPACITY);
+        for s in ["a", "ch", "🇩🇪", family].iter() {
+            let g = Grapheme::new(s).unwrap();
+            assert_eq!(g.as_str(), *s);
+            assert_eq!(g.to_string(), *s);
+        }
+        assert_eq!(Grapheme::from('ß'), Grapheme::new("ß").unwrap());
+    }
+
+    #[test]
+    fn ordering_like_chars() {
+        let mut chars = vec!['z', 'é', 'a', '🐶', 'Z'];
+        let mut graphemes: Vec<Grapheme> =
+            chars.iter().map(|c| Grapheme::from(*c)).collect();
+        chars.sort();
+        graphemes.sort();
+        let sorted: Vec<char> =
+            graphemes.iter().map(|g| g.as_char().unwrap()).collect();
+        assert_eq!(sorted, chars);
+    }
+
+    #[test]
+    fn finding_prefixes_among_items() {
+        let items = |s: &[&str]| -> Vec<Grapheme> {
+            s.iter().map(|s| s.parse().unwrap()).collect()
+        };
+        assert!(check_unambiguous(&items(&["a", "b", "a"])).is_ok());
+        assert!(check_unambiguous(&items(&["ch", "c"])).is_err());
+        assert!(check_unambiguous(&items(&["ab", "b", "abc"])).is_err());
+        assert!(check_unambiguous(&items(&["ab", "ac", "b"])).is_ok());
+    }
+}
@@ -0,0 +1,122 @@
+use std::io;
+use std::time::Duration;
+
+use crate::pwned_db::sha1_hex;
+use crate::BreachCheck;
+
+/// Default base URL of the Have I Been Pwned range API.
+pub const HIBP_RANGE_API: &str = "https://api.pwnedpasswords.com/range/";
+
+/// Seconds to wait for the range API before giving up.
+const HIBP_TIMEOUT_SECS: u64 = 10;
+
+/// Client of the Have I Been Pwned range API, which tells how often a
+/// password appears in known breaches.
+///
+/// Passwords never leave the machine: with k-anonymity, only the first five
+/// hex digits of their SHA-1 hash are sent, and the hash suffixes of all
+/// breached passwords sharing them are compared locally. Responses are
+/// padded with fake entries, so their size does not reveal the prefix
+/// either.
+///
+/// # Example
+/// ```no_run
+/// let hibp = yapg_core::HibpClient::new();
+/// assert!(hibp.is_pwned("password").unwrap());
+///
+/// let mut pwg = yapg_core::PasswordGenerator::from("abc123").length(16);
+/// let passwords = pwg.generate_unbreached(3, &hibp).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct HibpClient {
+    base_url: String,
+    agent: ureq::Agent,
+}
+
+impl HibpClient {
+    /// Creates a client of the public API at `HIBP_RANGE_API`.
+    pub fn new() -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .timeout(Duration::from_secs(HIBP_TIMEOUT_SECS))
+            .user_agent(concat!("yapg/", env!("CARGO_PKG_VERSION")))
+            .build();
+        Self { base_url: HIBP_RANGE_API.to_string(), agent }
+    }
+
+    /// Queries the range API at `url` instead, e.g. a mirror, to which hash
+    /// prefixes are appended. Consumes and returns itself.
+    pub fn base_url(mut self, url: &str) -> Self {
+        self.base_url = url.to_string();
+        self
+    }
+
+    /// How often `password` appears in known breaches. Fails if the API
+    /// cannot be reached.
+    pub fn breach_count(&self, password: &str) -> io::Result<u64> {
+        let hash = sha1_hex(password);
+        let (prefix, suffix) = hash.split_at(5);
+        let body = self
+            .agent
+            .get(&format!("{}{}", self.base_url, prefix))
+            .set("Add-Padding", "true")
+            .call()
+            .map_err(|e| {
+                io::Error::other(format!("Querying the HIBP API failed: {}", e))
+            })?
+            .into_string()?;
+        Ok(range_count(&body, suffix))
+    }
+
+    /// Whether `password` appears in known breaches, see `breach_count`.
+    #[inline]
+    pub fn is_pwned(&self, password: &str) -> io::Result<bool> {
+        Ok(self.breach_count(password)? > 0)
+    }
+}
+
+impl BreachCheck for HibpClient {
+    #[inline]
+    fn breach_count(&self, password: &str) -> io::Result<u64> {
+        HibpClient::breach_count(self, password)
+    }
+}
+
+impl Default for HibpClient {
+    fn default() -> Self { Self::new() }
+}
+
+/// The count of hash `suffix` in a range API response of `SUFFIX:COUNT`
+/// lines, or 0 if it is missing (or a padding entry).
+fn range_count(body: &str, suffix: &str) -> u64 {
+    body.lines()
+        .filter_map(|line| line.trim().split_once(':'))
+        .find(|(s, _)| s.eq_ignore_ascii_case(suffix))
+        .and_then(|(_, count)| count.parse().ok())
+        .unwrap_or(0)
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::range_count;
+    use crate::pwned_db::sha1_hex;
+
+    #[test]
+    fn matching_ranges() {
+        let hash = sha1_hex("password");
+        assert_eq!(hash, "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8");
+        let body = [
+            "003D68EB55068C33ACE09247EE4C639306B:3",
+            "1E4C9B93F3F0682250B6CF8331B7EE68FD8:9545824",
+            "1E4C9B93F3F0682250B6CF8331B7EE68FD9:0",
+        ]
+        .join("\r\n");
+        assert_eq!(range_count(&body, &hash[5..]), 9_545_824);
+        assert_eq!(
+            range_count(&body, "1E4C9B93F3F0682250B6CF8331B7EE68FD9"),
+            0
+        );
+        assert_eq!(range_count(&body, "FFFF"), 0);
+        assert_eq!(range_count("", &hash[5..]), 0);
+    }
+}
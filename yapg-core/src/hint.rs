@@ -0,0 +1,139 @@
+use crate::CharsetName;
+
+/// A password hint together with the amount of information it reveals.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hint {
+    /// Human-readable hint text, e.g. `"24 characters, starts with a digit"`.
+    pub text: String,
+    /// Bits of entropy an attacker gains from the hint.
+    pub leaked_bits: f64,
+}
+
+/// Generates hints for passwords drawn uniformly from a charset, revealing at
+/// most a configured number of bits.
+///
+/// Hints are assembled from the password length (which leaks nothing, since
+/// it is part of the generator configuration and thus assumed to be known)
+/// and the classes of the first and last characters. Revealing that a
+/// character belongs to a class of `k` out of `n` charset characters leaks
+/// `log2(n / k)` bits. Parts that would exceed the budget are left out.
+///
+/// # Example
+/// ```
+/// let charset = yapg_core::CharsetSpec::std64().construct();
+/// let hints = yapg_core::HintGenerator::new(charset, 4.0);
+///
+/// let hint = hints.hint("4bcd_");
+/// assert_eq!(hint.text, "5 characters, starts with a digit");
+/// assert!((hint.leaked_bits - (64.0f64 / 10.0).log2()).abs() < 1e-9);
+///
+/// let hint = hints.hint("abcd_");
+/// assert_eq!(hint.text, "5 characters, starts with a lower-case letter");
+/// ```
+#[derive(Debug, Clone)]
+pub struct HintGenerator {
+    charset: Vec<char>,
+    max_bits: f64,
+}
+
+impl HintGenerator {
+    /// Creates a generator for passwords drawn from `charset`, whose hints
+    /// leak at most `max_bits`.
+    pub fn new(mut charset: Vec<char>, max_bits: f64) -> Self {
+        charset.sort();
+        charset.dedup();
+        Self { charset, max_bits }
+    }
+
+    /// Describes the class of `c` and computes how many bits revealing it
+    /// leaks.
+    fn class_of(&self, c: char) -> (&'static str, f64) {
+        let (name, class) = CharsetName::ATOMIC
+            .iter()
+            .map(|name| (describe(*name), name.chars()))
+            .find(|(_, class)| class.contains(&c))
+            .unwrap_or(("an unlisted character", vec![]));
+        let size = self
+            .charset
+            .iter()
+            .filter(|x| {
+                class.contains(x) || (class.is_empty() && !is_named(**x))
+            })
+            .count()
+            .max(1);
+        (name, (self.charset.len() as f64 / size as f64).log2())
+    }
+
+    /// Creates a hint for `password`.
+    pub fn hint(&self, password: &str) -> Hint {
+        let mut text = format!("{} characters", password.chars().count());
+        let mut leaked_bits = 0.0;
+        let ends = [
+            ("starts", password.chars().next()),
+            ("ends", password.chars().last()),
+        ];
+        for (verb, c) in ends.iter() {
+            if let Some(c) = c {
+                let (class, bits) = self.class_of(*c);
+                if leaked_bits + bits <= self.max_bits {
+                    text.push_str(&format!(", {} with {}", verb, class));
+                    leaked_bits += bits;
+                }
+            }
+        }
+        Hint { text, leaked_bits }
+    }
+}
+
+/// Whether `c` belongs to any named charset.
+fn is_named(c: char) -> bool {
+    CharsetName::ATOMIC.iter().any(|name| name.chars().contains(&c))
+}
+
+/// Human-readable description of a member of an atomic charset.
+fn describe(name: CharsetName) -> &'static str {
+    match name {
+        CharsetName::AlphaLower => "a lower-case letter",
+        CharsetName::AlphaUpper => "an upper-case letter",
+        CharsetName::Numeric => "a digit",
+        CharsetName::Mathops => "a math operator",
+        CharsetName::Prose => "a punctuation character",
+        CharsetName::Delim => "a bracket",
+        _ => "a special character",
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::HintGenerator;
+
+    #[test]
+    fn hints_respect_budget() {
+        let charset = "ab01".chars().collect();
+        let hints = HintGenerator::new(charset, 1.0);
+        assert_eq!(
+            hints.hint("a0").text,
+            "2 characters, starts with a lower-case letter"
+        );
+        assert_eq!(hints.hint("a0").leaked_bits, 1.0);
+
+        let hints = HintGenerator::new("ab01".chars().collect(), 0.5);
+        assert_eq!(hints.hint("a0"), super::Hint {
+            text: "2 characters".to_string(),
+            leaked_bits: 0.0
+        });
+    }
+
+    #[test]
+    fn hints_for_other_characters() {
+        let hints = HintGenerator::new("aäöü".chars().collect(), 8.0);
+        let hint = hints.hint("äa");
+        assert_eq!(
+            hint.text,
+            "2 characters, starts with an unlisted character, ends with a \
+             lower-case letter"
+        );
+        assert!((hint.leaked_bits - (4.0f64 / 3.0).log2() - 2.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,331 @@
+//! Local, opt-in history of generated passwords, recording their
+//! fingerprints but never the passwords themselves.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::output::json_string;
+use crate::{date, secret_fingerprint, FingerprintKey};
+
+/// Directory holding user data, i.e. `$XDG_DATA_HOME/yapg` or
+/// `~/.local/share/yapg`.
+pub(crate) fn data_dir() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_DATA_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".local/share"),
+    };
+    Some(base.join("yapg"))
+}
+
+/// A batch of generated passwords as recorded in the `History`: when, for
+/// what and how they were generated, and their `secret_fingerprint`s.
+///
+/// # Example
+/// ```
+/// use yapg_core::{secret_fingerprint, FingerprintKey, HistoryEntry};
+///
+/// let key = FingerprintKey::generate();
+/// let pws = vec!["Kx9_pQ2-zzLmN0aB7yT4".to_string()];
+/// let entry = HistoryEntry::new(&key, "mail", "-l 20", 128.0, &pws);
+/// assert_eq!(entry.fingerprints, vec![secret_fingerprint(&key, &pws[0])]);
+/// let line = entry.to_json();
+/// assert!(!line.contains(&pws[0]));
+/// assert_eq!(line.parse::<HistoryEntry>().unwrap(), entry);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    /// Time of generation as RFC 3339 UTC timestamp.
+    pub timestamp: String,
+    /// What the passwords are for, e.g. the name of a service.
+    pub label: String,
+    /// The settings they were generated with, e.g. command line flags.
+    pub config: String,
+    /// Entropy of each password in bits.
+    pub entropy: f64,
+    /// The `secret_fingerprint` of each password, under the key of the
+    /// installation (see `FingerprintKey::open_default`).
+    pub fingerprints: Vec<String>,
+}
+
+impl HistoryEntry {
+    /// Records `passwords` by their fingerprints under `key`, timestamped
+    /// with the current time.
+    pub fn new<S: AsRef<str>>(
+        key: &FingerprintKey,
+        label: &str,
+        config: &str,
+        entropy: f64,
+        passwords: &[S],
+    ) -> Self {
+        Self {
+            timestamp: date::format_timestamp(date::unix_now()),
+            label: label.to_string(),
+            config: config.to_string(),
+            entropy,
+            fingerprints: passwords
+                .iter()
+                .map(|pw| secret_fingerprint(key, pw.as_ref()))
+                .collect(),
+        }
+    }
+
+    /// Renders the entry as a single-line JSON object.
+    pub fn to_json(&self) -> String {
+        let fingerprints: Vec<String> =
+            self.fingerprints.iter().map(|f| json_string(f)).collect();
+        format!(
+            "{{\"timestamp\":{},\"label\":{},\"config\":{},\"entropy\":{},\"\
+             fingerprints\":[{}]}}",
+            json_string(&self.timestamp),
+            json_string(&self.label),
+            json_string(&self.config),
+            self.entropy,
+            fingerprints.join(",")
+        )
+    }
+}
+
+impl std::str::FromStr for HistoryEntry {
+    type Err = io::Error;
+
+    /// Parses a line written by `HistoryEntry::to_json`.
+    fn from_str(s: &str) -> io::Result<Self> {
+        let mut parser = Parser { rest: s.trim() };
+        let mut entry = HistoryEntry {
+            timestamp: String::new(),
+            label: String::new(),
+            config: String::new(),
+            entropy: 0.0,
+            fingerprints: vec![],
+        };
+        parser.expect('{')?;
+        loop {
+            let key = parser.string()?;
+            parser.expect(':')?;
+            match key.as_str() {
+                "timestamp" => entry.timestamp = parser.string()?,
+                "label" => entry.label = parser.string()?,
+                "config" => entry.config = parser.string()?,
+                "entropy" => entry.entropy = parser.number()?,
+                "fingerprints" => {
+                    parser.expect('[')?;
+                    if !parser.eat(']') {
+                        loop {
+                            entry.fingerprints.push(parser.string()?);
+                            if parser.eat(']') {
+                                break;
+                            }
+                            parser.expect(',')?;
+                        }
+                    }
+                },
+                _ => return Err(invalid_entry()),
+            }
+            if parser.eat('}') {
+                break;
+            }
+            parser.expect(',')?;
+        }
+        if parser.rest.is_empty() {
+            Ok(entry)
+        } else {
+            Err(invalid_entry())
+        }
+    }
+}
+
+fn invalid_entry() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "Invalid history entry")
+}
+
+/// Reads the JSON subset written by `HistoryEntry::to_json`.
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl Parser<'_> {
+    /// Consumes `c` if it comes next.
+    fn eat(&mut self, c: char) -> bool {
+        match self.rest.strip_prefix(c) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Consumes `c`, failing if something else comes next.
+    fn expect(&mut self, c: char) -> io::Result<()> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(invalid_entry())
+        }
+    }
+
+    /// Consumes a string literal, returning it unescaped.
+    fn string(&mut self) -> io::Result<String> {
+        self.expect('"')?;
+        let mut s = String::new();
+        let mut chars = self.rest.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.rest = &self.rest[i + 1..];
+                    return Ok(s);
+                },
+                '\\' => s.push(match chars.next().map(|(_, c)| c) {
+                    Some('"') => '"',
+                    Some('\\') => '\\',
+                    Some('/') => '/',
+                    Some('n') => '\n',
+                    Some('r') => '\r',
+                    Some('t') => '\t',
+                    Some('u') => {
+                        let hex: String = (0..4)
+                            .filter_map(|_| chars.next())
+                            .map(|(_, c)| c)
+                            .collect();
+                        u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .filter(|_| hex.len() == 4)
+                            .and_then(std::char::from_u32)
+                            .ok_or_else(invalid_entry)?
+                    },
+                    _ => return Err(invalid_entry()),
+                }),
+                c => s.push(c),
+            }
+        }
+        Err(invalid_entry())
+    }
+
+    /// Consumes a number literal.
+    fn number(&mut self) -> io::Result<f64> {
+        let end = self
+            .rest
+            .find(|c: char| !"+-.0123456789eE".contains(c))
+            .unwrap_or(self.rest.len());
+        let number = self.rest[..end].parse().map_err(|_| invalid_entry())?;
+        self.rest = &self.rest[end..];
+        Ok(number)
+    }
+}
+
+/// History of generated passwords, one `HistoryEntry` per line of a JSON
+/// Lines file, so users can look up whether and how they already generated
+/// a secret for something.
+///
+/// # Example
+/// ```
+/// let path = std::env::temp_dir().join("yapg-doctest-history.jsonl");
+/// # let _ = std::fs::remove_file(&path);
+/// let history = yapg_core::History::at(&path);
+/// assert!(history.entries().unwrap().is_empty());
+/// let key = yapg_core::FingerprintKey::generate();
+/// let pws = vec!["Kx9_pQ2-zzLmN0aB7yT4".to_string()];
+/// let entry =
+///     yapg_core::HistoryEntry::new(&key, "Mail", "-l 20", 128.0, &pws);
+/// history.append(&entry).unwrap();
+/// assert_eq!(history.search("mail").unwrap()[0].label, "Mail");
+/// assert!(history.search("bank").unwrap().is_empty());
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct History {
+    path: PathBuf,
+}
+
+impl History {
+    /// The history in `~/.local/share/yapg/history.jsonl` (or below
+    /// `$XDG_DATA_HOME`). Fails if the home directory is unknown.
+    pub fn open_default() -> io::Result<Self> {
+        let dir = data_dir().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "Cannot locate the history without $HOME or $XDG_DATA_HOME",
+            )
+        })?;
+        Ok(Self::at(dir.join("history.jsonl")))
+    }
+
+    /// The history in the file at `path`.
+    pub fn at<P: AsRef<Path>>(path: P) -> Self {
+        Self { path: path.as_ref().to_path_buf() }
+    }
+
+    /// Location of the history file.
+    #[inline]
+    pub fn path(&self) -> &Path { &self.path }
+
+    /// Appends `entry`, creating the file and its directory if needed.
+    pub fn append(&self, entry: &HistoryEntry) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file =
+            OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", entry.to_json())
+    }
+
+    /// All entries, oldest first. A missing file is an empty history.
+    pub fn entries(&self) -> io::Result<Vec<HistoryEntry>> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(str::parse)
+            .collect()
+    }
+
+    /// Entries whose label contains `label`, ignoring case, oldest first.
+    pub fn search(&self, label: &str) -> io::Result<Vec<HistoryEntry>> {
+        let label = label.to_lowercase();
+        let mut entries = self.entries()?;
+        entries.retain(|entry| entry.label.to_lowercase().contains(&label));
+        Ok(entries)
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::HistoryEntry;
+
+    #[test]
+    fn parsing_entries() {
+        let entry = HistoryEntry {
+            timestamp: "2021-03-04T05:06:07Z".to_string(),
+            label: "\"quoted\" \\ tab\t ctrl\u{1} ünï".to_string(),
+            config: String::new(),
+            entropy: 95.27,
+            fingerprints: vec![],
+        };
+        assert_eq!(entry.to_json().parse::<HistoryEntry>().unwrap(), entry);
+        let spaced = "{\"label\":\"a\",\"fingerprints\":[\"x\",\"y\"]}";
+        let parsed: HistoryEntry = spaced.parse().unwrap();
+        assert_eq!(parsed.fingerprints, vec!["x", "y"]);
+
+        for invalid in [
+            "",
+            "{}",
+            "{\"label\":\"a\"",
+            "{\"label\":\"a\"} x",
+            "{\"label\":1}",
+            "{\"other\":\"a\"}",
+            "{\"label\":\"\\q\"}",
+            "{\"label\":\"\\u12\"}",
+            "{\"entropy\":x}",
+        ]
+        .iter()
+        {
+            assert!(invalid.parse::<HistoryEntry>().is_err(), "{}", invalid);
+        }
+    }
+}
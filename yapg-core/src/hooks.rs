@@ -0,0 +1,31 @@
+use std::fmt;
+use std::rc::Rc;
+
+use crate::FingerprintKey;
+
+/// What a generator reports to its `on_generated` hook for every generated
+/// password. Contains no secrets, so it may be passed on freely.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GenerationEvent {
+    /// The `secret_fingerprint` of the password, under the key the hook was
+    /// registered with.
+    pub fingerprint: String,
+    /// The `PasswordGenerator::config_hash` of the generator.
+    pub config_hash: String,
+    /// Length of the password in characters.
+    pub length: usize,
+    /// Entropy of the password in bits, as `PasswordGenerator::entropy`.
+    pub entropy: usize,
+}
+
+/// Callback invoked after each generated password, and the key its
+/// fingerprints are computed with.
+#[derive(Clone)]
+pub(crate) struct Hook(
+    pub(crate) Rc<dyn Fn(&GenerationEvent)>,
+    pub(crate) FingerprintKey,
+);
+
+impl fmt::Debug for Hook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "Hook") }
+}
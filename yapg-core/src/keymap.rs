@@ -0,0 +1,68 @@
+/// Keyboard layouts commonly confused with the US layout assumed by pre-boot
+/// prompts (BIOS passwords, LUKS unlock, bare consoles).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Keymap {
+    /// German/Austrian/Swiss QWERTZ.
+    Qwertz,
+    /// French/Belgian AZERTY.
+    Azerty,
+}
+
+impl Keymap {
+    /// All layouts that are checked by `keymap_hazards`.
+    pub const ALL: [Keymap; 2] = [Keymap::Qwertz, Keymap::Azerty];
+
+    /// Unshifted characters whose key lies elsewhere on this layout than on
+    /// the US layout. Typing them on this layout's physical keyboard yields a
+    /// different character if the prompt assumes a US keymap.
+    pub fn displaced(&self) -> &'static [char] {
+        match self {
+            Keymap::Qwertz => &['y', 'z', '-', '/', ';', '\'', '[', ']', '\\'],
+            Keymap::Azerty => &[
+                'a', 'q', 'w', 'z', 'm', '0', '1', '2', '3', '4', '5', '6',
+                '7', '8', '9', '-', ',', ';', '.', '/', '[', ']',
+            ],
+        }
+    }
+}
+
+impl std::fmt::Display for Keymap {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Keymap::Qwertz => write!(f, "QWERTZ"),
+            Keymap::Azerty => write!(f, "AZERTY"),
+        }
+    }
+}
+
+/// Lists, per layout, the characters in `charset` that end up differently
+/// when typed on that layout at a prompt assuming a US keymap. Layouts
+/// without any such characters are omitted.
+///
+/// # Example
+/// ```
+/// use yapg_core::{keymap_hazards, Keymap};
+///
+/// let hazards = keymap_hazards(&['b', 'y', '1']);
+/// assert_eq!(hazards, vec![
+///     (Keymap::Qwertz, vec!['y']),
+///     (Keymap::Azerty, vec!['1'])
+/// ]);
+/// ```
+pub fn keymap_hazards(charset: &[char]) -> Vec<(Keymap, Vec<char>)> {
+    Keymap::ALL
+        .iter()
+        .filter_map(|keymap| {
+            let chars: Vec<char> = charset
+                .iter()
+                .filter(|c| keymap.displaced().contains(c))
+                .cloned()
+                .collect();
+            if chars.is_empty() {
+                None
+            } else {
+                Some((*keymap, chars))
+            }
+        })
+        .collect()
+}
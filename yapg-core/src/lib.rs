@@ -0,0 +1,1480 @@
+//! This crate is for generating random passphrases from characters.
+//! The heavy lifting is done by `PasswordGenerator` structs, which needs to be
+//! mutable because of the encapsulated
+//! [underlying RNG](https://docs.rs/rand/0.8.3/rand/rngs/struct.ThreadRng.html).
+//!
+//! # Examples
+//! ```
+//! let mut pwg = yapg_core::PasswordGenerator::from("ab").length(10);
+//! let pass = pwg.generate();
+//! assert_eq!(pass.len(), 10);
+//! assert_eq!(pass.to_ascii_lowercase(), pass);
+//! assert_eq!(pwg.entropy(), 10);
+//!
+//! let pass_vec = pwg.length(2).generate_n(2);
+//! assert_eq!(pass_vec.len(), 2);
+//! let permutations = vec![
+//!     "aa".to_string(),
+//!     "ab".to_string(),
+//!     "ba".to_string(),
+//!     "bb".to_string(),
+//! ];
+//! assert!(permutations.contains(&pass_vec[0]));
+//! assert!(permutations.contains(&pass_vec[1]));
+//! ```
+//!
+//! Configurations can also be assembled with a builder, which validates them
+//! as a whole.
+//!
+//! ```
+//! let mut pwg = yapg_core::PasswordGenerator::builder()
+//!     .charset("ab".chars())
+//!     .length(10)
+//!     .require_entropy(10.0)
+//!     .build()
+//!     .unwrap();
+//! assert_eq!(pwg.generate().len(), 10);
+//! ```
+//!
+//! Passphrases from words are created by `PassphraseGenerator`s, e.g.
+//! `PassphraseGenerator::eff_long()` using the embedded EFF long wordlist.
+//!
+//! ```
+//! let mut ppg = yapg_core::PassphraseGenerator::eff_long().words(6);
+//! assert_eq!(ppg.generate().split('-').count(), 6);
+//! assert!(ppg.entropy() > 77.0);
+//! ```
+//!
+//! Pronounceable passwords from syllables are created by
+//! `PronounceableGenerator`s.
+//!
+//! ```
+//! let model = yapg_core::SyllableModel::default();
+//! let mut pg = yapg_core::PronounceableGenerator::new(model, 14);
+//! assert!(pg.generate().len() >= 28);
+//! assert!(pg.entropy() > 100.0);
+//! ```
+use std::{fmt, io};
+
+use rand::{Rng, RngCore};
+
+use crate::constraints::ConstrainedCharset;
+use crate::hooks::Hook;
+use crate::rng::GeneratorRng;
+use crate::sampling::UniformSampler;
+
+#[cfg(feature = "age")]
+mod age;
+mod analyze;
+mod attack;
+mod bidi;
+mod budget;
+mod builder;
+mod charsets;
+mod codes;
+mod color;
+mod compose;
+mod config;
+mod constraints;
+mod credentials;
+mod ct;
+mod date;
+mod dburl;
+mod dice;
+mod diceware;
+mod dictionary;
+mod digits;
+mod emoji;
+mod entropy_source;
+mod fingerprint;
+mod grapheme;
+#[cfg(feature = "hibp")]
+mod hibp;
+mod hint;
+mod history;
+mod hooks;
+mod keymap;
+mod limits;
+mod locked;
+mod markov;
+mod net;
+mod output;
+mod passphrase;
+mod pattern;
+mod pin;
+mod plan;
+mod policy;
+pub mod presets;
+mod pronounceable;
+mod proquint;
+mod provenance;
+mod pwned_db;
+mod qr;
+#[cfg(feature = "regex-gen")]
+mod regex_gen;
+mod rng;
+mod rotation;
+mod safety;
+mod sampling;
+mod schema;
+#[cfg(feature = "share")]
+mod share;
+mod simulate;
+mod skey;
+mod t9;
+mod temp;
+mod transcode;
+mod truncate;
+mod viz;
+mod wordlist;
+#[cfg(feature = "age")]
+pub use age::*;
+pub use analyze::*;
+pub use attack::*;
+pub use bidi::*;
+pub use budget::*;
+pub use builder::*;
+pub use charsets::*;
+pub use codes::*;
+pub use color::*;
+pub use compose::*;
+pub use config::*;
+pub use constraints::Constraints;
+pub use credentials::*;
+pub use ct::*;
+pub use dburl::*;
+pub use dice::*;
+pub use diceware::*;
+pub use dictionary::*;
+pub use digits::*;
+pub use emoji::*;
+pub use entropy_source::*;
+pub use fingerprint::*;
+pub use grapheme::*;
+#[cfg(feature = "hibp")]
+pub use hibp::*;
+pub use hint::*;
+pub use history::*;
+pub use hooks::*;
+pub use keymap::*;
+pub use limits::*;
+pub use locked::*;
+pub use markov::*;
+pub use net::*;
+/// Exact combination counts, see `PasswordGenerator::combinations_exact`.
+#[cfg(feature = "num-bigint")]
+pub use num_bigint::BigUint;
+pub use output::*;
+pub use passphrase::*;
+pub use pattern::*;
+pub use pin::*;
+pub use plan::*;
+pub use policy::*;
+pub use pronounceable::*;
+pub use proquint::*;
+pub use provenance::*;
+pub use pwned_db::*;
+pub use qr::*;
+#[cfg(feature = "regex-gen")]
+pub use regex_gen::*;
+pub use rng::RandomSource;
+pub use rotation::*;
+pub use safety::*;
+pub use sampling::*;
+pub use schema::*;
+/// Passwords zeroed on drop, see `PasswordGenerator::generate_secret`.
+#[cfg(feature = "secrecy")]
+pub use secrecy::{ExposeSecret, SecretString};
+#[cfg(feature = "share")]
+pub use share::*;
+pub use simulate::*;
+pub use skey::*;
+pub use t9::*;
+pub use temp::*;
+pub use transcode::*;
+pub use truncate::*;
+pub use viz::*;
+pub use wordlist::*;
+
+/// Number of candidates in a row `PasswordGenerator::generate_codes` and
+/// `PasswordGenerator::generate_filtered` may discard before giving up.
+const MAX_REJECTED_CANDIDATES: usize = 10_000;
+
+/// Longest length `PasswordGenerator::length_for_entropy` considers.
+const MAX_LENGTH_FOR_ENTROPY: usize = 4096;
+
+/// Classes of characters that `PasswordGenerator::require_each_class`
+/// demands, as in the composition rules of most sites.
+const PASSWORD_CLASSES: [CharsetName; 4] = [
+    CharsetName::AlphaLower,
+    CharsetName::AlphaUpper,
+    CharsetName::Numeric,
+    CharsetName::Special,
+];
+
+/// Encapsulates RNG and set of characters. See crate documentation for more.
+#[derive(Debug, Clone)]
+pub struct PasswordGenerator {
+    charset: Vec<Grapheme>,
+    length: usize,
+    rng: GeneratorRng,
+    hook: Option<Hook>,
+    min_entropy: Option<f64>,
+    constraints: Option<ConstrainedCharset>,
+    /// Indices of the items each position may take, see `from_pattern`.
+    pattern: Option<Vec<Vec<usize>>>,
+    /// The pattern `pattern` was indexed from, recorded in `Provenance`.
+    template: Option<Pattern>,
+}
+
+impl PasswordGenerator {
+    /// Creates the `PasswordGenerator` to yield passwords using either
+    /// `PasswordGenerator::generate` or `PasswordGenerator::generate_n`
+    /// `charset` will not be deduplicated, so that you could (but should not!)
+    /// increase the the probability density of the chars in the generated
+    /// passwords.
+    pub fn new(charset: Vec<char>, length: usize) -> Self {
+        PasswordGenerator {
+            charset: charset.into_iter().map(Grapheme::from).collect(),
+            length,
+            rng: GeneratorRng::Thread(rand::thread_rng()),
+            hook: None,
+            min_entropy: None,
+            constraints: None,
+            pattern: None,
+            template: None,
+        }
+    }
+
+    /// Creates a `PasswordGenerator` like `PasswordGenerator::new`, but fails
+    /// if the charset is empty or the length is zero, so that generating
+    /// passwords cannot fail.
+    ///
+    /// # Example
+    /// ```
+    /// use yapg_core::{BuildError, PasswordGenerator};
+    ///
+    /// let pwg = PasswordGenerator::try_new(vec![], 8);
+    /// assert!(matches!(pwg, Err(BuildError::EmptyCharset)));
+    /// let pwg = PasswordGenerator::try_new(vec!['a'], 0);
+    /// assert!(matches!(pwg, Err(BuildError::ZeroLength)));
+    /// assert_eq!(PasswordGenerator::try_new(vec!['a'], 2)?.generate(), "aa");
+    /// # Ok::<(), BuildError>(())
+    /// ```
+    pub fn try_new(
+        charset: Vec<char>,
+        length: usize,
+    ) -> Result<Self, BuildError> {
+        Self::builder().charset(charset).length(length).build()
+    }
+
+    /// Starts building a `PasswordGenerator`, validating the configuration
+    /// once it is complete, see `PasswordGeneratorBuilder`.
+    #[inline]
+    pub fn builder() -> PasswordGeneratorBuilder {
+        PasswordGeneratorBuilder::default()
+    }
+
+    /// Creates a `PasswordGenerator` drawing from `items`, which may contain
+    /// multi-char graphemes such as flag emoji or digraphs. The length
+    /// counts items, not chars. Fails if a password could be split into
+    /// items in more than one way (see `check_unambiguous`), as the entropy
+    /// would then be lower than computed.
+    ///
+    /// # Example
+    /// ```
+    /// use yapg_core::{Grapheme, PasswordGenerator};
+    ///
+    /// let items: Vec<Grapheme> =
+    ///     ["a", "ch", "ll"].iter().map(|s| s.parse().unwrap()).collect();
+    /// let mut pwg = PasswordGenerator::from_items(items, 4).unwrap();
+    /// let pw = pwg.generate();
+    /// assert!(pw.chars().count() >= 4 && pw.chars().count() <= 8);
+    /// assert_eq!(pwg.combinations(), 81.0);
+    ///
+    /// let items = vec!['c'.into(), 'h'.into(), "ch".parse().unwrap()];
+    /// assert!(PasswordGenerator::from_items(items, 4).is_err());
+    /// ```
+    pub fn from_items(items: Vec<Grapheme>, length: usize) -> io::Result<Self> {
+        check_unambiguous(&items)?;
+        let mut pwg = Self::new(vec![], length);
+        pwg.charset = items;
+        Ok(pwg)
+    }
+
+    /// Creates a `PasswordGenerator` for structured passwords such as serial
+    /// numbers, drawing each char from the class its position in `pattern`
+    /// denotes (see `Pattern`). The length is the pattern's; changing it
+    /// makes generation panic. Constraints cannot be combined with patterns.
+    /// Fails if the pattern is invalid.
+    ///
+    /// # Example
+    /// ```
+    /// let mut pwg =
+    ///     yapg_core::PasswordGenerator::from_pattern("Cvcvc-NNNN").unwrap();
+    /// let pw = pwg.generate();
+    /// assert_eq!(pw.len(), 10);
+    /// assert!(pw[..1].chars().all(|c| c.is_ascii_uppercase()));
+    /// assert!(pw[6..].chars().all(|c| c.is_ascii_digit()));
+    /// assert_eq!(&pw[5..6], "-");
+    /// assert_eq!(pwg.combinations(), 21f64.powi(3) * 25.0 * 1e4);
+    /// ```
+    pub fn from_pattern(pattern: &str) -> io::Result<Self> {
+        let pattern: Pattern = pattern.parse()?;
+        let (charset, slots) = pattern.indexed();
+        let mut pwg = Self::new(vec![], pattern.len());
+        pwg.charset = charset;
+        pwg.pattern = Some(slots);
+        pwg.template = Some(pattern);
+        Ok(pwg)
+    }
+
+    /// Sets an entropy floor: from now on, generating passwords of less than
+    /// `bits` bits fails (or panics, for the infallible methods), even if
+    /// the configuration is weakened later, e.g. by `length()`. Embedding
+    /// applications can thus not accidentally weaken settings at runtime.
+    /// Consumes and returns itself.
+    ///
+    /// # Example
+    /// ```
+    /// let mut pwg = yapg_core::PasswordGenerator::from("0123456789abcdef")
+    ///     .length(32)
+    ///     .require_entropy(128.0);
+    /// assert!(pwg.try_generate().is_ok());
+    /// let mut pwg = pwg.length(16);
+    /// match pwg.try_generate().unwrap_err() {
+    ///     yapg_core::GenerateError::EntropyTooLow(e) => {
+    ///         assert_eq!(e.actual, 64.0)
+    ///     },
+    ///     e => panic!("unexpected error: {}", e),
+    /// }
+    /// assert!(pwg.generate_codes(1, 0).is_err());
+    /// ```
+    #[inline]
+    pub fn require_entropy(mut self, bits: f64) -> Self {
+        self.min_entropy = Some(bits);
+        self
+    }
+
+    /// Fails if no password can be generated, i.e. if the charset is empty,
+    /// if none satisfies the constraints or pattern, or if they fall below
+    /// the entropy floor. Generation fails (or panics, for the infallible
+    /// methods) in exactly these cases.
+    ///
+    /// # Example
+    /// ```
+    /// use yapg_core::{CharsetName, Constraints, PasswordGenerator};
+    ///
+    /// let pwg = PasswordGenerator::from("ab01").length(2);
+    /// assert!(pwg.check().is_ok());
+    /// let constraints = Constraints::new().at_least(CharsetName::Numeric, 3);
+    /// assert!(pwg.constraints(constraints).unwrap().check().is_err());
+    /// ```
+    #[inline]
+    pub fn check(&self) -> Result<(), GenerateError> {
+        self.check_at(self.length)
+    }
+
+    /// Fails if no password of `length` items can be generated, or if they
+    /// fall below the entropy floor.
+    fn check_at(&self, length: usize) -> Result<(), GenerateError> {
+        if self.charset.is_empty() && length > 0 {
+            return Err(GenerateError::EmptyCharset);
+        }
+        let log2_combinations = self.log2_combinations(length);
+        if log2_combinations == f64::NEG_INFINITY {
+            return Err(self.unsatisfiable(length));
+        }
+        let actual = log2_combinations.max(0.0);
+        match self.min_entropy {
+            Some(required) if actual < required => {
+                Err(EntropyTooLow { required, actual }.into())
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// The error of no password of `length` items satisfying the
+    /// constraints or pattern.
+    fn unsatisfiable(&self, length: usize) -> GenerateError {
+        let constraints = match (&self.template, &self.constraints) {
+            (Some(pattern), _) => pattern.to_string(),
+            (None, Some(cc)) => cc.constraints().to_string(),
+            (None, None) => String::new(),
+        };
+        GenerateError::Unsatisfiable { length, constraints }
+    }
+
+    /// Makes every password contain at least one item of each class found
+    /// in the charset, as many sites demand, or lifts that requirement. The
+    /// classes are lowercase and uppercase letters, digits and special
+    /// characters (`CharsetName::Special`). Shorthand for `Constraints`
+    /// demanding at least one of each, replacing any set earlier. Items
+    /// outside these classes, e.g. emoji, form no class. Consumes and
+    /// returns itself.
+    ///
+    /// # Panics
+    /// Generation panics if the length is shorter than the number of
+    /// classes, i.e. if `PasswordGenerator::combinations` is zero.
+    ///
+    /// # Example
+    /// ```
+    /// let mut pwg = yapg_core::PasswordGenerator::from("abcdefXYZ012")
+    ///     .length(3)
+    ///     .require_each_class(true);
+    /// for pw in pwg.generate_n(20) {
+    ///     assert!(pw.chars().any(|c| c.is_ascii_lowercase()));
+    ///     assert!(pw.chars().any(|c| c.is_ascii_uppercase()));
+    ///     assert!(pw.chars().any(|c| c.is_ascii_digit()));
+    /// }
+    /// // 3! orders of one item of each class
+    /// assert!((pwg.combinations() - 6.0 * 6.0 * 3.0 * 3.0).abs() < 1e-6);
+    /// assert_eq!(pwg.length(2).combinations(), 0.0);
+    /// ```
+    pub fn require_each_class(mut self, required: bool) -> Self {
+        self.constraints = if required && self.pattern.is_none() {
+            let mut constraints = Constraints::new();
+            for name in PASSWORD_CLASSES.iter() {
+                let chars = name.chars();
+                let present = self.charset.iter().any(|item| {
+                    item.as_char().is_some_and(|c| chars.contains(&c))
+                });
+                if present {
+                    constraints = constraints.at_least(*name, 1);
+                }
+            }
+            // the classes never overlap
+            Some(ConstrainedCharset::new(constraints, &self.charset).unwrap())
+        } else {
+            None
+        };
+        self
+    }
+
+    /// Restricts how many items of some charsets passwords may contain, e.g.
+    /// at least 2 digits and at most 1 special character, replacing any
+    /// constraints set earlier. Passwords are drawn uniformly among those
+    /// satisfying the constraints, and `PasswordGenerator::entropy` only
+    /// counts those. Fails if the constraints are invalid (see
+    /// `Constraints::check`). Consumes and returns itself.
+    ///
+    /// # Panics
+    /// Generation panics if no password satisfies the constraints, i.e. if
+    /// `PasswordGenerator::combinations` is zero.
+    ///
+    /// # Example
+    /// ```
+    /// use yapg_core::{CharsetName, Constraints, PasswordGenerator};
+    ///
+    /// let constraints = Constraints::new()
+    ///     .at_least(CharsetName::Numeric, 2)
+    ///     .at_most(CharsetName::Special, 1);
+    /// let mut pwg = PasswordGenerator::from("abcdef0123+-*/")
+    ///     .length(8)
+    ///     .constraints(constraints)
+    ///     .unwrap();
+    /// for pw in pwg.generate_n(20) {
+    ///     assert!(pw.chars().filter(char::is_ascii_digit).count() >= 2);
+    ///     assert!(pw.chars().filter(|c| "+-*/".contains(*c)).count() <= 1);
+    /// }
+    /// assert!(pwg.combinations() < 14f64.powi(8));
+    /// ```
+    pub fn constraints(mut self, constraints: Constraints) -> io::Result<Self> {
+        if self.pattern.is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Patterns cannot be combined with constraints",
+            ));
+        }
+        self.constraints =
+            Some(ConstrainedCharset::new(constraints, &self.charset)?);
+        Ok(self)
+    }
+
+    /// Registers `hook` to be called after each generated password, e.g. to
+    /// notify a provisioning system, with the password's
+    /// `secret_fingerprint` under `key`. Replaces any earlier hook. Consumes
+    /// and returns itself.
+    ///
+    /// # Example
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    ///
+    /// use yapg_core::{secret_fingerprint, FingerprintKey};
+    ///
+    /// let key = FingerprintKey::generate();
+    /// let seen = Rc::new(RefCell::new(vec![]));
+    /// let log = seen.clone();
+    /// let mut pwg = yapg_core::PasswordGenerator::from("ab")
+    ///     .length(8)
+    ///     .on_generated(key.clone(), move |event| {
+    ///         log.borrow_mut().push(event.clone())
+    ///     });
+    /// let pw = pwg.generate();
+    /// assert_eq!(seen.borrow().len(), 1);
+    /// assert_eq!(seen.borrow()[0].fingerprint, secret_fingerprint(&key, &pw));
+    /// assert_eq!(seen.borrow()[0].entropy, 8);
+    /// ```
+    pub fn on_generated<F>(mut self, key: FingerprintKey, hook: F) -> Self
+    where
+        F: Fn(&GenerationEvent) + 'static,
+    {
+        self.hook = Some(Hook(std::rc::Rc::new(hook), key));
+        self
+    }
+
+    /// Creates a `PasswordGenerator` drawing directly from the OS RNG
+    /// rather than the userspace-buffered thread-local RNG. Fails if the OS
+    /// RNG is unavailable.
+    ///
+    /// # Example
+    /// ```
+    /// let mut pwg =
+    ///     yapg_core::PasswordGenerator::secure("abc".chars().collect(), 8)
+    ///         .unwrap();
+    /// assert_eq!(pwg.generate().len(), 8);
+    /// ```
+    pub fn secure(charset: Vec<char>, length: usize) -> io::Result<Self> {
+        Self::new(charset, length).random_source(RandomSource::Os)
+    }
+
+    /// Makes the generator draw from `source` instead of the thread-local
+    /// RNG. Fails if the OS RNG is unavailable. Consumes and returns itself.
+    pub fn random_source(mut self, source: RandomSource) -> io::Result<Self> {
+        self.rng = GeneratorRng::open(source)?;
+        Ok(self)
+    }
+
+    /// Makes the generator draw from ChaCha20 seeded with `seed` instead of
+    /// the thread-local RNG, so that it yields the same passwords on every
+    /// run. Consumes and returns itself.
+    ///
+    /// The passwords are only as secret as the seed: generate it randomly
+    /// and keep it safe.
+    ///
+    /// # Example
+    /// ```
+    /// let pwg = yapg_core::PasswordGenerator::from("abcd").seeded([7; 32]);
+    /// assert_eq!(pwg.clone().generate_n(3), pwg.clone().generate_n(3));
+    /// ```
+    #[inline]
+    pub fn seeded(mut self, seed: [u8; 32]) -> Self {
+        self.rng = GeneratorRng::seeded(seed);
+        self
+    }
+
+    /// Makes the generator deterministic like `PasswordGenerator::seeded`,
+    /// from a `u64` seed that is convenient in tests. The 32-byte seed is
+    /// the `u64`'s little-endian bytes followed by zeros. Consumes and
+    /// returns itself.
+    ///
+    /// Such seeds are easily guessed: never use them for real passwords.
+    ///
+    /// # Example
+    /// ```
+    /// use yapg_core::PasswordGenerator;
+    ///
+    /// let mut a = PasswordGenerator::from("abcd").length(8).with_seed(42);
+    /// let mut b = PasswordGenerator::from("abcd").length(8).with_seed(42);
+    /// assert_eq!(a.generate_n(3), b.generate_n(3));
+    /// let mut seed = [0; 32];
+    /// seed[0] = 42;
+    /// let mut c = PasswordGenerator::from("abcd").length(8).seeded(seed);
+    /// assert_eq!(a.generate(), c.clone().generate_n(4)[3]);
+    /// ```
+    #[inline]
+    pub fn with_seed(self, seed: u64) -> Self {
+        self.seeded(GeneratorRng::expand_seed(seed))
+    }
+
+    /// Draws all randomness from `source` instead of the thread-local RNG,
+    /// e.g. to force generation through a hardware security module.
+    /// Consumes and returns itself.
+    ///
+    /// Generation panics if the source fails, as there is no safe fallback.
+    ///
+    /// # Example
+    /// ```
+    /// let os = yapg_core::OsEntropy::new().unwrap();
+    /// let mut pwg =
+    ///     yapg_core::PasswordGenerator::from("abcd").length(8).entropy_source(os);
+    /// assert_eq!(pwg.generate().len(), 8);
+    /// assert_eq!(pwg.rng_name(), "OS RNG");
+    /// ```
+    pub fn entropy_source<S: EntropySource + 'static>(
+        mut self,
+        source: S,
+    ) -> Self {
+        self.rng = GeneratorRng::Source(rng::SourceRng::new(source));
+        self
+    }
+
+    /// Substream `index` of a seeded generator: a seeded generator with the
+    /// same charset and length, whose output depends only on the parent's
+    /// seed and `index`. Substreams are disjoint from each other and from
+    /// the parent, and can be split again.
+    ///
+    /// Distributed workers sharing a seed can thus each generate their own
+    /// reproducible part of a batch, without any coordination beyond
+    /// knowing their index. Fails if the generator is not seeded.
+    pub fn stream(&self, index: u32) -> io::Result<Self> {
+        match self.rng {
+            GeneratorRng::Seeded { ref seed, .. } => {
+                let seed = GeneratorRng::substream_seed(seed, index);
+                let mut pwg = self.clone();
+                pwg.rng = GeneratorRng::seeded_locked(seed);
+                Ok(pwg)
+            },
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Only seeded generators can be split into streams",
+            )),
+        }
+    }
+
+    /// Splits a seeded generator into the substreams `0..n`, see
+    /// `PasswordGenerator::stream`.
+    ///
+    /// # Example
+    /// ```
+    /// let pwg = yapg_core::PasswordGenerator::from("abcd").seeded([7; 32]);
+    /// let mut streams = pwg.split_streams(3).unwrap();
+    /// assert_eq!(streams.len(), 3);
+    /// // worker 1 reproduces its part on its own
+    /// let mut worker = pwg.stream(1).unwrap();
+    /// assert_eq!(streams[1].generate_n(5), worker.generate_n(5));
+    /// assert_ne!(streams[0].generate(), streams[2].generate());
+    ///
+    /// assert!(yapg_core::PasswordGenerator::from("abcd")
+    ///     .split_streams(3)
+    ///     .is_err());
+    /// ```
+    pub fn split_streams(&self, n: u32) -> io::Result<Vec<Self>> {
+        (0..n).map(|i| self.stream(i)).collect()
+    }
+
+    /// Changes the length of the generated passwords, consumes and returns
+    /// itself.
+    #[inline]
+    pub fn length(mut self, length: usize) -> Self {
+        self.length = length;
+        self
+    }
+
+    /// Shortest length whose passwords reach `target_bits` of entropy with
+    /// the charset and constraints, or `None` if no length does, e.g. for a
+    /// single-item charset or constraints capping the length. With a
+    /// pattern, only the pattern's length is considered.
+    ///
+    /// # Example
+    /// ```
+    /// use yapg_core::{CharsetName, Constraints, PasswordGenerator};
+    ///
+    /// let pwg = PasswordGenerator::from("0123456789abcdef");
+    /// assert_eq!(pwg.length_for_entropy(128.0), Some(32));
+    /// assert_eq!(pwg.length_for_entropy(129.0), Some(33));
+    /// let capped = pwg
+    ///     .constraints(Constraints::new().at_most(CharsetName::Numeric, 1))
+    ///     .unwrap();
+    /// assert_eq!(capped.length_for_entropy(128.0), Some(48));
+    /// assert_eq!(PasswordGenerator::from("a").length_for_entropy(1.0), None);
+    /// ```
+    pub fn length_for_entropy(&self, target_bits: f64) -> Option<usize> {
+        if let Some(slots) = &self.pattern {
+            return Some(slots.len())
+                .filter(|len| self.log2_combinations(*len) >= target_bits);
+        }
+        let per_item = (self.charset.len() as f64).log2();
+        let mut length = match target_bits {
+            _ if target_bits <= 0.0 => 0,
+            _ if per_item > 0.0 => (target_bits / per_item).ceil() as usize,
+            _ => return None,
+        };
+        // constraints only lower the entropy, so lengthen until it suffices
+        let mut previous = f64::NEG_INFINITY;
+        loop {
+            let bits = self.log2_combinations(length);
+            if bits >= target_bits {
+                return Some(length);
+            }
+            let capped = previous.is_finite() && bits <= previous;
+            if capped || length >= MAX_LENGTH_FOR_ENTROPY {
+                return None;
+            }
+            previous = bits;
+            length += 1;
+        }
+    }
+
+    /// Sets the length to the shortest reaching `target_bits` of entropy,
+    /// see `PasswordGenerator::length_for_entropy`. Fails if no length does.
+    /// Consumes and returns itself.
+    ///
+    /// # Example
+    /// ```
+    /// let pwg = yapg_core::PasswordGenerator::from("0123456789abcdef")
+    ///     .with_min_entropy(128.0)
+    ///     .unwrap();
+    /// assert_eq!(pwg.entropy_bits(), 128.0);
+    /// ```
+    pub fn with_min_entropy(self, target_bits: f64) -> io::Result<Self> {
+        match self.length_for_entropy(target_bits) {
+            Some(length) => Ok(self.length(length)),
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("No length reaches {} bits of entropy", target_bits),
+            )),
+        }
+    }
+
+    /// Generates one password, with characters randomly chosen from the
+    /// charset.
+    ///
+    /// # Panics
+    /// If `PasswordGenerator::try_generate` fails, i.e. if the charset is
+    /// empty and the length is not zero, if no password satisfies the
+    /// constraints, or if the password would fall below the entropy floor
+    /// (see `PasswordGenerator::require_entropy`). Generators validated by
+    /// `PasswordGenerator::builder` never panic.
+    #[inline]
+    pub fn generate(&mut self) -> String {
+        match self.try_generate() {
+            Ok(password) => password,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Generates one password like `PasswordGenerator::generate`, but fails
+    /// instead of panicking if the configuration cannot yield it.
+    ///
+    /// # Example
+    /// ```
+    /// use yapg_core::{GenerateError, PasswordGenerator};
+    ///
+    /// let mut pwg = PasswordGenerator::new(vec![], 8);
+    /// assert!(matches!(pwg.try_generate(), Err(GenerateError::EmptyCharset)));
+    /// assert_eq!(pwg.length(0).try_generate().unwrap(), "");
+    /// ```
+    pub fn try_generate(&mut self) -> Result<String, GenerateError> {
+        self.check_at(self.length)?;
+        let password = self.draw()?;
+        let password = self.render(&password);
+        self.notify(&password);
+        Ok(password)
+    }
+
+    /// Generates one password like `PasswordGenerator::generate`, but as a
+    /// `SecretString` zeroed on drop. The drawn indices are wiped as well,
+    /// and the password is composed without reallocating, so that no copies
+    /// are left behind in freed memory.
+    ///
+    /// # Panics
+    /// Like `PasswordGenerator::generate`.
+    ///
+    /// # Example
+    /// ```
+    /// use yapg_core::{ExposeSecret, PasswordGenerator};
+    ///
+    /// let mut pwg = PasswordGenerator::from("abcd").length(12);
+    /// let secret = pwg.generate_secret();
+    /// assert_eq!(secret.expose_secret().len(), 12);
+    /// assert!(!format!("{:?}", secret).contains(secret.expose_secret()));
+    /// ```
+    #[cfg(feature = "secrecy")]
+    pub fn generate_secret(&mut self) -> SecretString {
+        use zeroize::Zeroize;
+
+        let mut indices =
+            match self.check_at(self.length).and_then(|_| self.draw()) {
+                Ok(indices) => indices,
+                Err(e) => panic!("{}", e),
+            };
+        let password = self.render(&indices);
+        indices.zeroize();
+        self.notify(&password);
+        SecretString::from(password)
+    }
+
+    /// Generates one password of the items at the indices drawn by
+    /// `sampler` instead of the generator's RNG, e.g. to test composition
+    /// deterministically or to plug in another sampler. Fails if the
+    /// password would fall below the entropy floor, or if the sampler draws
+    /// too few, too many or out-of-range indices.
+    pub fn generate_with<S: IndexSampler + ?Sized>(
+        &mut self,
+        sampler: &mut S,
+    ) -> io::Result<String> {
+        self.check_at(self.length)?;
+        let indices = sampler.sample(self.charset.len(), self.length);
+        if indices.len() != self.length {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Sampler drew {} indices instead of {}",
+                    indices.len(),
+                    self.length
+                ),
+            ));
+        }
+        let password = compose(&self.charset, &indices)?;
+        let matches_pattern = |slots: &Vec<Vec<usize>>| {
+            slots.iter().zip(&indices).all(|(slot, i)| slot.contains(i))
+        };
+        if !self.pattern.iter().all(matches_pattern) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Sampled password does not match the pattern",
+            ));
+        }
+        if !self.constraints.iter().all(|cc| cc.accepts(&indices)) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Sampled password violates the constraints",
+            ));
+        }
+        self.notify(&password);
+        Ok(password)
+    }
+
+    /// Draws the indices of `length` items, satisfying the constraints.
+    #[inline]
+    fn draw(&mut self) -> Result<Vec<usize>, GenerateError> {
+        self.draw_n(self.length)
+    }
+
+    /// Draws the indices of `length` items, satisfying the constraints or
+    /// matching the pattern. Fails if no password does.
+    fn draw_n(&mut self, length: usize) -> Result<Vec<usize>, GenerateError> {
+        if let Some(slots) = &self.pattern {
+            assert_eq!(
+                length,
+                slots.len(),
+                "Passwords of a pattern cannot change their length"
+            );
+            let rng = &mut self.rng;
+            let drawn = slots
+                .iter()
+                .map(|slot| rng.choose(slot).copied())
+                .collect::<Option<_>>();
+            return drawn.ok_or_else(|| self.unsatisfiable(length));
+        }
+        match &self.constraints {
+            Some(cc) => cc.sample(&mut self.rng, length),
+            None => Ok(UniformSampler(&mut self.rng)
+                .sample(self.charset.len(), length)),
+        }
+    }
+
+    /// Concatenates the items at `indices`, which are in range.
+    fn render(&self, indices: &[usize]) -> String {
+        compose(&self.charset, indices).expect("index out of range")
+    }
+
+    /// Reports `password` to the `on_generated` hook, if any.
+    fn notify(&self, password: &str) {
+        if let Some(Hook(hook, key)) = &self.hook {
+            hook(&GenerationEvent {
+                fingerprint: secret_fingerprint(key, password),
+                config_hash: self.config_hash(),
+                length: self.length,
+                entropy: self.entropy(),
+            });
+        }
+    }
+
+    /// Fills `buf` with characters randomly chosen from the charset, without
+    /// allocating unless there are constraints or a pattern. The configured
+    /// length is ignored in favour of `buf.len()`. Returns the entropy of the
+    /// filled buffer in bits.
+    ///
+    /// # Panics
+    /// If the charset has multi-char items (see
+    /// `PasswordGenerator::from_items`), or if the buffer would fall below
+    /// the entropy floor.
+    ///
+    /// # Example
+    /// ```
+    /// let mut pwg = yapg_core::PasswordGenerator::from("abcd");
+    /// let mut grid = ['\0'; 16];
+    /// assert_eq!(pwg.generate_chars_into(&mut grid), 32);
+    /// assert!(grid.iter().all(|c| "abcd".contains(*c)));
+    /// ```
+    #[inline]
+    pub fn generate_chars_into(&mut self, buf: &mut [char]) -> usize {
+        if let Err(e) = self.check_at(buf.len()) {
+            panic!("{}", e);
+        }
+        let as_char = |item: &Grapheme| {
+            item.as_char().expect("multi-char item in char buffer")
+        };
+        if self.constraints.is_some() || self.pattern.is_some() {
+            let indices = match self.draw_n(buf.len()) {
+                Ok(indices) => indices,
+                Err(e) => panic!("{}", e),
+            };
+            for (c, i) in buf.iter_mut().zip(indices) {
+                *c = as_char(&self.charset[i]);
+            }
+        } else {
+            for c in buf.iter_mut() {
+                *c = as_char(self.rng.choose(&self.charset).unwrap());
+            }
+        }
+        self.bits(buf.len()).floor() as usize
+    }
+
+    /// Generates a vector of passwords with length n, calling
+    /// `PasswordGenerator::generate` internally.
+    /// Cannot return an iterator, because that iterator would need to hold
+    /// a mutable reference to the generator.
+    #[inline]
+    pub fn generate_n(&mut self, n: usize) -> Vec<String> {
+        (0..n).map(|_| self.generate()).collect()
+    }
+
+    /// Generates a batch of `n` codes, any two of which differ in at least
+    /// `min_distance` positions, so that mistyping fewer characters than
+    /// that never yields someone else's code. Candidates too close to an
+    /// earlier code are discarded and redrawn.
+    ///
+    /// Fails if the charset and length leave too little room for the
+    /// requested distance, i.e. if many candidates in a row are discarded.
+    /// For multi-char items, positions are items rather than chars.
+    ///
+    /// # Example
+    /// ```
+    /// let mut pwg = yapg_core::PasswordGenerator::from("0123456789").length(8);
+    /// let codes = pwg.generate_codes(100, 3).unwrap();
+    /// for (i, a) in codes.iter().enumerate() {
+    ///     for b in codes[i + 1..].iter() {
+    ///         assert!(yapg_core::hamming_distance(a, b).unwrap() >= 3);
+    ///     }
+    /// }
+    /// assert!(pwg.length(2).generate_codes(2, 3).is_err());
+    /// ```
+    pub fn generate_codes(
+        &mut self,
+        n: usize,
+        min_distance: usize,
+    ) -> io::Result<Vec<String>> {
+        let length = self.length;
+        let too_close = || {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Cannot find {} codes of length {} differing in {} \
+                     positions, use longer codes or a larger charset",
+                    n, length, min_distance
+                ),
+            )
+        };
+        if n > 1 && min_distance > self.length {
+            return Err(too_close());
+        }
+        self.check_at(self.length)?;
+        let mut codes: Vec<Vec<usize>> = Vec::with_capacity(n);
+        let mut rejected = 0;
+        while codes.len() < n {
+            let candidate = self.draw()?;
+            let far_enough = codes.iter().all(|code| {
+                let distance =
+                    code.iter().zip(&candidate).filter(|(a, b)| a != b);
+                distance.count() >= min_distance
+            });
+            if far_enough {
+                self.notify(&self.render(&candidate));
+                codes.push(candidate);
+                rejected = 0;
+            } else {
+                rejected += 1;
+                if rejected == MAX_REJECTED_CANDIDATES {
+                    return Err(too_close());
+                }
+            }
+        }
+        Ok(codes.iter().map(|code| self.render(code)).collect())
+    }
+
+    /// Generates `n` passwords accepted by `accept`, redrawing rejected
+    /// candidates, e.g. to express one-off constraints. Fails if many
+    /// candidates in a row are rejected.
+    ///
+    /// Filtering shrinks the set of possible passwords, so they are weaker
+    /// than `PasswordGenerator::entropy` suggests; see
+    /// `FilteredBatch::entropy`.
+    ///
+    /// # Example
+    /// ```
+    /// let mut pwg = yapg_core::PasswordGenerator::from("ab").length(4);
+    /// let batch = pwg.generate_filtered(10, |pw| pw.starts_with('a')).unwrap();
+    /// assert!(batch.passwords.iter().all(|pw| pw.starts_with('a')));
+    /// assert!(batch.candidates >= 10);
+    /// assert!(pwg.generate_filtered(1, |pw| pw.contains('c')).is_err());
+    /// ```
+    pub fn generate_filtered<F>(
+        &mut self,
+        n: usize,
+        mut accept: F,
+    ) -> io::Result<FilteredBatch>
+    where
+        F: FnMut(&str) -> bool,
+    {
+        self.check_at(self.length)?;
+        let mut batch = FilteredBatch { passwords: vec![], candidates: 0 };
+        let mut rejected = 0;
+        while batch.passwords.len() < n {
+            let candidate = self.draw()?;
+            let candidate = self.render(&candidate);
+            batch.candidates += 1;
+            if accept(&candidate) {
+                self.notify(&candidate);
+                batch.passwords.push(candidate);
+                rejected = 0;
+            } else {
+                rejected += 1;
+                if rejected == MAX_REJECTED_CANDIDATES {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!(
+                            "Gave up after {} candidates in a row were \
+                             rejected, loosen the filter",
+                            rejected
+                        ),
+                    ));
+                }
+            }
+        }
+        Ok(batch)
+    }
+
+    /// Generates `n` passwords not found in known breaches by `breaches`,
+    /// e.g. a `HibpClient` or `PwnedDb`, redrawing breached candidates.
+    /// Fails if the check fails or several candidates in a row are
+    /// breached, as with tiny charsets.
+    ///
+    /// For passwords of decent entropy, a breached candidate is so unlikely
+    /// that the entropy is unaffected.
+    pub fn generate_unbreached(
+        &mut self,
+        n: usize,
+        breaches: &impl BreachCheck,
+    ) -> io::Result<Vec<String>> {
+        self.check_at(self.length)?;
+        let passwords = generate_unbreached_with(n, breaches, || {
+            let indices = self.draw()?;
+            Ok(self.render(&indices))
+        })?;
+        for password in passwords.iter() {
+            self.notify(password);
+        }
+        Ok(passwords)
+    }
+
+    /// Generates a password with as many characters from each charset as
+    /// `composition` demands, in random order. Charset and length of the
+    /// generator are ignored, and so is the `on_generated` hook. See
+    /// `Composition::entropy` for the entropy.
+    ///
+    /// # Example
+    /// ```
+    /// let composition = "L:6,N:2".parse().unwrap();
+    /// let mut pwg = yapg_core::PasswordGenerator::from("");
+    /// let pw = pwg.generate_composed(&composition);
+    /// assert_eq!(pw.chars().filter(char::is_ascii_lowercase).count(), 6);
+    /// assert_eq!(pw.chars().filter(char::is_ascii_digit).count(), 2);
+    /// ```
+    pub fn generate_composed(&mut self, composition: &Composition) -> String {
+        let mut chars = vec![];
+        for (name, n) in composition.parts() {
+            let charset = name.chars();
+            chars.extend((0..*n).map(|_| *self.rng.choose(&charset).unwrap()));
+        }
+        self.rng.shuffle(&mut chars);
+        chars.into_iter().collect()
+    }
+
+    /// Shuffles `items` in place, using the generator's RNG. All orderings
+    /// are equally likely (Fisher-Yates).
+    ///
+    /// # Example
+    /// ```
+    /// let mut pwg = yapg_core::PasswordGenerator::from("");
+    /// let mut entries = vec!["alice", "bob", "carol"];
+    /// pwg.shuffle_with(&mut entries);
+    /// entries.sort();
+    /// assert_eq!(entries, vec!["alice", "bob", "carol"]);
+    /// ```
+    #[inline]
+    pub fn shuffle_with<T>(&mut self, items: &mut [T]) {
+        self.rng.shuffle(items)
+    }
+
+    /// Picks `k` of `items` uniformly at random, consuming the iterator only
+    /// once and keeping no more than `k` items in memory (reservoir
+    /// sampling). If there are fewer than `k` items, all of them are
+    /// returned. The picked items are returned in random order.
+    ///
+    /// # Example
+    /// ```
+    /// let mut pwg = yapg_core::PasswordGenerator::from("");
+    /// let winners = pwg.pick_with(1..=100, 3);
+    /// assert_eq!(winners.len(), 3);
+    /// assert!(winners.iter().all(|w| (1..=100).contains(w)));
+    /// assert_eq!(pwg.pick_with(vec!["only"], 3), vec!["only"]);
+    /// ```
+    pub fn pick_with<T, I>(&mut self, items: I, k: usize) -> Vec<T>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut reservoir = Vec::with_capacity(k);
+        for (i, item) in items.into_iter().enumerate() {
+            if i < k {
+                reservoir.push(item);
+            } else {
+                let j = self.rng.gen_range(0, i + 1);
+                if j < k {
+                    reservoir[j] = item;
+                }
+            }
+        }
+        self.rng.shuffle(&mut reservoir);
+        reservoir
+    }
+
+    /// Draws an integer uniformly from `min..=max`, using rejection sampling
+    /// to avoid the bias of reducing random numbers modulo the range size.
+    ///
+    /// # Panics
+    /// If `min > max`.
+    ///
+    /// # Example
+    /// ```
+    /// let mut pwg = yapg_core::PasswordGenerator::from("");
+    /// let n = pwg.gen_int(-5, 5);
+    /// assert!(-5 <= n && n <= 5);
+    /// assert_eq!(pwg.gen_int(7, 7), 7);
+    /// ```
+    pub fn gen_int(&mut self, min: i64, max: i64) -> i64 {
+        assert!(min <= max, "empty range {}..={}", min, max);
+        let range = (max as i128 - min as i128 + 1) as u128;
+        if range > u64::MAX as u128 {
+            return self.rng.next_u64() as i64;
+        }
+        let offset = uniform_below(&mut self.rng, range as u64);
+        (min as i128 + offset as i128) as i64
+    }
+
+    /// Rolls `dice`, returning the result of each die.
+    ///
+    /// # Example
+    /// ```
+    /// let mut pwg = yapg_core::PasswordGenerator::from("");
+    /// let rolls = pwg.roll_with(&"4d6".parse().unwrap());
+    /// assert_eq!(rolls.len(), 4);
+    /// assert!(rolls.iter().all(|r| 1 <= *r && *r <= 6));
+    /// ```
+    pub fn roll_with(&mut self, dice: &Dice) -> Vec<i64> {
+        (0..dice.count).map(|_| self.gen_int(1, dice.sides as i64)).collect()
+    }
+
+    /// Appends randomly chosen characters (as many as the configured length)
+    /// to a user-chosen, memorable `base`. Only the appended characters are
+    /// random, so only they count towards the entropy: the base is assumed
+    /// to be known to (or guessable by) an attacker.
+    ///
+    /// # Example
+    /// ```
+    /// let mut pwg =
+    ///     yapg_core::PasswordGenerator::from("0123456789abcdef").length(4);
+    /// let extended = pwg.extend("MyCat");
+    /// assert!(extended.password.starts_with("MyCat"));
+    /// assert_eq!(extended.password.len(), 9);
+    /// assert_eq!(extended.random_bits, 16.0);
+    /// ```
+    pub fn extend(&mut self, base: &str) -> Extended {
+        let mut password = base.to_string();
+        password.push_str(&self.generate());
+        let random_bits = self.bits(self.length);
+        Extended { password, random_bits }
+    }
+
+    /// Number of all possible combinations arising from charset and length,
+    /// and the constraints, if any.
+    #[inline]
+    pub fn combinations(&self) -> f64 { self.combinations_at(self.length) }
+
+    /// Exact number of all possible combinations arising from charset and
+    /// length, and the constraints, if any. Unlike
+    /// `PasswordGenerator::combinations`, it is neither rounded nor
+    /// overflows for large charsets and lengths.
+    ///
+    /// Requires the `num-bigint` feature.
+    ///
+    /// # Example
+    /// ```
+    /// use yapg_core::{BigUint, CharsetName, Constraints, PasswordGenerator};
+    ///
+    /// let pwg = PasswordGenerator::from("0123456789").length(400);
+    /// assert_eq!(pwg.combinations_exact(), BigUint::from(10u8).pow(400));
+    /// let pwg = PasswordGenerator::from("ab01")
+    ///     .length(3)
+    ///     .constraints(Constraints::new().at_least(CharsetName::Numeric, 2))
+    ///     .unwrap();
+    /// assert_eq!(pwg.combinations_exact(), BigUint::from(32u8));
+    /// ```
+    #[cfg(feature = "num-bigint")]
+    pub fn combinations_exact(&self) -> BigUint {
+        if let Some(slots) = &self.pattern {
+            if self.length != slots.len() {
+                return BigUint::default();
+            }
+            return slots
+                .iter()
+                .map(|slot| BigUint::from(slot.len()))
+                .product();
+        }
+        match &self.constraints {
+            Some(cc) => cc.count_exact(self.length),
+            None => BigUint::from(self.charset.len()).pow(self.length as u32),
+        }
+    }
+
+    /// Number of possible passwords of `length` items.
+    fn combinations_at(&self, length: usize) -> f64 {
+        if let Some(slots) = &self.pattern {
+            if length != slots.len() {
+                return 0.0;
+            }
+            return slots.iter().map(|slot| slot.len() as f64).product();
+        }
+        match &self.constraints {
+            // the fraction may underflow where the total overflows
+            Some(_) => self.log2_combinations(length).exp2(),
+            None => (self.charset.len() as f64).powf(length as f64),
+        }
+    }
+
+    /// Binary logarithm of the number of possible passwords of `length`
+    /// items, computed in the log domain so that it stays finite where
+    /// `combinations_at` overflows.
+    fn log2_combinations(&self, length: usize) -> f64 {
+        if let Some(slots) = &self.pattern {
+            if length != slots.len() {
+                return f64::NEG_INFINITY;
+            }
+            return slots.iter().map(|slot| (slot.len() as f64).log2()).sum();
+        }
+        let all = match self.charset.len() {
+            _ if length == 0 => 0.0,
+            0 => f64::NEG_INFINITY,
+            n => length as f64 * (n as f64).log2(),
+        };
+        match &self.constraints {
+            Some(cc) => all + cc.ln_fraction(length) / std::f64::consts::LN_2,
+            None => all,
+        }
+    }
+
+    /// Entropy of passwords of `length` items in bits.
+    fn bits(&self, length: usize) -> f64 {
+        self.log2_combinations(length).max(0.0)
+    }
+
+    /// Entropy of the generated passwords in bits, truncated. Prefer
+    /// `PasswordGenerator::entropy_bits`.
+    #[inline]
+    pub fn entropy(&self) -> usize { self.entropy_bits().floor() as usize }
+
+    /// Entropy of the generated passwords in bits, i.e. `length *
+    /// log2(charset_len)` without constraints. Computed in the log domain,
+    /// it stays precise where `PasswordGenerator::combinations` overflows.
+    ///
+    /// # Example
+    /// ```
+    /// let pwg = yapg_core::PasswordGenerator::from("0123456789").length(400);
+    /// assert_eq!(pwg.combinations(), f64::INFINITY);
+    /// assert!((pwg.entropy_bits() - 400.0 * 10f64.log2()).abs() < 1e-9);
+    /// assert_eq!(pwg.entropy(), 1328);
+    /// ```
+    #[inline]
+    pub fn entropy_bits(&self) -> f64 { self.bits(self.length) }
+
+    /// Fingerprint of charset, length and any constraints or pattern, as
+    /// recorded in `Provenance`, see `config_fingerprint`.
+    pub fn config_hash(&self) -> String {
+        config_fingerprint(
+            &self.charset,
+            self.length,
+            self.constraints.as_ref().map(ConstrainedCharset::constraints),
+            self.template.as_ref(),
+        )
+    }
+
+    /// Name of the underlying RNG, as recorded in `Provenance`.
+    #[inline]
+    pub fn rng_name(&self) -> String { self.rng.name() }
+}
+
+/// A user-chosen base extended by random characters, see
+/// `PasswordGenerator::extend`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Extended {
+    /// The base followed by the random characters.
+    pub password: String,
+    /// Entropy of the random characters in bits, the only part of the
+    /// password's strength that can be relied upon.
+    pub random_bits: f64,
+}
+
+/// Error of generating passwords below the entropy floor set by
+/// `PasswordGenerator::require_entropy`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntropyTooLow {
+    /// The entropy floor in bits.
+    pub required: f64,
+    /// Entropy of the passwords in bits.
+    pub actual: f64,
+}
+
+impl fmt::Display for EntropyTooLow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Password entropy of {:.1} bits is below the required {:.1} bits",
+            self.actual, self.required
+        )
+    }
+}
+
+impl std::error::Error for EntropyTooLow {}
+
+impl From<EntropyTooLow> for io::Error {
+    fn from(e: EntropyTooLow) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidInput, e)
+    }
+}
+
+/// Error of generating passwords, see `PasswordGenerator::try_generate`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GenerateError {
+    /// There are no items to draw from.
+    EmptyCharset,
+    /// No password of `length` items satisfies the constraints or pattern.
+    Unsatisfiable {
+        /// Length of the passwords in items.
+        length: usize,
+        /// The constraints or pattern, e.g. `N>=3,S<=1` or `CvcvN`.
+        constraints: String,
+    },
+    /// The passwords would fall below the entropy floor.
+    EntropyTooLow(EntropyTooLow),
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::EmptyCharset => write!(f, "The charset is empty"),
+            Self::Unsatisfiable { length, constraints } => write!(
+                f,
+                "No password of length {} satisfies the constraints {}",
+                length, constraints
+            ),
+            Self::EntropyTooLow(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for GenerateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::EntropyTooLow(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<EntropyTooLow> for GenerateError {
+    fn from(e: EntropyTooLow) -> Self { Self::EntropyTooLow(e) }
+}
+
+impl From<GenerateError> for io::Error {
+    fn from(e: GenerateError) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidInput, e)
+    }
+}
+
+/// Passwords passing a filter, see `PasswordGenerator::generate_filtered`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilteredBatch {
+    /// The accepted passwords.
+    pub passwords: Vec<String>,
+    /// Number of candidates drawn, including the accepted ones.
+    pub candidates: usize,
+}
+
+impl FilteredBatch {
+    /// Share of candidates accepted by the filter.
+    pub fn acceptance(&self) -> f64 {
+        if self.candidates == 0 {
+            return 1.0;
+        }
+        self.passwords.len() as f64 / self.candidates as f64
+    }
+
+    /// Estimated entropy in bits of the accepted passwords, given the
+    /// `unfiltered` entropy: the filter keeps about `acceptance()` of all
+    /// possible passwords, each accepted one remaining equally likely. Only
+    /// an estimate, as the acceptance is measured on this batch.
+    ///
+    /// # Example
+    /// ```
+    /// let batch = yapg_core::FilteredBatch {
+    ///     passwords: vec!["a".to_string()],
+    ///     candidates: 4,
+    /// };
+    /// assert_eq!(batch.entropy(10.0), 8.0);
+    /// ```
+    pub fn entropy(&self, unfiltered: f64) -> f64 {
+        (unfiltered + self.acceptance().log2()).max(0.0)
+    }
+}
+
+/// Draws `length` characters uniformly from `charset` using `rng`.
+pub(crate) fn random_string<R: Rng>(
+    rng: &mut R,
+    charset: &[char],
+    length: usize,
+) -> String {
+    let mut s = String::with_capacity(length);
+    for _ in 0..length {
+        s.push(*rng.choose(charset).unwrap());
+    }
+    s
+}
+
+/// Draws an integer uniformly from `0..range` using `rng`, rejecting the
+/// values that would bias the result towards small numbers.
+pub(crate) fn uniform_below<R: RngCore>(rng: &mut R, range: u64) -> u64 {
+    debug_assert!(range > 0);
+    // largest value such that 0..=zone has a multiple of range elements
+    let zone = u64::MAX - (u64::MAX - range + 1) % range;
+    loop {
+        let v = rng.next_u64();
+        if v <= zone {
+            return v % range;
+        }
+    }
+}
+
+impl std::convert::From<Vec<char>> for PasswordGenerator {
+    fn from(charset: Vec<char>) -> PasswordGenerator {
+        PasswordGenerator::new(charset, 20)
+    }
+}
+
+impl std::convert::From<&str> for PasswordGenerator {
+    fn from(charset: &str) -> PasswordGenerator {
+        PasswordGenerator::new((charset).chars().collect(), 20)
+    }
+}
@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+
+/// Truncation and case folding a target system silently applies to
+/// passwords, e.g. before hashing them, so that only part of what is typed
+/// counts.
+///
+/// # Example
+/// ```
+/// use yapg_core::InputLimit;
+///
+/// let crypt = InputLimit::chars(8);
+/// assert_eq!(crypt.apply("abcdefghij"), "abcdefgh");
+/// let charset: Vec<char> = ('a'..='p').collect();
+/// assert_eq!(crypt.effective_length(&charset, 12), 8);
+/// assert_eq!(crypt.effective_entropy(&charset, 12), 32.0);
+///
+/// let bcrypt = InputLimit::bytes(72);
+/// assert_eq!(bcrypt.effective_length(&['a', 'ä'], 60), 36);
+///
+/// let racf = InputLimit::chars(8).case_insensitive();
+/// assert_eq!(racf.apply("Secret"), "SECRET");
+/// assert_eq!(racf.effective_entropy(&['a', 'b', 'A', 'B'], 4), 4.0);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct InputLimit {
+    /// Number of leading chars that count, if limited.
+    pub max_chars: Option<usize>,
+    /// Number of leading bytes (of UTF-8) that count, if limited.
+    pub max_bytes: Option<usize>,
+    /// Whether upper- and lower-case letters count alike.
+    pub case_insensitive: bool,
+}
+
+impl InputLimit {
+    /// Only the first `n` chars count.
+    pub fn chars(n: usize) -> Self {
+        Self { max_chars: Some(n), ..Self::default() }
+    }
+
+    /// Only the first `n` bytes count.
+    pub fn bytes(n: usize) -> Self {
+        Self { max_bytes: Some(n), ..Self::default() }
+    }
+
+    /// Makes upper- and lower-case letters count alike, consumes and returns
+    /// itself.
+    #[inline]
+    pub fn case_insensitive(mut self) -> Self {
+        self.case_insensitive = true;
+        self
+    }
+
+    /// Whether passwords are taken into account completely.
+    pub fn is_unlimited(&self) -> bool { *self == Self::default() }
+
+    /// The part of `password` that counts, in upper case if case-insensitive.
+    /// Byte limits cut at the last char boundary.
+    pub fn apply(&self, password: &str) -> String {
+        let mut end = password.len();
+        if let Some(n) = self.max_chars {
+            end = password.char_indices().nth(n).map_or(end, |(i, _)| i);
+        }
+        if let Some(n) = self.max_bytes {
+            end = end.min(n);
+            while !password.is_char_boundary(end) {
+                end -= 1;
+            }
+        }
+        if self.case_insensitive {
+            password[..end].to_uppercase()
+        } else {
+            password[..end].to_string()
+        }
+    }
+
+    /// Number of leading chars of passwords of `length` from `charset` that
+    /// count in any case, assuming the widest chars for byte limits.
+    pub fn effective_length(&self, charset: &[char], length: usize) -> usize {
+        let mut n = length;
+        if let Some(max) = self.max_chars {
+            n = n.min(max);
+        }
+        if let Some(max) = self.max_bytes {
+            let widest =
+                charset.iter().map(|c| c.len_utf8()).max().unwrap_or(1);
+            n = n.min(max / widest);
+        }
+        n
+    }
+
+    /// Entropy in bits that counts of passwords of `length` drawn uniformly
+    /// from `charset`. Case folding makes letters of either case a single
+    /// (more likely) outcome.
+    pub fn effective_entropy(&self, charset: &[char], length: usize) -> f64 {
+        let mut outcomes: HashMap<char, usize> = HashMap::new();
+        for c in charset {
+            let c = match self.case_insensitive {
+                true => c.to_uppercase().next().unwrap_or(*c),
+                false => *c,
+            };
+            *outcomes.entry(c).or_default() += 1;
+        }
+        let total = charset.len() as f64;
+        let bits_per_char: f64 = outcomes
+            .values()
+            .map(|n| {
+                let p = *n as f64 / total;
+                -p * p.log2()
+            })
+            .sum();
+        bits_per_char * self.effective_length(charset, length) as f64
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::InputLimit;
+
+    #[test]
+    fn applying_limits() {
+        assert_eq!(InputLimit::bytes(3).apply("aäb"), "aä");
+        assert_eq!(InputLimit::bytes(2).apply("aäb"), "a");
+        assert_eq!(InputLimit::default().apply("aäb"), "aäb");
+        assert!(InputLimit::default().is_unlimited());
+        assert!(!InputLimit::chars(8).is_unlimited());
+
+        // 'a' and 'A' are one outcome with probability 1/2
+        let folding = InputLimit::default().case_insensitive();
+        let bits = folding.effective_entropy(&['a', 'A', 'b', '1'], 1);
+        assert!((bits - 1.5).abs() < 1e-9);
+        let bits = InputLimit::default().effective_entropy(&['a', 'A'], 10);
+        assert!((bits - 10.0).abs() < 1e-9);
+        assert_eq!(InputLimit::chars(8).effective_entropy(&[], 10), 0.0);
+    }
+}
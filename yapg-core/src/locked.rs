@@ -0,0 +1,129 @@
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+/// A buffer for long-lived secrets, such as the master secret of a
+/// deterministic generator, which is wiped when dropped.
+///
+/// With the `mlock` feature, the buffer is also locked into memory
+/// (`mlock` on Unix, `VirtualLock` on Windows), so that it is never swapped
+/// to disk. If the OS denies locking, e.g. because `RLIMIT_MEMLOCK` is
+/// exhausted, the buffer is still usable, merely unlocked: check
+/// `LockedBuffer::is_locked` if that matters.
+///
+/// # Example
+/// ```
+/// let secret = yapg_core::LockedBuffer::new(vec![0xca, 0xfe]);
+/// assert_eq!(&secret[..], [0xca, 0xfe]);
+/// assert_eq!(format!("{:?}", secret), "LockedBuffer([2 bytes])");
+/// if !cfg!(feature = "mlock") {
+///     assert!(!secret.is_locked());
+/// }
+/// ```
+pub struct LockedBuffer {
+    bytes: Box<[u8]>,
+    locked: bool,
+}
+
+impl LockedBuffer {
+    /// Moves `bytes` into a new buffer, locking it if possible, and wipes
+    /// them.
+    pub fn new(mut bytes: Vec<u8>) -> Self {
+        // `into_boxed_slice` may reallocate and leave a copy behind, so the
+        // buffer is allocated at its final size and locked before copying
+        let mut buffer = vec![0; bytes.len()].into_boxed_slice();
+        let locked = !buffer.is_empty() && sys::lock(&buffer);
+        buffer.copy_from_slice(&bytes);
+        bytes.resize(bytes.capacity(), 0);
+        wipe(&mut bytes);
+        Self { bytes: buffer, locked }
+    }
+
+    /// Whether the buffer is locked into memory.
+    #[inline]
+    pub fn is_locked(&self) -> bool { self.locked }
+}
+
+impl Deref for LockedBuffer {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] { &self.bytes }
+}
+
+impl DerefMut for LockedBuffer {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [u8] { &mut self.bytes }
+}
+
+impl fmt::Debug for LockedBuffer {
+    /// Prints the length only, never the secret.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "LockedBuffer([{} bytes])", self.bytes.len())
+    }
+}
+
+impl Drop for LockedBuffer {
+    fn drop(&mut self) {
+        wipe(&mut self.bytes);
+        if self.locked {
+            sys::unlock(&self.bytes);
+        }
+    }
+}
+
+/// Zeroes `bytes`, e.g. copies of secrets on the stack.
+pub(crate) fn wipe(bytes: &mut [u8]) {
+    for byte in bytes.iter_mut() {
+        // volatile, so the wiping of soon-to-be freed memory is not
+        // optimized away
+        unsafe { std::ptr::write_volatile(byte, 0) };
+    }
+}
+
+#[cfg(all(feature = "mlock", unix))]
+mod sys {
+    pub(super) fn lock(bytes: &[u8]) -> bool {
+        unsafe { libc::mlock(bytes.as_ptr() as *const _, bytes.len()) == 0 }
+    }
+
+    pub(super) fn unlock(bytes: &[u8]) {
+        unsafe { libc::munlock(bytes.as_ptr() as *const _, bytes.len()) };
+    }
+}
+
+#[cfg(all(feature = "mlock", windows))]
+mod sys {
+    use winapi::um::memoryapi::{VirtualLock, VirtualUnlock};
+
+    pub(super) fn lock(bytes: &[u8]) -> bool {
+        unsafe { VirtualLock(bytes.as_ptr() as *mut _, bytes.len()) != 0 }
+    }
+
+    pub(super) fn unlock(bytes: &[u8]) {
+        unsafe { VirtualUnlock(bytes.as_ptr() as *mut _, bytes.len()) };
+    }
+}
+
+#[cfg(not(all(feature = "mlock", any(unix, windows))))]
+mod sys {
+    pub(super) fn lock(_: &[u8]) -> bool { false }
+
+    pub(super) fn unlock(_: &[u8]) {}
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::LockedBuffer;
+
+    #[test]
+    fn locking_is_optional() {
+        let buffer = LockedBuffer::new(vec![1; 64]);
+        assert_eq!(&buffer[..], &[1; 64][..]);
+        assert!(!LockedBuffer::new(vec![]).is_locked());
+        let mut bytes = Vec::with_capacity(64);
+        bytes.extend_from_slice(&[2; 3]);
+        assert_eq!(&LockedBuffer::new(bytes)[..], [2; 3]);
+        drop(buffer);
+    }
+}
@@ -0,0 +1,316 @@
+use std::collections::BTreeMap;
+use std::io;
+
+use rand::Rng;
+
+use crate::rng::GeneratorRng;
+use crate::RandomSource;
+
+/// Highest order accepted by `MarkovModel::train`. Higher orders mostly
+/// reproduce the words of the corpus.
+const MAX_ORDER: usize = 4;
+
+/// Pads the context at the start of words. Never part of a trained word,
+/// as only alphabetic chars are.
+const START: char = '\0';
+
+/// Character-level Markov model of the words of a corpus: the probability
+/// of each letter (or the end of the word) following the previous `order`
+/// letters.
+///
+/// # Example
+/// ```
+/// let model = yapg_core::MarkovModel::train("banana bandana", 2).unwrap();
+/// assert_eq!(model.order(), 2);
+/// assert!(yapg_core::MarkovModel::train("1234 !?", 2).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkovModel {
+    order: usize,
+    /// Followers (`None` ending the word) with their counts, by context.
+    transitions: BTreeMap<Vec<char>, Vec<(Option<char>, u32)>>,
+}
+
+impl MarkovModel {
+    /// Trains a model of `order` (1 to 4) on the words of `corpus`, i.e. its
+    /// runs of alphabetic chars, lowercased. Fails if there are none.
+    pub fn train(corpus: &str, order: usize) -> io::Result<Self> {
+        if order == 0 || order > MAX_ORDER {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Markov order must be 1 to {}, not {}",
+                    MAX_ORDER, order
+                ),
+            ));
+        }
+        let mut counts: BTreeMap<Vec<char>, BTreeMap<Option<char>, u32>> =
+            BTreeMap::new();
+        let corpus = corpus.to_lowercase();
+        let words = corpus.split(|c: char| !c.is_alphabetic());
+        for word in words.filter(|w| !w.is_empty()) {
+            let mut context = vec![START; order];
+            for c in word.chars().map(Some).chain(std::iter::once(None)) {
+                *counts
+                    .entry(context.clone())
+                    .or_default()
+                    .entry(c)
+                    .or_default() += 1;
+                if let Some(c) = c {
+                    context.remove(0);
+                    context.push(c);
+                }
+            }
+        }
+        if counts.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Corpus contains no words to train on",
+            ));
+        }
+        let transitions = counts
+            .into_iter()
+            .map(|(context, followers)| {
+                (context, followers.into_iter().collect())
+            })
+            .collect();
+        Ok(Self { order, transitions })
+    }
+
+    /// Number of preceding letters each letter depends on.
+    #[inline]
+    pub fn order(&self) -> usize { self.order }
+
+    /// The context at the start of a word.
+    fn start(&self) -> Vec<char> { vec![START; self.order] }
+
+    /// Probabilities of the next letter and of the context it leads to,
+    /// given `context`. Ending the word is folded into starting a new one,
+    /// so each step yields a letter.
+    fn steps(&self, context: &[char]) -> Vec<(char, Vec<char>, f64)> {
+        let followers = &self.transitions[context];
+        let total: u32 = followers.iter().map(|(_, n)| n).sum();
+        let mut steps = vec![];
+        for (c, n) in followers {
+            let p = *n as f64 / total as f64;
+            match c {
+                Some(c) => steps.push((*c, shift(context, *c), p)),
+                None => {
+                    let start = self.start();
+                    for (c, _, q) in self.steps(&start) {
+                        steps.push((c, shift(&start, c), p * q));
+                    }
+                },
+            }
+        }
+        steps
+    }
+}
+
+/// `context` with `c` appended and its first letter dropped.
+fn shift(context: &[char], c: char) -> Vec<char> {
+    let mut next = context[1..].to_vec();
+    next.push(c);
+    next
+}
+
+/// Generates pronounceable passwords of letters sampled from a
+/// `MarkovModel`, e.g. trained on a novel or a dictionary. Words of the
+/// corpus end where the model would end them and a new one begins.
+///
+/// Letters are far from uniformly distributed, so the entropy is derived
+/// from the model's probabilities, not from the number of letters.
+///
+/// # Example
+/// ```
+/// let corpus = "the quick brown fox jumps over the lazy dog";
+/// let model = yapg_core::MarkovModel::train(corpus, 2).unwrap();
+/// let mut mg = yapg_core::MarkovGenerator::new(model, 12);
+/// let pw = mg.generate();
+/// assert_eq!(pw.chars().count(), 12);
+/// assert!(mg.information(&pw).unwrap() > 0.0);
+/// assert!(mg.entropy() < 12.0 * 26f64.log2());
+/// ```
+#[derive(Debug, Clone)]
+pub struct MarkovGenerator {
+    model: MarkovModel,
+    length: usize,
+    rng: GeneratorRng,
+}
+
+impl MarkovGenerator {
+    /// Creates a generator of passwords of `length` letters.
+    pub fn new(model: MarkovModel, length: usize) -> Self {
+        Self { model, length, rng: GeneratorRng::Thread(rand::thread_rng()) }
+    }
+
+    /// Changes the length of the generated passwords, consumes and returns
+    /// itself.
+    #[inline]
+    pub fn length(mut self, length: usize) -> Self {
+        self.length = length;
+        self
+    }
+
+    /// Makes the generator draw from `source` instead of the thread-local
+    /// RNG, like `PasswordGenerator::random_source`. Consumes and returns
+    /// itself.
+    pub fn random_source(mut self, source: RandomSource) -> io::Result<Self> {
+        self.rng = GeneratorRng::open(source)?;
+        Ok(self)
+    }
+
+    /// Generates one password.
+    pub fn generate(&mut self) -> String {
+        let mut password = String::with_capacity(self.length);
+        let mut context = self.model.start();
+        for _ in 0..self.length {
+            let steps = self.model.steps(&context);
+            let mut x: f64 = self.rng.gen();
+            let (c, next, _) = steps
+                .iter()
+                .find(|(_, _, p)| {
+                    x -= p;
+                    x < 0.0
+                })
+                // rounding may leave a tiny remainder
+                .unwrap_or_else(|| steps.last().unwrap());
+            password.push(*c);
+            context = next.clone();
+        }
+        password
+    }
+
+    /// Generates a vector of `n` passwords.
+    #[inline]
+    pub fn generate_n(&mut self, n: usize) -> Vec<String> {
+        (0..n).map(|_| self.generate()).collect()
+    }
+
+    /// Entropy of the generated passwords in bits, i.e. the average
+    /// information of a password. Individual passwords can be weaker, see
+    /// `MarkovGenerator::information`.
+    ///
+    /// Computed from the model's probabilities, conservatively: where ending
+    /// a word and continuing a longer one yield the same letter, only the
+    /// letter counts.
+    pub fn entropy(&self) -> f64 {
+        self.entropy_until(self.length, |_| false).1
+    }
+
+    /// Entropy of passwords of growing length, up to `max_length` or until
+    /// `done` with it. Returns the last length and its entropy.
+    fn entropy_until<F>(&self, max_length: usize, done: F) -> (usize, f64)
+    where
+        F: Fn(f64) -> bool,
+    {
+        let mut contexts = BTreeMap::new();
+        contexts.insert(self.model.start(), 1.0);
+        let mut bits = 0.0;
+        for length in 0..max_length {
+            if done(bits) {
+                return (length, bits);
+            }
+            let mut next: BTreeMap<Vec<char>, f64> = BTreeMap::new();
+            for (context, p) in contexts {
+                let mut letters: BTreeMap<char, f64> = BTreeMap::new();
+                for (c, to, q) in self.model.steps(&context) {
+                    *letters.entry(c).or_default() += q;
+                    *next.entry(to).or_default() += p * q;
+                }
+                let h: f64 = letters.values().map(|q| -q * q.log2()).sum();
+                bits += p * h;
+            }
+            contexts = next;
+        }
+        (max_length, bits)
+    }
+
+    /// Information of `password` in bits, i.e. how unlikely the model is to
+    /// generate it, or `None` if it cannot generate it.
+    pub fn information(&self, password: &str) -> Option<f64> {
+        let mut contexts = BTreeMap::new();
+        contexts.insert(self.model.start(), 1.0);
+        for c in password.chars() {
+            let mut next: BTreeMap<Vec<char>, f64> = BTreeMap::new();
+            for (context, p) in contexts {
+                for (letter, to, q) in self.model.steps(&context) {
+                    if letter == c {
+                        *next.entry(to).or_default() += p * q;
+                    }
+                }
+            }
+            contexts = next;
+        }
+        let p: f64 = contexts.values().sum();
+        if p > 0.0 {
+            Some(-p.log2())
+        } else {
+            None
+        }
+    }
+
+    /// Shortest length reaching `target_bits` of entropy, or `None` if it
+    /// would exceed `max_length`, e.g. for a corpus of too few words.
+    pub fn length_for_entropy(
+        &self,
+        target_bits: f64,
+        max_length: usize,
+    ) -> Option<usize> {
+        let (length, bits) =
+            self.entropy_until(max_length, |bits| bits >= target_bits);
+        if bits >= target_bits {
+            Some(length)
+        } else {
+            None
+        }
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::{MarkovGenerator, MarkovModel};
+
+    #[test]
+    fn training_on_words() {
+        let model = MarkovModel::train("Ab, ab! AC", 1).unwrap();
+        let start = model.steps(&['\0']);
+        assert_eq!(start, vec![('a', vec!['a'], 1.0)]);
+        let after_a = model.steps(&['a']);
+        assert_eq!(after_a.len(), 2);
+        assert!((after_a[0].2 - 2.0 / 3.0).abs() < 1e-9);
+        // words end after b and c, a new one starts with a
+        assert_eq!(model.steps(&['b']), vec![('a', vec!['a'], 1.0)]);
+        assert!(MarkovModel::train("abc", 0).is_err());
+        assert!(MarkovModel::train("abc", 5).is_err());
+    }
+
+    #[test]
+    fn computing_entropy_and_information() {
+        // "ab" and "ac" equally likely, words always end after two letters
+        let model = MarkovModel::train("ab ac", 2).unwrap();
+        let mut mg = MarkovGenerator::new(model, 6);
+        assert!((mg.entropy() - 3.0).abs() < 1e-9);
+        for pw in mg.generate_n(20) {
+            assert!((mg.information(&pw).unwrap() - 3.0).abs() < 1e-9);
+        }
+        assert_eq!(mg.information("abb"), None);
+        assert_eq!(mg.length_for_entropy(2.0, 10), Some(4));
+        assert_eq!(mg.length_for_entropy(100.0, 10), None);
+    }
+
+    #[test]
+    fn generating_from_deterministic_models() {
+        let model = MarkovModel::train("abc", 3).unwrap();
+        let mut mg = MarkovGenerator::new(model, 7)
+            .random_source(crate::RandomSource::ChaCha)
+            .unwrap();
+        assert_eq!(
+            mg.rng.name(),
+            "rand::ChaChaRng (reseeding from rand::OsRng)"
+        );
+        assert_eq!(mg.generate(), "abcabca");
+        assert_eq!(mg.entropy(), 0.0);
+    }
+}
@@ -0,0 +1,190 @@
+//! Random identifiers for lab and test networks: hex colors, locally
+//! administered MAC addresses and IPv6 interface identifiers.
+
+use std::fmt;
+use std::io;
+use std::net::Ipv6Addr;
+
+use rand::RngCore;
+
+use crate::hex_encode;
+
+/// Kinds of identifiers generated by `NetIdGenerator`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NetIdKind {
+    /// A CSS hex color, e.g. `#3fa2c9`, 24 bits.
+    HexColor,
+    /// A locally administered unicast MAC address, e.g.
+    /// `06:1b:9e:c2:7d:40`, 46 bits as two bits of the first octet are
+    /// fixed.
+    Mac,
+    /// A 64-bit IPv6 interface identifier, e.g. `8c1e:04d2:9f7a:31b5`, or a
+    /// full address if a prefix is given.
+    Ipv6Suffix,
+}
+
+impl NetIdKind {
+    /// Entropy in bits of each identifier.
+    pub fn entropy(&self) -> f64 {
+        match self {
+            Self::HexColor => 24.0,
+            Self::Mac => 46.0,
+            Self::Ipv6Suffix => 64.0,
+        }
+    }
+}
+
+impl std::str::FromStr for NetIdKind {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        match s {
+            "color" => Ok(Self::HexColor),
+            "mac" => Ok(Self::Mac),
+            "ipv6" => Ok(Self::Ipv6Suffix),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Invalid identifier kind {:?} (expected color, mac or \
+                     ipv6)",
+                    s
+                ),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for NetIdKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::HexColor => write!(f, "color"),
+            Self::Mac => write!(f, "mac"),
+            Self::Ipv6Suffix => write!(f, "ipv6"),
+        }
+    }
+}
+
+/// Generates random network identifiers of one `NetIdKind`.
+///
+/// MAC addresses have the locally administered bit set and the multicast
+/// bit cleared, so they never collide with vendor-assigned addresses.
+/// IPv6 interface identifiers are fully random like temporary addresses
+/// (RFC 8981), as the universal/local bit carries no meaning (RFC 7136).
+///
+/// # Example
+/// ```
+/// use yapg_core::{NetIdGenerator, NetIdKind};
+///
+/// let color = NetIdGenerator::new(NetIdKind::HexColor).generate();
+/// assert!(color.starts_with('#') && color.len() == 7);
+///
+/// let mac = NetIdGenerator::new(NetIdKind::Mac).generate();
+/// let first = u8::from_str_radix(&mac[..2], 16).unwrap();
+/// assert_eq!(first & 0b11, 0b10);
+///
+/// let mut nig = NetIdGenerator::new(NetIdKind::Ipv6Suffix)
+///     .prefix("fd00:1::".parse().unwrap());
+/// let addr: std::net::Ipv6Addr = nig.generate().parse().unwrap();
+/// assert_eq!(addr.segments()[..4], [0xfd00, 1, 0, 0]);
+/// assert_eq!(nig.entropy(), 64.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct NetIdGenerator {
+    kind: NetIdKind,
+    prefix: Option<Ipv6Addr>,
+    rng: rand::ThreadRng,
+}
+
+impl NetIdGenerator {
+    /// Creates a generator of identifiers of `kind`.
+    pub fn new(kind: NetIdKind) -> Self {
+        Self { kind, prefix: None, rng: rand::thread_rng() }
+    }
+
+    /// Makes IPv6 interface identifiers full addresses in the /64 network
+    /// of `prefix`, whose lower 64 bits are ignored. Consumes and returns
+    /// itself.
+    #[inline]
+    pub fn prefix(mut self, prefix: Ipv6Addr) -> Self {
+        self.prefix = Some(prefix);
+        self
+    }
+
+    /// Generates one identifier.
+    pub fn generate(&mut self) -> String {
+        match self.kind {
+            NetIdKind::HexColor => {
+                let mut rgb = [0; 3];
+                self.rng.fill_bytes(&mut rgb);
+                format!("#{}", hex_encode(&rgb))
+            },
+            NetIdKind::Mac => {
+                let mut octets = [0; 6];
+                self.rng.fill_bytes(&mut octets);
+                // locally administered, unicast
+                octets[0] = (octets[0] | 0b10) & !0b01;
+                let octets: Vec<String> =
+                    octets.iter().map(|o| hex_encode(&[*o])).collect();
+                octets.join(":")
+            },
+            NetIdKind::Ipv6Suffix => {
+                let mut suffix = [0; 8];
+                self.rng.fill_bytes(&mut suffix);
+                match self.prefix {
+                    Some(prefix) => {
+                        let mut octets = prefix.octets();
+                        octets[8..].copy_from_slice(&suffix);
+                        Ipv6Addr::from(octets).to_string()
+                    },
+                    None => {
+                        let groups: Vec<String> =
+                            suffix.chunks(2).map(hex_encode).collect();
+                        groups.join(":")
+                    },
+                }
+            },
+        }
+    }
+
+    /// Generates a vector of `n` identifiers.
+    #[inline]
+    pub fn generate_n(&mut self, n: usize) -> Vec<String> {
+        (0..n).map(|_| self.generate()).collect()
+    }
+
+    /// Entropy in bits of each identifier, see `NetIdKind::entropy`.
+    #[inline]
+    pub fn entropy(&self) -> f64 { self.kind.entropy() }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::{NetIdGenerator, NetIdKind};
+
+    #[test]
+    fn generating_identifiers() {
+        let mut macs = NetIdGenerator::new(NetIdKind::Mac);
+        for mac in macs.generate_n(100) {
+            let octets: Vec<u8> = mac
+                .split(':')
+                .map(|o| u8::from_str_radix(o, 16).unwrap())
+                .collect();
+            assert_eq!(octets.len(), 6);
+            assert_eq!(octets[0] & 0b11, 0b10, "{}", mac);
+        }
+        let suffix = NetIdGenerator::new(NetIdKind::Ipv6Suffix).generate();
+        assert_eq!(suffix.len(), 19);
+        let addr: std::net::Ipv6Addr =
+            format!("fe80::{}", suffix).parse().unwrap();
+        assert_eq!(addr.segments()[..4], [0xfe80, 0, 0, 0]);
+        let mut colors = NetIdGenerator::new(NetIdKind::HexColor);
+        let distinct: std::collections::HashSet<_> =
+            colors.generate_n(100).into_iter().collect();
+        assert!(distinct.len() > 90);
+        for kind in ["color", "mac", "ipv6"].iter() {
+            assert_eq!(kind.parse::<NetIdKind>().unwrap().to_string(), *kind);
+        }
+        assert!("ipv4".parse::<NetIdKind>().is_err());
+    }
+}
@@ -0,0 +1,265 @@
+use std::fmt::Write;
+use std::io;
+
+use crate::{Provenance, QrCode};
+
+/// Output formats for generated passwords.
+///
+/// `Json` and `Yaml` render a document with a `passwords` list and, if
+/// given, `provenance` metadata. YAML strings are double-quoted using the
+/// JSON escaping rules, which YAML accepts as well. `BackupSheet` renders a
+/// printable HTML page per password, for storage in a safe.
+///
+/// # Example
+/// ```
+/// use yapg_core::OutputFormat;
+///
+/// let pws = vec!["a\"b".to_string()];
+/// assert_eq!(OutputFormat::Plain.render(&pws, None), "a\"b\n");
+/// assert_eq!(
+///     OutputFormat::Json.render(&pws, None),
+///     "{\"passwords\":[\"a\\\"b\"]}\n"
+/// );
+/// assert_eq!(
+///     OutputFormat::Yaml.render(&pws, None),
+///     "passwords:\n  - \"a\\\"b\"\n"
+/// );
+/// let sheet = OutputFormat::BackupSheet.render(&pws, None);
+/// assert!(sheet.contains("<p class=\"secret\">a&quot;b</p>"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OutputFormat {
+    /// One password per line, no metadata.
+    Plain,
+    /// A single-line JSON object.
+    Json,
+    /// A YAML mapping.
+    Yaml,
+    /// An HTML document with one printable sheet per password, showing it
+    /// along with a QR code, the metadata and blank fields for notes.
+    BackupSheet,
+}
+
+impl OutputFormat {
+    /// Renders `passwords` (and optional `provenance`) in this format,
+    /// including a trailing newline.
+    pub fn render(
+        &self,
+        passwords: &[String],
+        provenance: Option<&Provenance>,
+    ) -> String {
+        let mut s = String::new();
+        match self {
+            Self::Plain => {
+                for pw in passwords {
+                    s.push_str(pw);
+                    s.push('\n');
+                }
+            },
+            Self::Json => {
+                let pws: Vec<String> =
+                    passwords.iter().map(|pw| json_string(pw)).collect();
+                write!(s, "{{\"passwords\":[{}]", pws.join(",")).unwrap();
+                if let Some(p) = provenance {
+                    let fields: Vec<String> = p
+                        .fields()
+                        .iter()
+                        .map(|(k, v)| format!("\"{}\":{}", k, v))
+                        .collect();
+                    write!(s, ",\"provenance\":{{{}}}", fields.join(","))
+                        .unwrap();
+                }
+                s.push_str("}\n");
+            },
+            Self::Yaml => {
+                s.push_str("passwords:\n");
+                for pw in passwords {
+                    writeln!(s, "  - {}", json_string(pw)).unwrap();
+                }
+                if let Some(p) = provenance {
+                    s.push_str("provenance:\n");
+                    for (k, v) in p.fields() {
+                        writeln!(s, "  {}: {}", k, v).unwrap();
+                    }
+                }
+            },
+            Self::BackupSheet => {
+                render_backup_sheets(&mut s, passwords, provenance)
+            },
+        }
+        s
+    }
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        match s {
+            "plain" => Ok(Self::Plain),
+            "json" => Ok(Self::Json),
+            "yaml" => Ok(Self::Yaml),
+            "backup-sheet" => Ok(Self::BackupSheet),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid output format: {}", s),
+            )),
+        }
+    }
+}
+
+/// Styles of the backup sheets, one per printed page.
+const BACKUP_SHEET_STYLE: &str =
+    "body { font-family: sans-serif; } .sheet { page-break-after: always; \
+     max-width: 40em; margin: 2em auto; } .secret { font: 1.6em monospace; \
+     word-break: break-all; padding: 0.5em; border: 2px solid #000; } .qr svg \
+     { width: 12em; height: 12em; } dt { font-weight: bold; } .note { \
+     border-bottom: 1px solid #000; height: 2.5em; }";
+
+/// Appends an HTML document with one backup sheet per password to `s`.
+fn render_backup_sheets(
+    s: &mut String,
+    passwords: &[String],
+    provenance: Option<&Provenance>,
+) {
+    s.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    s.push_str("<title>yapg backup sheet</title>\n");
+    writeln!(s, "<style>{}</style>\n</head>\n<body>", BACKUP_SHEET_STYLE)
+        .unwrap();
+    for (i, pw) in passwords.iter().enumerate() {
+        s.push_str("<section class=\"sheet\">\n");
+        writeln!(s, "<h1>Backup sheet {} of {}</h1>", i + 1, passwords.len())
+            .unwrap();
+        writeln!(s, "<p class=\"secret\">{}</p>", html_escape(pw)).unwrap();
+        match QrCode::encode(pw.as_bytes()) {
+            Ok(qr) => writeln!(s, "<div class=\"qr\">{}</div>", qr.to_svg()),
+            Err(_) => writeln!(s, "<p>Too long for a QR code.</p>"),
+        }
+        .unwrap();
+        if let Some(p) = provenance {
+            s.push_str("<dl>\n");
+            let mut metadata = vec![
+                ("Created", p.timestamp.clone()),
+                ("Generator", p.generator.clone()),
+                ("Configuration fingerprint", p.config_hash.clone()),
+                ("Charset", p.charset.clone()),
+                ("Length", p.length.to_string()),
+                ("Random number generator", p.rng.clone()),
+            ];
+            if let Some(constraints) = &p.constraints {
+                metadata.push(("Constraints", constraints.clone()));
+            }
+            if let Some(pattern) = &p.pattern {
+                metadata.push(("Pattern", pattern.clone()));
+            }
+            for (term, value) in metadata.iter() {
+                writeln!(s, "<dt>{}</dt><dd>{}</dd>", term, html_escape(value))
+                    .unwrap();
+            }
+            s.push_str("</dl>\n");
+        }
+        for field in
+            ["Recipient", "Account / purpose", "Stored at", "Notes"].iter()
+        {
+            writeln!(s, "<p class=\"note\">{}:</p>", field).unwrap();
+        }
+        s.push_str("</section>\n");
+    }
+    s.push_str("</body>\n</html>\n");
+}
+
+/// Escapes the characters with special meaning in HTML text and attributes.
+fn html_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Renders `s` as double-quoted JSON string.
+pub(crate) fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                write!(out, "\\u{:04x}", c as u32).unwrap();
+            },
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Renders `export VAR='secret'` for POSIX shells (and fish), quoting the
+/// secret so that pasting the line into a shell sets the variable to
+/// exactly the secret: single quotes keep everything literal, and each
+/// single quote in the secret becomes `'\''`.
+///
+/// Fails if `var` is not a valid variable name, or if the secret contains
+/// a NUL char, which environment variables cannot hold.
+///
+/// # Example
+/// ```
+/// assert_eq!(
+///     yapg_core::shell_export("DB_PASS", "it's $x\\").unwrap(),
+///     "export DB_PASS='it'\\''s $x\\'"
+/// );
+/// assert!(yapg_core::shell_export("DB-PASS", "x").is_err());
+/// assert!(yapg_core::shell_export("1PASS", "x").is_err());
+/// ```
+pub fn shell_export(var: &str, secret: &str) -> io::Result<String> {
+    let mut chars = var.chars();
+    let valid_var =
+        chars.next().is_some_and(|c| c == '_' || c.is_ascii_alphabetic())
+            && chars.all(|c| c == '_' || c.is_ascii_alphanumeric());
+    if !valid_var {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid shell variable name {:?}", var),
+        ));
+    }
+    if secret.contains('\0') {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Environment variables cannot hold NUL chars",
+        ));
+    }
+    Ok(format!("export {}='{}'", var, secret.replace('\'', "'\\''")))
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::shell_export;
+
+    #[test]
+    #[cfg(unix)]
+    fn exporting_to_the_shell() {
+        let secrets = ["plain", "'", "''\\'", "$HOME `id` \"$(id)\"", "a\nb"];
+        for secret in secrets.iter() {
+            let export = shell_export("YAPG_SECRET", secret).unwrap();
+            let script = format!("{}\nprintf %s \"$YAPG_SECRET\"", export);
+            let output = std::process::Command::new("sh")
+                .arg("-c")
+                .arg(script)
+                .output()
+                .unwrap();
+            assert_eq!(String::from_utf8(output.stdout).unwrap(), *secret);
+        }
+    }
+}
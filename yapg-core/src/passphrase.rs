@@ -0,0 +1,512 @@
+use std::collections::BTreeMap;
+use std::io;
+
+use crate::constraints::{ln_sum_exp, pick};
+use crate::rng::GeneratorRng;
+use crate::{CharsetSpec, PasswordGenerator, RandomSource, Wordlist};
+
+/// Shortest abbreviation of words still considered memorable by
+/// `PassphraseGenerator::fit_to`.
+const MIN_ABBREVIATION: usize = 3;
+
+/// How `PassphraseGenerator::fit_to` made passphrases fit a length limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FitStrategy {
+    /// The passphrases already fit.
+    Unchanged,
+    /// Words are joined without separator. This loses no entropy unless
+    /// the wordlist has prefix collisions (see `WordlistReport`), which make
+    /// some concatenations ambiguous.
+    DropSeparator,
+    /// Words are cut to their first `n` characters. Words sharing a prefix
+    /// become indistinguishable, the generator's entropy accounts for that.
+    Abbreviate(usize),
+}
+
+/// Result of `PassphraseGenerator::fit_to`.
+#[derive(Debug, Clone)]
+pub enum Fitted {
+    /// Passphrases from words, fitted using the given strategy.
+    Words(PassphraseGenerator, FitStrategy),
+    /// No word-based strategy fits, so characters from
+    /// `CharsetSpec::std64` are used instead.
+    Chars(PasswordGenerator),
+}
+
+/// Estimates the number of syllables of an English word, by counting groups
+/// of vowels and discounting a silent final `e`. Parts separated by
+/// non-letters (`yo-yo`) are counted separately. Never returns 0 for words
+/// containing letters.
+///
+/// # Example
+/// ```
+/// assert_eq!(yapg_core::estimate_syllables("horse"), 1);
+/// assert_eq!(yapg_core::estimate_syllables("table"), 2);
+/// assert_eq!(yapg_core::estimate_syllables("battery"), 3);
+/// assert_eq!(yapg_core::estimate_syllables("yo-yo"), 2);
+/// ```
+pub fn estimate_syllables(word: &str) -> usize {
+    word.to_lowercase()
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let chars: Vec<char> = part.chars().collect();
+            let is_vowel = |i: usize| {
+                "aeiou".contains(chars[i]) || (chars[i] == 'y' && i > 0)
+            };
+            let mut count = (0..chars.len())
+                .filter(|i| is_vowel(*i) && (*i == 0 || !is_vowel(*i - 1)))
+                .count();
+            let n = chars.len();
+            // "make" has one syllable, but "table" has two
+            let silent_e = n > 2
+                && chars[n - 1] == 'e'
+                && !is_vowel(n - 2)
+                && (chars[n - 2] != 'l' || is_vowel(n - 3));
+            if silent_e && count > 1 {
+                count -= 1;
+            }
+            count.max(1)
+        })
+        .sum()
+}
+
+/// Encapsulates RNG and wordlist for generating passphrases from words, e.g.
+/// `correct-horse-battery-staple`.
+///
+/// # Example
+/// ```
+/// let list: yapg_core::Wordlist =
+///     "correct\nhorse\nbattery\nstaple".parse().unwrap();
+/// let mut ppg = yapg_core::PassphraseGenerator::new(list, 4);
+/// assert_eq!(ppg.entropy(), 8.0);
+/// let pass = ppg.generate();
+/// assert_eq!(pass.split('-').count(), 4);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PassphraseGenerator {
+    wordlist: Wordlist,
+    words: usize,
+    separator: String,
+    max_syllables: Option<usize>,
+    rng: GeneratorRng,
+}
+
+impl PassphraseGenerator {
+    /// Creates a generator of passphrases with `words` words drawn from
+    /// `wordlist`, separated by `-`.
+    pub fn new(wordlist: Wordlist, words: usize) -> Self {
+        Self {
+            wordlist,
+            words,
+            separator: "-".to_string(),
+            max_syllables: None,
+            rng: GeneratorRng::Thread(rand::thread_rng()),
+        }
+    }
+
+    /// Creates a generator of 8-word passphrases (103 bits) from the
+    /// embedded EFF long wordlist.
+    ///
+    /// # Example
+    /// ```
+    /// let mut ppg = yapg_core::PassphraseGenerator::eff_long().words(6);
+    /// assert_eq!(ppg.generate().split('-').count(), 6);
+    /// assert!((ppg.entropy() - 6.0 * 7776f64.log2()).abs() < 1e-9);
+    /// ```
+    pub fn eff_long() -> Self {
+        Self::new(Wordlist::embedded("eff-long").unwrap(), 8)
+    }
+
+    /// Creates a generator of 10-word passphrases (103 bits) from the
+    /// embedded EFF short wordlist, whose words are shorter and easier to
+    /// type.
+    ///
+    /// # Example
+    /// ```
+    /// let ppg = yapg_core::PassphraseGenerator::eff_short();
+    /// assert!((ppg.entropy() - 10.0 * 1296f64.log2()).abs() < 1e-9);
+    /// ```
+    pub fn eff_short() -> Self {
+        Self::new(Wordlist::embedded("eff-short").unwrap(), 10)
+    }
+
+    /// Makes the generator draw from ChaCha20 seeded with `seed`, so that it
+    /// yields the same passphrases on every run, like
+    /// `PasswordGenerator::seeded`. Consumes and returns itself.
+    #[inline]
+    pub fn seeded(mut self, seed: [u8; 32]) -> Self {
+        self.rng = GeneratorRng::seeded(seed);
+        self
+    }
+
+    /// Makes the generator draw from `source` instead of the thread-local
+    /// RNG, like `PasswordGenerator::random_source`. Consumes and returns
+    /// itself.
+    pub fn random_source(mut self, source: RandomSource) -> io::Result<Self> {
+        self.rng = GeneratorRng::open(source)?;
+        Ok(self)
+    }
+
+    /// Makes the generator deterministic from a `u64` seed, like
+    /// `PasswordGenerator::with_seed`. Never use it for real passphrases.
+    /// Consumes and returns itself.
+    ///
+    /// # Example
+    /// ```
+    /// use yapg_core::PassphraseGenerator;
+    ///
+    /// let mut a = PassphraseGenerator::eff_long().with_seed(7);
+    /// let mut b = PassphraseGenerator::eff_long().with_seed(7);
+    /// assert_eq!(a.generate(), b.generate());
+    /// ```
+    #[inline]
+    pub fn with_seed(self, seed: u64) -> Self {
+        self.seeded(GeneratorRng::expand_seed(seed))
+    }
+
+    /// Changes the number of words, consumes and returns itself.
+    #[inline]
+    pub fn words(mut self, words: usize) -> Self {
+        self.words = words;
+        self
+    }
+
+    /// Changes the separator between words, consumes and returns itself.
+    #[inline]
+    pub fn separator(mut self, separator: &str) -> Self {
+        self.separator = separator.to_string();
+        self
+    }
+
+    /// Caps the total (estimated) number of syllables of the passphrases,
+    /// so that they are quick to say, consumes and returns itself.
+    /// Passphrases exceeding the cap are discarded, the remaining ones are
+    /// as likely as before relative to each other. See
+    /// `estimate_syllables`.
+    ///
+    /// # Example
+    /// ```
+    /// let list: yapg_core::Wordlist = "cat\ndog\nelephant".parse().unwrap();
+    /// let mut ppg = yapg_core::PassphraseGenerator::new(list, 2).max_syllables(2);
+    /// assert!(!ppg.generate().contains("elephant"));
+    /// assert_eq!(ppg.entropy(), 2.0);
+    /// ```
+    #[inline]
+    pub fn max_syllables(mut self, max_syllables: usize) -> Self {
+        self.max_syllables = Some(max_syllables);
+        self
+    }
+
+    /// Length in characters of the longest passphrase that can be generated.
+    pub fn max_len(&self) -> usize {
+        let longest = self.wordlist.words().iter().map(|w| w.chars().count());
+        let separators = self.words.saturating_sub(1);
+        (longest.max().unwrap_or(0).saturating_mul(self.words)).saturating_add(
+            separators.saturating_mul(self.separator.chars().count()),
+        )
+    }
+
+    /// Makes sure no generated passphrase exceeds `max_len` characters, for
+    /// target systems capping the password length. Tries, in order: leaving
+    /// the passphrases as they are, dropping the separator, abbreviating the
+    /// words (to no less than three characters), and switching to
+    /// characters.
+    ///
+    /// # Example
+    /// ```
+    /// use yapg_core::{FitStrategy, Fitted, PassphraseGenerator, Wordlist};
+    ///
+    /// let list: Wordlist = "correct\nhorse\nbattery\nstaple".parse().unwrap();
+    /// let ppg = PassphraseGenerator::new(list, 4);
+    /// assert_eq!(ppg.max_len(), 31);
+    /// match ppg.clone().fit_to(28) {
+    ///     Fitted::Words(_, strategy) => {
+    ///         assert_eq!(strategy, FitStrategy::DropSeparator)
+    ///     },
+    ///     _ => unreachable!(),
+    /// }
+    /// match ppg.clone().fit_to(19) {
+    ///     Fitted::Words(mut ppg, strategy) => {
+    ///         assert_eq!(strategy, FitStrategy::Abbreviate(4));
+    ///         assert!(ppg.generate().chars().count() <= 19);
+    ///     },
+    ///     _ => unreachable!(),
+    /// }
+    /// assert!(matches!(ppg.fit_to(12), Fitted::Chars(_)));
+    /// ```
+    pub fn fit_to(self, max_len: usize) -> Fitted {
+        if self.max_len() <= max_len {
+            return Fitted::Words(self, FitStrategy::Unchanged);
+        }
+        let joined = self.clone().separator("");
+        if joined.max_len() <= max_len {
+            return Fitted::Words(joined, FitStrategy::DropSeparator);
+        }
+        if self.words > 0 {
+            let separators = self.words.saturating_sub(1);
+            let room = max_len.saturating_sub(
+                separators.saturating_mul(self.separator.chars().count()),
+            );
+            let chars = room / self.words;
+            if chars >= MIN_ABBREVIATION {
+                let mut ppg = self;
+                ppg.wordlist = ppg.wordlist.abbreviated(chars);
+                return Fitted::Words(ppg, FitStrategy::Abbreviate(chars));
+            }
+        }
+        let mut pwg =
+            PasswordGenerator::new(CharsetSpec::std64().construct(), max_len);
+        // a seeded generator stays seeded
+        pwg.rng = self.rng;
+        Fitted::Chars(pwg)
+    }
+
+    /// Whether any passphrase satisfies the configured syllable cap.
+    pub fn is_satisfiable(&self) -> bool {
+        let min = self
+            .wordlist
+            .words()
+            .iter()
+            .map(|word| estimate_syllables(word))
+            .min();
+        match (self.max_syllables, min) {
+            (Some(max), Some(min)) => min * self.words <= max,
+            (Some(_), None) => self.words == 0,
+            (None, _) => true,
+        }
+    }
+
+    /// Generates one passphrase.
+    ///
+    /// # Panics
+    /// If no passphrase satisfies the syllable cap, see
+    /// `PassphraseGenerator::is_satisfiable`.
+    pub fn generate(&mut self) -> String {
+        assert!(self.is_satisfiable(), "syllable cap cannot be satisfied");
+        let phrase: Vec<String> = match self.max_syllables {
+            None => (0..self.words)
+                .map(|_| self.wordlist.sample(&mut self.rng).to_string())
+                .collect(),
+            Some(max) => self.sample_capped(max),
+        };
+        phrase.join(&self.separator)
+    }
+
+    /// Draws the words of a passphrase with at most `max` syllables, with
+    /// the probability of drawing it without the cap, conditioned on
+    /// meeting it.
+    ///
+    /// Like `capped_entropy`, `ln_ways[k][b]` sums the weights of all
+    /// sequences of `k` words with at most `b` syllables, in the log domain
+    /// as they overflow for long passphrases. Each word's syllable count is
+    /// drawn weighted by the ways to complete the passphrase within the
+    /// remaining budget, then the word among those with that count.
+    fn sample_capped(&mut self, max: usize) -> Vec<String> {
+        let counts: Vec<usize> = self
+            .wordlist
+            .words()
+            .iter()
+            .map(|w| estimate_syllables(w))
+            .collect();
+        let mut groups: BTreeMap<usize, Wordlist> = BTreeMap::new();
+        for syllables in counts.iter().filter(|s| **s <= max) {
+            if !groups.contains_key(syllables) {
+                let mut count = counts.iter();
+                let group =
+                    self.wordlist.filtered(|_| count.next() == Some(syllables));
+                groups.insert(*syllables, group);
+            }
+        }
+        let groups: Vec<(usize, f64, Wordlist)> = groups
+            .into_iter()
+            .map(|(s, group)| (s, (group.total_weight() as f64).ln(), group))
+            .collect();
+        // more syllables than the longest words use make no difference
+        let longest = groups.last().map_or(0, |(s, ..)| *s);
+        let max = max.min(longest.saturating_mul(self.words));
+
+        let mut ln_ways = vec![vec![0.0; max + 1]];
+        for k in 1..=self.words {
+            let row = (0..=max)
+                .map(|b| {
+                    ln_sum_exp(
+                        groups
+                            .iter()
+                            .take_while(|(s, ..)| *s <= b)
+                            .map(|(s, ln_w, _)| ln_w + ln_ways[k - 1][b - s]),
+                    )
+                })
+                .collect();
+            ln_ways.push(row);
+        }
+
+        let mut budget = max;
+        let mut phrase = Vec::with_capacity(self.words);
+        for k in (1..=self.words).rev() {
+            let weights: Vec<f64> = groups
+                .iter()
+                .map(|(s, ln_w, _)| {
+                    if *s <= budget {
+                        ln_w + ln_ways[k - 1][budget - s]
+                    } else {
+                        f64::NEG_INFINITY
+                    }
+                })
+                .collect();
+            // the cap is satisfiable, so some completion remains
+            let (s, _, group) = &groups[pick(&mut self.rng, &weights).unwrap()];
+            phrase.push(group.sample(&mut self.rng).to_string());
+            budget -= s;
+        }
+        phrase
+    }
+
+    /// Generates a vector of `n` passphrases.
+    #[inline]
+    pub fn generate_n(&mut self, n: usize) -> Vec<String> {
+        (0..n).map(|_| self.generate()).collect()
+    }
+
+    /// Entropy of the generated passphrases in bits. For weighted wordlists,
+    /// this is the Shannon entropy of the weighted distribution, which is
+    /// lower than that of drawing uniformly from the same words.
+    ///
+    /// With a syllable cap, this is the entropy of the passphrases remaining
+    /// under the cap.
+    pub fn entropy(&self) -> f64 {
+        match self.max_syllables {
+            None => self.wordlist.entropy_per_word() * self.words as f64,
+            Some(max) => self.capped_entropy(max),
+        }
+    }
+
+    /// Shannon entropy of passphrases with at most `max` syllables.
+    ///
+    /// Dynamic programming over the number of syllables `t` so far: `z[t]`
+    /// sums the weights `W` of all word sequences with `t` syllables, where
+    /// `W` is the product of the word weights, and `h[t]` sums `W log2 W`.
+    /// The entropy is then `log2 Z - (sum of W log2 W) / Z`.
+    fn capped_entropy(&self, max: usize) -> f64 {
+        // total weight and sum of w log2 w per syllable count
+        let mut groups: BTreeMap<usize, (f64, f64)> = BTreeMap::new();
+        for (word, weight) in self.wordlist.distinct_weights() {
+            let syllables = estimate_syllables(word);
+            if syllables <= max {
+                let w = weight as f64;
+                let group = groups.entry(syllables).or_insert((0.0, 0.0));
+                group.0 += w;
+                group.1 += w * w.log2();
+            }
+        }
+
+        let mut z = vec![0.0; max + 1];
+        let mut h = vec![0.0; max + 1];
+        z[0] = 1.0;
+        for _ in 0..self.words {
+            let mut next_z = vec![0.0; max + 1];
+            let mut next_h = vec![0.0; max + 1];
+            for t in 0..=max {
+                if z[t] == 0.0 {
+                    continue;
+                }
+                for (s, (w, wlogw)) in groups.iter() {
+                    if t + s > max {
+                        break;
+                    }
+                    next_z[t + s] += z[t] * w;
+                    next_h[t + s] += h[t] * w + z[t] * wlogw;
+                }
+            }
+            z = next_z;
+            h = next_h;
+        }
+
+        let total: f64 = z.iter().sum();
+        if total == 0.0 {
+            return 0.0;
+        }
+        (total.log2() - h.iter().sum::<f64>() / total).max(0.0)
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::{estimate_syllables, PassphraseGenerator};
+
+    #[test]
+    fn seeding_reproduces_passphrases() {
+        let ppg = PassphraseGenerator::eff_short().with_seed(1);
+        assert_eq!(ppg.clone().generate_n(3), ppg.clone().generate_n(3));
+        let other = PassphraseGenerator::eff_short().with_seed(2);
+        assert_ne!(ppg.clone().generate(), other.clone().generate());
+        // falling back to chars keeps the seed
+        let fitted = |ppg: PassphraseGenerator| match ppg.fit_to(10) {
+            super::Fitted::Chars(mut pwg) => pwg.generate(),
+            _ => unreachable!(),
+        };
+        assert_eq!(fitted(ppg.clone()), fitted(ppg));
+    }
+
+    #[test]
+    fn estimating_syllables() {
+        let cases = [
+            ("cat", 1),
+            ("make", 1),
+            ("the", 1),
+            ("apple", 2),
+            ("unicorn", 3),
+            ("yellow", 2),
+            ("happily", 3),
+            ("x", 1),
+            ("", 0),
+            ("drop-down", 2),
+        ];
+        for (word, syllables) in cases.iter() {
+            assert_eq!(estimate_syllables(word), *syllables, "{}", word);
+        }
+    }
+
+    #[test]
+    fn capped_entropy_counts_remaining_phrases() {
+        // 2 one-syllable words and 1 two-syllable word, 3 words, at most 4
+        // syllables: 2^3 phrases with 3 syllables + 3 * 2^2 with 4
+        let list = "cat\ndog\napple".parse().unwrap();
+        let ppg = PassphraseGenerator::new(list, 3).max_syllables(4);
+        assert!((ppg.entropy() - 20f64.log2()).abs() < 1e-9);
+        assert!(ppg.is_satisfiable());
+        assert!(!ppg.clone().max_syllables(2).is_satisfiable());
+
+        // without cap (or with one that is never hit) weights are honored
+        let list: crate::Wordlist = "cat\t3\ndog\t1".parse().unwrap();
+        let capped = PassphraseGenerator::new(list.clone(), 2).max_syllables(9);
+        let uncapped = PassphraseGenerator::new(list, 2);
+        assert!((capped.entropy() - uncapped.entropy()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn sampling_capped_phrases() {
+        // each of the 20 phrases of `capped_entropy_counts_remaining_phrases`
+        // is equally likely
+        let list = "cat\ndog\napple".parse().unwrap();
+        let mut ppg =
+            PassphraseGenerator::new(list, 3).max_syllables(4).with_seed(3);
+        let mut counts = std::collections::HashMap::new();
+        for phrase in ppg.generate_n(20_000) {
+            *counts.entry(phrase).or_insert(0) += 1;
+        }
+        assert_eq!(counts.len(), 20);
+        assert!(
+            counts.values().all(|n| (800..1200).contains(n)),
+            "{:?}",
+            counts
+        );
+
+        // caps only a small fraction of passphrases meet
+        let mut ppg = PassphraseGenerator::eff_long().words(8).max_syllables(8);
+        for phrase in ppg.generate_n(10) {
+            assert!(phrase.split('-').all(|w| estimate_syllables(w) == 1));
+        }
+    }
+}
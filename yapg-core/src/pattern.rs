@@ -0,0 +1,196 @@
+use std::{fmt, io};
+
+use crate::{CharsetName, Grapheme};
+
+const VOWELS: &str = "aeiou";
+
+/// One position of a `Pattern`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PatternSlot {
+    /// A random char of the class denoted by the template letter.
+    Class(char),
+    /// A fixed char.
+    Literal(char),
+}
+
+impl PatternSlot {
+    /// The chars this slot may take, sorted.
+    pub fn chars(self) -> Vec<char> {
+        let letters = |vowel: bool, upper: bool| {
+            ('a'..='z')
+                .filter(|c| VOWELS.contains(*c) == vowel)
+                .map(|c| if upper { c.to_ascii_uppercase() } else { c })
+                .collect()
+        };
+        match self {
+            Self::Class('C') => letters(false, true),
+            Self::Class('c') => letters(false, false),
+            Self::Class('V') => letters(true, true),
+            Self::Class('v') => letters(true, false),
+            Self::Class('N') => CharsetName::Numeric.chars(),
+            Self::Class('X') => {
+                let mut chars = CharsetName::Special.chars();
+                chars.retain(|c| *c != ' ');
+                chars
+            },
+            Self::Class(c) | Self::Literal(c) => vec![c],
+        }
+    }
+}
+
+/// Template for structured passwords such as serial numbers or site-specific
+/// formats, see `PasswordGenerator::from_pattern`. Each char of the template
+/// stands for one char of the password:
+///
+/// | Template | Password char                                         |
+/// | -------- | ----------------------------------------------------- |
+/// | `C`, `c` | upper- or lowercase consonant (including `y`)         |
+/// | `V`, `v` | upper- or lowercase vowel                             |
+/// | `N`      | digit                                                 |
+/// | `X`      | special character (`CharsetName::Special` but space)  |
+/// | `\` + c  | c itself, e.g. `\N` for a literal `N`                 |
+/// | other    | itself                                                |
+///
+/// # Example
+/// ```
+/// use yapg_core::Pattern;
+///
+/// let pattern: Pattern = "Cvcvc-NNNN-XX".parse().unwrap();
+/// assert_eq!(pattern.len(), 13);
+/// let letters = 21f64.powi(3) * 5f64.powi(2);
+/// let specials = 32f64.powi(2);
+/// assert_eq!(pattern.combinations(), letters * 1e4 * specials);
+/// let pattern: Pattern = r"\N-N".parse().unwrap();
+/// assert_eq!(pattern.combinations(), 10.0);
+/// assert_eq!(pattern.to_string(), r"\N-N");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Pattern {
+    slots: Vec<PatternSlot>,
+}
+
+impl Pattern {
+    /// The positions of the pattern.
+    #[inline]
+    pub fn slots(&self) -> &[PatternSlot] { &self.slots }
+
+    /// Number of chars of the passwords.
+    #[inline]
+    pub fn len(&self) -> usize { self.slots.len() }
+
+    /// Whether the pattern is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.slots.is_empty() }
+
+    /// Number of passwords matching the pattern.
+    pub fn combinations(&self) -> f64 {
+        self.slots.iter().map(|slot| slot.chars().len() as f64).product()
+    }
+
+    /// All chars the passwords may contain, sorted.
+    pub fn chars(&self) -> Vec<char> {
+        let mut chars: Vec<char> =
+            self.slots.iter().flat_map(|slot| slot.chars()).collect();
+        chars.sort_unstable();
+        chars.dedup();
+        chars
+    }
+
+    /// The union of all slots' chars as charset, and for each slot the
+    /// indices of its chars within that charset.
+    pub(crate) fn indexed(&self) -> (Vec<Grapheme>, Vec<Vec<usize>>) {
+        let charset = self.chars();
+        let slots = self
+            .slots
+            .iter()
+            .map(|slot| {
+                slot.chars()
+                    .iter()
+                    .map(|c| charset.binary_search(c).unwrap())
+                    .collect()
+            })
+            .collect();
+        (charset.into_iter().map(Grapheme::from).collect(), slots)
+    }
+}
+
+impl std::str::FromStr for Pattern {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        let mut slots = vec![];
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            slots.push(match c {
+                '\\' => {
+                    PatternSlot::Literal(chars.next().ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            "Pattern ends with an unescaped backslash",
+                        )
+                    })?)
+                },
+                'C' | 'c' | 'V' | 'v' | 'N' | 'X' => PatternSlot::Class(c),
+                c => PatternSlot::Literal(c),
+            });
+        }
+        Ok(Self { slots })
+    }
+}
+
+impl fmt::Display for Pattern {
+    /// Renders the template, escaping literals where needed.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for slot in &self.slots {
+            match slot {
+                PatternSlot::Class(c) => write!(f, "{}", c)?,
+                PatternSlot::Literal(c) if "CcVvNX\\".contains(*c) => {
+                    write!(f, "\\{}", c)?
+                },
+                PatternSlot::Literal(c) => write!(f, "{}", c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::{Pattern, PatternSlot};
+
+    #[test]
+    fn parsing_patterns() {
+        let pattern: Pattern = r"Nv-\X\\".parse().unwrap();
+        assert_eq!(pattern.slots(), [
+            PatternSlot::Class('N'),
+            PatternSlot::Class('v'),
+            PatternSlot::Literal('-'),
+            PatternSlot::Literal('X'),
+            PatternSlot::Literal('\\'),
+        ]);
+        assert_eq!(pattern.to_string(), r"Nv-\X\\");
+        assert_eq!(pattern.to_string().parse::<Pattern>().unwrap(), pattern);
+        assert!(r"NN\".parse::<Pattern>().is_err());
+        assert!("".parse::<Pattern>().unwrap().is_empty());
+    }
+
+    #[test]
+    fn indexing_slots() {
+        let pattern: Pattern = "Vv-X".parse().unwrap();
+        let (charset, slots) = pattern.indexed();
+        let chars: Vec<char> =
+            charset.iter().map(|item| item.as_char().unwrap()).collect();
+        assert!(chars.windows(2).all(|w| w[0] < w[1]));
+        for (slot, indices) in pattern.slots().iter().zip(&slots) {
+            let chars: Vec<char> = indices
+                .iter()
+                .map(|i| charset[*i].as_char().unwrap())
+                .collect();
+            assert_eq!(chars, slot.chars());
+        }
+        assert_eq!(slots[0].len(), 5);
+        assert_eq!(slots[2].len(), 1);
+        assert!(!pattern.slots()[3].chars().contains(&' '));
+    }
+}
@@ -0,0 +1,154 @@
+use std::io;
+
+/// Birthday-problem statistics for a planned batch of random ids or codes:
+/// how likely any two of `count` passwords drawn from `charset_len` chars at
+/// `length` collide, and how long they need to be to keep that unlikely.
+///
+/// The numbers are the usual approximations, accurate as long as the batch
+/// is much smaller than the number of possible passwords.
+///
+/// # Example
+/// ```
+/// // a billion ids of 10 alphanumerics
+/// let plan = yapg_core::UniquenessPlan::new(1e9, 62, 10).unwrap();
+/// assert!((plan.expected_collisions() - 0.596).abs() < 0.001);
+/// assert!((plan.collision_probability() - 0.449).abs() < 0.001);
+/// assert_eq!(plan.recommended_length(1e-6), Some(14));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UniquenessPlan {
+    /// Number of passwords in the batch.
+    pub count: f64,
+    /// Number of chars passwords are drawn from.
+    pub charset_len: usize,
+    /// Length of each password.
+    pub length: usize,
+}
+
+impl UniquenessPlan {
+    /// Plans a batch of `count` passwords. Fails if `count` is negative or
+    /// not finite.
+    pub fn new(
+        count: f64,
+        charset_len: usize,
+        length: usize,
+    ) -> io::Result<Self> {
+        if !count.is_finite() || count < 0.0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid number of passwords: {}", count),
+            ));
+        }
+        Ok(Self { count, charset_len, length })
+    }
+
+    /// Bits of the number of possible passwords, i.e. their entropy.
+    pub fn bits(&self) -> f64 {
+        match self.charset_len {
+            0 | 1 => 0.0,
+            n => (n as f64).log2() * self.length as f64,
+        }
+    }
+
+    /// Bits of the number of pairs in the batch.
+    fn pair_bits(&self) -> f64 {
+        if self.count < 2.0 {
+            return f64::NEG_INFINITY;
+        }
+        (self.count * (self.count - 1.0) / 2.0).log2()
+    }
+
+    /// Expected number of colliding pairs in the batch.
+    pub fn expected_collisions(&self) -> f64 {
+        (self.pair_bits() - self.bits()).exp2()
+    }
+
+    /// Probability that any two passwords of the batch are equal.
+    pub fn collision_probability(&self) -> f64 {
+        -(-self.expected_collisions()).exp_m1()
+    }
+
+    /// Expected number of passwords drawn until the first collision,
+    /// regardless of the planned count: about `sqrt(pi/2 * combinations) +
+    /// 2/3`.
+    ///
+    /// # Example
+    /// ```
+    /// // 23 people for an even chance of a shared birthday, 24.6 on average
+    /// let plan = yapg_core::UniquenessPlan::new(0.0, 365, 1).unwrap();
+    /// assert!((plan.expected_draws_until_collision() - 24.6).abs() < 0.1);
+    /// ```
+    pub fn expected_draws_until_collision(&self) -> f64 {
+        (std::f64::consts::PI / 2.0).sqrt() * (self.bits() / 2.0).exp2()
+            + 2.0 / 3.0
+    }
+
+    /// Shortest length keeping the probability of any collision in the
+    /// batch at most `max_probability`, or `None` if no length does (i.e.
+    /// for charsets of fewer than two chars).
+    pub fn recommended_length(&self, max_probability: f64) -> Option<usize> {
+        if self.collision_probability_at(0) <= max_probability {
+            return Some(0);
+        }
+        if self.charset_len < 2 || max_probability <= 0.0 {
+            return None;
+        }
+        // at most this many colliding pairs may be expected
+        let max_expected = -(-max_probability).ln_1p();
+        let bits = self.pair_bits() - max_expected.log2();
+        let length = (bits / (self.charset_len as f64).log2()).ceil() as usize;
+        // rounding might leave the estimate a length short
+        (length..=length + 1)
+            .find(|l| self.collision_probability_at(*l) <= max_probability)
+    }
+
+    /// `collision_probability` at another length.
+    fn collision_probability_at(&self, length: usize) -> f64 {
+        Self { length, ..*self }.collision_probability()
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::UniquenessPlan;
+
+    #[test]
+    fn matching_the_birthday_problem() {
+        let plan = UniquenessPlan::new(23.0, 365, 1).unwrap();
+        // the exact value is 0.507, the approximation slightly lower
+        assert!((plan.collision_probability() - 0.5).abs() < 0.01);
+        assert_eq!(
+            UniquenessPlan::new(1.0, 2, 0).unwrap().expected_collisions(),
+            0.0
+        );
+        let pair = UniquenessPlan::new(2.0, 2, 0).unwrap();
+        assert!(
+            (pair.collision_probability() - (1.0 - (-1f64).exp())).abs()
+                < 1e-12
+        );
+    }
+
+    #[test]
+    fn recommending_lengths() {
+        // ids for 1e10 people with 1e5 items each, collisions 1e-21 likely
+        let plan = UniquenessPlan::new(1e15, 64, 0).unwrap();
+        let length = plan.recommended_length(1e-21).unwrap();
+        assert_eq!(length, 29);
+        let planned = UniquenessPlan { length, ..plan };
+        assert!(planned.collision_probability() <= 1e-21);
+        let shorter = UniquenessPlan { length: length - 1, ..plan };
+        assert!(shorter.collision_probability() > 1e-21);
+
+        assert_eq!(
+            UniquenessPlan::new(5.0, 1, 0).unwrap().recommended_length(0.1),
+            None
+        );
+        assert_eq!(
+            UniquenessPlan::new(1.0, 1, 0).unwrap().recommended_length(0.1),
+            Some(0)
+        );
+        assert!(UniquenessPlan::new(-1.0, 10, 1).is_err());
+        assert!(UniquenessPlan::new(f64::NAN, 10, 1).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+use std::{fmt, io};
+
+use crate::CharsetName;
+
+/// Ways in which a policy can prevent a requested configuration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PolicyError {
+    /// The maximum length allowed by the policy caps the achievable entropy
+    /// below the requested one. `max_bits` is the best that can be done.
+    EntropyCappedBy { max_bits: f64 },
+}
+
+impl fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::EntropyCappedBy { max_bits } => write!(
+                f,
+                "Policy caps password entropy at {:.1} bits",
+                max_bits
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PolicyError {}
+
+impl From<PolicyError> for io::Error {
+    fn from(e: PolicyError) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidInput, e)
+    }
+}
+
+/// Password policy of a target system: acceptable lengths and the charsets of
+/// which each password must contain at least one character.
+///
+/// # Example
+/// ```
+/// use yapg_core::{CharsetName, Policy};
+///
+/// let policy = Policy::new(8, Some(12), vec![CharsetName::Numeric]);
+/// assert!(policy.allows("password1"));
+/// assert!(!policy.allows("password"));
+/// assert!(!policy.allows("passw0rd-much-too-long"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Policy {
+    /// Minimum number of characters.
+    pub min_length: usize,
+    /// Maximum number of characters, if limited.
+    pub max_length: Option<usize>,
+    /// Charsets that must each be represented in a password.
+    pub required: Vec<CharsetName>,
+}
+
+impl Policy {
+    /// Creates a policy from its parts.
+    pub fn new(
+        min_length: usize,
+        max_length: Option<usize>,
+        required: Vec<CharsetName>,
+    ) -> Self {
+        Self { min_length, max_length, required }
+    }
+
+    /// Whether `length` lies within the policy's length bounds.
+    #[inline]
+    pub fn allows_length(&self, length: usize) -> bool {
+        length >= self.min_length && self.max_length.is_none_or(|m| length <= m)
+    }
+
+    /// Checks `length` against the policy's length bounds, returning an
+    /// `io::ErrorKind::InvalidInput` error if it is out of bounds.
+    pub fn check_length(&self, length: usize) -> io::Result<()> {
+        if self.allows_length(length) {
+            return Ok(());
+        }
+        let max = self.max_length.map_or("∞".to_string(), |m| m.to_string());
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Length {} violates policy (allowed: {}..={})",
+                length, self.min_length, max
+            ),
+        ))
+    }
+
+    /// Highest entropy in bits achievable with `charset_len` characters, or
+    /// `None` if the length is not limited and the entropy thus neither.
+    ///
+    /// # Example
+    /// ```
+    /// let policy = yapg_core::Policy::new(8, Some(12), vec![]);
+    /// assert_eq!(policy.max_entropy(16), Some(48.0));
+    /// ```
+    pub fn max_entropy(&self, charset_len: usize) -> Option<f64> {
+        match self.max_length {
+            Some(m) if charset_len > 1 => {
+                Some((charset_len as f64).log2() * m as f64)
+            },
+            Some(_) => Some(0.0),
+            None if charset_len > 1 => None,
+            None => Some(0.0),
+        }
+    }
+
+    /// Shortest length allowed by the policy that reaches `target_bits` of
+    /// entropy with `charset_len` characters.
+    ///
+    /// # Example
+    /// ```
+    /// use yapg_core::{Policy, PolicyError};
+    ///
+    /// let policy = Policy::new(8, Some(12), vec![]);
+    /// assert_eq!(policy.length_for_entropy(16, 32.0), Ok(8));
+    /// assert_eq!(policy.length_for_entropy(16, 44.0), Ok(11));
+    /// assert_eq!(
+    ///     policy.length_for_entropy(16, 128.0),
+    ///     Err(PolicyError::EntropyCappedBy { max_bits: 48.0 })
+    /// );
+    /// ```
+    pub fn length_for_entropy(
+        &self,
+        charset_len: usize,
+        target_bits: f64,
+    ) -> Result<usize, PolicyError> {
+        if let Some(max_bits) = self.max_entropy(charset_len) {
+            if max_bits < target_bits {
+                return Err(PolicyError::EntropyCappedBy { max_bits });
+            }
+        }
+        let length = if target_bits <= 0.0 {
+            0
+        } else {
+            (target_bits / (charset_len as f64).log2()).ceil() as usize
+        };
+        Ok(length.max(self.min_length))
+    }
+
+    /// Whether `password` satisfies the policy.
+    pub fn allows(&self, password: &str) -> bool {
+        self.allows_length(password.chars().count())
+            && self.required.iter().all(|name| {
+                let charset = name.chars();
+                password.chars().any(|c| charset.contains(&c))
+            })
+    }
+}
@@ -0,0 +1,321 @@
+//! Ready-made configurations for common target systems.
+//!
+//! # Example
+//! ```
+//! use yapg_core::presets;
+//!
+//! let preset = presets::wpa2();
+//! assert!(preset.policy.allows_length(preset.length));
+//! let pw = preset.generator().generate();
+//! assert_eq!(pw.len(), preset.length);
+//! assert!(presets::by_name("wpa2").is_some());
+//! ```
+
+use crate::CharsetName::*;
+use crate::{
+    CharsetSpec, InputLimit, PasswordGenerator, Policy, BCRYPT_MAX_BYTES,
+};
+
+/// A named bundle of charset, length and policy.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Preset {
+    /// Name used to select the preset, e.g. on the command line.
+    pub name: &'static str,
+    /// Short human-readable description.
+    pub description: &'static str,
+    /// Charset passwords are drawn from.
+    pub charset: CharsetSpec,
+    /// Default length of generated passwords.
+    pub length: usize,
+    /// Policy of the target system.
+    pub policy: Policy,
+    /// Truncation and case folding the target system applies.
+    pub limit: InputLimit,
+}
+
+impl Preset {
+    /// Creates a `PasswordGenerator` for the preset's charset and length.
+    pub fn generator(&self) -> PasswordGenerator {
+        PasswordGenerator::new(self.charset.build(), self.length)
+    }
+}
+
+/// Alphanumerics, `-` and `_`, see `CharsetSpec::std64`.
+pub fn std64() -> Preset {
+    Preset {
+        name: "std64",
+        description: "Alphanumerics, '-' and '_'",
+        charset: CharsetSpec::std64(),
+        length: 24,
+        policy: Policy::default(),
+        limit: InputLimit::default(),
+    }
+}
+
+/// All printable ASCII characters, see `CharsetSpec::printable_ascii`.
+pub fn ascii() -> Preset {
+    Preset {
+        name: "ascii",
+        description: "All printable ASCII characters",
+        charset: CharsetSpec::printable_ascii(),
+        length: 20,
+        policy: Policy::default(),
+        limit: InputLimit::default(),
+    }
+}
+
+/// Alphanumerics and `-._~`, the characters RFC 3986 leaves unreserved,
+/// which need no escaping in URLs, basic-auth headers and `.pgpass` files.
+pub fn url_safe() -> Preset {
+    let mut charset: CharsetSpec = "LUN".parse().unwrap();
+    charset += "-._~";
+    Preset {
+        name: "url-safe",
+        description: "Unescaped in URLs, basic-auth headers and .pgpass",
+        charset,
+        length: 24,
+        policy: Policy::default(),
+        limit: InputLimit::default(),
+    }
+}
+
+/// Pre-boot prompts assuming a US keymap, see `CharsetSpec::console`.
+pub fn console() -> Preset {
+    Preset {
+        name: "console",
+        description: "Pre-boot prompts (BIOS, LUKS) assuming a US keymap",
+        charset: CharsetSpec::console(),
+        length: 28,
+        policy: Policy::default(),
+        limit: InputLimit::default(),
+    }
+}
+
+/// AWS management console (IAM user) passwords: alphanumerics and the
+/// special characters accepted by IAM, at most 128 characters.
+pub fn aws_console() -> Preset {
+    let mut charset: CharsetSpec = "LUN".parse().unwrap();
+    charset += "!@#$%^&*()_+-=[]{}|'";
+    Preset {
+        name: "aws-console",
+        description: "AWS management console (IAM user)",
+        charset,
+        length: 24,
+        policy: Policy::new(8, Some(128), vec![
+            AlphaLower, AlphaUpper, Numeric,
+        ]),
+        limit: InputLimit::default(),
+    }
+}
+
+/// MySQL 8 accounts with `validate_password.policy=MEDIUM`. Quotes and
+/// backslashes are left out to keep `CREATE USER` statements simple.
+pub fn mysql_8() -> Preset {
+    let mut charset: CharsetSpec = "LUNMD".parse().unwrap();
+    charset += "#@$%&~^_!?.:,;";
+    Preset {
+        name: "mysql-8",
+        description: "MySQL 8 (validate_password MEDIUM)",
+        charset,
+        length: 24,
+        policy: Policy::new(8, Some(32), vec![AlphaLower, AlphaUpper, Numeric]),
+        limit: InputLimit::default(),
+    }
+}
+
+/// WPA2-PSK passphrases: 8 to 63 printable ASCII characters. Spaces are
+/// left out, as many device setup screens handle them badly.
+pub fn wpa2() -> Preset {
+    let mut charset = CharsetSpec::printable_ascii();
+    charset -= Prose;
+    charset += ".:,;!?'\"";
+    Preset {
+        name: "wpa2",
+        description: "WPA2-PSK wifi passphrases",
+        charset,
+        length: 32,
+        policy: Policy::new(8, Some(63), vec![]),
+        limit: InputLimit::default(),
+    }
+}
+
+/// Active Directory accounts with password complexity enabled: printable
+/// ASCII without spaces, with all four classes of characters (AD demands
+/// three of them) and at most 256 characters.
+pub fn active_directory() -> Preset {
+    let mut charset = CharsetSpec::printable_ascii();
+    charset -= Prose;
+    charset += ".:,;!?'\"";
+    Preset {
+        name: "ad",
+        description: "Active Directory (complexity requirements enabled)",
+        charset,
+        length: 20,
+        policy: Policy::new(7, Some(256), vec![
+            AlphaLower, AlphaUpper, Numeric, Special,
+        ]),
+        limit: InputLimit::default(),
+    }
+}
+
+/// Six-digit numeric PINs.
+pub fn pin6() -> Preset {
+    Preset {
+        name: "pin6",
+        description: "Six-digit numeric PINs",
+        charset: "N".parse().unwrap(),
+        length: 6,
+        policy: Policy::new(6, Some(6), vec![Numeric]),
+        limit: InputLimit::default(),
+    }
+}
+
+/// Passwords hashed with bcrypt, which ignores all but the first 72 bytes:
+/// printable ASCII, capped at 72 characters.
+pub fn bcrypt() -> Preset {
+    Preset {
+        name: "bcrypt",
+        description: "Hashed with bcrypt (first 72 bytes count)",
+        charset: CharsetSpec::printable_ascii(),
+        length: 24,
+        policy: Policy::default(),
+        limit: InputLimit::bytes(BCRYPT_MAX_BYTES),
+    }
+}
+
+/// Traditional DES-based Unix `crypt(3)`, which ignores all but the first 8
+/// characters.
+pub fn crypt_des() -> Preset {
+    Preset {
+        name: "crypt-des",
+        description: "Traditional Unix crypt (first 8 characters count)",
+        charset: CharsetSpec::printable_ascii(),
+        length: 8,
+        policy: Policy::default(),
+        limit: InputLimit::chars(8),
+    }
+}
+
+/// Mainframe (RACF) passwords: at most 8 upper-case letters, digits and
+/// the national characters `@#$`, compared ignoring case.
+pub fn racf() -> Preset {
+    let mut charset: CharsetSpec = "UN".parse().unwrap();
+    charset += "@#$";
+    Preset {
+        name: "racf",
+        description: "Mainframe RACF (8 characters, case-insensitive)",
+        charset,
+        length: 8,
+        policy: Policy::new(1, Some(8), vec![]),
+        limit: InputLimit::chars(8).case_insensitive(),
+    }
+}
+
+/// One-time credentials sent by email: alphanumerics and `!#$%+=?@`.
+/// Mail clients autocorrect quotes into smart quotes, `--` into dashes,
+/// `...` into ellipses, `(c)` into `©`, `:)` into emoji and `1/2` into `½`,
+/// and turn `*_~` into formatting, so none of these characters are drawn.
+pub fn email_safe() -> Preset {
+    let mut charset: CharsetSpec = "LUN".parse().unwrap();
+    charset += "!#$%+=?@";
+    Preset {
+        name: "email-safe",
+        description: "Sent by email (nothing autocorrect rewrites)",
+        charset,
+        length: 16,
+        policy: Policy::default(),
+        limit: InputLimit::default(),
+    }
+}
+
+/// One-time credentials sent by SMS: alphanumerics and the punctuation of
+/// the GSM 03.38 basic character set. Characters of its extension table
+/// (e.g. `[]{}~|`) take two septets and are mangled by some networks, any
+/// other character switches the whole message to UCS-2 and so do quotes
+/// turned into smart quotes when the message is typed on a phone. `@` is
+/// left out as well, as it is encoded as a zero septet that some gateways
+/// truncate at.
+pub fn sms_safe() -> Preset {
+    let mut charset: CharsetSpec = "LUN".parse().unwrap();
+    charset += "!#$%&()*+,-./:;<=>?_";
+    Preset {
+        name: "sms-safe",
+        description: "Sent by SMS (GSM-7 only, no smart quotes)",
+        charset,
+        length: 16,
+        policy: Policy::default(),
+        limit: InputLimit::default(),
+    }
+}
+
+/// All presets, in the order they are listed by `yapg presets list`.
+pub fn all() -> Vec<Preset> {
+    vec![
+        std64(),
+        ascii(),
+        url_safe(),
+        console(),
+        aws_console(),
+        mysql_8(),
+        active_directory(),
+        wpa2(),
+        pin6(),
+        bcrypt(),
+        crypt_des(),
+        racf(),
+        email_safe(),
+        sms_safe(),
+    ]
+}
+
+/// Looks up a preset by its name.
+pub fn by_name(name: &str) -> Option<Preset> {
+    all().into_iter().find(|preset| preset.name == name)
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn presets_are_consistent() {
+        for preset in super::all() {
+            assert!(
+                preset.policy.allows_length(preset.length),
+                "{}",
+                preset.name
+            );
+            assert!(!preset.charset.is_empty(), "{}", preset.name);
+            assert_eq!(super::by_name(preset.name), Some(preset.clone()));
+        }
+        assert_eq!(super::wpa2().charset.len(), 94);
+        let url_safe = super::url_safe().charset.build();
+        assert_eq!(url_safe.len(), 66);
+        for ctx in ["url", "basic-auth", "pgpass"].iter() {
+            let ctx: crate::SafetyContext = ctx.parse().unwrap();
+            assert_eq!(ctx.restrict(url_safe.clone()), url_safe);
+        }
+        assert!(super::by_name("nope").is_none());
+
+        let racf = super::racf();
+        let charset = racf.charset.build();
+        assert_eq!(charset.len(), 39);
+        assert_eq!(racf.limit.effective_length(&charset, 12), 8);
+        let bits = racf.limit.effective_entropy(&charset, 8);
+        assert!((bits - 8.0 * 39f64.log2()).abs() < 1e-9);
+        let bcrypt = super::bcrypt();
+        let charset = bcrypt.charset.build();
+        assert_eq!(bcrypt.limit.effective_length(&charset, 100), 72);
+
+        // the printable ASCII chars of the GSM 03.38 basic character set
+        let gsm7 = concat!(
+            " !\"#$%&'()*+,-./0123456789:;<=>?@",
+            "ABCDEFGHIJKLMNOPQRSTUVWXYZ_abcdefghijklmnopqrstuvwxyz"
+        );
+        let sms_safe = super::sms_safe().charset.build();
+        assert!(sms_safe.iter().all(|c| gsm7.contains(*c)));
+        assert!(!sms_safe.iter().any(|c| "'\"@ ".contains(*c)));
+        let email_safe = super::email_safe().charset.build();
+        assert_eq!(email_safe.len(), 70);
+        assert!(!email_safe.iter().any(|c| "'\"`-.()*_~/: ".contains(*c)));
+    }
+}
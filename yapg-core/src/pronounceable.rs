@@ -0,0 +1,304 @@
+use std::collections::BTreeSet;
+use std::io;
+
+use rand::Rng;
+
+use crate::rng::GeneratorRng;
+use crate::RandomSource;
+
+/// Consonants of the default `SyllableModel`, leaving out letters whose
+/// pronunciation is ambiguous (`c`, `q`, `x`) or easily confused (`w`, `y`).
+const DEFAULT_CONSONANTS: &str = "bdfghjklmnprstvz";
+
+/// Vowels of the default `SyllableModel`.
+const DEFAULT_VOWELS: &str = "aeiou";
+
+/// Syllable templates of the default `SyllableModel`.
+const DEFAULT_TEMPLATES: [&str; 2] = ["CV", "CVC"];
+
+/// Letters and syllable templates (e.g. `CV` and `CVC`, of `C`onsonants and
+/// `V`owels) from which a `PronounceableGenerator` builds passwords.
+///
+/// # Example
+/// ```
+/// use yapg_core::SyllableModel;
+///
+/// let model = SyllableModel::new("bdk", "ao", &["CV", "VC"]).unwrap();
+/// assert_eq!(model.templates(), &["CV".to_string(), "VC".to_string()]);
+/// // "CV" could also be "C" followed by "V"
+/// assert!(SyllableModel::new("bdk", "ao", &["CV", "C", "V"]).is_err());
+/// assert!(SyllableModel::new("bdk", "ako", &["CV"]).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SyllableModel {
+    consonants: Vec<char>,
+    vowels: Vec<char>,
+    templates: Vec<String>,
+}
+
+impl SyllableModel {
+    /// Creates a model from its letters and templates. Fails if the letters
+    /// are empty or shared by consonants and vowels, if templates contain
+    /// anything but `C` and `V`, or if a sequence of syllables could be
+    /// read as a different one (e.g. `CV`+`C` as `C`+`VC`), as the
+    /// entropy would then be lower than computed.
+    pub fn new(
+        consonants: &str,
+        vowels: &str,
+        templates: &[&str],
+    ) -> io::Result<Self> {
+        let invalid =
+            |msg: String| io::Error::new(io::ErrorKind::InvalidInput, msg);
+        let consonants = dedup(consonants);
+        let vowels = dedup(vowels);
+        if consonants.is_empty() || vowels.is_empty() {
+            return Err(invalid(
+                "Consonants and vowels must not be empty".into(),
+            ));
+        }
+        if let Some(c) = consonants.iter().find(|c| vowels.contains(c)) {
+            return Err(invalid(format!(
+                "{:?} is both a consonant and a vowel",
+                c
+            )));
+        }
+        if templates.is_empty() {
+            return Err(invalid("No syllable templates given".into()));
+        }
+        for template in templates {
+            if template.is_empty()
+                || !template.chars().all(|c| c == 'C' || c == 'V')
+            {
+                return Err(invalid(format!(
+                    "Invalid syllable template {:?} (expected e.g. CVC)",
+                    template
+                )));
+            }
+        }
+        if let Some(shape) = ambiguous_shape(templates) {
+            return Err(invalid(format!(
+                "Syllable templates are ambiguous, e.g. {} splits into \
+                 syllables in more than one way",
+                shape
+            )));
+        }
+        Ok(Self {
+            consonants,
+            vowels,
+            templates: templates.iter().map(|t| t.to_string()).collect(),
+        })
+    }
+
+    /// The syllable templates, e.g. `CVC`.
+    #[inline]
+    pub fn templates(&self) -> &[String] { &self.templates }
+
+    /// Entropy in bits of the least random syllable: its template plus its
+    /// letters.
+    fn min_bits_per_syllable(&self) -> f64 {
+        let (c, v) = (self.consonants.len() as f64, self.vowels.len() as f64);
+        let letters = self
+            .templates
+            .iter()
+            .map(|t| {
+                t.chars()
+                    .map(|slot| if slot == 'C' { c.log2() } else { v.log2() })
+                    .sum::<f64>()
+            })
+            .fold(f64::INFINITY, f64::min);
+        (self.templates.len() as f64).log2() + letters
+    }
+}
+
+impl Default for SyllableModel {
+    /// Templates `CV` and `CVC` of 16 consonants and 5 vowels, e.g.
+    /// `bamkotipuz`.
+    fn default() -> Self {
+        Self::new(DEFAULT_CONSONANTS, DEFAULT_VOWELS, &DEFAULT_TEMPLATES)
+            .unwrap()
+    }
+}
+
+/// The chars of `s`, without duplicates, in order of first occurrence.
+fn dedup(s: &str) -> Vec<char> {
+    let mut chars = vec![];
+    for c in s.chars() {
+        if !chars.contains(&c) {
+            chars.push(c);
+        }
+    }
+    chars
+}
+
+/// Finds a shape (e.g. `CVCV`) that more than one sequence of `templates`
+/// produces, using the Sardinas-Patterson algorithm.
+fn ambiguous_shape(templates: &[&str]) -> Option<String> {
+    let code: BTreeSet<&str> = templates.iter().cloned().collect();
+    if code.len() < templates.len() {
+        let mut seen = BTreeSet::new();
+        return templates
+            .iter()
+            .find(|t| !seen.insert(*t))
+            .map(|t| t.to_string());
+    }
+    // dangling suffixes, with a shape they are left over from
+    let mut dangling: BTreeSet<(String, String)> = BTreeSet::new();
+    for u in code.iter() {
+        for v in code.iter() {
+            if u != v && v.starts_with(u) {
+                dangling.insert((v[u.len()..].to_string(), v.to_string()));
+            }
+        }
+    }
+    let mut seen: BTreeSet<String> = BTreeSet::new();
+    while !dangling.is_empty() {
+        let mut next = BTreeSet::new();
+        for (suffix, shape) in dangling {
+            if code.contains(suffix.as_str()) {
+                return Some(shape);
+            }
+            if !seen.insert(suffix.clone()) {
+                continue;
+            }
+            for word in code.iter() {
+                if let Some(rest) = suffix.strip_prefix(word) {
+                    next.insert((rest.to_string(), shape.clone()));
+                } else if let Some(rest) = word.strip_prefix(suffix.as_str()) {
+                    next.insert((
+                        rest.to_string(),
+                        format!("{}{}", shape, rest),
+                    ));
+                }
+            }
+        }
+        dangling = next.into_iter().filter(|(s, _)| !s.is_empty()).collect();
+    }
+    None
+}
+
+/// Generates pronounceable passwords from syllables, e.g. `bamkotipuz`,
+/// which are easier to memorize than random characters.
+///
+/// Syllables follow a randomly chosen template of the `SyllableModel`,
+/// which makes them far less random than their length suggests. The entropy
+/// is therefore computed from the model, for the most likely passwords
+/// (those of the shortest syllables), which an attacker would try first.
+///
+/// # Example
+/// ```
+/// let mut pg = yapg_core::PronounceableGenerator::new(
+///     yapg_core::SyllableModel::default(),
+///     4,
+/// );
+/// let pw = pg.generate();
+/// assert!(pw.len() >= 8 && pw.len() <= 12);
+/// // 4 syllables of 1 bit template, 4 bits consonant and 2.3 bits vowel
+/// assert!((pg.entropy() - 4.0 * (1.0 + 4.0 + 5f64.log2())).abs() < 1e-9);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PronounceableGenerator {
+    model: SyllableModel,
+    syllables: usize,
+    rng: GeneratorRng,
+}
+
+impl PronounceableGenerator {
+    /// Creates a generator of passwords of `syllables` syllables.
+    pub fn new(model: SyllableModel, syllables: usize) -> Self {
+        Self { model, syllables, rng: GeneratorRng::Thread(rand::thread_rng()) }
+    }
+
+    /// Changes the number of syllables, consumes and returns itself.
+    #[inline]
+    pub fn syllables(mut self, syllables: usize) -> Self {
+        self.syllables = syllables;
+        self
+    }
+
+    /// Makes the generator draw from `source` instead of the thread-local
+    /// RNG, like `PasswordGenerator::random_source`. Consumes and returns
+    /// itself.
+    pub fn random_source(mut self, source: RandomSource) -> io::Result<Self> {
+        self.rng = GeneratorRng::open(source)?;
+        Ok(self)
+    }
+
+    /// Generates one password.
+    pub fn generate(&mut self) -> String {
+        let model = &self.model;
+        let mut password = String::new();
+        for _ in 0..self.syllables {
+            let template = self.rng.choose(&model.templates).unwrap();
+            for slot in template.chars() {
+                let letters =
+                    if slot == 'C' { &model.consonants } else { &model.vowels };
+                password.push(*self.rng.choose(letters).unwrap());
+            }
+        }
+        password
+    }
+
+    /// Generates a vector of `n` passwords.
+    #[inline]
+    pub fn generate_n(&mut self, n: usize) -> Vec<String> {
+        (0..n).map(|_| self.generate()).collect()
+    }
+
+    /// Entropy in bits of the most likely passwords, a lower bound for all
+    /// of them.
+    #[inline]
+    pub fn entropy(&self) -> f64 {
+        self.model.min_bits_per_syllable() * self.syllables as f64
+    }
+
+    /// Number of syllables needed to reach `target_bits` of entropy.
+    ///
+    /// # Example
+    /// ```
+    /// let model = yapg_core::SyllableModel::default();
+    /// let pg = yapg_core::PronounceableGenerator::new(model, 0);
+    /// let n = pg.syllables_for_entropy(100.0);
+    /// assert!(pg.syllables(n).entropy() >= 100.0);
+    /// ```
+    pub fn syllables_for_entropy(&self, target_bits: f64) -> usize {
+        (target_bits / self.model.min_bits_per_syllable()).ceil().max(0.0)
+            as usize
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::{ambiguous_shape, PronounceableGenerator, SyllableModel};
+
+    #[test]
+    fn detecting_ambiguous_templates() {
+        assert_eq!(ambiguous_shape(&["CV", "CVC"]), None);
+        assert!(ambiguous_shape(&["CV", "CVC", "VC"]).is_some());
+        assert_eq!(ambiguous_shape(&["V", "CV", "CCV"]), None);
+        assert_eq!(ambiguous_shape(&["CV", "CV"]), Some("CV".to_string()));
+        // CVC+V reads as CV+CV
+        let shape = ambiguous_shape(&["CV", "CVC", "V"]).unwrap();
+        assert_eq!(shape, "CVCV");
+    }
+
+    #[test]
+    fn following_templates() {
+        let model = SyllableModel::new("b", "a", &["CVC"]).unwrap();
+        let mut pg = PronounceableGenerator::new(model, 3)
+            .random_source(crate::RandomSource::Os)
+            .unwrap();
+        assert_eq!(pg.rng.name(), "rand::OsRng");
+        assert_eq!(pg.generate(), "babbabbab");
+        assert_eq!(pg.entropy(), 0.0);
+
+        let model = SyllableModel::new("bd", "ao", &["CV", "V"]).unwrap();
+        let mut pg = PronounceableGenerator::new(model, 5);
+        assert_eq!(pg.entropy(), 10.0);
+        for pw in pg.generate_n(50) {
+            assert!(!pw.contains("bb") && !pw.contains("bd"));
+            assert!(!pw.contains("db") && !pw.contains("dd"));
+            assert!(pw.len() >= 5 && pw.len() <= 10);
+        }
+    }
+}
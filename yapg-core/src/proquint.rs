@@ -0,0 +1,166 @@
+//! Proquints: pronounceable quintuplets of alternating consonants and vowels,
+//! each encoding 16 bits, e.g. `lusab-babad` for the bytes of `127.0.0.1`.
+
+use std::io;
+
+use rand::RngCore;
+
+/// Consonants of proquints, each encoding 4 bits.
+const CONSONANTS: &[u8; 16] = b"bdfghjklmnprstvz";
+
+/// Vowels of proquints, each encoding 2 bits.
+const VOWELS: &[u8; 4] = b"aiou";
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+/// Encodes `bytes` as proquints separated by `-`, two bytes (big-endian)
+/// per proquint. Fails if the number of bytes is odd.
+///
+/// # Example
+/// ```
+/// let ip = [127, 0, 0, 1];
+/// assert_eq!(yapg_core::proquint_encode(&ip).unwrap(), "lusab-babad");
+/// assert_eq!(yapg_core::proquint_decode("lusab-babad").unwrap(), ip);
+/// assert!(yapg_core::proquint_encode(&[1, 2, 3]).is_err());
+/// ```
+pub fn proquint_encode(bytes: &[u8]) -> io::Result<String> {
+    if !bytes.len().is_multiple_of(2) {
+        return Err(invalid(format!(
+            "Proquints encode pairs of bytes, got {} bytes",
+            bytes.len()
+        )));
+    }
+    let quints: Vec<String> = bytes
+        .chunks(2)
+        .map(|pair| {
+            let n = ((pair[0] as usize) << 8) | pair[1] as usize;
+            [
+                CONSONANTS[n >> 12],
+                VOWELS[(n >> 10) & 0x3],
+                CONSONANTS[(n >> 6) & 0xf],
+                VOWELS[(n >> 4) & 0x3],
+                CONSONANTS[n & 0xf],
+            ]
+            .iter()
+            .map(|b| *b as char)
+            .collect()
+        })
+        .collect();
+    Ok(quints.join("-"))
+}
+
+/// Decodes proquints created by `proquint_encode`, ignoring case. Fails on
+/// anything but proquints separated by `-`.
+pub fn proquint_decode(s: &str) -> io::Result<Vec<u8>> {
+    let s = s.trim().to_ascii_lowercase();
+    if s.is_empty() {
+        return Ok(vec![]);
+    }
+    let mut bytes = vec![];
+    for quint in s.split('-') {
+        let invalid_quint = || invalid(format!("Invalid proquint: {}", quint));
+        if quint.len() != 5 {
+            return Err(invalid_quint());
+        }
+        let mut n = 0;
+        for (i, b) in quint.bytes().enumerate() {
+            let (alphabet, bits): (&[u8], _) =
+                if i % 2 == 0 { (CONSONANTS, 4) } else { (VOWELS, 2) };
+            let digit = alphabet
+                .iter()
+                .position(|a| *a == b)
+                .ok_or_else(invalid_quint)?;
+            n = (n << bits) | digit;
+        }
+        bytes.push((n >> 8) as u8);
+        bytes.push(n as u8);
+    }
+    Ok(bytes)
+}
+
+/// Generates random proquints, e.g. as readable machine IDs or keys.
+///
+/// # Example
+/// ```
+/// let mut pqg = yapg_core::ProquintGenerator::new(4);
+/// let id = pqg.generate();
+/// assert_eq!(id.len(), 4 * 6 - 1);
+/// assert_eq!(yapg_core::proquint_decode(&id).unwrap().len(), 8);
+/// assert_eq!(pqg.entropy(), 64.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProquintGenerator {
+    quints: usize,
+    rng: rand::ThreadRng,
+}
+
+impl ProquintGenerator {
+    /// Creates a generator of `quints` proquints per ID.
+    pub fn new(quints: usize) -> Self {
+        Self { quints, rng: rand::thread_rng() }
+    }
+
+    /// Changes the number of proquints per ID, consumes and returns itself.
+    #[inline]
+    pub fn quints(mut self, quints: usize) -> Self {
+        self.quints = quints;
+        self
+    }
+
+    /// Generates one ID.
+    pub fn generate(&mut self) -> String {
+        let mut bytes = vec![0; 2 * self.quints];
+        self.rng.fill_bytes(&mut bytes);
+        // an even number of bytes always encodes
+        proquint_encode(&bytes).unwrap()
+    }
+
+    /// Generates a vector of `n` IDs.
+    #[inline]
+    pub fn generate_n(&mut self, n: usize) -> Vec<String> {
+        (0..n).map(|_| self.generate()).collect()
+    }
+
+    /// Entropy in bits, 16 per proquint.
+    #[inline]
+    pub fn entropy(&self) -> f64 { 16.0 * self.quints as f64 }
+
+    /// Number of proquints needed to reach `target_bits` of entropy.
+    pub fn quints_for_entropy(target_bits: f64) -> usize {
+        (target_bits / 16.0).ceil().max(0.0) as usize
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::{proquint_decode, proquint_encode, ProquintGenerator};
+
+    #[test]
+    fn round_trips() {
+        // examples from the proquint proposal
+        for (ip, quints) in [
+            ([63, 84, 220, 193], "gutih-tugad"),
+            ([63, 118, 7, 35], "gutuk-bisog"),
+            ([140, 98, 193, 141], "mudof-sakat"),
+            ([255, 255, 255, 255], "zuzuz-zuzuz"),
+            ([0, 0, 0, 0], "babab-babab"),
+        ]
+        .iter()
+        {
+            assert_eq!(proquint_encode(ip).unwrap(), *quints);
+            assert_eq!(proquint_decode(quints).unwrap(), ip);
+        }
+        assert_eq!(proquint_decode("LUSAB-babad").unwrap(), [127, 0, 0, 1]);
+        assert_eq!(proquint_encode(&[]).unwrap(), "");
+        assert_eq!(proquint_decode("").unwrap(), Vec::<u8>::new());
+        for invalid in
+            ["lusab-", "lusa", "lusabb", "lxsab", "aaaaa", "lusab babad"].iter()
+        {
+            assert!(proquint_decode(invalid).is_err(), "{}", invalid);
+        }
+        assert_eq!(ProquintGenerator::quints_for_entropy(100.0), 7);
+    }
+}
@@ -0,0 +1,338 @@
+use crate::fingerprint::{fnv1a64, render_fingerprint};
+use crate::output::json_string;
+use crate::{
+    config_fingerprint, date, presets, CharsetName, CharsetSpec, Constraints,
+    Grapheme, PasswordGenerator, Wordlist, EMBEDDED_WORDLISTS,
+};
+
+/// Version of the `eff-wordlist` crate the embedded wordlists are taken
+/// from. Keep in sync with `Cargo.toml`.
+const EFF_WORDLIST_VERSION: &str = "eff-wordlist 1.0.3";
+
+/// Longest password length tried by `find_configuration`.
+const MAX_SEARCHED_LENGTH: usize = 256;
+
+/// Metadata describing how a batch of passwords was produced, so that audits
+/// can reconstruct the configuration behind every secret.
+///
+/// Emitted alongside the passwords by `OutputFormat::Json` and
+/// `OutputFormat::Yaml`. Contains no information about the passwords
+/// themselves.
+///
+/// # Example
+/// ```
+/// let pwg = yapg_core::PasswordGenerator::from("abc").length(8);
+/// let p = yapg_core::Provenance::of(&pwg);
+/// assert_eq!(
+///     p.config_hash,
+///     yapg_core::charset_fingerprint(&['a', 'b', 'c'], 8)
+/// );
+/// assert_eq!(p.charset, "abc");
+/// assert_eq!(p.length, 8);
+/// assert_eq!(p.constraints, None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Provenance {
+    /// Name and version of the generating crate, e.g. `yapg-core 0.1.0`.
+    pub generator: String,
+    /// The `config_fingerprint` of charset, length, constraints and
+    /// pattern, see `PasswordGenerator::config_hash`.
+    pub config_hash: String,
+    /// All characters passwords were drawn from.
+    pub charset: String,
+    /// Length of each password.
+    pub length: usize,
+    /// Constraints the passwords satisfy, if any, e.g. `N>=2,S<=1`.
+    pub constraints: Option<String>,
+    /// Pattern the passwords match, if any, e.g. `Cvcvc-NNNN`.
+    pub pattern: Option<String>,
+    /// Time of generation as RFC 3339 UTC timestamp.
+    pub timestamp: String,
+    /// The random number generator backing the generator.
+    pub rng: String,
+}
+
+impl Provenance {
+    /// Collects the metadata of `pwg`, timestamped with the current time.
+    pub fn of(pwg: &PasswordGenerator) -> Self {
+        Self {
+            generator: format!(
+                "{} {}",
+                env!("CARGO_PKG_NAME"),
+                env!("CARGO_PKG_VERSION")
+            ),
+            config_hash: pwg.config_hash(),
+            charset: pwg.charset.iter().map(Grapheme::as_str).collect(),
+            length: pwg.length,
+            constraints: pwg
+                .constraints
+                .as_ref()
+                .map(|cc| cc.constraints().to_string()),
+            pattern: pwg.template.as_ref().map(|p| p.to_string()),
+            timestamp: date::format_timestamp(date::unix_now()),
+            rng: pwg.rng_name().to_string(),
+        }
+    }
+
+    /// Key-value pairs with values rendered as JSON scalars, in output order.
+    pub(crate) fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("generator", json_string(&self.generator)),
+            ("config_hash", json_string(&self.config_hash)),
+            ("charset", json_string(&self.charset)),
+            ("length", self.length.to_string()),
+            ("constraints", json_optional(&self.constraints)),
+            ("pattern", json_optional(&self.pattern)),
+            ("timestamp", json_string(&self.timestamp)),
+            ("rng", json_string(&self.rng)),
+        ]
+    }
+}
+
+/// The JSON string of `value`, or `null`.
+fn json_optional(value: &Option<String>) -> String {
+    value.as_deref().map_or_else(|| "null".to_string(), json_string)
+}
+
+/// Reconstructs charset and length from a recorded `config_hash` (see
+/// `Provenance`), e.g. to reproduce a seeded batch for incident forensics.
+///
+/// Tries `charsets` (e.g. the recorded `Provenance::charset`), the charsets
+/// of all presets and all combinations of the atomic `CharsetName`s, each
+/// with lengths up to 256, and returns the first match. Batches drawn with
+/// `constraints` are only found with the recorded
+/// `Provenance::constraints`; those of patterns need no search, see
+/// `PasswordGenerator::from_pattern`.
+///
+/// # Example
+/// ```
+/// let charset = yapg_core::CharsetSpec::std64().construct();
+/// let hash = yapg_core::charset_fingerprint(&charset, 20);
+/// let found = yapg_core::find_configuration(&hash, &[], None);
+/// assert_eq!(found, Some((charset, 20)));
+///
+/// let hash = yapg_core::charset_fingerprint(&['x', 'y'], 5);
+/// assert_eq!(yapg_core::find_configuration(&hash, &[], None), None);
+/// let found = yapg_core::find_configuration(&hash, &[vec!['y', 'x']], None);
+/// assert_eq!(found, Some((vec!['y', 'x'], 5)));
+/// ```
+pub fn find_configuration(
+    config_hash: &str,
+    charsets: &[Vec<char>],
+    constraints: Option<&Constraints>,
+) -> Option<(Vec<char>, usize)> {
+    let mut candidates = charsets.to_vec();
+    candidates.extend(presets::all().iter().map(|p| p.charset.build()));
+    let atomic = CharsetName::ATOMIC;
+    for mask in 1..(1 << atomic.len()) {
+        let mut spec = CharsetSpec::empty();
+        for (i, name) in atomic.iter().enumerate() {
+            if mask & (1 << i) != 0 {
+                spec += *name;
+            }
+        }
+        candidates.push(spec.construct());
+    }
+    candidates.into_iter().find_map(|charset| {
+        let items: Vec<Grapheme> =
+            charset.iter().map(|c| (*c).into()).collect();
+        (1..=MAX_SEARCHED_LENGTH)
+            .find(|&length| {
+                config_fingerprint(&items, length, constraints, None)
+                    == config_hash
+            })
+            .map(|length| (charset, length))
+    })
+}
+
+/// Description of the running build of yapg, so that bug reports and
+/// reproducibility audits can name the exact build.
+///
+/// # Example
+/// ```
+/// let info = yapg_core::BuildInfo::current();
+/// assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+/// assert_eq!(info.rng, "rand::ThreadRng");
+/// assert_eq!(info.wordlists[0].name, "eff-long");
+/// assert!(info.to_json().starts_with("{\"version\":"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BuildInfo {
+    /// Version of the yapg-core crate.
+    pub version: String,
+    /// Enabled cargo features.
+    pub features: Vec<String>,
+    /// The random number generator backing all generators.
+    pub rng: String,
+    /// The wordlists compiled into the binary.
+    pub wordlists: Vec<EmbeddedWordlistInfo>,
+}
+
+/// Origin and content fingerprint of an embedded wordlist, see `BuildInfo`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EmbeddedWordlistInfo {
+    /// Name of the list, e.g. `eff-long`.
+    pub name: String,
+    /// Name and version of the crate providing the list.
+    pub source: String,
+    /// Number of words.
+    pub words: usize,
+    /// Fingerprint of the words, changing with any edit to the list.
+    pub fingerprint: String,
+}
+
+impl BuildInfo {
+    /// Describes the running build.
+    pub fn current() -> Self {
+        let mut features = vec![];
+        if cfg!(feature = "serde") {
+            features.push("serde".to_string());
+        }
+        if cfg!(feature = "mlock") {
+            features.push("mlock".to_string());
+        }
+        if cfg!(feature = "regex-gen") {
+            features.push("regex-gen".to_string());
+        }
+        if cfg!(feature = "age") {
+            features.push("age".to_string());
+        }
+        if cfg!(feature = "num-bigint") {
+            features.push("num-bigint".to_string());
+        }
+        if cfg!(feature = "hibp") {
+            features.push("hibp".to_string());
+        }
+        if cfg!(feature = "secrecy") {
+            features.push("secrecy".to_string());
+        }
+        if cfg!(feature = "share") {
+            features.push("share".to_string());
+        }
+        let wordlists = EMBEDDED_WORDLISTS
+            .iter()
+            .map(|name| {
+                let list = Wordlist::embedded(name).unwrap();
+                EmbeddedWordlistInfo {
+                    name: name.to_string(),
+                    source: EFF_WORDLIST_VERSION.to_string(),
+                    words: list.len(),
+                    fingerprint: render_fingerprint(fnv1a64(
+                        list.words().join("\n").as_bytes(),
+                    )),
+                }
+            })
+            .collect();
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            features,
+            rng: PasswordGenerator::from("").rng_name().to_string(),
+            wordlists,
+        }
+    }
+
+    /// Renders the build info as a single-line JSON object.
+    pub fn to_json(&self) -> String {
+        let features: Vec<String> =
+            self.features.iter().map(|f| json_string(f)).collect();
+        let wordlists: Vec<String> = self
+            .wordlists
+            .iter()
+            .map(|w| {
+                format!(
+                    "{}:{{\"source\":{},\"words\":{},\"fingerprint\":{}}}",
+                    json_string(&w.name),
+                    json_string(&w.source),
+                    w.words,
+                    json_string(&w.fingerprint)
+                )
+            })
+            .collect();
+        format!(
+            "{{\"version\":{},\"features\":[{}],\"rng\":{},\"wordlists\":\
+             {{{}}}}}",
+            json_string(&self.version),
+            features.join(","),
+            json_string(&self.rng),
+            wordlists.join(",")
+        )
+    }
+}
+
+impl std::fmt::Display for BuildInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "{} {}", env!("CARGO_PKG_NAME"), self.version)?;
+        let features = if self.features.is_empty() {
+            "none".to_string()
+        } else {
+            self.features.join(", ")
+        };
+        writeln!(f, "features:  {}", features)?;
+        writeln!(f, "rng:       {}", self.rng)?;
+        write!(f, "wordlists:")?;
+        for w in &self.wordlists {
+            write!(
+                f,
+                "\n  {:<10} {} words from {} ({})",
+                w.name, w.words, w.source, w.fingerprint
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::{find_configuration, BuildInfo, EFF_WORDLIST_VERSION};
+    use crate::{CharsetSpec, PasswordGenerator, Provenance};
+
+    #[test]
+    fn eff_wordlist_version_matches_manifest() {
+        let version = EFF_WORDLIST_VERSION.split(' ').nth(1).unwrap();
+        let dependency = format!("eff-wordlist = \"{}\"", version);
+        assert!(include_str!("../Cargo.toml").contains(&dependency));
+    }
+
+    #[test]
+    fn replaying_constrained_batch() {
+        let charset = CharsetSpec::std64().construct();
+        let mut pwg = PasswordGenerator::new(charset, 12)
+            .require_each_class(true)
+            .with_seed(7);
+        let batch = pwg.generate_n(5);
+        let p = Provenance::of(&pwg);
+        assert_eq!(p.constraints.as_deref(), Some("L>=1,U>=1,N>=1,S>=1"));
+
+        // the hash tells constrained batches from unconstrained ones
+        assert_eq!(find_configuration(&p.config_hash, &[], None), None);
+        let constraints = p.constraints.unwrap().parse().unwrap();
+        let (charset, length) =
+            find_configuration(&p.config_hash, &[], Some(&constraints))
+                .unwrap();
+        let mut replayed = PasswordGenerator::new(charset, length)
+            .constraints(constraints)
+            .unwrap()
+            .with_seed(7);
+        assert_eq!(replayed.generate_n(5), batch);
+
+        let pwg = PasswordGenerator::from_pattern("Cvcvc-NNNN").unwrap();
+        let p = Provenance::of(&pwg);
+        assert_eq!(p.pattern.as_deref(), Some("Cvcvc-NNNN"));
+        assert_ne!(p.config_hash, crate::items_fingerprint(&pwg.charset, 10));
+    }
+
+    #[test]
+    fn build_info_json() {
+        let info = BuildInfo::current();
+        let json: serde_json::Value =
+            serde_json::from_str(&info.to_json()).unwrap();
+        assert_eq!(json["version"], info.version.as_str());
+        assert_eq!(
+            json["features"].as_array().unwrap().len(),
+            info.features.len()
+        );
+        assert_eq!(json["wordlists"]["eff-long"]["words"], 7776);
+        assert_eq!(json["wordlists"]["eff-short"]["words"], 1296);
+    }
+}
@@ -0,0 +1,416 @@
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::transcode::hex_decode;
+
+/// Magic bytes starting a `PwnedBloom` file.
+const BLOOM_MAGIC: &[u8; 8] = b"YAPGBLM1";
+
+/// Sources telling whether passwords appear in known breaches, see
+/// `PasswordGenerator::generate_unbreached`.
+pub trait BreachCheck {
+    /// How often `password` appears in known breaches, 0 if not at all.
+    fn breach_count(&self, password: &str) -> io::Result<u64>;
+
+    /// Whether `password` appears in known breaches.
+    fn is_pwned(&self, password: &str) -> io::Result<bool> {
+        Ok(self.breach_count(password)? > 0)
+    }
+}
+
+/// Number of breached candidates in a row `generate_unbreached_with` may
+/// discard before giving up, lower than for filters as each check may be a
+/// request.
+const MAX_BREACHED_CANDIDATES: usize = 10;
+
+/// Generates `n` passwords with `generate` that `breaches` does not find,
+/// redrawing breached candidates, for generators other than
+/// `PasswordGenerator` (see `PasswordGenerator::generate_unbreached`).
+/// Fails if `generate` or the check fails, or if several candidates in a
+/// row are breached.
+///
+/// # Example
+/// ```
+/// use std::io;
+///
+/// use yapg_core::{generate_unbreached_with, BreachCheck};
+///
+/// struct Digits;
+/// impl BreachCheck for Digits {
+///     fn breach_count(&self, password: &str) -> io::Result<u64> {
+///         Ok(password.chars().all(|c| c.is_ascii_digit()) as u64)
+///     }
+/// }
+///
+/// let mut candidates = vec!["abc", "123", "xyz"].into_iter();
+/// let passwords = generate_unbreached_with(2, &Digits, || {
+///     Ok(candidates.next().unwrap().to_string())
+/// });
+/// assert_eq!(passwords.unwrap(), ["abc", "xyz"]);
+/// let passwords = generate_unbreached_with(1, &Digits, || Ok("0".into()));
+/// assert!(passwords.is_err());
+/// ```
+pub fn generate_unbreached_with<F>(
+    n: usize,
+    breaches: &impl BreachCheck,
+    mut generate: F,
+) -> io::Result<Vec<String>>
+where
+    F: FnMut() -> io::Result<String>,
+{
+    let mut passwords = Vec::with_capacity(n);
+    let mut breached = 0;
+    while passwords.len() < n {
+        let candidate = generate()?;
+        if !breaches.is_pwned(&candidate)? {
+            passwords.push(candidate);
+            breached = 0;
+            continue;
+        }
+        breached += 1;
+        if breached == MAX_BREACHED_CANDIDATES {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Gave up after {} breached candidates in a row, increase \
+                     the entropy",
+                    breached
+                ),
+            ));
+        }
+    }
+    Ok(passwords)
+}
+
+/// Upper-case hex SHA-1 hash of `password`, as in the HIBP corpus.
+pub(crate) fn sha1_hex(password: &str) -> String {
+    sha1_smol::Sha1::from(password).digest().to_string().to_uppercase()
+}
+
+/// A bloom filter of the SHA-1 hashes of breached passwords: a compact
+/// alternative to the multi-gigabyte HIBP corpus with a small, tunable rate
+/// of false positives (never false negatives).
+///
+/// # Example
+/// ```
+/// use yapg_core::PwnedBloom;
+///
+/// let mut bloom = PwnedBloom::new(1000, 0.001);
+/// bloom.insert("password");
+/// assert!(bloom.contains("password"));
+/// assert!(!bloom.contains("correct horse battery staple"));
+///
+/// let mut file = vec![];
+/// bloom.write_to(&mut file).unwrap();
+/// assert_eq!(PwnedBloom::read_from(&file[..]).unwrap(), bloom);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PwnedBloom {
+    hashes: u32,
+    bits: Vec<u8>,
+}
+
+impl PwnedBloom {
+    /// Creates an empty filter sized for `capacity` hashes at a false
+    /// positive rate of `fp_rate`.
+    pub fn new(capacity: usize, fp_rate: f64) -> Self {
+        let ln2 = std::f64::consts::LN_2;
+        let bits = (-(capacity.max(1) as f64) * fp_rate.ln() / (ln2 * ln2))
+            .ceil()
+            .max(8.0) as usize;
+        let hashes = ((bits as f64 / capacity.max(1) as f64) * ln2)
+            .round()
+            .max(1.0) as u32;
+        Self { hashes, bits: vec![0; bits.div_ceil(8)] }
+    }
+
+    /// Adds a SHA-1 hash.
+    pub fn insert_hash(&mut self, sha1: &[u8; 20]) {
+        let indices: Vec<usize> = self.indices(sha1).collect();
+        for i in indices {
+            self.bits[i / 8] |= 1 << (i % 8);
+        }
+    }
+
+    /// Whether a SHA-1 hash (probably) was added.
+    pub fn contains_hash(&self, sha1: &[u8; 20]) -> bool {
+        self.indices(sha1).all(|i| self.bits[i / 8] & (1 << (i % 8)) != 0)
+    }
+
+    /// Adds the hash of a `HASH:COUNT` (or just `HASH`) line of a hash file
+    /// like the HIBP corpus. Fails if it is no hex SHA-1 hash.
+    pub fn insert_line(&mut self, line: &str) -> io::Result<()> {
+        let hash = line.trim().split(':').next().unwrap_or("");
+        match hex_decode(hash) {
+            Some(bytes) if bytes.len() == 20 => {
+                let mut sha1 = [0; 20];
+                sha1.copy_from_slice(&bytes);
+                self.insert_hash(&sha1);
+                Ok(())
+            },
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Not a SHA-1 hash: {:?}", hash),
+            )),
+        }
+    }
+
+    /// Adds the SHA-1 hash of `password`.
+    #[inline]
+    pub fn insert(&mut self, password: &str) {
+        self.insert_hash(&sha1_smol::Sha1::from(password).digest().bytes())
+    }
+
+    /// Whether the SHA-1 hash of `password` (probably) was added.
+    #[inline]
+    pub fn contains(&self, password: &str) -> bool {
+        self.contains_hash(&sha1_smol::Sha1::from(password).digest().bytes())
+    }
+
+    /// Writes the filter: magic bytes, the number of hash functions (`u32`)
+    /// and of bits (`u64`), both little-endian, and the bits.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(BLOOM_MAGIC)?;
+        writer.write_all(&self.hashes.to_le_bytes())?;
+        writer.write_all(&(self.bits.len() as u64 * 8).to_le_bytes())?;
+        writer.write_all(&self.bits)
+    }
+
+    /// Reads a filter written by `write_to`.
+    pub fn read_from<R: Read>(mut reader: R) -> io::Result<Self> {
+        let invalid = |msg: &str| {
+            io::Error::new(io::ErrorKind::InvalidInput, msg.to_string())
+        };
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != BLOOM_MAGIC {
+            return Err(invalid("Not a yapg bloom filter"));
+        }
+        let (mut hashes, mut len) = ([0; 4], [0; 8]);
+        reader.read_exact(&mut hashes)?;
+        reader.read_exact(&mut len)?;
+        let hashes = u32::from_le_bytes(hashes);
+        let len = u64::from_le_bytes(len);
+        if hashes == 0 || len == 0 || len % 8 != 0 {
+            return Err(invalid("Corrupt bloom filter header"));
+        }
+        let mut bits = vec![];
+        reader.read_to_end(&mut bits)?;
+        if bits.len() as u64 * 8 != len {
+            return Err(invalid("Truncated bloom filter"));
+        }
+        Ok(Self { hashes, bits })
+    }
+
+    /// Bits to check for a hash, derived by double hashing from its first
+    /// 16 bytes.
+    fn indices<'a>(
+        &'a self,
+        sha1: &[u8; 20],
+    ) -> impl Iterator<Item = usize> + 'a {
+        let mut h1 = [0; 8];
+        let mut h2 = [0; 8];
+        h1.copy_from_slice(&sha1[..8]);
+        h2.copy_from_slice(&sha1[8..16]);
+        let (h1, h2) = (u64::from_be_bytes(h1), u64::from_be_bytes(h2) | 1);
+        let len = self.bits.len() as u64 * 8;
+        (0..self.hashes as u64)
+            .map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % len) as usize)
+    }
+}
+
+/// Lines of a sorted hash file, looked up by binary search.
+#[derive(Debug)]
+struct SortedHashes {
+    file: File,
+    len: u64,
+}
+
+impl SortedHashes {
+    /// The first complete line starting at or after `offset`, along with
+    /// its offset and length including the line break.
+    fn line_from(&self, offset: u64) -> io::Result<Option<(u64, String)>> {
+        let mut reader = BufReader::new(&self.file);
+        let mut start = offset;
+        reader.seek(SeekFrom::Start(offset.saturating_sub(1)))?;
+        if offset > 0 {
+            start += reader.read_until(b'\n', &mut vec![])? as u64 - 1;
+        }
+        let mut line = String::new();
+        match reader.read_line(&mut line)? {
+            0 => Ok(None),
+            _ => Ok(Some((start, line))),
+        }
+    }
+
+    /// The count of `hash` (upper-case hex), 0 if missing.
+    fn count(&self, hash: &str) -> io::Result<u64> {
+        // compares lines to the hash, or its prefix as long as theirs
+        let compare = |line: &str| {
+            let entry = line.trim().split(':').next().unwrap_or("");
+            let entry = entry.to_uppercase();
+            entry.as_str().cmp(&hash[..entry.len().min(hash.len())])
+        };
+        let (mut lo, mut hi) = (0, self.len);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            match self.line_from(mid)? {
+                Some((start, line)) if compare(&line) == Ordering::Less => {
+                    lo = start + line.len() as u64;
+                },
+                _ => hi = mid,
+            }
+        }
+        Ok(match self.line_from(lo)? {
+            Some((_, line))
+                if !line.trim().is_empty()
+                    && compare(&line) == Ordering::Equal =>
+            {
+                count_of(&line)
+            },
+            _ => 0,
+        })
+    }
+}
+
+/// The count of a `HASH:COUNT` line, 1 if it has none.
+fn count_of(line: &str) -> u64 {
+    match line.trim().split_once(':') {
+        Some((_, count)) => count.parse().unwrap_or(1),
+        None => 1,
+    }
+}
+
+/// Storage of an offline breach database.
+#[derive(Debug)]
+enum Source {
+    Sorted(SortedHashes),
+    Bloom(PwnedBloom),
+}
+
+/// An offline database of breached passwords, for air-gapped machines.
+///
+/// Either a text file of SHA-1 hashes sorted ascending, one `HASH:COUNT`
+/// (or just `HASH`) per line like the HIBP corpus ordered by hash, or a
+/// `PwnedBloom` file. Hashes in the text file may be shortened to a common
+/// prefix length to save space, at the cost of false positives. Text files
+/// are searched on disk, so even the full corpus takes no memory; bloom
+/// filters are loaded completely.
+///
+/// # Example
+/// ```
+/// use yapg_core::{BreachCheck, PwnedDb};
+///
+/// let path = std::env::temp_dir().join("yapg-doc-pwned.txt");
+/// let hashes = [
+///     "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8:9545824",
+///     "7C4A8D09CA3762AF61E59520943DC26494F8941B:37359195",
+/// ];
+/// std::fs::write(&path, hashes.join("\n")).unwrap();
+/// let db = PwnedDb::open(&path).unwrap();
+/// assert_eq!(db.breach_count("123456").unwrap(), 37359195);
+/// assert!(db.is_pwned("password").unwrap());
+/// assert!(!db.is_pwned("x7Kq-2mPw_9vLz").unwrap());
+/// ```
+#[derive(Debug)]
+pub struct PwnedDb {
+    source: Source,
+}
+
+impl PwnedDb {
+    /// Opens the database at `path`, telling bloom filters from text files
+    /// by their magic bytes.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let mut magic = [0; 8];
+        let is_bloom = matches!(file.read_exact(&mut magic), Ok(()))
+            && &magic == BLOOM_MAGIC;
+        file.seek(SeekFrom::Start(0))?;
+        let source = if is_bloom {
+            Source::Bloom(PwnedBloom::read_from(BufReader::new(file))?)
+        } else {
+            let len = file.metadata()?.len();
+            Source::Sorted(SortedHashes { file, len })
+        };
+        Ok(Self { source })
+    }
+}
+
+impl BreachCheck for PwnedDb {
+    /// How often `password` appears in the database. Bloom filters and text
+    /// files without counts report 1 for any breached password.
+    fn breach_count(&self, password: &str) -> io::Result<u64> {
+        let hash = sha1_hex(password);
+        match &self.source {
+            Source::Sorted(hashes) => hashes.count(&hash),
+            Source::Bloom(bloom) => {
+                // the hex hash is valid by construction
+                let mut sha1 = [0; 20];
+                sha1.copy_from_slice(&hex_decode(&hash).unwrap());
+                Ok(bloom.contains_hash(&sha1) as u64)
+            },
+        }
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::{sha1_hex, BreachCheck, PwnedBloom, PwnedDb};
+
+    #[test]
+    fn looking_up_sorted_hashes() {
+        let breached = ["123456", "password", "qwerty", "letmein", "dragon"];
+        let mut hashes: Vec<String> =
+            breached.iter().map(|pw| sha1_hex(pw)).collect();
+        hashes.sort();
+        let dir = std::env::temp_dir();
+
+        let full: Vec<String> = hashes
+            .iter()
+            .enumerate()
+            .map(|(i, hash)| format!("{}:{}\r\n", hash, i + 1))
+            .collect();
+        let path =
+            dir.join(format!("yapg-test-pwned-{}.txt", std::process::id()));
+        std::fs::write(&path, full.concat()).unwrap();
+        let db = PwnedDb::open(&path).unwrap();
+        for (i, hash) in hashes.iter().enumerate() {
+            let pw = breached.iter().find(|pw| sha1_hex(pw) == *hash).unwrap();
+            assert_eq!(db.breach_count(pw).unwrap(), i as u64 + 1);
+        }
+        for pw in ["", "0", "zzzz", "x7Kq-2mPw_9vLz"].iter() {
+            assert!(!db.is_pwned(pw).unwrap(), "{}", pw);
+        }
+
+        // lower-case prefixes without counts
+        let prefixes: Vec<String> = hashes
+            .iter()
+            .map(|h| format!("{}\n", h[..10].to_lowercase()))
+            .collect();
+        std::fs::write(&path, prefixes.concat()).unwrap();
+        let db = PwnedDb::open(&path).unwrap();
+        assert_eq!(db.breach_count("dragon").unwrap(), 1);
+        assert!(!db.is_pwned("dragon!").unwrap());
+
+        std::fs::write(&path, "").unwrap();
+        assert!(!PwnedDb::open(&path).unwrap().is_pwned("password").unwrap());
+
+        // bloom filters
+        let mut bloom = PwnedBloom::new(breached.len(), 0.001);
+        for line in full.iter() {
+            bloom.insert_line(line).unwrap();
+        }
+        assert!(bloom.insert_line("5BAA61E4:3").is_err());
+        let mut file = vec![];
+        bloom.write_to(&mut file).unwrap();
+        std::fs::write(&path, &file).unwrap();
+        let db = PwnedDb::open(&path).unwrap();
+        assert!(breached.iter().all(|pw| db.is_pwned(pw).unwrap()));
+        assert!(!db.is_pwned("x7Kq-2mPw_9vLz").unwrap());
+        assert!(PwnedBloom::read_from(&file[..file.len() - 1]).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
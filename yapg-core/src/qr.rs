@@ -0,0 +1,630 @@
+//! Minimal QR code encoder (byte mode, error correction level M), following
+//! ISO/IEC 18004 and Project Nayuki's reference implementation, so that
+//! secrets can be printed scannable without pulling in an imaging crate.
+
+use std::io;
+
+/// Error correction codewords per block, by version (level M).
+static ECC_CODEWORDS_PER_BLOCK: [usize; 41] = [
+    0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26,
+    26, 26, 26, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28,
+    28, 28, 28,
+];
+
+/// Number of error correction blocks, by version (level M).
+static NUM_ERROR_CORRECTION_BLOCKS: [usize; 41] = [
+    0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17,
+    17, 18, 20, 21, 23, 25, 26, 28, 29, 31, 33, 35, 37, 38, 40, 43, 45, 47, 49,
+];
+
+/// Format bits of error correction level M.
+const ECC_LEVEL_M: u32 = 0;
+
+/// A QR code, i.e. a square grid of dark and light modules.
+///
+/// # Example
+/// ```
+/// let qr =
+///     yapg_core::QrCode::encode(b"correct-horse-battery-staple").unwrap();
+/// assert_eq!(qr.size(), 29); // version 3
+/// assert!(qr.is_dark(0, 0)); // corner of a finder pattern
+/// assert!(qr.to_svg().starts_with("<svg"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QrCode {
+    size: usize,
+    modules: Vec<bool>,
+    is_function: Vec<bool>,
+}
+
+impl QrCode {
+    /// Encodes `data` in the smallest version that fits it. Fails if `data`
+    /// exceeds the capacity of version 40 (2331 bytes).
+    pub fn encode(data: &[u8]) -> io::Result<Self> {
+        let version = (1..=40)
+            .find(|v| {
+                let count_bits = if *v <= 9 { 8 } else { 16 };
+                4 + count_bits + data.len() * 8 <= num_data_codewords(*v) * 8
+            })
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Too much data for a QR code: {} bytes",
+                        data.len()
+                    ),
+                )
+            })?;
+
+        // mode indicator, character count, data, terminator and padding
+        let capacity = num_data_codewords(version) * 8;
+        let mut bits = BitBuffer::default();
+        bits.append(0b0100, 4);
+        bits.append(data.len() as u32, if version <= 9 { 8 } else { 16 });
+        for b in data {
+            bits.append(*b as u32, 8);
+        }
+        bits.append(0, 4.min(capacity - bits.0.len()));
+        bits.append(0, (8 - bits.0.len() % 8) % 8);
+        for pad in [0xec, 0x11].iter().cycle() {
+            if bits.0.len() >= capacity {
+                break;
+            }
+            bits.append(*pad, 8);
+        }
+        let codewords: Vec<u8> = bits
+            .0
+            .chunks(8)
+            .map(|byte| byte.iter().fold(0, |acc, b| (acc << 1) | *b as u8))
+            .collect();
+
+        let size = version * 4 + 17;
+        let mut qr = Self {
+            size,
+            modules: vec![false; size * size],
+            is_function: vec![false; size * size],
+        };
+        qr.draw_function_patterns(version);
+        qr.draw_codewords(&add_ecc_and_interleave(&codewords, version));
+
+        // choose the mask with the lowest penalty
+        let mask = (0..8)
+            .min_by_key(|mask| {
+                qr.apply_mask(*mask);
+                qr.draw_format_bits(*mask);
+                let penalty = qr.penalty_score();
+                qr.apply_mask(*mask); // undo, as masking is an XOR
+                penalty
+            })
+            .unwrap();
+        qr.apply_mask(mask);
+        qr.draw_format_bits(mask);
+        Ok(qr)
+    }
+
+    /// Number of modules per side.
+    #[inline]
+    pub fn size(&self) -> usize { self.size }
+
+    /// Whether the module at column `x` and row `y` is dark.
+    #[inline]
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x]
+    }
+
+    /// Renders the code as SVG image, including the quiet zone of four
+    /// modules around it.
+    pub fn to_svg(&self) -> String {
+        let mut path = String::new();
+        for y in 0..self.size {
+            for x in 0..self.size {
+                if self.is_dark(x, y) {
+                    path.push_str(&format!("M{},{}h1v1h-1z", x + 4, y + 4));
+                }
+            }
+        }
+        let side = self.size + 8;
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {0} \
+             {0}\" shape-rendering=\"crispEdges\"><rect width=\"{0}\" \
+             height=\"{0}\" fill=\"#fff\"/><path d=\"{1}\" \
+             fill=\"#000\"/></svg>",
+            side, path
+        )
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y * self.size + x] = dark;
+        self.is_function[y * self.size + x] = true;
+    }
+
+    fn draw_function_patterns(&mut self, version: usize) {
+        let size = self.size;
+        for i in 0..size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+        for (x, y) in [(3, 3), (size - 4, 3), (3, size - 4)].iter() {
+            self.draw_finder_pattern(*x as isize, *y as isize);
+        }
+        let positions = alignment_pattern_positions(version);
+        let n = positions.len();
+        for (i, x) in positions.iter().enumerate() {
+            for (j, y) in positions.iter().enumerate() {
+                // skip the corners occupied by finder patterns
+                let corner = (i == 0 && (j == 0 || j == n - 1))
+                    || (i == n - 1 && j == 0);
+                if !corner {
+                    self.draw_alignment_pattern(*x, *y);
+                }
+            }
+        }
+        self.draw_format_bits(0); // placeholder, reserving the modules
+        self.draw_version(version);
+    }
+
+    /// Draws a finder pattern with its separator, centered at `(x, y)`.
+    fn draw_finder_pattern(&mut self, x: isize, y: isize) {
+        for dy in -4..=4isize {
+            for dx in -4..=4isize {
+                let (xx, yy) = (x + dx, y + dy);
+                let inside = 0 <= xx
+                    && xx < self.size as isize
+                    && 0 <= yy
+                    && yy < self.size as isize;
+                if inside {
+                    let dist = dx.abs().max(dy.abs());
+                    self.set_function(
+                        xx as usize,
+                        yy as usize,
+                        dist != 2 && dist != 4,
+                    );
+                }
+            }
+        }
+    }
+
+    fn draw_alignment_pattern(&mut self, x: usize, y: usize) {
+        for dy in 0..5 {
+            for dx in 0..5 {
+                let dist = (dx as isize - 2).abs().max((dy as isize - 2).abs());
+                self.set_function(x + dx - 2, y + dy - 2, dist != 1);
+            }
+        }
+    }
+
+    fn draw_format_bits(&mut self, mask: u32) {
+        let data = (ECC_LEVEL_M << 3) | mask;
+        let bits = ((data << 10) | bch_remainder(data, 0x537, 10)) ^ 0x5412;
+        let bit = |i: usize| (bits >> i) & 1 != 0;
+        let size = self.size;
+
+        // around the top left finder pattern
+        for i in 0..6 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+
+        // split between the other finder patterns
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i));
+        }
+        self.set_function(8, size - 8, true); // always dark
+    }
+
+    fn draw_version(&mut self, version: usize) {
+        if version < 7 {
+            return;
+        }
+        let version = version as u32;
+        let bits = (version << 12) | bch_remainder(version, 0x1f25, 12);
+        for i in 0..18 {
+            let dark = (bits >> i) & 1 != 0;
+            let (a, b) = (self.size - 11 + i % 3, i / 3);
+            self.set_function(a, b, dark);
+            self.set_function(b, a, dark);
+        }
+    }
+
+    /// Places the codewords in the zigzag pattern of two-module columns,
+    /// from the bottom right, skipping the function patterns.
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let size = self.size;
+        let mut i = 0;
+        let mut right = size - 1;
+        loop {
+            if right == 6 {
+                right = 5; // skip the vertical timing pattern
+            }
+            let upward = (right + 1) & 2 == 0;
+            for vert in 0..size {
+                let y = if upward { size - 1 - vert } else { vert };
+                for x in [right, right - 1].iter() {
+                    let idx = y * size + x;
+                    if !self.is_function[idx] && i < codewords.len() * 8 {
+                        self.modules[idx] =
+                            (codewords[i / 8] >> (7 - i % 8)) & 1 != 0;
+                        i += 1;
+                    }
+                }
+            }
+            if right < 3 {
+                break;
+            }
+            right -= 2;
+        }
+    }
+
+    fn apply_mask(&mut self, mask: u32) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let invert = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                let idx = y * self.size + x;
+                if invert && !self.is_function[idx] {
+                    self.modules[idx] = !self.modules[idx];
+                }
+            }
+        }
+    }
+
+    /// Penalty for patterns hampering scanning: long runs, 2x2 blocks,
+    /// finder-like patterns and an imbalance of dark and light modules.
+    fn penalty_score(&self) -> usize {
+        let size = self.size;
+        let mut penalty = 0;
+        let lines = (0..size)
+            .map(|y| (0..size).map(|x| self.is_dark(x, y)).collect::<Vec<_>>())
+            .chain(
+                (0..size)
+                    .map(|x| (0..size).map(|y| self.is_dark(x, y)).collect()),
+            );
+        for line in lines {
+            let mut run = 1;
+            for i in 1..=size {
+                if i < size && line[i] == line[i - 1] {
+                    run += 1;
+                } else {
+                    if run >= 5 {
+                        penalty += run - 2;
+                    }
+                    run = 1;
+                }
+            }
+            // runs as (dark, length), the quiet zone around counts as light
+            let mut runs = vec![(false, size)];
+            for dark in line.iter().chain([false].iter()) {
+                match runs.last_mut() {
+                    Some((d, len)) if d == dark => *len += 1,
+                    _ => runs.push((*dark, 1)),
+                }
+            }
+            runs.last_mut().unwrap().1 += size - 1;
+            // dark-light-dark-light-dark in proportion 1:1:3:1:1, with four
+            // light modules before or after
+            for w in runs.windows(7).filter(|w| w[1].0) {
+                let n = w[1].1;
+                let lens = [w[2].1, w[3].1, w[4].1, w[5].1];
+                if lens == [n, 3 * n, n, n] {
+                    let (before, after) = (w[0].1, w[6].1);
+                    penalty += 40 * (before >= 4 * n && after >= n) as usize;
+                    penalty += 40 * (after >= 4 * n && before >= n) as usize;
+                }
+            }
+        }
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let c = self.is_dark(x, y);
+                if c == self.is_dark(x + 1, y)
+                    && c == self.is_dark(x, y + 1)
+                    && c == self.is_dark(x + 1, y + 1)
+                {
+                    penalty += 3;
+                }
+            }
+        }
+        let dark = self.modules.iter().filter(|m| **m).count() as isize;
+        let total = (size * size) as isize;
+        let k = ((dark * 20 - total * 10).abs() + total - 1) / total - 1;
+        penalty + k as usize * 10
+    }
+}
+
+/// Bits appended to the last bit of the buffer.
+#[derive(Default)]
+struct BitBuffer(Vec<bool>);
+
+impl BitBuffer {
+    /// Appends the `len` lowest bits of `value`, most significant first.
+    fn append(&mut self, value: u32, len: usize) {
+        for i in (0..len).rev() {
+            self.0.push((value >> i) & 1 != 0);
+        }
+    }
+}
+
+/// Remainder of `data << degree` divided by the generator `poly`, as used for
+/// format and version information.
+fn bch_remainder(data: u32, poly: u32, degree: u32) -> u32 {
+    let mut rem = data;
+    for _ in 0..degree {
+        rem = (rem << 1) ^ ((rem >> (degree - 1)) * poly);
+    }
+    rem
+}
+
+/// Number of modules available for data and error correction.
+fn num_raw_data_modules(version: usize) -> usize {
+    let mut result = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let num_align = version / 7 + 2;
+        result -= (25 * num_align - 10) * num_align - 55;
+        if version >= 7 {
+            result -= 36;
+        }
+    }
+    result
+}
+
+/// Number of data codewords of `version` at level M.
+fn num_data_codewords(version: usize) -> usize {
+    num_raw_data_modules(version) / 8
+        - ECC_CODEWORDS_PER_BLOCK[version]
+            * NUM_ERROR_CORRECTION_BLOCKS[version]
+}
+
+/// Centers of the alignment patterns along either axis.
+fn alignment_pattern_positions(version: usize) -> Vec<usize> {
+    if version == 1 {
+        return vec![];
+    }
+    let num_align = version / 7 + 2;
+    let step = if version == 32 {
+        26
+    } else {
+        (version * 4 + num_align * 2 + 1) / (num_align * 2 - 2) * 2
+    };
+    let mut result = vec![6];
+    let mut pos = version * 4 + 10;
+    for _ in 0..num_align - 1 {
+        result.insert(1, pos);
+        pos -= step;
+    }
+    result
+}
+
+/// Splits the data into blocks, appends Reed-Solomon error correction to
+/// each and interleaves them.
+fn add_ecc_and_interleave(data: &[u8], version: usize) -> Vec<u8> {
+    let num_blocks = NUM_ERROR_CORRECTION_BLOCKS[version];
+    let ecc_len = ECC_CODEWORDS_PER_BLOCK[version];
+    let raw_codewords = num_raw_data_modules(version) / 8;
+    let num_short_blocks = num_blocks - raw_codewords % num_blocks;
+    let short_block_len = raw_codewords / num_blocks;
+
+    let divisor = reed_solomon_divisor(ecc_len);
+    let mut blocks = Vec::with_capacity(num_blocks);
+    let mut k = 0;
+    for i in 0..num_blocks {
+        let len = short_block_len - ecc_len
+            + if i < num_short_blocks { 0 } else { 1 };
+        let dat = &data[k..k + len];
+        k += len;
+        let mut block = dat.to_vec();
+        if i < num_short_blocks {
+            block.push(0); // placeholder, skipped when interleaving
+        }
+        block.extend(reed_solomon_remainder(dat, &divisor));
+        blocks.push(block);
+    }
+
+    let mut result = Vec::with_capacity(raw_codewords);
+    for i in 0..blocks[0].len() {
+        for (j, block) in blocks.iter().enumerate() {
+            if i != short_block_len - ecc_len || j >= num_short_blocks {
+                result.push(block[i]);
+            }
+        }
+    }
+    result
+}
+
+/// Generator polynomial of degree `degree` (without its leading term), most
+/// significant coefficient first.
+fn reed_solomon_divisor(degree: usize) -> Vec<u8> {
+    let mut result = vec![0; degree];
+    result[degree - 1] = 1;
+    let mut root = 1;
+    for _ in 0..degree {
+        for j in 0..degree {
+            result[j] = gf_multiply(result[j], root);
+            if j + 1 < degree {
+                result[j] ^= result[j + 1];
+            }
+        }
+        root = gf_multiply(root, 0x02);
+    }
+    result
+}
+
+/// Error correction codewords of `data`.
+fn reed_solomon_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut result = vec![0; divisor.len()];
+    for b in data {
+        let factor = b ^ result.remove(0);
+        result.push(0);
+        for (x, y) in result.iter_mut().zip(divisor.iter()) {
+            *x ^= gf_multiply(*y, factor);
+        }
+    }
+    result
+}
+
+/// Product in GF(2^8) modulo x^8 + x^4 + x^3 + x^2 + 1.
+fn gf_multiply(x: u8, y: u8) -> u8 {
+    let mut z: u8 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x1d);
+        z ^= ((y >> i) & 1) * x;
+    }
+    z
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reed_solomon() {
+        // "HELLO WORLD" in version 1-M (thonky.com QR code tutorial)
+        let data = [
+            32, 91, 11, 120, 209, 114, 220, 77, 67, 64, 236, 17, 236, 17, 236,
+            17,
+        ];
+        let ecc = reed_solomon_remainder(&data, &reed_solomon_divisor(10));
+        assert_eq!(ecc, [196, 35, 39, 119, 235, 215, 231, 226, 93, 23]);
+    }
+
+    #[test]
+    fn format_and_version_information() {
+        let format = |mask: u32| {
+            let data = (ECC_LEVEL_M << 3) | mask;
+            ((data << 10) | bch_remainder(data, 0x537, 10)) ^ 0x5412
+        };
+        assert_eq!(format(0), 0b101010000010010);
+        assert_eq!(format(5), 0b100000011001110);
+        assert_eq!(format(7), 0b100101010100000);
+        assert_eq!(
+            (7 << 12) | bch_remainder(7, 0x1f25, 12),
+            0b000111110010010100
+        );
+    }
+
+    #[test]
+    fn layout() {
+        assert_eq!(alignment_pattern_positions(2), [6, 18]);
+        assert_eq!(alignment_pattern_positions(7), [6, 22, 38]);
+        assert_eq!(alignment_pattern_positions(14), [6, 26, 46, 66]);
+        assert_eq!(alignment_pattern_positions(32), [6, 34, 60, 86, 112, 138]);
+        assert_eq!(alignment_pattern_positions(40), [
+            6, 30, 58, 86, 114, 142, 170
+        ]);
+        // byte capacities at level M
+        for (version, bytes) in [(1, 14), (2, 26), (10, 213), (40, 2331)].iter()
+        {
+            let count_bits = if *version <= 9 { 8 } else { 16 };
+            let capacity =
+                (num_data_codewords(*version) * 8 - 4 - count_bits) / 8;
+            assert_eq!(capacity, *bytes, "version {}", version);
+        }
+        assert_eq!(add_ecc_and_interleave(&[0; 16], 1).len(), 26);
+        assert_eq!(
+            add_ecc_and_interleave(&vec![0; num_data_codewords(40)], 40).len(),
+            num_raw_data_modules(40) / 8
+        );
+    }
+
+    #[test]
+    fn encoding() {
+        let qr = QrCode::encode(&[b'a'; 14]).unwrap();
+        assert_eq!(qr.size(), 21);
+        assert_eq!(QrCode::encode(&[b'a'; 15]).unwrap().size(), 25);
+        assert!(QrCode::encode(&[0; 2332]).is_err());
+        // dark module and timing pattern
+        assert!(qr.is_dark(8, qr.size() - 8));
+        assert!((8..13).all(|i| qr.is_dark(i, 6) == (i % 2 == 0)));
+        // format information is stored twice
+        let first: Vec<bool> = (0..6).map(|i| qr.is_dark(8, i)).collect();
+        let second: Vec<bool> = (0..6).map(|i| qr.is_dark(20 - i, 8)).collect();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn matching_reference_encoder() {
+        let rows = |data: &[u8]| {
+            let qr = QrCode::encode(data).unwrap();
+            (0..qr.size())
+                .map(|y| {
+                    (0..qr.size())
+                        .map(|x| if qr.is_dark(x, y) { '#' } else { '.' })
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+        };
+        // as encoded by Project Nayuki's qrcodegen 1.8.0 in byte mode at
+        // level M, without boosting the level: version 1 with mask 2 ...
+        assert_eq!(rows(b"'Twas brillig"), [
+            "#######..#..#.#######",
+            "#.....#..###..#.....#",
+            "#.###.#.####..#.###.#",
+            "#.###.#.###...#.###.#",
+            "#.###.#.###.#.#.###.#",
+            "#.....#.#..#..#.....#",
+            "#######.#.#.#.#######",
+            "........##...........",
+            "#.#####..#.#..#####..",
+            ".##.##.###..#...####.",
+            "##..#####...#.#...##.",
+            "#..###..##.#.#.######",
+            "#.###.###.###.##.#.#.",
+            "........#.....#####.#",
+            "#######..#####...#...",
+            "#.....#.######.#.###.",
+            "#.###.#.#.#...##...##",
+            "#.###.#.###..####.#..",
+            "#.###.#.#####..#.##..",
+            "#.....#...#.##..###..",
+            "#######.#.##..##.#.#.",
+        ]);
+        // ... and version 3 with mask 2, which needs finder-like patterns
+        // at the quiet zone to be penalized
+        assert_eq!(rows(b"correct-horse-battery-staple"), [
+            "#######..#.#.###...##.#######",
+            "#.....#...#########.#.#.....#",
+            "#.###.#.####.#.#....#.#.###.#",
+            "#.###.#.##..#.##.#.#..#.###.#",
+            "#.###.#.###.#..##..#..#.###.#",
+            "#.....#.###.........#.#.....#",
+            "#######.#.#.#.#.#.#.#.#######",
+            "........##.#..#.#.#..........",
+            "#.#####...#.....##.##.#####..",
+            ".#..##..##..####..#######...#",
+            "####.##.##...####.#....#..#..",
+            ".#...#.##...##.#...#....##.##",
+            "#..####.#..##.##.#..##.#..##.",
+            "#.####..#.#....##..#######..#",
+            "#...#.##..###.....#.#.#..#...",
+            "###.##.##...#.#.#.#.#...##..#",
+            "#.###.##.#.##...##.....#..#.#",
+            "#...##...#.#.###.########..##",
+            "#..#..#.###.#####....#.#.##..",
+            "#..###..#..###.#..#.####.#..#",
+            "#..#..#.###...##.#..########.",
+            "........##..#..##.###...##.##",
+            "#######...#.#......##.#.#....",
+            "#.....#.#..##.#.#..##...#..#.",
+            "#.###.#.##.##...#.#.#####.#.#",
+            "#.###.#.#..#.###.##......##..",
+            "#.###.#.#..##.####.#.#####.#.",
+            "#.....#...#.#.##.#####..##.#.",
+            "#######.##..#.##...#......#..",
+        ]);
+    }
+}
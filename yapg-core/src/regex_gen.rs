@@ -0,0 +1,299 @@
+use std::io;
+
+use rand::Rng;
+use regex_syntax::hir::{
+    Class, Hir, HirKind, Literal, RepetitionKind, RepetitionRange,
+};
+
+/// Default of `RegexGenerator::repeat_limit`.
+const DEFAULT_REPEAT_LIMIT: u32 = 8;
+
+/// Regex as tree of the parts relevant for generation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Literal(char),
+    /// Inclusive ranges of scalar values, none spanning the surrogates.
+    Class(Vec<(u32, u32)>),
+    Concat(Vec<Node>),
+    Alternation(Vec<Node>),
+    /// Minimum and (if bounded) maximum number of repetitions.
+    Repeat(Box<Node>, u32, Option<u32>),
+}
+
+impl Node {
+    fn from_hir(hir: &Hir) -> io::Result<Self> {
+        let invalid = |msg: &str| {
+            io::Error::new(io::ErrorKind::InvalidInput, msg.to_string())
+        };
+        Ok(match hir.kind() {
+            // generated strings are matched as a whole anyway
+            HirKind::Empty | HirKind::Anchor(_) => Self::Concat(vec![]),
+            HirKind::Literal(Literal::Unicode(c)) => Self::Literal(*c),
+            HirKind::Literal(Literal::Byte(b)) if b.is_ascii() => {
+                Self::Literal(*b as char)
+            },
+            HirKind::Literal(Literal::Byte(_)) => {
+                return Err(invalid("Regex contains a non-ASCII byte"));
+            },
+            HirKind::Class(class) => {
+                let ranges: Vec<(u32, u32)> = match class {
+                    Class::Unicode(class) => class
+                        .iter()
+                        .flat_map(|r| split_surrogates(r.start(), r.end()))
+                        .collect(),
+                    // bytes beyond ASCII would not be valid UTF-8
+                    Class::Bytes(class) => class
+                        .iter()
+                        .filter(|r| r.start().is_ascii())
+                        .map(|r| (r.start() as u32, r.end().min(0x7f) as u32))
+                        .collect(),
+                };
+                if ranges.is_empty() {
+                    return Err(invalid(
+                        "Regex contains a class matching nothing",
+                    ));
+                }
+                Self::Class(ranges)
+            },
+            HirKind::WordBoundary(_) => {
+                return Err(invalid("Word boundaries are not supported"));
+            },
+            HirKind::Repetition(repetition) => {
+                let (min, max) = match &repetition.kind {
+                    RepetitionKind::ZeroOrOne => (0, Some(1)),
+                    RepetitionKind::ZeroOrMore => (0, None),
+                    RepetitionKind::OneOrMore => (1, None),
+                    RepetitionKind::Range(RepetitionRange::Exactly(n)) => {
+                        (*n, Some(*n))
+                    },
+                    RepetitionKind::Range(RepetitionRange::AtLeast(n)) => {
+                        (*n, None)
+                    },
+                    RepetitionKind::Range(RepetitionRange::Bounded(m, n)) => {
+                        (*m, Some(*n))
+                    },
+                };
+                let node = Self::from_hir(&repetition.hir)?;
+                Self::Repeat(Box::new(node), min, max)
+            },
+            HirKind::Group(group) => Self::from_hir(&group.hir)?,
+            HirKind::Concat(hirs) => Self::Concat(
+                hirs.iter().map(Self::from_hir).collect::<io::Result<_>>()?,
+            ),
+            HirKind::Alternation(hirs) => Self::Alternation(
+                hirs.iter().map(Self::from_hir).collect::<io::Result<_>>()?,
+            ),
+        })
+    }
+
+    /// Binary logarithm of the number of ways to generate a string, with
+    /// unbounded repetitions repeating at most `limit` times more than
+    /// their minimum.
+    fn bits(&self, limit: u32) -> f64 {
+        match self {
+            Self::Literal(_) => 0.0,
+            Self::Class(ranges) => (class_size(ranges) as f64).log2(),
+            Self::Concat(nodes) => nodes.iter().map(|n| n.bits(limit)).sum(),
+            Self::Alternation(nodes) => log2_sum(
+                &nodes.iter().map(|n| n.bits(limit)).collect::<Vec<_>>(),
+            ),
+            Self::Repeat(node, min, max) => {
+                log2_sum(&repeat_bits(node.bits(limit), *min, *max, limit))
+            },
+        }
+    }
+
+    /// Appends a string drawn uniformly among all ways to generate one.
+    fn sample<R: Rng>(&self, rng: &mut R, limit: u32, out: &mut String) {
+        match self {
+            Self::Literal(c) => out.push(*c),
+            Self::Class(ranges) => {
+                let mut i = rng.gen_range(0, class_size(ranges));
+                for (start, end) in ranges {
+                    let len = (end - start + 1) as u64;
+                    if i < len {
+                        // ranges never contain surrogates
+                        out.push(
+                            std::char::from_u32(start + i as u32).unwrap(),
+                        );
+                        return;
+                    }
+                    i -= len;
+                }
+            },
+            Self::Concat(nodes) => {
+                for node in nodes {
+                    node.sample(rng, limit, out);
+                }
+            },
+            Self::Alternation(nodes) => {
+                let bits: Vec<f64> =
+                    nodes.iter().map(|n| n.bits(limit)).collect();
+                nodes[pick(rng, &bits)].sample(rng, limit, out);
+            },
+            Self::Repeat(node, min, max) => {
+                let bits = repeat_bits(node.bits(limit), *min, *max, limit);
+                for _ in 0..*min as usize + pick(rng, &bits) {
+                    node.sample(rng, limit, out);
+                }
+            },
+        }
+    }
+}
+
+/// The scalar values from `start` to `end`, leaving out the surrogates.
+fn split_surrogates(start: char, end: char) -> Vec<(u32, u32)> {
+    let (start, end) = (start as u32, end as u32);
+    if start < 0xd800 && end > 0xdfff {
+        vec![(start, 0xd7ff), (0xe000, end)]
+    } else {
+        vec![(start, end)]
+    }
+}
+
+fn class_size(ranges: &[(u32, u32)]) -> u64 {
+    ranges.iter().map(|(start, end)| (end - start + 1) as u64).sum()
+}
+
+/// Bits of repeating a node of `bits` bits `min`, `min + 1`, … times.
+fn repeat_bits(bits: f64, min: u32, max: Option<u32>, limit: u32) -> Vec<f64> {
+    let max = max.unwrap_or_else(|| min.saturating_add(limit));
+    (min..=max).map(|k| k as f64 * bits).collect()
+}
+
+/// Binary logarithm of the sum of `2^b` for all `b` in `bits`.
+fn log2_sum(bits: &[f64]) -> f64 {
+    let max = bits.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    max + bits.iter().map(|b| (b - max).exp2()).sum::<f64>().log2()
+}
+
+/// Index into `bits`, drawn with probabilities proportional to `2^b`.
+fn pick<R: Rng>(rng: &mut R, bits: &[f64]) -> usize {
+    let max = bits.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let weights: Vec<f64> = bits.iter().map(|b| (b - max).exp2()).collect();
+    let mut x = rng.gen::<f64>() * weights.iter().sum::<f64>();
+    for (i, weight) in weights.iter().enumerate() {
+        if x < *weight {
+            return i;
+        }
+        x -= weight;
+    }
+    weights.len() - 1
+}
+
+/// Generates strings matching a regex, e.g. the exact format a legacy system
+/// validates credentials against. Strings are drawn uniformly among all ways
+/// the regex can generate them.
+///
+/// The syntax is that of the `regex` crate, except that `\d`, `\w`, `\s`
+/// and `.` are ASCII-only, like in most validators, and non-ASCII chars
+/// must be enabled with `(?u)`. Anchors are ignored, as generated strings
+/// always match as a whole, while word boundaries are not supported.
+/// Unbounded repetitions such as `*` and `+` are capped, see
+/// `RegexGenerator::repeat_limit`.
+///
+/// Requires the `regex-gen` feature.
+///
+/// # Example
+/// ```
+/// let mut rg =
+///     yapg_core::RegexGenerator::new(r"[A-Z]{2}\d{4}-[a-z]{6}").unwrap();
+/// let pw = rg.generate();
+/// assert_eq!(pw.len(), 13);
+/// assert!(pw[2..6].chars().all(|c| c.is_ascii_digit()));
+/// let letters = 26f64.log2();
+/// let expected = 8.0 * letters + 4.0 * 10f64.log2();
+/// assert!((rg.entropy() - expected).abs() < 1e-9);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RegexGenerator {
+    root: Node,
+    repeat_limit: u32,
+    rng: rand::ThreadRng,
+}
+
+impl RegexGenerator {
+    /// Creates a generator of strings matching `regex`. Fails if the regex
+    /// is invalid, contains a class matching no char or a word boundary.
+    pub fn new(regex: &str) -> io::Result<Self> {
+        let hir = regex_syntax::ParserBuilder::new()
+            .unicode(false)
+            .allow_invalid_utf8(true)
+            .build()
+            .parse(regex)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        Ok(Self {
+            root: Node::from_hir(&hir)?,
+            repeat_limit: DEFAULT_REPEAT_LIMIT,
+            rng: rand::thread_rng(),
+        })
+    }
+
+    /// Lets unbounded repetitions such as `*`, `+` and `{n,}` repeat at
+    /// most `limit` times more than their minimum (default: 8), consumes and
+    /// returns itself.
+    #[inline]
+    pub fn repeat_limit(mut self, limit: u32) -> Self {
+        self.repeat_limit = limit;
+        self
+    }
+
+    /// Generates one string.
+    pub fn generate(&mut self) -> String {
+        let mut out = String::new();
+        self.root.sample(&mut self.rng, self.repeat_limit, &mut out);
+        out
+    }
+
+    /// Generates a vector of `n` strings.
+    #[inline]
+    pub fn generate_n(&mut self, n: usize) -> Vec<String> {
+        (0..n).map(|_| self.generate()).collect()
+    }
+
+    /// Entropy in bits, i.e. the binary logarithm of the number of ways to
+    /// generate a string. For ambiguous regexes such as `a|a` or `a*a*`,
+    /// which generate some strings in more than one way, this overestimates
+    /// the entropy.
+    #[inline]
+    pub fn entropy(&self) -> f64 { self.root.bits(self.repeat_limit) }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::RegexGenerator;
+
+    #[test]
+    fn generating_matches() {
+        let regex = r"^(?:ab|[x-z]\d?)+(\.|-)?\w{2,3}(?u:é)*$";
+        let checker = regex::Regex::new(regex).unwrap();
+        let mut rg = RegexGenerator::new(regex).unwrap().repeat_limit(3);
+        for pw in rg.generate_n(200) {
+            assert!(checker.is_match(&pw), "{}", pw);
+            assert!(pw.chars().filter(|c| *c == 'é').count() <= 3);
+        }
+        assert!(RegexGenerator::new(r"\bword").is_err());
+        assert!(RegexGenerator::new(r"[^\x00-\x7f]").is_err());
+        assert!(RegexGenerator::new(r"(").is_err());
+        assert_eq!(RegexGenerator::new("").unwrap().generate(), "");
+    }
+
+    #[test]
+    fn sampling_uniformly() {
+        // 1 + 2 + 4 strings of 0, 1 or 2 chars
+        let mut rg = RegexGenerator::new("[ab]{0,2}").unwrap();
+        assert!((rg.entropy() - 7f64.log2()).abs() < 1e-9);
+        let mut counts = BTreeMap::new();
+        for pw in rg.generate_n(7000) {
+            *counts.entry(pw).or_insert(0) += 1;
+        }
+        assert_eq!(counts.len(), 7);
+        assert!(counts.values().all(|n| *n > 800 && *n < 1200), "{:?}", counts);
+
+        let rg = RegexGenerator::new("a*").unwrap().repeat_limit(4);
+        assert!((rg.entropy() - 5f64.log2()).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,278 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::{fmt, io};
+
+use rand::prng::chacha::ChaChaCore;
+use rand::prng::ChaChaRng;
+use rand::rngs::adapter::ReseedingRng;
+use rand::rngs::OsRng;
+use rand::{RngCore, SeedableRng};
+
+use crate::locked::wipe;
+use crate::{EntropySource, LockedBuffer};
+
+/// Bytes `RandomSource::ChaCha` generates before reseeding from the OS.
+const RESEED_THRESHOLD: u64 = 64 * 1024;
+
+/// The cryptographically secure RNG a generator draws from, for users whose
+/// policies prescribe one.
+///
+/// # Example
+/// ```
+/// use yapg_core::{PasswordGenerator, RandomSource};
+///
+/// let pwg = PasswordGenerator::from("abc").random_source(RandomSource::Os);
+/// assert_eq!(pwg.unwrap().rng_name(), "rand::OsRng");
+/// assert_eq!("chacha".parse::<RandomSource>().unwrap(), RandomSource::ChaCha);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum RandomSource {
+    /// The thread-local RNG of `rand`, buffered in userspace and reseeded
+    /// from the OS. The default.
+    Thread,
+    /// The OS RNG, queried for every draw (e.g. `getrandom(2)` on Linux).
+    Os,
+    /// ChaCha20, reseeded from the OS RNG every 64 KiB.
+    ChaCha,
+}
+
+impl fmt::Display for RandomSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Thread => "thread",
+            Self::Os => "os",
+            Self::ChaCha => "chacha",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl std::str::FromStr for RandomSource {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        match s {
+            "thread" => Ok(Self::Thread),
+            "os" => Ok(Self::Os),
+            "chacha" => Ok(Self::ChaCha),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid RNG {:?} (expected os, thread or chacha)", s),
+            )),
+        }
+    }
+}
+
+/// The random number generator backing a `PasswordGenerator`: a
+/// `RandomSource`, ChaCha20 from a fixed seed for reproducible output, or a
+/// user-provided `EntropySource`.
+#[derive(Debug, Clone)]
+pub(crate) enum GeneratorRng {
+    Thread(rand::ThreadRng),
+    Os(OsRng),
+    Reseeding(ReseedingRng<ChaChaCore, OsRng>),
+    Seeded { seed: Rc<LockedBuffer>, rng: ChaChaRng },
+    Source(SourceRng),
+}
+
+/// Adapts an `EntropySource` to `RngCore`, buffering its output so that
+/// e.g. hardware modules are not queried for every single char. Clones share
+/// the source.
+#[derive(Clone)]
+pub(crate) struct SourceRng(Rc<RefCell<BufferedSource>>);
+
+struct BufferedSource {
+    source: Box<dyn EntropySource>,
+    buffer: [u8; 64],
+    /// Number of bytes at the end of `buffer` not handed out yet.
+    available: usize,
+}
+
+impl SourceRng {
+    pub(crate) fn new<S: EntropySource + 'static>(source: S) -> Self {
+        Self(Rc::new(RefCell::new(BufferedSource {
+            source: Box::new(source),
+            buffer: [0; 64],
+            available: 0,
+        })))
+    }
+
+    /// Fills `dest` from the buffer, refilling it from the source as needed.
+    fn try_fill(&self, dest: &mut [u8]) -> std::io::Result<()> {
+        let mut inner = self.0.borrow_mut();
+        let inner = &mut *inner;
+        let mut filled = 0;
+        while filled < dest.len() {
+            if inner.available == 0 {
+                inner.source.fill(&mut inner.buffer)?;
+                inner.available = inner.buffer.len();
+            }
+            let n = inner.available.min(dest.len() - filled);
+            let start = inner.buffer.len() - inner.available;
+            dest[filled..filled + n]
+                .copy_from_slice(&inner.buffer[start..start + n]);
+            // handed out bytes must never be handed out again
+            for byte in inner.buffer[start..start + n].iter_mut() {
+                *byte = 0;
+            }
+            inner.available -= n;
+            filled += n;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for SourceRng {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SourceRng({})", self.0.borrow().source.name())
+    }
+}
+
+impl RngCore for SourceRng {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    /// # Panics
+    /// If the source fails, as there is no safe fallback.
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if let Err(e) = self.try_fill(dest) {
+            panic!("Entropy source failed: {}", e);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.try_fill(dest).map_err(|e| {
+            rand::Error::with_cause(
+                rand::ErrorKind::Unavailable,
+                "entropy source failed",
+                e,
+            )
+        })
+    }
+}
+
+/// ChaCha20 seeded with the 32 bytes of `seed`, wiping the copy made for
+/// seeding.
+fn chacha(seed: &[u8]) -> ChaChaRng {
+    let mut key = [0; 32];
+    key.copy_from_slice(seed);
+    let rng = ChaChaRng::from_seed(key);
+    wipe(&mut key);
+    rng
+}
+
+impl GeneratorRng {
+    /// Opens `source`, which fails if the OS RNG is unavailable.
+    pub(crate) fn open(source: RandomSource) -> io::Result<Self> {
+        let os = || OsRng::new().map_err(io::Error::other);
+        Ok(match source {
+            RandomSource::Thread => Self::Thread(rand::thread_rng()),
+            RandomSource::Os => Self::Os(os()?),
+            RandomSource::ChaCha => {
+                let mut reseeder = os()?;
+                let core = ChaChaCore::from_rng(&mut reseeder)
+                    .map_err(io::Error::other)?;
+                Self::Reseeding(ReseedingRng::new(
+                    core,
+                    RESEED_THRESHOLD,
+                    reseeder,
+                ))
+            },
+        })
+    }
+
+    pub(crate) fn seeded(seed: [u8; 32]) -> Self {
+        Self::seeded_locked(LockedBuffer::new(seed.to_vec()))
+    }
+
+    /// ChaCha20 seeded with the 32 bytes of `seed`, which stays locked in
+    /// memory as long as the generator lives, e.g. for derived secrets.
+    pub(crate) fn seeded_locked(seed: LockedBuffer) -> Self {
+        let rng = chacha(&seed);
+        Self::Seeded { seed: Rc::new(seed), rng }
+    }
+
+    /// The 32-byte seed of a `u64` seed: its little-endian bytes followed by
+    /// zeros, so that other implementations can reproduce it.
+    pub(crate) fn expand_seed(seed: u64) -> [u8; 32] {
+        let mut expanded = [0; 32];
+        expanded[..8].copy_from_slice(&seed.to_le_bytes());
+        expanded
+    }
+
+    /// The seed of substream `index`: the first 32 bytes of ChaCha20 stream
+    /// `index + 1` under `seed`. Stream 0 is the one of the seeded generator
+    /// itself, so all substreams and the parent stream are disjoint.
+    pub(crate) fn substream_seed(seed: &[u8], index: u32) -> LockedBuffer {
+        let mut rng = chacha(seed);
+        rng.set_stream(index as u64 + 1);
+        let mut substream = LockedBuffer::new(vec![0; 32]);
+        rng.fill_bytes(&mut substream);
+        substream
+    }
+
+    pub(crate) fn name(&self) -> String {
+        match self {
+            Self::Thread(_) => "rand::ThreadRng".to_string(),
+            Self::Os(_) => "rand::OsRng".to_string(),
+            Self::Reseeding(_) => {
+                "rand::ChaChaRng (reseeding from rand::OsRng)".to_string()
+            },
+            Self::Seeded { .. } => "rand::ChaChaRng (seeded)".to_string(),
+            Self::Source(rng) => rng.0.borrow().source.name(),
+        }
+    }
+}
+
+impl RngCore for GeneratorRng {
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            Self::Thread(rng) => rng.next_u32(),
+            Self::Os(rng) => rng.next_u32(),
+            Self::Reseeding(rng) => rng.next_u32(),
+            Self::Seeded { rng, .. } => rng.next_u32(),
+            Self::Source(rng) => rng.next_u32(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            Self::Thread(rng) => rng.next_u64(),
+            Self::Os(rng) => rng.next_u64(),
+            Self::Reseeding(rng) => rng.next_u64(),
+            Self::Seeded { rng, .. } => rng.next_u64(),
+            Self::Source(rng) => rng.next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match self {
+            Self::Thread(rng) => rng.fill_bytes(dest),
+            Self::Os(rng) => rng.fill_bytes(dest),
+            Self::Reseeding(rng) => rng.fill_bytes(dest),
+            Self::Seeded { rng, .. } => rng.fill_bytes(dest),
+            Self::Source(rng) => rng.fill_bytes(dest),
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        match self {
+            Self::Thread(rng) => rng.try_fill_bytes(dest),
+            Self::Os(rng) => rng.try_fill_bytes(dest),
+            Self::Reseeding(rng) => rng.try_fill_bytes(dest),
+            Self::Seeded { rng, .. } => rng.try_fill_bytes(dest),
+            Self::Source(rng) => rng.try_fill_bytes(dest),
+        }
+    }
+}
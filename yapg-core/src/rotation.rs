@@ -0,0 +1,250 @@
+use std::{fmt, io};
+
+use crate::date::{civil_from_days, days_from_civil, format_date};
+use crate::locked::wipe;
+use crate::rng::GeneratorRng;
+use crate::{LockedBuffer, PasswordGenerator};
+
+/// Length of the periods after which a `Rotation` yields a new secret. All
+/// periods are aligned to the UTC calendar; weeks start on Mondays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RotationPeriod {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl RotationPeriod {
+    /// Number of the period containing `secs` (seconds since the unix
+    /// epoch), counted from the one containing the epoch.
+    pub fn index_at(&self, secs: u64) -> u64 {
+        let days = (secs / 86400) as i64;
+        let (year, month, _) = civil_from_days(days);
+        match self {
+            Self::Day => days as u64,
+            // 1970-01-01 was a thursday
+            Self::Week => ((days + 3) / 7) as u64,
+            Self::Month => ((year - 1970) * 12 + month as i64 - 1) as u64,
+            Self::Year => (year - 1970) as u64,
+        }
+    }
+
+    /// Start of period `index` in days since the unix epoch, which is
+    /// negative for the first week.
+    fn start_day(&self, index: u64) -> i64 {
+        let index = index as i64;
+        match self {
+            Self::Day => index,
+            Self::Week => index * 7 - 3,
+            Self::Month => {
+                days_from_civil(1970 + index / 12, (index % 12) as u32 + 1, 1)
+            },
+            Self::Year => days_from_civil(1970 + index, 1, 1),
+        }
+    }
+
+    /// Start of period `index` in seconds since the unix epoch.
+    pub fn start(&self, index: u64) -> u64 {
+        self.start_day(index).max(0) as u64 * 86400
+    }
+
+    /// Human-readable name of period `index`, e.g. `2026-10` for a month.
+    pub fn label(&self, index: u64) -> String {
+        let date = format_date(self.start_day(index));
+        match self {
+            Self::Day => date,
+            Self::Week => format!("week of {}", date),
+            Self::Month => date[..7].to_string(),
+            Self::Year => date[..4].to_string(),
+        }
+    }
+
+    fn id(&self) -> u8 {
+        match self {
+            Self::Day => b'd',
+            Self::Week => b'w',
+            Self::Month => b'm',
+            Self::Year => b'y',
+        }
+    }
+}
+
+impl std::str::FromStr for RotationPeriod {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        match s {
+            "day" => Ok(Self::Day),
+            "week" => Ok(Self::Week),
+            "month" => Ok(Self::Month),
+            "year" => Ok(Self::Year),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid period (expected day|week|month|year): {}", s),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for RotationPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+            Self::Year => "year",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Derives one secret per period from a master key and a label, so that
+/// rotating a shared service password becomes re-running the same command
+/// in the next period, on any machine holding the master key.
+///
+/// The secrets are drawn by a `PasswordGenerator` seeded with the
+/// HMAC-SHA256 of label and period under the master key. The master key
+/// and the seeds are kept in `LockedBuffer`s.
+///
+/// # Example
+/// ```
+/// use yapg_core::{PasswordGenerator, Rotation, RotationPeriod};
+///
+/// let rotation =
+///     Rotation::new(vec![42; 32], "db-backup", RotationPeriod::Month)
+///         .unwrap();
+/// let pwg = PasswordGenerator::from("abcdef0123456789").length(16);
+///
+/// // 2026-10-17 and 2026-10-31 are in the same month, 2026-11-01 is not
+/// let october = rotation.secret_at(pwg.clone(), 1_792_195_200);
+/// assert_eq!(october, rotation.secret_at(pwg.clone(), 1_793_404_800));
+/// assert_ne!(october, rotation.secret_at(pwg.clone(), 1_793_491_200));
+/// assert_eq!(october.len(), 16);
+///
+/// // around the turn of the month, both secrets are valid
+/// let day = 86400;
+/// let valid = rotation.periods_valid_at(1_793_491_200, 2 * day);
+/// let labels: Vec<String> =
+///     valid.iter().map(|i| RotationPeriod::Month.label(*i)).collect();
+/// assert_eq!(labels, ["2026-10", "2026-11"]);
+/// ```
+#[derive(Debug)]
+pub struct Rotation {
+    master: LockedBuffer,
+    label: String,
+    period: RotationPeriod,
+}
+
+impl Rotation {
+    /// Creates a rotation of the secrets called `label`. The master key must
+    /// be 32 random bytes.
+    pub fn new(
+        master: Vec<u8>,
+        label: &str,
+        period: RotationPeriod,
+    ) -> io::Result<Self> {
+        if master.len() != 32 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Master key must be 32 bytes, not {}", master.len()),
+            ));
+        }
+        let master = LockedBuffer::new(master);
+        Ok(Self { master, label: label.to_string(), period })
+    }
+
+    /// The length of the periods.
+    #[inline]
+    pub fn period(&self) -> RotationPeriod { self.period }
+
+    /// Seed of the generator for period `index`: the HMAC-SHA256 under the
+    /// master key of the label's length (`u64`, little-endian), the label,
+    /// the period's id (`d`, `w`, `m` or `y`) and `index` (`u64`,
+    /// little-endian).
+    fn seed(&self, index: u64) -> LockedBuffer {
+        let label = self.label.as_bytes();
+        let mut input = Vec::with_capacity(label.len() + 17);
+        input.extend_from_slice(&(label.len() as u64).to_le_bytes());
+        input.extend_from_slice(label);
+        input.push(self.period.id());
+        input.extend_from_slice(&index.to_le_bytes());
+
+        let mut mac = hmac_sha256::HMAC::mac(&input, &self.master[..]);
+        let seed = LockedBuffer::new(mac.to_vec());
+        wipe(&mut mac);
+        seed
+    }
+
+    /// The secret of period `index`, drawn by `pwg`.
+    pub fn secret(&self, mut pwg: PasswordGenerator, index: u64) -> String {
+        pwg.rng = GeneratorRng::seeded_locked(self.seed(index));
+        pwg.generate()
+    }
+
+    /// The secret of the period containing `secs` (seconds since the unix
+    /// epoch), drawn by `pwg`.
+    pub fn secret_at(&self, pwg: PasswordGenerator, secs: u64) -> String {
+        self.secret(pwg, self.period.index_at(secs))
+    }
+
+    /// Periods whose secrets should be accepted at `secs`, for rolling out
+    /// new secrets gradually: the current one, and neighbours starting or
+    /// ending within `overlap` seconds. In ascending order.
+    pub fn periods_valid_at(&self, secs: u64, overlap: u64) -> Vec<u64> {
+        let first = self.period.index_at(secs.saturating_sub(overlap));
+        let last = self.period.index_at(secs.saturating_add(overlap));
+        (first..=last).collect()
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::{Rotation, RotationPeriod};
+
+    #[test]
+    fn indexing_periods() {
+        use RotationPeriod::*;
+        // 2026-10-17 (a saturday)
+        let now = 1_792_195_200;
+        for period in [Day, Week, Month, Year].iter() {
+            let index = period.index_at(now);
+            assert!(period.start(index) <= now, "{}", period);
+            assert!(now < period.start(index + 1), "{}", period);
+            assert_eq!(period.index_at(period.start(index)), index);
+        }
+        assert_eq!(Day.label(Day.index_at(now)), "2026-10-17");
+        assert_eq!(Week.label(Week.index_at(now)), "week of 2026-10-12");
+        assert_eq!(Month.label(Month.index_at(now)), "2026-10");
+        assert_eq!(Year.label(Year.index_at(now)), "2026");
+        assert_eq!(Week.start(0), 0);
+    }
+
+    #[test]
+    fn seeds_depend_on_all_inputs() {
+        let a = Rotation::new(vec![1; 32], "a", RotationPeriod::Day).unwrap();
+        let b = Rotation::new(vec![1; 32], "b", RotationPeriod::Day).unwrap();
+        let c = Rotation::new(vec![2; 32], "a", RotationPeriod::Day).unwrap();
+        let d = Rotation::new(vec![1; 32], "a", RotationPeriod::Week).unwrap();
+        let seeds = [a.seed(0), a.seed(1), b.seed(0), c.seed(0), d.seed(0)];
+        for (i, x) in seeds.iter().enumerate() {
+            assert!(seeds[i + 1..].iter().all(|y| x[..] != y[..]));
+        }
+        assert_eq!(a.seed(7)[..], a.seed(7)[..]);
+        assert!(Rotation::new(vec![1; 16], "a", RotationPeriod::Day).is_err());
+    }
+
+    #[test]
+    fn deriving_seeds() {
+        // as computed by Python's hmac module
+        let rotation =
+            Rotation::new(vec![42; 32], "db-backup", RotationPeriod::Month)
+                .unwrap();
+        let seed = crate::hex_encode(&rotation.seed(681));
+        assert_eq!(
+            seed,
+            "363f9cb52f37166c3c4417191a3a009baeb718c2770af638743b885754296283"
+        );
+    }
+}
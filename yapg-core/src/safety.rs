@@ -0,0 +1,182 @@
+use std::{fmt, io};
+
+/// Target contexts into which generated passwords may be embedded verbatim.
+///
+/// Each context knows the characters that would need escaping in it, so that
+/// a charset can be restricted to characters that are safe to paste into e.g.
+/// a config template without further treatment.
+///
+/// | SafetyContext | name            | forbidden chars              |
+/// | ------------- | --------------- | ---------------------------- |
+/// | `Json`        | `"json"`        | `"`, `\`                     |
+/// | `Xml`         | `"xml"`         | `<`, `>`, `&`, `'`, `"`      |
+/// | `SqlLiteral`  | `"sql-literal"` | `'`, `\`                     |
+/// | `Url`         | `"url"`         | all but `A-Z a-z 0-9 - . _ ~` |
+/// | `BasicAuth`   | `"basic-auth"`  | `:`                          |
+/// | `Pgpass`      | `"pgpass"`      | `:`, `\`                     |
+///
+/// Control characters are never safe and are rejected in every context, as
+/// are non-ASCII characters in URLs and basic-auth headers.
+///
+/// # Example
+///
+/// ```
+/// use yapg_core::SafetyContext;
+///
+/// let charset = SafetyContext::Json.restrict(vec!['a', '"', '\\', 'b']);
+/// assert_eq!(charset, vec!['a', 'b']);
+/// assert_eq!("xml".parse::<SafetyContext>().unwrap(), SafetyContext::Xml);
+/// assert_eq!(SafetyContext::Url.unsafe_chars("a@b:c@"), vec!['@', ':']);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SafetyContext {
+    /// Inside a double-quoted JSON string.
+    Json,
+    /// Inside XML text nodes and (single- or double-quoted) attributes.
+    Xml,
+    /// Inside a single-quoted SQL string literal. Backslashes are excluded,
+    /// as MySQL treats them as escapes by default.
+    SqlLiteral,
+    /// Inside the userinfo of a URL, e.g. a database connection string,
+    /// without percent-encoding.
+    Url,
+    /// Inside an HTTP basic-auth header (RFC 7617), whose user-id and
+    /// password are joined by a colon and whose charset is ambiguous beyond
+    /// ASCII.
+    BasicAuth,
+    /// Inside a PostgreSQL `.pgpass` file, whose fields are separated by
+    /// colons and escape with backslashes.
+    Pgpass,
+}
+
+impl SafetyContext {
+    /// Characters that would need escaping in this context.
+    pub fn forbidden(&self) -> &'static [char] {
+        match self {
+            Self::Json => &['"', '\\'],
+            Self::Xml => &['<', '>', '&', '\'', '"'],
+            Self::SqlLiteral => &['\'', '\\'],
+            Self::Url => &[
+                ' ', '!', '"', '#', '$', '%', '&', '\'', '(', ')', '*', '+',
+                ',', '/', ':', ';', '<', '=', '>', '?', '@', '[', '\\', ']',
+                '^', '`', '{', '|', '}',
+            ],
+            Self::BasicAuth => &[':'],
+            Self::Pgpass => &[':', '\\'],
+        }
+    }
+
+    /// Whether `c` can be embedded in this context without escaping.
+    #[inline]
+    pub fn is_safe(&self, c: char) -> bool {
+        let ascii_only = matches!(self, Self::Url | Self::BasicAuth);
+        !c.is_control()
+            && !self.forbidden().contains(&c)
+            && (c.is_ascii() || !ascii_only)
+    }
+
+    /// The distinct characters of `password` that are unsafe in this
+    /// context, in order of their first occurrence.
+    pub fn unsafe_chars(&self, password: &str) -> Vec<char> {
+        let mut chars = vec![];
+        for c in password.chars() {
+            if !self.is_safe(c) && !chars.contains(&c) {
+                chars.push(c);
+            }
+        }
+        chars
+    }
+
+    /// Removes all characters from `charset` that are unsafe in this context.
+    pub fn restrict(&self, mut charset: Vec<char>) -> Vec<char> {
+        charset.retain(|c| self.is_safe(*c));
+        charset
+    }
+}
+
+impl std::str::FromStr for SafetyContext {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        match s {
+            "json" => Ok(Self::Json),
+            "xml" => Ok(Self::Xml),
+            "sql-literal" => Ok(Self::SqlLiteral),
+            "url" => Ok(Self::Url),
+            "basic-auth" => Ok(Self::BasicAuth),
+            "pgpass" => Ok(Self::Pgpass),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid safety context: {}", s),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SafetyContext {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Json => write!(f, "json"),
+            Self::Xml => write!(f, "xml"),
+            Self::SqlLiteral => write!(f, "sql-literal"),
+            Self::Url => write!(f, "url"),
+            Self::BasicAuth => write!(f, "basic-auth"),
+            Self::Pgpass => write!(f, "pgpass"),
+        }
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::SafetyContext;
+    use crate::CharsetSpec;
+
+    #[test]
+    fn parsing_safety_contexts() {
+        assert_eq!(
+            "json".parse::<SafetyContext>().unwrap(),
+            SafetyContext::Json
+        );
+        assert_eq!("xml".parse::<SafetyContext>().unwrap(), SafetyContext::Xml);
+        assert_eq!(
+            "sql-literal".parse::<SafetyContext>().unwrap(),
+            SafetyContext::SqlLiteral
+        );
+        assert_eq!(
+            "basic-auth".parse::<SafetyContext>().unwrap(),
+            SafetyContext::BasicAuth
+        );
+        assert_eq!(SafetyContext::Pgpass.to_string(), "pgpass");
+        assert!("html".parse::<SafetyContext>().is_err());
+    }
+
+    #[test]
+    fn restricting_printable_ascii() {
+        for ctx in [
+            SafetyContext::Json,
+            SafetyContext::Xml,
+            SafetyContext::SqlLiteral,
+            SafetyContext::Url,
+            SafetyContext::BasicAuth,
+            SafetyContext::Pgpass,
+        ]
+        .iter()
+        {
+            let charset =
+                ctx.restrict(CharsetSpec::printable_ascii().construct());
+            assert_eq!(charset.len(), 95 - ctx.forbidden().len());
+            assert!(charset.iter().all(|c| !ctx.forbidden().contains(c)));
+        }
+    }
+
+    #[test]
+    fn finding_unsafe_chars() {
+        let url = SafetyContext::Url;
+        assert_eq!(url.restrict(CharsetSpec::std64().construct()).len(), 64);
+        assert_eq!(url.unsafe_chars("a-b.c_d~e"), Vec::<char>::new());
+        assert_eq!(url.unsafe_chars("ä b"), vec!['ä', ' ']);
+        assert_eq!(SafetyContext::BasicAuth.unsafe_chars("a:b@ä"), [':', 'ä']);
+        assert_eq!(SafetyContext::Pgpass.unsafe_chars("a\\b:ä"), ['\\', ':']);
+    }
+}
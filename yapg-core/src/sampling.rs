@@ -0,0 +1,119 @@
+use std::io;
+
+use rand::{Rng, RngCore};
+
+use crate::Grapheme;
+
+/// Source of the charset indices passwords are composed of, see
+/// `PasswordGenerator::generate_with`. Separating sampling from composition
+/// allows testing the latter with fixed indices and plugging in other
+/// samplers.
+///
+/// # Example
+/// ```
+/// use yapg_core::{IndexSampler, PasswordGenerator};
+///
+/// /// Cycles through the charset, for tests only!
+/// struct Cycle(usize);
+///
+/// impl IndexSampler for Cycle {
+///     fn sample(&mut self, charset_len: usize, length: usize) -> Vec<usize> {
+///         (0..length)
+///             .map(|_| {
+///                 self.0 += 1;
+///                 (self.0 - 1) % charset_len
+///             })
+///             .collect()
+///     }
+/// }
+///
+/// let mut pwg = PasswordGenerator::from("abc").length(5);
+/// assert_eq!(pwg.generate_with(&mut Cycle(0)).unwrap(), "abcab");
+/// ```
+pub trait IndexSampler {
+    /// Draws `length` indices, each from `0..charset_len`.
+    fn sample(&mut self, charset_len: usize, length: usize) -> Vec<usize>;
+}
+
+/// Samples uniformly from any RNG of `rand`.
+pub(crate) struct UniformSampler<'a, R: RngCore>(pub(crate) &'a mut R);
+
+impl<R: RngCore> IndexSampler for UniformSampler<'_, R> {
+    fn sample(&mut self, charset_len: usize, length: usize) -> Vec<usize> {
+        (0..length).map(|_| self.0.gen_range(0, charset_len)).collect()
+    }
+}
+
+/// Composes a password of the charset items at `indices`. Pure, i.e. free
+/// of randomness: all randomness lies in the indices. Fails if an index is
+/// out of range.
+///
+/// # Example
+/// ```
+/// let charset: Vec<yapg_core::Grapheme> =
+///     vec!['a'.into(), "ch".parse().unwrap(), 'z'.into()];
+/// assert_eq!(yapg_core::compose(&charset, &[1, 0, 2]).unwrap(), "chaz");
+/// assert!(yapg_core::compose(&charset, &[3]).is_err());
+/// ```
+pub fn compose(charset: &[Grapheme], indices: &[usize]) -> io::Result<String> {
+    // exact capacity, so that growing leaves no copies in freed memory
+    let bytes = indices
+        .iter()
+        .filter_map(|i| charset.get(*i))
+        .map(|item| item.as_str().len())
+        .sum();
+    let mut password = String::with_capacity(bytes);
+    for i in indices {
+        let item = charset.get(*i).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Index {} out of range for a charset of {} items",
+                    i,
+                    charset.len()
+                ),
+            )
+        })?;
+        password.push_str(item.as_str());
+    }
+    Ok(password)
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::{compose, IndexSampler, UniformSampler};
+    use crate::Grapheme;
+
+    #[test]
+    fn composing_all_index_sequences() {
+        let charset: Vec<Grapheme> =
+            ["a", "b", "ch"].iter().map(|s| s.parse().unwrap()).collect();
+        let length = 4;
+        let mut passwords = BTreeSet::new();
+        for n in 0..3usize.pow(length) {
+            let indices: Vec<usize> =
+                (0..length).map(|k| n / 3usize.pow(k) % 3).collect();
+            let password = compose(&charset, &indices).unwrap();
+            let items = indices.iter().map(|i| charset[*i].as_str());
+            assert_eq!(password, items.collect::<String>());
+            assert_eq!(password.capacity(), password.len());
+            passwords.insert(password);
+        }
+        // unambiguous items give distinct passwords for distinct indices
+        assert_eq!(passwords.len(), 81);
+        assert_eq!(compose(&charset, &[]).unwrap(), "");
+        assert!(compose(&[], &[0]).is_err());
+    }
+
+    #[test]
+    fn sampling_within_range() {
+        let mut rng = rand::thread_rng();
+        let indices = UniformSampler(&mut rng).sample(3, 300);
+        assert_eq!(indices.len(), 300);
+        assert!(indices.iter().all(|i| *i < 3));
+        assert_eq!(indices.iter().collect::<BTreeSet<_>>().len(), 3);
+    }
+}
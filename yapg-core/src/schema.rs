@@ -0,0 +1,77 @@
+//! JSON Schemas of the documents yapg reads and writes, for validation and
+//! completion in editors and for linting in CI. The schemas are published in
+//! the `schemas` directory of the repository.
+//!
+//! yapg has no configuration file (yet), so there is no schema for one.
+
+/// Names of the available schemas, see `json_schema`.
+pub static SCHEMAS: [&str; 3] = ["policy", "manifest", "credentials"];
+
+/// The JSON Schema (draft 2020-12) called `name`:
+///
+/// - `policy`: a `Policy`, as serialized with the `serde` feature
+/// - `manifest`: a batch of passwords as printed by `OutputFormat::Json`,
+///   optionally with `Provenance`
+/// - `credentials`: a `CredentialSet`, as serialized with the `serde`
+///   feature
+///
+/// # Example
+/// ```
+/// let schema = yapg_core::json_schema("policy").unwrap();
+/// assert!(schema.contains("\"min_length\""));
+/// assert!(yapg_core::json_schema("nope").is_none());
+/// ```
+pub fn json_schema(name: &str) -> Option<&'static str> {
+    match name {
+        "policy" => Some(include_str!("../schemas/policy.schema.json")),
+        "manifest" => Some(include_str!("../schemas/manifest.schema.json")),
+        "credentials" => {
+            Some(include_str!("../schemas/credentials.schema.json"))
+        },
+        _ => None,
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::{json_schema, SCHEMAS};
+    use crate::CharsetName;
+
+    #[test]
+    fn schemas_are_valid_json() {
+        for name in SCHEMAS.iter() {
+            let schema: serde_json::Value =
+                serde_json::from_str(json_schema(name).unwrap()).unwrap();
+            assert!(schema["title"].is_string(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn policy_schema_lists_all_charsets() {
+        let schema: serde_json::Value =
+            serde_json::from_str(json_schema("policy").unwrap()).unwrap();
+        let names = schema["$defs"]["charset_name"]["enum"].as_array().unwrap();
+        let mut expected: Vec<CharsetName> = CharsetName::ATOMIC.to_vec();
+        expected.extend([CharsetName::Alpha, CharsetName::Special].iter());
+        assert_eq!(names.len(), expected.len());
+        for (name, charset) in names.iter().zip(expected.iter()) {
+            assert_eq!(name.as_str().unwrap(), format!("{:?}", charset));
+        }
+    }
+
+    #[test]
+    fn manifest_schema_matches_output() {
+        let schema: serde_json::Value =
+            serde_json::from_str(json_schema("manifest").unwrap()).unwrap();
+        let pwg = crate::PasswordGenerator::from("ab").length(4);
+        let provenance = crate::Provenance::of(&pwg);
+        let fields = &schema["properties"]["provenance"]["required"];
+        let expected: Vec<&str> =
+            provenance.fields().iter().map(|(k, _)| *k).collect();
+        assert_eq!(fields.as_array().unwrap().len(), expected.len());
+        for (field, key) in fields.as_array().unwrap().iter().zip(expected) {
+            assert_eq!(field.as_str().unwrap(), key);
+        }
+    }
+}
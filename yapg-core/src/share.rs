@@ -0,0 +1,187 @@
+use std::time::Duration;
+use std::{fmt, io};
+
+/// Base URL of the public onetimesecret.com instance.
+pub const ONETIMESECRET_URL: &str = "https://onetimesecret.com";
+
+/// Seconds a shared secret stays retrievable by default (a week).
+pub const DEFAULT_SHARE_TTL: u64 = 7 * 86400;
+
+/// Seconds to wait for the service before giving up.
+const SHARE_TIMEOUT_SECS: u64 = 10;
+
+/// Services holding secrets behind self-destructing links, see
+/// `ShareClient`.
+///
+/// Parses from `"onetimesecret"` or an `https://` URL of a custom service.
+///
+/// # Example
+/// ```
+/// use yapg_core::ShareTarget;
+///
+/// let ots: ShareTarget = "onetimesecret".parse().unwrap();
+/// assert_eq!(ots, ShareTarget::OneTimeSecret);
+/// let custom: ShareTarget =
+///     "https://secrets.example.com/new".parse().unwrap();
+/// assert_eq!(custom.to_string(), "https://secrets.example.com/new");
+/// assert!("http://secrets.example.com/new".parse::<ShareTarget>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ShareTarget {
+    /// The public onetimesecret.com, used anonymously.
+    OneTimeSecret,
+    /// A custom service, receiving the secret as plain-text body of a POST
+    /// request to this URL and answering with the link alone.
+    Url(String),
+}
+
+impl fmt::Display for ShareTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::OneTimeSecret => write!(f, "onetimesecret"),
+            Self::Url(url) => write!(f, "{}", url),
+        }
+    }
+}
+
+impl std::str::FromStr for ShareTarget {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        match s {
+            "onetimesecret" => Ok(Self::OneTimeSecret),
+            // secrets must not cross the network in the clear
+            url if url.starts_with("https://") => Ok(Self::Url(url.into())),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Unknown share target {}, expected onetimesecret or an \
+                     https:// URL",
+                    s
+                ),
+            )),
+        }
+    }
+}
+
+/// Client posting secrets to a one-time-secret service, which hands out a
+/// link that shows the secret once and then destroys it, e.g. to pass
+/// passwords on to people over chat without leaving them in its history.
+///
+/// The service does see the secret: use one you trust, or host your own.
+///
+/// # Example
+/// ```no_run
+/// use yapg_core::{ShareClient, ShareTarget};
+///
+/// let client = ShareClient::new(ShareTarget::OneTimeSecret).ttl(3600);
+/// let link = client.share("correct horse battery staple").unwrap();
+/// println!("{}", link);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ShareClient {
+    target: ShareTarget,
+    ttl: u64,
+    agent: ureq::Agent,
+}
+
+impl ShareClient {
+    /// Creates a client of `target`, with links expiring after
+    /// `DEFAULT_SHARE_TTL` seconds.
+    pub fn new(target: ShareTarget) -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .timeout(Duration::from_secs(SHARE_TIMEOUT_SECS))
+            .user_agent(concat!("yapg/", env!("CARGO_PKG_VERSION")))
+            .build();
+        Self { target, ttl: DEFAULT_SHARE_TTL, agent }
+    }
+
+    /// Sets the seconds after which unopened links expire, consumes and
+    /// returns itself. Custom services decide on their own.
+    #[inline]
+    pub fn ttl(mut self, seconds: u64) -> Self {
+        self.ttl = seconds;
+        self
+    }
+
+    /// Posts `secret` to the service and returns the link retrieving it.
+    /// Fails if the service cannot be reached or answers with something
+    /// other than a link.
+    pub fn share(&self, secret: &str) -> io::Result<String> {
+        let failed = |e: ureq::Error| {
+            io::Error::other(format!(
+                "Sharing via {} failed: {}",
+                self.target, e
+            ))
+        };
+        match &self.target {
+            ShareTarget::OneTimeSecret => {
+                let body = self
+                    .agent
+                    .post(&format!("{}/api/v1/share", ONETIMESECRET_URL))
+                    .send_form(&[
+                        ("secret", secret),
+                        ("ttl", &self.ttl.to_string()),
+                    ])
+                    .map_err(failed)?
+                    .into_string()?;
+                let key =
+                    json_string(&body, "secret_key").ok_or_else(|| {
+                        io::Error::other("onetimesecret answered without a key")
+                    })?;
+                Ok(format!("{}/secret/{}", ONETIMESECRET_URL, key))
+            },
+            ShareTarget::Url(url) => {
+                let body = self
+                    .agent
+                    .post(url)
+                    .set("Content-Type", "text/plain; charset=utf-8")
+                    .send_string(secret)
+                    .map_err(failed)?
+                    .into_string()?;
+                let link = body.trim();
+                if !link.starts_with("https://")
+                    || link.contains(char::is_whitespace)
+                {
+                    return Err(io::Error::other(format!(
+                        "{} answered with something other than an https:// \
+                         link",
+                        url
+                    )));
+                }
+                Ok(link.to_string())
+            },
+        }
+    }
+}
+
+/// The string value of `key` in a flat JSON object, if it holds one without
+/// escapes (as random keys do).
+fn json_string<'a>(json: &'a str, key: &str) -> Option<&'a str> {
+    let rest = &json[json.find(&format!("\"{}\"", key))? + key.len() + 2..];
+    let rest = rest.trim_start().strip_prefix(':')?.trim_start();
+    let value = rest.strip_prefix('"')?.split('"').next()?;
+    match value.contains('\\') {
+        true => None,
+        false => Some(value),
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::json_string;
+
+    #[test]
+    fn extracting_keys() {
+        let body = concat!(
+            r#"{"custid":"anon","metadata_key":"m3t4","#,
+            r#" "secret_key" : "s3cr3t","ttl":604800}"#
+        );
+        assert_eq!(json_string(body, "secret_key"), Some("s3cr3t"));
+        assert_eq!(json_string(body, "metadata_key"), Some("m3t4"));
+        assert_eq!(json_string(body, "ttl"), None);
+        assert_eq!(json_string(body, "state"), None);
+        assert_eq!(json_string(r#"{"secret_key":"a\"b"}"#, "secret_key"), None);
+    }
+}
@@ -0,0 +1,249 @@
+//! Measuring how much entropy a password policy destroys by rejecting
+//! random passwords.
+
+use std::convert::TryFrom;
+use std::io;
+
+use crate::{CharsetName, CharsetSpec, PasswordGenerator, Policy};
+
+/// A policy along with the random passwords put to it, read from a small
+/// TOML file:
+///
+/// ```toml
+/// # uniform random passwords of 8 chars drawn from these charsets
+/// charset = "LUNS"
+/// length = 8
+/// # the policy: lengths, charsets required, most identical chars in a row
+/// min_length = 8
+/// max_length = 16
+/// required = "LUNS"
+/// max_repeat = 2
+/// ```
+///
+/// All keys are optional. The charset defaults to std64, the length to the
+/// minimum length, and the policy to allowing everything.
+///
+/// # Example
+/// ```
+/// use yapg_core::PolicySpec;
+///
+/// let spec: PolicySpec =
+///     "charset = \"N\"\nlength = 2\nmax_repeat = 1".parse().unwrap();
+/// assert!(spec.allows("12"));
+/// assert!(!spec.allows("11"));
+/// // 10 of the 100 possible passwords are rejected
+/// let sim = spec.simulate(100_000);
+/// assert!((sim.pass_rate() - 0.9).abs() < 0.01);
+/// assert!((sim.effective_entropy() - 90f64.log2()).abs() < 0.02);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PolicySpec {
+    /// Charset the random passwords are drawn from.
+    pub charset: CharsetSpec,
+    /// Length of the random passwords.
+    pub length: usize,
+    /// Lengths and charsets the policy demands.
+    pub policy: Policy,
+    /// Most identical chars in a row the policy accepts, if limited.
+    pub max_repeat: Option<usize>,
+}
+
+impl PolicySpec {
+    /// Whether `password` satisfies the policy.
+    pub fn allows(&self, password: &str) -> bool {
+        let chars: Vec<char> = password.chars().collect();
+        self.policy.allows(password)
+            && self.max_repeat.is_none_or(|max| {
+                chars.windows(max + 1).all(|w| w.iter().any(|c| *c != w[0]))
+            })
+    }
+
+    /// Puts `samples` uniform random passwords to the policy.
+    pub fn simulate(&self, samples: usize) -> PolicySimulation {
+        let mut pwg = PasswordGenerator::new(self.charset.build(), self.length);
+        let passed =
+            (0..samples).filter(|_| self.allows(&pwg.generate())).count();
+        PolicySimulation { samples, passed, entropy: pwg.entropy_bits() }
+    }
+}
+
+impl std::str::FromStr for PolicySpec {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        let invalid = |line: usize, msg: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Line {} of policy file: {}", line + 1, msg),
+            )
+        };
+        let (mut charset, mut length) = (CharsetSpec::std64(), None);
+        let (mut policy, mut max_repeat) = (Policy::default(), None);
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| invalid(i, "expected key = value".into()))?;
+            let (key, value) = (key.trim(), toml_value(value.trim()));
+            let string = || match value {
+                TomlValue::String(s) => Ok(s),
+                _ => Err(invalid(i, format!("{} must be a string", key))),
+            };
+            let integer = || match value {
+                TomlValue::Integer(n) => Ok(n),
+                _ => Err(invalid(i, format!("{} must be an integer", key))),
+            };
+            match key {
+                "charset" => charset = string()?.parse()?,
+                "length" => length = Some(integer()?),
+                "min_length" => policy.min_length = integer()?,
+                "max_length" => policy.max_length = Some(integer()?),
+                "required" => {
+                    policy.required = string()?
+                        .chars()
+                        .map(CharsetName::try_from)
+                        .collect::<io::Result<_>>()?
+                },
+                "max_repeat" => match integer()? {
+                    0 => {
+                        let msg = "max_repeat must be positive".into();
+                        return Err(invalid(i, msg));
+                    },
+                    n => max_repeat = Some(n),
+                },
+                _ => return Err(invalid(i, format!("unknown key {}", key))),
+            }
+        }
+        let length = length.unwrap_or(policy.min_length);
+        if length == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Policy file sets neither length nor min_length",
+            ));
+        }
+        Ok(Self { charset, length, policy, max_repeat })
+    }
+}
+
+/// Values of the TOML subset `PolicySpec` reads.
+enum TomlValue<'a> {
+    String(&'a str),
+    Integer(usize),
+    Invalid,
+}
+
+/// Parses a basic string without escapes or a non-negative integer,
+/// followed by an optional comment.
+fn toml_value(value: &str) -> TomlValue<'_> {
+    if let Some(rest) = value.strip_prefix('"') {
+        return match rest.split_once('"') {
+            Some((s, tail)) if is_comment(tail) && !s.contains('\\') => {
+                TomlValue::String(s)
+            },
+            _ => TomlValue::Invalid,
+        };
+    }
+    let (number, tail) = value.split_at(value.find('#').unwrap_or(value.len()));
+    match number.trim().replace('_', "").parse() {
+        Ok(n) if is_comment(tail) => TomlValue::Integer(n),
+        _ => TomlValue::Invalid,
+    }
+}
+
+/// Whether `tail` is empty or a comment.
+fn is_comment(tail: &str) -> bool {
+    let tail = tail.trim();
+    tail.is_empty() || tail.starts_with('#')
+}
+
+/// Results of putting random passwords to a policy, see
+/// `PolicySpec::simulate`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PolicySimulation {
+    /// Number of random passwords drawn.
+    pub samples: usize,
+    /// Number of them satisfying the policy.
+    pub passed: usize,
+    /// Entropy in bits of the random passwords.
+    pub entropy: f64,
+}
+
+impl PolicySimulation {
+    /// Share of random passwords satisfying the policy.
+    pub fn pass_rate(&self) -> f64 {
+        if self.samples == 0 {
+            return 1.0;
+        }
+        self.passed as f64 / self.samples as f64
+    }
+
+    /// Estimated entropy in bits of passwords drawn at random until one
+    /// satisfies the policy: it keeps about `pass_rate()` of all passwords,
+    /// each remaining equally likely. Estimates below the resolution of the
+    /// samples are unreliable, and 0 if none passed.
+    pub fn effective_entropy(&self) -> f64 {
+        (self.entropy + self.pass_rate().log2()).max(0.0)
+    }
+
+    /// Bits of entropy the policy destroys.
+    #[inline]
+    pub fn entropy_lost(&self) -> f64 {
+        self.entropy - self.effective_entropy()
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::PolicySpec;
+    use crate::CharsetName::*;
+
+    #[test]
+    fn parsing_policy_files() {
+        let spec: PolicySpec = [
+            "# AD-style complexity",
+            "charset = \"LUNS\"  # drawn from",
+            "",
+            "min_length = 8",
+            "max_length = 1_000",
+            "required = \"LUNS\"",
+        ]
+        .join("\n")
+        .parse()
+        .unwrap();
+        assert_eq!(spec.length, 8);
+        assert_eq!(spec.policy.max_length, Some(1000));
+        assert_eq!(spec.policy.required, vec![
+            AlphaLower, AlphaUpper, Numeric, Special
+        ]);
+        assert_eq!(spec.max_repeat, None);
+        assert!(spec.allows("aB3$aB3$"));
+        assert!(!spec.allows("aB3$aB3"));
+
+        for bad in [
+            "length = 0",
+            "length = -1",
+            "length = \"8\"",
+            "charset = LUNS",
+            "charset = \"LUNS\" trailing",
+            "colour = \"blue\"",
+            "length",
+            "required = \"Q\"",
+            "max_repeat = 0",
+        ]
+        .iter()
+        {
+            assert!(bad.parse::<PolicySpec>().is_err(), "{}", bad);
+        }
+
+        // requiring all four classes in 8 chars rejects more than half
+        let sim = spec.simulate(20_000);
+        assert!(sim.pass_rate() > 0.42 && sim.pass_rate() < 0.49);
+        assert!(sim.entropy_lost() > 1.0 && sim.entropy_lost() < 1.25);
+        let spec: PolicySpec = "length = 4\nmax_repeat = 1".parse().unwrap();
+        assert!(spec.allows("abab") && !spec.allows("abba"));
+    }
+}
@@ -0,0 +1,137 @@
+//! RFC 1751 (S/Key) encoding of 64-bit keys as six short English words, e.g.
+//! `TIDE ITCH SLOW REIN RULE MOT`, as still expected by legacy OTP and key
+//! exchange tooling.
+
+use std::io;
+
+use rand::RngCore;
+use rfc1751::{FromRfc1751, FromRfc1751Error, ToRfc1751};
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+/// Encodes `bytes` as RFC 1751 words separated by spaces, six words per 8
+/// bytes. Fails if the number of bytes is not a multiple of 8.
+///
+/// # Example
+/// ```
+/// let key = [0xEB, 0x33, 0xF7, 0x7E, 0xE7, 0x3D, 0x40, 0x53];
+/// let words = "TIDE ITCH SLOW REIN RULE MOT";
+/// assert_eq!(yapg_core::rfc1751_encode(&key).unwrap(), words);
+/// assert_eq!(yapg_core::rfc1751_decode(words).unwrap(), key);
+/// assert!(yapg_core::rfc1751_encode(&[1, 2, 3]).is_err());
+/// ```
+pub fn rfc1751_encode(bytes: &[u8]) -> io::Result<String> {
+    bytes.to_rfc1751().map_err(|_| {
+        invalid(format!(
+            "RFC 1751 encodes blocks of 8 bytes, got {} bytes",
+            bytes.len()
+        ))
+    })
+}
+
+/// Decodes words created by `rfc1751_encode`, ignoring case and separated by
+/// any whitespace. Fails on unknown words, a number of words that is not a
+/// multiple of six and wrong parity, i.e. a mistyped word.
+pub fn rfc1751_decode(s: &str) -> io::Result<Vec<u8>> {
+    s.to_ascii_uppercase().from_rfc1751().map_err(|e| match e {
+        FromRfc1751Error::InvalidWord(word) => {
+            invalid(format!("Invalid RFC 1751 word: {}", word))
+        },
+        FromRfc1751Error::IncorrectParity(_) => {
+            invalid("RFC 1751 parity check failed".to_string())
+        },
+        FromRfc1751Error::NotMultipleOfSixWords => {
+            invalid("RFC 1751 keys consist of six words each".to_string())
+        },
+    })
+}
+
+/// Generates random 64-bit keys encoded as RFC 1751 words.
+///
+/// # Example
+/// ```
+/// let mut rg = yapg_core::Rfc1751Generator::new(2);
+/// let key = rg.generate();
+/// assert_eq!(key.split(' ').count(), 12);
+/// assert_eq!(yapg_core::rfc1751_decode(&key).unwrap().len(), 16);
+/// assert_eq!(rg.entropy(), 128.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Rfc1751Generator {
+    keys: usize,
+    rng: rand::ThreadRng,
+}
+
+impl Rfc1751Generator {
+    /// Creates a generator of `keys` 64-bit keys per output.
+    pub fn new(keys: usize) -> Self { Self { keys, rng: rand::thread_rng() } }
+
+    /// Changes the number of 64-bit keys per output, consumes and returns
+    /// itself.
+    #[inline]
+    pub fn keys(mut self, keys: usize) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    /// Generates one output.
+    pub fn generate(&mut self) -> String {
+        let mut bytes = vec![0; 8 * self.keys];
+        self.rng.fill_bytes(&mut bytes);
+        // a multiple of 8 bytes always encodes
+        rfc1751_encode(&bytes).unwrap()
+    }
+
+    /// Generates a vector of `n` outputs.
+    #[inline]
+    pub fn generate_n(&mut self, n: usize) -> Vec<String> {
+        (0..n).map(|_| self.generate()).collect()
+    }
+
+    /// Entropy in bits, 64 per key.
+    #[inline]
+    pub fn entropy(&self) -> f64 { 64.0 * self.keys as f64 }
+
+    /// Number of keys needed to reach `target_bits` of entropy.
+    pub fn keys_for_entropy(target_bits: f64) -> usize {
+        (target_bits / 64.0).ceil().max(0.0) as usize
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::{rfc1751_decode, rfc1751_encode, Rfc1751Generator};
+
+    #[test]
+    fn round_trips() {
+        // examples from RFC 1751
+        let key = [
+            0xCC, 0xAC, 0x2A, 0xED, 0x59, 0x10, 0x56, 0xBE, 0x4F, 0x90, 0xFD,
+            0x44, 0x1C, 0x53, 0x47, 0x66,
+        ];
+        let words = "RASH BUSH MILK LOOK BAD BRIM AVID GAFF BAIT ROT POD LOVE";
+        assert_eq!(rfc1751_encode(&key).unwrap(), words);
+        assert_eq!(rfc1751_decode(words).unwrap(), key);
+        let lower = "trod mute tail warm char kong\nhaag city bore o teal awl";
+        assert_eq!(rfc1751_decode(lower).unwrap(), [
+            0xEF, 0xF8, 0x1F, 0x9B, 0xFB, 0xC6, 0x53, 0x50, 0x92, 0x0C, 0xDD,
+            0x74, 0x16, 0xDE, 0x80, 0x09,
+        ]);
+        assert_eq!(rfc1751_encode(&[]).unwrap(), "");
+        assert_eq!(rfc1751_decode("").unwrap(), Vec::<u8>::new());
+        for invalid in [
+            "TIDE ITCH SLOW REIN RULE",
+            "TIDE ITCH SLOW REIN RULE MOTH",
+            "TIDE ITCH SLOW REIN RULE MUD",
+            "TIDE ITCH SLOW REIN RULES MOT",
+        ]
+        .iter()
+        {
+            assert!(rfc1751_decode(invalid).is_err(), "{}", invalid);
+        }
+        assert_eq!(Rfc1751Generator::keys_for_entropy(100.0), 2);
+    }
+}
@@ -0,0 +1,97 @@
+use std::io;
+
+/// Maps a single character to the phone keypad digit it is entered with.
+///
+/// Latin letters (of either case) map to the key labelled with them, digits
+/// map to themselves and space maps to `'0'`. Other characters cannot be
+/// entered on a keypad and yield `None`.
+pub fn t9_key(c: char) -> Option<char> {
+    match c.to_ascii_lowercase() {
+        'a'..='c' => Some('2'),
+        'd'..='f' => Some('3'),
+        'g'..='i' => Some('4'),
+        'j'..='l' => Some('5'),
+        'm'..='o' => Some('6'),
+        'p'..='s' => Some('7'),
+        't'..='v' => Some('8'),
+        'w'..='z' => Some('9'),
+        ' ' => Some('0'),
+        d @ '0'..='9' => Some(d),
+        _ => None,
+    }
+}
+
+/// Translates `s` into the digit sequence typed on a phone keypad or TV
+/// remote (one key press per character, as with T9 predictive input).
+///
+/// # Example
+/// ```
+/// assert_eq!(yapg_core::t9_encode("Hello 42").unwrap(), "43556042");
+/// assert!(yapg_core::t9_encode("a+b").is_err());
+/// ```
+pub fn t9_encode(s: &str) -> io::Result<String> {
+    s.chars()
+        .map(|c| {
+            t9_key(c).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Character cannot be typed on a keypad: {:?}", c),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Number of distinct keypad keys needed to enter any char of `charset`.
+///
+/// Since the receiving system only sees the key presses, this (and not the
+/// size of `charset`) bounds the entropy of a keypad-entered secret, see
+/// `t9_entropy`.
+///
+/// # Example
+/// ```
+/// let charset = yapg_core::CharsetSpec::console().construct();
+/// assert_eq!(yapg_core::t9_key_count(&charset).unwrap(), 10);
+/// ```
+pub fn t9_key_count(charset: &[char]) -> io::Result<usize> {
+    let mut keys = t9_encode(&charset.iter().collect::<String>())?
+        .chars()
+        .collect::<Vec<_>>();
+    keys.sort();
+    keys.dedup();
+    Ok(keys.len())
+}
+
+/// Entropy in bits of the key presses entering a password of `length` chars
+/// drawn uniformly from `charset`.
+///
+/// Since the receiving system only sees the key presses, this (and not the
+/// entropy of the password) is what an attacker faces. Keys entering more
+/// chars of `charset` are pressed more often, so it falls short of
+/// `length` times the binary logarithm of `t9_key_count`.
+///
+/// # Example
+/// ```
+/// // a, b and c share key 2, d is on key 3
+/// let bits = yapg_core::t9_entropy(&['a', 'b', 'c', 'd'], 4).unwrap();
+/// let per_key = -(0.75f64 * 0.75f64.log2() + 0.25 * 0.25f64.log2());
+/// assert!((bits - 4.0 * per_key).abs() < 1e-9);
+/// assert!(bits < 4.0);
+/// assert_eq!(yapg_core::t9_entropy(&['0', '1'], 8).unwrap(), 8.0);
+/// ```
+pub fn t9_entropy(charset: &[char], length: usize) -> io::Result<f64> {
+    let keys = t9_encode(&charset.iter().collect::<String>())?;
+    let mut presses = [0usize; 10];
+    for key in keys.chars() {
+        presses[key as usize - '0' as usize] += 1;
+    }
+    let per_key: f64 = presses
+        .iter()
+        .filter(|n| **n > 0)
+        .map(|n| {
+            let p = *n as f64 / charset.len() as f64;
+            -p * p.log2()
+        })
+        .sum();
+    Ok(per_key * length as f64)
+}
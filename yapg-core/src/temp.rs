@@ -0,0 +1,147 @@
+//! Temporary passwords carrying their own expiry date.
+
+use std::{fmt, io};
+
+use crate::ct_eq;
+use crate::date::format_date;
+use crate::fingerprint::{fnv1a64, FINGERPRINT_ALPHABET};
+
+/// Separates the random part of a temporary password from its expiry segment.
+const TEMP_SEPARATOR: char = '.';
+
+/// Number of characters `with_expiry` appends: the separator, four base32
+/// digits of the expiry day and a check digit.
+pub const TEMP_SUFFIX_LEN: usize = 6;
+
+/// Largest encodable expiry, in days since the unix epoch (in the year 4840).
+const MAX_DAY: u64 = 32 * 32 * 32 * 32 - 1;
+
+/// Check digit over everything preceding it, catching typos in both the
+/// random part and the expiry segment.
+fn check_digit(s: &str) -> char {
+    FINGERPRINT_ALPHABET[(fnv1a64(s.as_bytes()) >> 59) as usize]
+}
+
+/// Value of a base32 digit.
+fn digit_value(c: char) -> Option<u64> {
+    FINGERPRINT_ALPHABET.iter().position(|d| *d == c).map(|i| i as u64)
+}
+
+/// Appends an expiry segment to `password`, such that `verify_temp` rejects
+/// it from `expires_at` (seconds since the unix epoch) on. The deadline is
+/// rounded up to the next midnight UTC.
+///
+/// The segment is not authenticated: it does not keep anyone from making up
+/// a password with a later expiry, but a password altered that way no longer
+/// matches the one stored by the issuing system.
+///
+/// # Example
+/// ```
+/// // 2023-11-14T22:13:20Z, valid up to and including that day
+/// let pw = yapg_core::with_expiry("hunter2", 1_700_000_000);
+/// assert_eq!(pw.len(), 7 + yapg_core::TEMP_SUFFIX_LEN);
+///
+/// let expiry = yapg_core::verify_temp(&pw, 1_700_000_000).unwrap();
+/// assert_eq!(expiry.to_string(), "2023-11-14");
+/// assert!(yapg_core::verify_temp(&pw, 1_700_006_400).is_err());
+/// assert!(yapg_core::verify_temp("hunter2", 0).is_err());
+/// ```
+pub fn with_expiry(password: &str, expires_at: u64) -> String {
+    let day = expires_at.div_ceil(86400).min(MAX_DAY);
+    let mut s = String::with_capacity(password.len() + TEMP_SUFFIX_LEN);
+    s.push_str(password);
+    s.push(TEMP_SEPARATOR);
+    for i in (0..4).rev() {
+        s.push(FINGERPRINT_ALPHABET[(day >> (5 * i)) as usize & 0x1f]);
+    }
+    s.push(check_digit(&s));
+    s
+}
+
+/// The point in time from which a temporary password is rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TempExpiry {
+    /// Seconds since the unix epoch, always a midnight UTC.
+    pub expires_at: u64,
+}
+
+impl TempExpiry {
+    /// Decodes the expiry segment of a password created by `with_expiry`.
+    pub fn of(password: &str) -> io::Result<Self> {
+        let invalid = |msg: &str| {
+            io::Error::new(io::ErrorKind::InvalidInput, msg.to_string())
+        };
+        let chars: Vec<char> = password.chars().collect();
+        if chars.len() < TEMP_SUFFIX_LEN
+            || chars[chars.len() - TEMP_SUFFIX_LEN] != TEMP_SEPARATOR
+        {
+            return Err(invalid("Not a temporary password"));
+        }
+        let (check, body) = chars.split_last().unwrap();
+        let expected = check_digit(&body.iter().collect::<String>());
+        if !ct_eq(check.to_string(), expected.to_string()) {
+            return Err(invalid("Temporary password is mistyped"));
+        }
+        let day = chars[chars.len() - 5..chars.len() - 1]
+            .iter()
+            .try_fold(0, |day, c| digit_value(*c).map(|v| (day << 5) | v))
+            .ok_or_else(|| invalid("Temporary password is mistyped"))?;
+        Ok(Self { expires_at: day * 86400 })
+    }
+}
+
+impl fmt::Display for TempExpiry {
+    /// Formats the last day of validity as `YYYY-MM-DD`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", format_date(self.expires_at as i64 / 86400 - 1))
+    }
+}
+
+/// Checks a password created by `with_expiry` at time `now` (seconds since
+/// the unix epoch), returning its expiry if it is still valid. Malformed,
+/// mistyped and expired passwords yield `io::ErrorKind::InvalidInput` errors.
+pub fn verify_temp(password: &str, now: u64) -> io::Result<TempExpiry> {
+    let expiry = TempExpiry::of(password)?;
+    if now >= expiry.expires_at {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Temporary password expired after {}", expiry),
+        ));
+    }
+    Ok(expiry)
+}
+
+/// Like `verify_temp`, checking against the current system time.
+pub fn verify_temp_now(password: &str) -> io::Result<TempExpiry> {
+    verify_temp(password, crate::date::unix_now())
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::{verify_temp, with_expiry, TempExpiry};
+
+    #[test]
+    fn round_tripping_expiry() {
+        let pw = with_expiry("ab.cdefg", 86400 * 19_000);
+        assert_eq!(TempExpiry::of(&pw).unwrap().expires_at, 86400 * 19_000);
+        assert_eq!(TempExpiry::of(&pw).unwrap().to_string(), "2022-01-07");
+        assert!(verify_temp(&pw, 86400 * 19_000 - 1).is_ok());
+        assert!(verify_temp(&pw, 86400 * 19_000).is_err());
+        assert!(verify_temp(&pw.to_uppercase(), 0).is_err());
+
+        // non-ascii random parts
+        let pw = with_expiry("äöü", 1);
+        assert_eq!(TempExpiry::of(&pw).unwrap().expires_at, 86400);
+    }
+
+    #[test]
+    fn detecting_typos() {
+        let pw = with_expiry("correct", 86400 * 20_000);
+        let typo = pw.replacen('c', "x", 1);
+        assert!(TempExpiry::of(&typo).is_err());
+        assert!(TempExpiry::of("short").is_err());
+        assert!(TempExpiry::of("no-separator-here").is_err());
+        assert!(TempExpiry::of("ab.0000ä").is_err());
+    }
+}
@@ -0,0 +1,231 @@
+//! Re-encoding binary secrets as words and back.
+
+use std::collections::HashMap;
+use std::io;
+
+use crate::Wordlist;
+
+/// Alphabet of standard base64 (RFC 4648).
+static BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+/// Words of `wordlist`, which must be at least two distinct ones to serve as
+/// digits.
+fn digits(wordlist: &Wordlist) -> io::Result<&[String]> {
+    let words = wordlist.words();
+    if words.len() < 2 {
+        return Err(invalid("Wordlist needs at least two words".to_string()));
+    }
+    if !wordlist.report().duplicates.is_empty() {
+        return Err(invalid("Wordlist contains duplicates".to_string()));
+    }
+    Ok(words)
+}
+
+/// Encodes `secret` as words, interpreting it as a big-endian number written
+/// in base `wordlist.len()`. As in base58, each leading zero byte becomes a
+/// leading first word, so that decoding restores the exact bytes.
+///
+/// # Example
+/// ```
+/// let list = yapg_core::Wordlist::embedded("eff-long").unwrap();
+/// let words = yapg_core::encode_words(&[0, 0xca, 0xfe], &list).unwrap();
+/// assert_eq!(words, ["abacus", "ablaze", "replica"]);
+/// assert_eq!(yapg_core::decode_words(&words, &list).unwrap(), [
+///     0, 0xca, 0xfe
+/// ]);
+/// ```
+pub fn encode_words<'a>(
+    secret: &[u8],
+    wordlist: &'a Wordlist,
+) -> io::Result<Vec<&'a str>> {
+    let words = digits(wordlist)?;
+    let base = words.len() as u64;
+    let zeros = secret.iter().take_while(|b| **b == 0).count();
+
+    // repeated long division of the big-endian number by the base
+    let mut number = secret[zeros..].to_vec();
+    let mut indices = vec![];
+    while !number.is_empty() {
+        let mut remainder = 0u64;
+        let mut quotient = Vec::with_capacity(number.len());
+        for byte in number {
+            let acc = (remainder << 8) | byte as u64;
+            let q = acc / base;
+            remainder = acc % base;
+            if !(quotient.is_empty() && q == 0) {
+                quotient.push(q as u8);
+            }
+        }
+        indices.push(remainder as usize);
+        number = quotient;
+    }
+
+    let mut encoded = vec![words[0].as_str(); zeros];
+    encoded.extend(indices.iter().rev().map(|i| words[*i].as_str()));
+    Ok(encoded)
+}
+
+/// Decodes words created by `encode_words` with the same wordlist.
+pub fn decode_words<S: AsRef<str>>(
+    encoded: &[S],
+    wordlist: &Wordlist,
+) -> io::Result<Vec<u8>> {
+    let words = digits(wordlist)?;
+    let base = words.len() as u64;
+    let index: HashMap<&str, u64> =
+        words.iter().enumerate().map(|(i, w)| (w.as_str(), i as u64)).collect();
+    let digits = encoded
+        .iter()
+        .map(|word| {
+            index.get(word.as_ref()).copied().ok_or_else(|| {
+                invalid(format!("Not in wordlist: {}", word.as_ref()))
+            })
+        })
+        .collect::<io::Result<Vec<u64>>>()?;
+    let zeros = digits.iter().take_while(|d| **d == 0).count();
+
+    // little-endian bytes of the number, multiplied by the base per digit
+    let mut number: Vec<u8> = vec![];
+    for digit in digits[zeros..].iter() {
+        let mut carry = *digit;
+        for byte in number.iter_mut() {
+            let acc = *byte as u64 * base + carry;
+            *byte = acc as u8;
+            carry = acc >> 8;
+        }
+        while carry > 0 {
+            number.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+
+    let mut secret = vec![0; zeros];
+    secret.extend(number.iter().rev());
+    Ok(secret)
+}
+
+/// Parses a secret given as hex (preferred, if valid) or base64, either
+/// standard or URL-safe, with or without padding.
+///
+/// # Example
+/// ```
+/// assert_eq!(yapg_core::parse_secret("cafe").unwrap(), [0xca, 0xfe]);
+/// assert_eq!(yapg_core::parse_secret("yv4=").unwrap(), [0xca, 0xfe]);
+/// assert_eq!(yapg_core::parse_secret("yv4").unwrap(), [0xca, 0xfe]);
+/// assert!(yapg_core::parse_secret("not a secret").is_err());
+/// ```
+pub fn parse_secret(s: &str) -> io::Result<Vec<u8>> {
+    let s = s.trim();
+    hex_decode(s)
+        .or_else(|| base64_decode(s))
+        .ok_or_else(|| invalid("Secret is neither hex nor base64".to_string()))
+}
+
+/// Encodes `bytes` as lower-case hex.
+pub fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub(crate) fn hex_decode(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Encodes `bytes` as padded standard base64.
+pub fn base64_encode(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().fold(0u32, |n, b| (n << 8) | *b as u32)
+            << (8 * (3 - chunk.len()));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                s.push(
+                    BASE64_ALPHABET[(n >> (18 - 6 * i)) as usize & 0x3f]
+                        as char,
+                );
+            } else {
+                s.push('=');
+            }
+        }
+    }
+    s
+}
+
+fn base64_decode(s: &str) -> Option<Vec<u8>> {
+    let s = s.trim_end_matches('=');
+    let mut bytes = Vec::with_capacity(s.len() * 3 / 4);
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in s.bytes() {
+        let value = match c {
+            b'-' => 62,
+            b'_' => 63,
+            _ => BASE64_ALPHABET.iter().position(|a| *a == c)? as u32,
+        };
+        buffer = (buffer << 6) | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    // a single base64 digit cannot encode a whole byte
+    if bits >= 6 {
+        return None;
+    }
+    Some(bytes)
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::{base64_decode, base64_encode, decode_words, encode_words};
+
+    #[test]
+    fn round_tripping_words() {
+        let bits: crate::Wordlist = "o\ni".parse().unwrap();
+        assert_eq!(encode_words(&[5], &bits).unwrap(), ["i", "o", "i"]);
+        assert_eq!(encode_words(&[0, 0, 1], &bits).unwrap(), ["o", "o", "i"]);
+        assert!(encode_words(&[], &bits).unwrap().is_empty());
+
+        let list = crate::Wordlist::embedded("eff-short").unwrap();
+        for secret in [&b""[..], b"\0", b"\0\0\xff", b"\xff\0", b"yapg"].iter()
+        {
+            let words = encode_words(secret, &list).unwrap();
+            assert_eq!(&decode_words(&words, &list).unwrap(), secret);
+        }
+
+        assert!(decode_words(&["i", "x"], &bits).is_err());
+        let dupes = "a\na".parse().unwrap();
+        assert!(encode_words(&[1], &dupes).is_err());
+    }
+
+    #[test]
+    fn encoding_base64() {
+        // test vectors from RFC 4648
+        let vectors = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for (plain, encoded) in vectors.iter() {
+            assert_eq!(base64_encode(plain.as_bytes()), *encoded);
+            assert_eq!(base64_decode(encoded).unwrap(), plain.as_bytes());
+        }
+        assert_eq!(base64_decode("-_8").unwrap(), [0xfb, 0xff]);
+        assert!(base64_decode("Zm9vY").is_none());
+    }
+}
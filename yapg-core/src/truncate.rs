@@ -0,0 +1,49 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Bytes of a password bcrypt takes into account, ignoring the rest.
+pub const BCRYPT_MAX_BYTES: usize = 72;
+
+/// The longest prefix of `secret` fitting into `max_bytes` of UTF-8 that
+/// ends on a grapheme boundary, so that neither chars nor e.g. flag emoji
+/// or letters with combining accents are cut in half.
+///
+/// # Example
+/// ```
+/// use yapg_core::truncate_to_bytes;
+///
+/// assert_eq!(truncate_to_bytes("abcdef", 4), "abcd");
+/// // 'é' as 'e' with a combining accent takes three bytes
+/// assert_eq!(truncate_to_bytes("abe\u{301}f", 4), "ab");
+/// assert_eq!(truncate_to_bytes("🇩🇪🇫🇷", 12), "🇩🇪");
+/// assert_eq!(truncate_to_bytes("abc", 72), "abc");
+/// ```
+pub fn truncate_to_bytes(secret: &str, max_bytes: usize) -> &str {
+    let end = secret
+        .grapheme_indices(true)
+        .map(|(i, g)| i + g.len())
+        .take_while(|end| *end <= max_bytes)
+        .last()
+        .unwrap_or(0);
+    &secret[..end]
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::truncate_to_bytes;
+
+    #[test]
+    fn truncating_at_boundaries() {
+        let secret = "aä€😀";
+        let cuts =
+            [(0, ""), (1, "a"), (2, "a"), (3, "aä"), (5, "aä"), (6, "aä€")];
+        for (bytes, expected) in cuts.iter() {
+            assert_eq!(truncate_to_bytes(secret, *bytes), *expected);
+        }
+        assert_eq!(truncate_to_bytes(secret, 10), secret);
+        // a family emoji joined by zero-width joiners is one grapheme
+        let family = "👨\u{200d}👩\u{200d}👧x";
+        assert_eq!(truncate_to_bytes(family, 17), "");
+        assert_eq!(truncate_to_bytes(family, 18), "👨\u{200d}👩\u{200d}👧");
+    }
+}
@@ -0,0 +1,175 @@
+use std::{fmt, io};
+
+/// Unshifted and shifted chars of the keys in each row of a US keyboard.
+const US_ROWS: [(&str, &str); 4] = [
+    ("`1234567890-=", "~!@#$%^&*()_+"),
+    ("qwertyuiop[]\\", "QWERTYUIOP{}|"),
+    ("asdfghjkl;'", "ASDFGHJKL:\""),
+    ("zxcvbnm,./", "ZXCVBNM<>?"),
+];
+
+/// Indentation of each row, mimicking the staggered keys.
+const ROW_INDENT: [usize; 4] = [0, 2, 3, 4];
+
+/// Shades from unused to most used key.
+const SHADES: [char; 5] = [' ', '.', ':', '*', '#'];
+
+/// Visualizations of generated passwords.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Visualization {
+    /// A `KeyboardHeatmap`.
+    Keyboard,
+}
+
+impl Visualization {
+    /// Renders the visualization of `password`.
+    pub fn render(self, password: &str) -> String {
+        match self {
+            Self::Keyboard => KeyboardHeatmap::of(password).to_string(),
+        }
+    }
+}
+
+impl std::str::FromStr for Visualization {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        match s {
+            "keyboard" => Ok(Self::Keyboard),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid visualization: {} (expected keyboard)", s),
+            )),
+        }
+    }
+}
+
+/// How often a password presses each key of a US keyboard, and how often
+/// shift, to eyeball how well it can be typed. Displays as a keyboard with
+/// the keys shaded by use, from ` ` (unused) over `.`, `:` and `*` to `#`
+/// (most used).
+///
+/// # Example
+/// ```
+/// let heatmap = yapg_core::KeyboardHeatmap::of("aA1!ü");
+/// assert_eq!(heatmap.presses('a'), 2);
+/// assert_eq!(heatmap.presses('1'), 2);
+/// assert_eq!(heatmap.shifted(), 2);
+/// assert_eq!(heatmap.total(), 4);
+/// assert_eq!(heatmap.unmapped(), ['ü']);
+/// assert!(heatmap.to_string().contains("2 of 4 key presses shifted"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct KeyboardHeatmap {
+    /// Presses of each key, by row, the space bar last.
+    rows: [Vec<usize>; 5],
+    shifted: usize,
+    unmapped: Vec<char>,
+}
+
+impl KeyboardHeatmap {
+    /// Counts the key presses needed to type `password`.
+    pub fn of(password: &str) -> Self {
+        let mut heatmap = Self::default();
+        for (row, (keys, _)) in US_ROWS.iter().enumerate() {
+            heatmap.rows[row] = vec![0; keys.chars().count()];
+        }
+        heatmap.rows[4] = vec![0];
+        for c in password.chars() {
+            match key_of(c) {
+                Some((row, key, shifted)) => {
+                    heatmap.rows[row][key] += 1;
+                    heatmap.shifted += shifted as usize;
+                },
+                None if !heatmap.unmapped.contains(&c) => {
+                    heatmap.unmapped.push(c)
+                },
+                None => {},
+            }
+        }
+        heatmap
+    }
+
+    /// Presses of the key typing `c`, shifted or not.
+    pub fn presses(&self, c: char) -> usize {
+        key_of(c).map_or(0, |(row, key, _)| self.rows[row][key])
+    }
+
+    /// Key presses with shift held.
+    #[inline]
+    pub fn shifted(&self) -> usize { self.shifted }
+
+    /// Key presses in total, not counting shift.
+    pub fn total(&self) -> usize { self.rows.iter().flatten().sum() }
+
+    /// Chars of the password without a key on a US keyboard, in order of
+    /// appearance.
+    #[inline]
+    pub fn unmapped(&self) -> &[char] { &self.unmapped }
+}
+
+/// Row, index in the row and whether shift is needed of the key typing `c`.
+fn key_of(c: char) -> Option<(usize, usize, bool)> {
+    if c == ' ' {
+        return Some((4, 0, false));
+    }
+    US_ROWS.iter().enumerate().find_map(|(row, (plain, shifted))| {
+        plain.chars().position(|k| k == c).map(|key| (row, key, false)).or_else(
+            || shifted.chars().position(|k| k == c).map(|key| (row, key, true)),
+        )
+    })
+}
+
+impl fmt::Display for KeyboardHeatmap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let max = self.rows.iter().flatten().cloned().max().unwrap_or(0);
+        let shade = |presses: usize| match presses {
+            0 => SHADES[0],
+            n => SHADES[1 + (n * 4 - 1) / max],
+        };
+        for (row, (keys, _)) in US_ROWS.iter().enumerate() {
+            let indent = " ".repeat(ROW_INDENT[row]);
+            let labels: Vec<String> = keys.chars().map(String::from).collect();
+            let heat: Vec<String> =
+                self.rows[row].iter().map(|n| shade(*n).to_string()).collect();
+            writeln!(f, "{}{}", indent, labels.join(" "))?;
+            let heat = format!("{}{}", indent, heat.join(" "));
+            writeln!(f, "{}", heat.trim_end())?;
+        }
+        writeln!(f, "{}space", " ".repeat(10))?;
+        let space = shade(self.rows[4][0]).to_string().repeat(5);
+        writeln!(f, "{}", format!("{}{}", " ".repeat(10), space).trim_end())?;
+        let total = self.total();
+        write!(f, "{} of {} key presses shifted", self.shifted, total)?;
+        if total > 0 {
+            write!(f, " ({:.0}%)", self.shifted as f64 * 100.0 / total as f64)?;
+        }
+        if !self.unmapped.is_empty() {
+            let unmapped: String = self.unmapped.iter().collect();
+            write!(f, "\nNot on a US keyboard: {}", unmapped)?;
+        }
+        Ok(())
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::KeyboardHeatmap;
+
+    #[test]
+    fn rendering_heatmaps() {
+        let heatmap = KeyboardHeatmap::of("qqqqQ a");
+        let rendered = heatmap.to_string();
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines[2], "  q w e r t y u i o p [ ] \\");
+        assert_eq!(lines[3], "  #");
+        assert_eq!(lines[5], "   .");
+        assert_eq!(lines[9], "          .....");
+        assert_eq!(lines[10], "1 of 7 key presses shifted (14%)");
+        assert_eq!(lines.len(), 11);
+
+        let empty = KeyboardHeatmap::of("").to_string();
+        assert!(empty.ends_with("0 of 0 key presses shifted"));
+    }
+}
@@ -0,0 +1,734 @@
+//! Wordlists for passphrases and their preprocessing.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::{fmt, fs, io};
+
+use rand::RngCore;
+
+use crate::uniform_below;
+
+/// Directory holding user configuration, i.e. `$XDG_CONFIG_HOME/yapg` or
+/// `~/.config/yapg`.
+pub(crate) fn config_dir() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("yapg"))
+}
+
+/// Names of the wordlists compiled into the crate: the
+/// [EFF lists](https://www.eff.org/deeplinks/2016/07/new-wordlists-random-passphrases)
+/// for five (long list) or four (short list) dice.
+pub static EMBEDDED_WORDLISTS: [&str; 2] = ["eff-long", "eff-short"];
+
+/// Where a named wordlist is read from, see `Wordlist::named`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum WordlistSource {
+    /// A file in the user's wordlist directory.
+    File(PathBuf),
+    /// The list compiled into the crate.
+    Embedded,
+}
+
+impl fmt::Display for WordlistSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::Embedded => write!(f, "embedded"),
+        }
+    }
+}
+
+/// Whether `name` is usable as file name without leaving its directory.
+fn is_plain_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
+
+/// Resolves the source of the wordlist called `name`, looking for files in
+/// `dir`.
+fn source_in(dir: Option<&Path>, name: &str) -> Option<WordlistSource> {
+    if !is_plain_name(name) {
+        return None;
+    }
+    let file = dir.map(|dir| dir.join(format!("{}.txt", name)));
+    match file {
+        Some(path) if path.is_file() => Some(WordlistSource::File(path)),
+        _ if EMBEDDED_WORDLISTS.contains(&name) => {
+            Some(WordlistSource::Embedded)
+        },
+        _ => None,
+    }
+}
+
+/// All available wordlists with the source each is read from, sorted by
+/// name. These are the embedded lists and the `.txt` files in the user's
+/// wordlist directory, see `Wordlist::named`.
+pub fn wordlist_sources() -> io::Result<Vec<(String, WordlistSource)>> {
+    let dir = config_dir().map(|dir| dir.join("wordlists"));
+    let mut names: Vec<String> =
+        EMBEDDED_WORDLISTS.iter().map(|name| name.to_string()).collect();
+    if let Some(dir) = dir.as_ref() {
+        match fs::read_dir(dir) {
+            Ok(entries) => {
+                for entry in entries {
+                    let path = entry?.path();
+                    if path.extension().is_some_and(|ext| ext == "txt") {
+                        if let Some(stem) = path.file_stem() {
+                            names.push(stem.to_string_lossy().into_owned());
+                        }
+                    }
+                }
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {},
+            Err(e) => return Err(e),
+        }
+    }
+    names.sort();
+    names.dedup();
+    Ok(names
+        .into_iter()
+        .filter_map(|name| {
+            source_in(dir.as_deref(), &name).map(|source| (name, source))
+        })
+        .collect())
+}
+
+/// A list of words to build passphrases from.
+///
+/// Lists are read from plain text with one word per line. Blank lines and
+/// lines starting with `#` are ignored, as are the dice indices of diceware
+/// lists (`11111 abacus`). In weighted lists, each word is followed by a
+/// tab and an integer weight (`the<TAB>50`), making it proportionally more
+/// likely to be drawn. If any word lacks a weight, all lines are words of
+/// weight 1.
+///
+/// # Example
+/// ```
+/// let list: yapg_core::Wordlist =
+///     "# animals\n11111 cat\n11112 dog\n\nfox\n".parse().unwrap();
+/// assert_eq!(list.words(), ["cat", "dog", "fox"]);
+/// assert!((list.entropy_per_word() - 3f64.log2()).abs() < 1e-9);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Wordlist {
+    words: Vec<String>,
+    weights: Vec<u64>,
+}
+
+impl Wordlist {
+    /// Creates a wordlist from its words, all with the same weight.
+    pub fn new(words: Vec<String>) -> Self {
+        let weights = vec![1; words.len()];
+        Self { words, weights }
+    }
+
+    /// Creates a wordlist whose words are drawn with probability
+    /// proportional to their weights.
+    ///
+    /// # Example
+    /// ```
+    /// let words = vec!["the".to_string(), "anemone".to_string()];
+    /// let list = yapg_core::Wordlist::weighted(words, vec![3, 1]).unwrap();
+    /// // less than log2(2) = 1 bit, as "the" is drawn 3 out of 4 times
+    /// assert!((list.entropy_per_word() - 0.8113).abs() < 1e-4);
+    /// assert!(yapg_core::Wordlist::weighted(vec![], vec![1]).is_err());
+    /// ```
+    pub fn weighted(words: Vec<String>, weights: Vec<u64>) -> io::Result<Self> {
+        if words.len() != weights.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Number of words and weights differ",
+            ));
+        }
+        if weights.contains(&0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Word weights must be positive",
+            ));
+        }
+        check_total_weight(&weights)?;
+        Ok(Self { words, weights })
+    }
+
+    /// Reads a wordlist from the file at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    /// The embedded wordlist called `name`, see `EMBEDDED_WORDLISTS`.
+    ///
+    /// # Example
+    /// ```
+    /// let list = yapg_core::Wordlist::embedded("eff-long").unwrap();
+    /// assert_eq!(list.len(), 7776);
+    /// assert_eq!(list.words()[0], "abacus");
+    /// assert!(yapg_core::Wordlist::embedded("nope").is_none());
+    /// ```
+    pub fn embedded(name: &str) -> Option<Self> {
+        let list = match name {
+            "eff-long" => eff_wordlist::large::LIST,
+            "eff-short" => eff_wordlist::short::LIST,
+            _ => return None,
+        };
+        Some(Self::new(list.iter().map(|(_, w)| w.to_string()).collect()))
+    }
+
+    /// Looks up the wordlist called `name`, in order of precedence:
+    ///
+    /// 1. the file `<name>.txt` in `~/.config/yapg/wordlists/` (or
+    ///    `$XDG_CONFIG_HOME/yapg/wordlists/`),
+    /// 2. the embedded list of that name.
+    ///
+    /// Files thus override embedded lists, without recompiling. Use
+    /// `wordlist_sources` to find out which source is active.
+    pub fn named(name: &str) -> io::Result<Self> {
+        let dir = config_dir().map(|dir| dir.join("wordlists"));
+        match source_in(dir.as_deref(), name) {
+            Some(WordlistSource::File(path)) => Self::load(path),
+            Some(WordlistSource::Embedded) => Ok(Self::embedded(name).unwrap()),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No wordlist named {}", name),
+            )),
+        }
+    }
+
+    /// The words of the list.
+    #[inline]
+    pub fn words(&self) -> &[String] { &self.words }
+
+    /// Number of words in the list.
+    #[inline]
+    pub fn len(&self) -> usize { self.words.len() }
+
+    /// Whether the list contains no words.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.words.is_empty() }
+
+    /// Whether the words are drawn with differing probabilities.
+    pub fn is_weighted(&self) -> bool {
+        self.weights.windows(2).any(|pair| pair[0] != pair[1])
+    }
+
+    /// Draws a word with probability proportional to its weight.
+    pub(crate) fn sample<R: RngCore>(&self, rng: &mut R) -> &str {
+        let mut target = uniform_below(rng, self.total_weight());
+        for (word, weight) in self.words.iter().zip(self.weights.iter()) {
+            if target < *weight {
+                return word;
+            }
+            target -= weight;
+        }
+        unreachable!("target exceeds the total weight")
+    }
+
+    /// The list with each word cut to its first `chars` characters, keeping
+    /// the weights. Words sharing a prefix become duplicates, which lowers
+    /// `entropy_per_word` accordingly.
+    ///
+    /// # Example
+    /// ```
+    /// let list: yapg_core::Wordlist = "abacus\nabdomen\nzebra".parse().unwrap();
+    /// let short = list.abbreviated(2);
+    /// assert_eq!(short.words(), ["ab", "ab", "ze"]);
+    /// assert!(short.entropy_per_word() < list.entropy_per_word());
+    /// ```
+    pub fn abbreviated(&self, chars: usize) -> Self {
+        let words = self
+            .words
+            .iter()
+            .map(|word| word.chars().take(chars).collect())
+            .collect();
+        Self { words, weights: self.weights.clone() }
+    }
+
+    /// Total weight of each distinct word, adding up duplicate entries.
+    pub(crate) fn distinct_weights(&self) -> HashMap<&str, u64> {
+        let mut weights = HashMap::new();
+        for (word, weight) in self.words.iter().zip(self.weights.iter()) {
+            *weights.entry(word.as_str()).or_insert(0) += weight;
+        }
+        weights
+    }
+
+    /// The words (and their weights) for which `f` is true.
+    pub(crate) fn filtered<F: FnMut(&str) -> bool>(&self, mut f: F) -> Self {
+        let (words, weights) = self
+            .words
+            .iter()
+            .zip(self.weights.iter())
+            .filter(|(word, _)| f(word))
+            .map(|(word, weight)| (word.clone(), *weight))
+            .unzip();
+        Self { words, weights }
+    }
+
+    /// Sum of the weights of all words.
+    #[inline]
+    pub(crate) fn total_weight(&self) -> u64 { self.weights.iter().sum() }
+
+    /// Keeps only the words (and their weights) for which `f` is true.
+    fn retain_words<F: FnMut(&str) -> bool>(&mut self, mut f: F) {
+        let keep: Vec<bool> = self.words.iter().map(|w| f(w)).collect();
+        let mut keep_iter = keep.iter();
+        self.words.retain(|_| *keep_iter.next().unwrap());
+        let mut keep_iter = keep.iter();
+        self.weights.retain(|_| *keep_iter.next().unwrap());
+    }
+
+    /// Shannon entropy in bits of a word drawn from the list. Duplicate
+    /// entries and uneven weights make some words more likely to be drawn
+    /// and thus lower the entropy below `log2(len)`.
+    pub fn entropy_per_word(&self) -> f64 {
+        if self.words.is_empty() {
+            return 0.0;
+        }
+        let total = self.weights.iter().sum::<u64>() as f64;
+        self.distinct_weights()
+            .values()
+            .map(|w| *w as f64 / total)
+            .map(|p| p * (1.0 / p).log2())
+            .sum()
+    }
+
+    /// Assesses the quality of the list, see `WordlistReport`.
+    ///
+    /// # Example
+    /// ```
+    /// let list: yapg_core::Wordlist = "sun\nset\nsunset\nset".parse().unwrap();
+    /// let report = list.report();
+    /// assert_eq!(report.size, 4);
+    /// assert_eq!(report.duplicates, ["set"]);
+    /// assert_eq!(report.prefix_collisions, [(
+    ///     "sun".to_string(),
+    ///     "sunset".to_string()
+    /// )]);
+    /// assert_eq!(report.average_length, 3.75);
+    /// ```
+    pub fn report(&self) -> WordlistReport {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for word in self.words.iter() {
+            *counts.entry(word).or_insert(0) += 1;
+        }
+        let mut duplicates: Vec<String> = counts
+            .iter()
+            .filter(|(_, n)| **n > 1)
+            .map(|(word, _)| word.to_string())
+            .collect();
+        duplicates.sort();
+
+        // in sorted order, all words prefixed by a word directly follow it
+        let mut unique: Vec<&str> = counts.keys().copied().collect();
+        unique.sort();
+        let mut prefix_collisions = vec![];
+        for (i, prefix) in unique.iter().enumerate() {
+            for word in unique[i + 1..].iter() {
+                if !word.starts_with(prefix) {
+                    break;
+                }
+                prefix_collisions.push((prefix.to_string(), word.to_string()));
+            }
+        }
+
+        let mut normalized = self.clone();
+        let total_chars: usize =
+            self.words.iter().map(|w| w.chars().count()).sum();
+        WordlistReport {
+            size: self.words.len(),
+            entropy_per_word: self.entropy_per_word(),
+            duplicates,
+            near_duplicates: normalized.normalize(),
+            prefix_collisions,
+            average_length: total_chars as f64 / self.words.len().max(1) as f64,
+        }
+    }
+
+    /// Removes all words on `blocklist`, returning how many were removed.
+    ///
+    /// # Example
+    /// ```
+    /// let mut list: yapg_core::Wordlist = "gift\nrock\npaper".parse().unwrap();
+    /// let blocklist: yapg_core::Blocklist = "Gift".parse().unwrap();
+    /// assert_eq!(list.remove_blocked(&blocklist), 1);
+    /// assert_eq!(list.words(), ["rock", "paper"]);
+    /// ```
+    pub fn remove_blocked(&mut self, blocklist: &Blocklist) -> usize {
+        let before = self.words.len();
+        self.retain_words(|word| !blocklist.contains(word));
+        before - self.words.len()
+    }
+
+    /// Removes near-duplicate word forms, keeping the first of each: exact
+    /// duplicates, capitalization variants and (English) plural/singular
+    /// pairs like `box`/`boxes` or `city`/`cities`. Returns how many words
+    /// were removed.
+    ///
+    /// Near-duplicates are easily confused when remembering a passphrase and
+    /// do not add to its strength in practice, so counting them overstates
+    /// the entropy of a list. The plural detection is a heuristic that errs
+    /// on removing too much (e.g. `news` as plural of `new`), which only
+    /// understates the entropy.
+    ///
+    /// # Example
+    /// ```
+    /// let mut list: yapg_core::Wordlist =
+    ///     "Horse\nhorses\nhorse\ncity\ncities\nglass".parse().unwrap();
+    /// assert_eq!(list.normalize(), 3);
+    /// assert_eq!(list.words(), ["Horse", "city", "glass"]);
+    /// ```
+    pub fn normalize(&mut self) -> usize {
+        let before = self.words.len();
+        let mut seen = HashSet::new();
+        self.retain_words(|word| {
+            let word = word.to_lowercase();
+            let forms = singular_forms(&word);
+            if seen.contains(&word) || forms.iter().any(|f| seen.contains(f)) {
+                return false;
+            }
+            seen.insert(word);
+            seen.extend(forms);
+            true
+        });
+        before - self.words.len()
+    }
+}
+
+/// Quality assessment of a wordlist, see `Wordlist::report`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordlistReport {
+    /// Number of entries.
+    pub size: usize,
+    /// Entropy in bits of a word drawn from the list, see
+    /// `Wordlist::entropy_per_word`.
+    pub entropy_per_word: f64,
+    /// Words listed more than once, sorted.
+    pub duplicates: Vec<String>,
+    /// Number of entries `Wordlist::normalize` would remove.
+    pub near_duplicates: usize,
+    /// Pairs of words where the first is a prefix of the second, sorted.
+    /// Without separators, passphrases containing these are ambiguous, e.g.
+    /// `sun` `set` `tee` vs. `sunset` `tee`.
+    pub prefix_collisions: Vec<(String, String)>,
+    /// Average number of characters per word.
+    pub average_length: f64,
+}
+
+impl WordlistReport {
+    /// Number of words needed for `target_bits` of entropy, and the expected
+    /// number of characters of such a passphrase with one-character
+    /// separators. `None` if the list cannot produce any entropy.
+    ///
+    /// # Example
+    /// ```
+    /// let words: Vec<_> = (0..1024).map(|i| format!("{:04}", i)).collect();
+    /// let report = yapg_core::Wordlist::new(words).report();
+    /// assert_eq!(report.passphrase_estimate(64.0), Some((7, 34)));
+    /// ```
+    pub fn passphrase_estimate(
+        &self,
+        target_bits: f64,
+    ) -> Option<(usize, usize)> {
+        if self.entropy_per_word <= 0.0 {
+            return None;
+        }
+        let words =
+            (target_bits / self.entropy_per_word - 1e-9).ceil().max(1.0);
+        let chars = words * (self.average_length + 1.0) - 1.0;
+        Some((words as usize, chars.round() as usize))
+    }
+}
+
+/// Candidates for the singular of `word`, if it is an English plural.
+fn singular_forms(word: &str) -> Vec<String> {
+    let mut forms = vec![];
+    if let Some(stem) = word.strip_suffix("ies") {
+        if stem.len() >= 2 {
+            forms.push(format!("{}y", stem));
+        }
+    }
+    if let Some(stem) = word.strip_suffix("es") {
+        if stem.len() >= 2 {
+            forms.push(stem.to_string());
+        }
+    }
+    if let Some(stem) = word.strip_suffix('s') {
+        if stem.len() >= 2 && !stem.ends_with('s') {
+            forms.push(stem.to_string());
+        }
+    }
+    forms
+}
+
+/// Makes sure the weights can be summed up without overflowing, which all
+/// sampling and entropy calculations rely on.
+fn check_total_weight(weights: &[u64]) -> io::Result<()> {
+    weights
+        .iter()
+        .try_fold(0u64, |total, w| total.checked_add(*w))
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Word weights must not add up to more than 2^64 - 1",
+            )
+        })?;
+    Ok(())
+}
+
+impl std::str::FromStr for Wordlist {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        let entries: Vec<&str> = s
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| match line.split_once(char::is_whitespace) {
+                Some((index, word))
+                    if index.bytes().all(|b| b.is_ascii_digit()) =>
+                {
+                    word.trim()
+                },
+                _ => line,
+            })
+            .collect();
+        // weights only count if all words have one, so that the words of
+        // unweighted lists are taken as they are
+        let entries: Vec<(&str, u64)> = entries
+            .iter()
+            .map(|entry| {
+                let (word, weight) = entry.rsplit_once('\t')?;
+                Some((word.trim(), weight.trim().parse::<u64>().ok()?))
+            })
+            .collect::<Option<_>>()
+            .unwrap_or_else(|| {
+                entries.iter().map(|entry| (*entry, 1)).collect()
+            });
+
+        let mut list = Self::default();
+        for (word, weight) in entries {
+            if weight == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Word weights must be positive: {}", word),
+                ));
+            }
+            list.words.push(word.to_string());
+            list.weights.push(weight);
+        }
+        check_total_weight(&list.weights)?;
+        Ok(list)
+    }
+}
+
+/// Words to keep out of wordlists, e.g. because they are offensive or
+/// confusing in the language or region of the people seeing the passphrases.
+/// Matching is exact, but ignores case.
+///
+/// Blocklists use the same format as wordlists. Per-locale lists are read
+/// from `~/.config/yapg/blocklists/<locale>.txt` (or below
+/// `$XDG_CONFIG_HOME`), see `Blocklist::for_locale`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Blocklist {
+    words: HashSet<String>,
+}
+
+impl Blocklist {
+    /// Reads a blocklist from the file at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    /// Reads the configured blocklists for a locale such as `de` or `de-AT`.
+    /// Regional locales include the list of their language, i.e. `de-AT`
+    /// reads both `de-AT.txt` and `de.txt`, and it suffices if one of them
+    /// exists.
+    pub fn for_locale(locale: &str) -> io::Result<Self> {
+        let dir = config_dir().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "Could not locate the configuration directory",
+            )
+        })?;
+        Self::for_locale_in(&dir.join("blocklists"), locale)
+    }
+
+    /// Like `for_locale`, reading the lists from `dir`.
+    fn for_locale_in(dir: &Path, locale: &str) -> io::Result<Self> {
+        let valid = !locale.is_empty()
+            && locale.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-');
+        if !valid {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid locale: {}", locale),
+            ));
+        }
+
+        let mut locales = vec![locale];
+        if let Some((language, _)) = locale.split_once('-') {
+            locales.push(language);
+        }
+        let mut blocklist = Self::default();
+        let mut found = false;
+        for name in locales {
+            match Self::load(dir.join(format!("{}.txt", name))) {
+                Ok(list) => {
+                    blocklist.extend(list);
+                    found = true;
+                },
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {},
+                Err(e) => return Err(e),
+            }
+        }
+        if !found {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "No blocklist for locale {} in {}",
+                    locale,
+                    dir.display()
+                ),
+            ));
+        }
+        Ok(blocklist)
+    }
+
+    /// Adds all words of `other`.
+    pub fn extend(&mut self, other: Blocklist) {
+        self.words.extend(other.words);
+    }
+
+    /// Whether `word` is blocked.
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains(&word.to_lowercase())
+    }
+}
+
+impl std::str::FromStr for Blocklist {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        let list: Wordlist = s.parse()?;
+        let words = list.words.iter().map(|w| w.to_lowercase()).collect();
+        Ok(Self { words })
+    }
+}
+
+// ------------------------------- unit tests ------------------------------- //
+#[cfg(test)]
+mod tests {
+    use super::{Blocklist, Wordlist};
+
+    #[test]
+    fn parsing_wordlists() {
+        let list: Wordlist = "  11111\tabacus \n#11112 abdomen\n3 words \
+                              here\n42\n"
+            .parse()
+            .unwrap();
+        assert_eq!(list.words(), ["abacus", "words here", "42"]);
+        assert_eq!(Wordlist::default().entropy_per_word(), 0.0);
+    }
+
+    #[test]
+    fn rejecting_overflowing_weights() {
+        let lines = ["a\t18446744073709551615", "b\t1"];
+        assert!(lines.join("\n").parse::<Wordlist>().is_err());
+        assert!(lines[0].parse::<Wordlist>().is_ok());
+        let words = vec!["a".to_string(), "b".to_string()];
+        assert!(Wordlist::weighted(words, vec![u64::MAX, 1]).is_err());
+    }
+
+    #[test]
+    fn weighting_words() {
+        let list: Wordlist =
+            "11111\tthe\t2\na\t2\nanemone\t1\n".parse().unwrap();
+        assert_eq!(list.words(), ["the", "a", "anemone"]);
+        assert!(list.is_weighted());
+        assert!((list.entropy_per_word() - 1.5219).abs() < 1e-4);
+        assert!("a\t0".parse::<Wordlist>().is_err());
+
+        // dropping words keeps the weights in sync
+        let mut list = list;
+        list.remove_blocked(&"the".parse().unwrap());
+        assert_eq!(list.words(), ["a", "anemone"]);
+        assert!(list.is_weighted());
+        assert!((list.entropy_per_word() - 0.9183).abs() < 1e-4);
+
+        let mut rng = rand::thread_rng();
+        let list: Wordlist = "rare\t1\ncommon\t1000".parse().unwrap();
+        let common = (0..100).filter(|_| list.sample(&mut rng) == "common");
+        assert!(common.count() > 80);
+
+        // without weights on all lines, the tabs belong to the words
+        let list: Wordlist = "cat\ndog\t3\n".parse().unwrap();
+        assert_eq!(list.words(), ["cat", "dog\t3"]);
+        assert!(!list.is_weighted());
+    }
+
+    #[test]
+    fn normalizing_word_forms() {
+        let words = [
+            "boxes", "box", "Box", "bus", "buses", "wish", "wishes", "pie",
+            "pies", "glass", "glasses", "cities", "city", "box",
+        ];
+        let mut list =
+            Wordlist::new(words.iter().map(|w| w.to_string()).collect());
+        assert_eq!(list.normalize(), 8);
+        assert_eq!(list.words(), [
+            "boxes", "bus", "wish", "pie", "glass", "cities"
+        ]);
+        assert_eq!(list.normalize(), 0);
+    }
+
+    #[test]
+    fn resolving_wordlist_sources() {
+        use super::{source_in, WordlistSource};
+
+        let dir = std::env::temp_dir()
+            .join(format!("yapg-wordlists-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("eff-short.txt"), "a\nb\n").unwrap();
+        std::fs::write(dir.join("mine.txt"), "c\n").unwrap();
+
+        let file = |name: &str| WordlistSource::File(dir.join(name));
+        assert_eq!(
+            source_in(Some(&dir), "eff-short"),
+            Some(file("eff-short.txt"))
+        );
+        assert_eq!(source_in(Some(&dir), "mine"), Some(file("mine.txt")));
+        assert_eq!(
+            source_in(Some(&dir), "eff-long"),
+            Some(WordlistSource::Embedded)
+        );
+        assert_eq!(
+            source_in(None, "eff-short"),
+            Some(WordlistSource::Embedded)
+        );
+        assert_eq!(source_in(Some(&dir), "other"), None);
+        assert_eq!(source_in(Some(&dir), "../mine"), None);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn blocking_regional_words() {
+        let dir = std::env::temp_dir()
+            .join(format!("yapg-blocklists-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("de.txt"), "gift\n").unwrap();
+        std::fs::write(dir.join("de-AT.txt"), "Sackerl\n").unwrap();
+
+        let mut list: Wordlist = "gift\nsackerl\nhouse".parse().unwrap();
+        let blocklist = Blocklist::for_locale_in(&dir, "de-AT").unwrap();
+        assert_eq!(list.remove_blocked(&blocklist), 2);
+        assert_eq!(list.words(), ["house"]);
+
+        assert!(Blocklist::for_locale_in(&dir, "de-CH").is_ok());
+        assert!(Blocklist::for_locale_in(&dir, "fr").is_err());
+        assert!(Blocklist::for_locale_in(&dir, "../de").is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}